@@ -0,0 +1,47 @@
+use std::fmt::Write;
+use std::path::Path;
+
+/// File extension used for animation scripts.
+const EXTENSION: &str = "sh";
+
+/// Seconds each frame is shown before the next one is drawn.
+const FRAME_DELAY: &str = "0.2";
+
+/// Delimiter of the here-documents containing the frames.
+const DELIMITER: &str = "SKETCH_FRAME";
+
+/// Check if a path should use the animation format.
+pub fn is_animation(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == EXTENSION)
+}
+
+/// Create a shell script playing back rendered frames.
+///
+/// Every frame is drawn after clearing the screen and moving the cursor to
+/// the top left corner, followed by a short delay.
+pub fn script(frames: &[String]) -> String {
+    let mut script = String::from("#!/bin/sh\n");
+
+    for (i, frame) in frames.iter().enumerate() {
+        if i > 0 {
+            let _ = writeln!(script, "sleep {FRAME_DELAY}");
+        }
+
+        script.push_str("printf '\\033[2J\\033[H'\n");
+        let _ = writeln!(script, "cat <<'{DELIMITER}'\n{frame}\x1b[0m\n{DELIMITER}");
+    }
+
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_delays() {
+        let script = script(&["a".into(), "b".into()]);
+        assert_eq!(script.matches("sleep").count(), 1);
+        assert!(script.starts_with("#!/bin/sh\nprintf '\\033[2J\\033[H'\ncat <<'SKETCH_FRAME'\na"));
+    }
+}
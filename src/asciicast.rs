@@ -0,0 +1,198 @@
+use std::cmp::max;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use sketch::{Cell, Point};
+use unicode_width::UnicodeWidthChar;
+
+use crate::terminal::{Color, TextStyle};
+
+/// File extension used for asciicast recordings.
+const EXTENSION: &str = "cast";
+
+/// Longest pause in seconds kept when replaying a recording.
+const IDLE_TIME_LIMIT: f32 = 1.;
+
+/// Timestamped history of all changes to the canvas.
+///
+/// Every frame contains the terminal output required to turn the previous
+/// frame into the current canvas, which allows exporting the drawing process
+/// as an [asciicast](https://docs.asciinema.org/manual/asciicast/v2/).
+pub struct Asciicast {
+    start: Instant,
+    frames: Vec<(Duration, String)>,
+
+    /// Canvas dimensions of the last frame.
+    size: (usize, usize),
+
+    /// Largest canvas dimensions over the entire recording.
+    columns: usize,
+    lines: usize,
+}
+
+impl Default for Asciicast {
+    fn default() -> Self {
+        Self {
+            start: Instant::now(),
+            frames: Default::default(),
+            size: Default::default(),
+            columns: Default::default(),
+            lines: Default::default(),
+        }
+    }
+}
+
+impl Asciicast {
+    /// Record the cells changed since the previous frame.
+    ///
+    /// The changed points are usually taken from a journal revision. The entire
+    /// canvas is redrawn after its dimensions changed.
+    pub fn update(&mut self, cells: &[Vec<Cell>], changes: impl IntoIterator<Item = Point>) {
+        if canvas_size(cells) != self.size {
+            self.redraw(cells);
+            return;
+        }
+
+        let mut points: Vec<_> =
+            changes.into_iter().map(|point| (point.line, point.column)).collect();
+        points.sort_unstable();
+        points.dedup();
+
+        self.push_frame(String::new(), cells, points);
+    }
+
+    /// Record the entire canvas, replacing everything drawn before.
+    pub fn redraw(&mut self, cells: &[Vec<Cell>]) {
+        let (columns, lines) = canvas_size(cells);
+        self.columns = max(self.columns, columns);
+        self.lines = max(self.lines, lines);
+        self.size = (columns, lines);
+
+        // Start over with a cleared screen, only drawing cells with content.
+        let points = (1..=lines)
+            .flat_map(|line| (1..=columns).map(move |column| (line, column)))
+            .filter(|(line, column)| cells[line - 1][column - 1] != Cell::default());
+        self.push_frame(String::from("\x1b[2J"), cells, points.collect());
+    }
+
+    /// Append a frame drawing the cells at the sorted `(line, column)` points.
+    fn push_frame(&mut self, mut output: String, cells: &[Vec<Cell>], points: Vec<(usize, usize)>) {
+        let mut cursor = None;
+        let mut attributes = (Color::default(), Color::default(), TextStyle::empty());
+        for (line, column) in points {
+            let Some(cell) = cells.get(line - 1).and_then(|cells| cells.get(column - 1)) else {
+                continue;
+            };
+
+            // Skip spacers of fullwidth characters.
+            if column > 1 && cells[line - 1][column - 2].c.width() == Some(2) {
+                continue;
+            }
+
+            if cursor != Some((line, column)) {
+                let _ = write!(output, "\x1b[{line};{column}H");
+            }
+
+            if attributes != (cell.foreground, cell.background, cell.style) {
+                attributes = (cell.foreground, cell.background, cell.style);
+                output.push_str(&cell.foreground.escape_with_separator(true, ';'));
+                output.push_str(&cell.background.escape_with_separator(false, ';'));
+                output.push_str(cell.style.escape());
+            }
+
+            let width = match cell.c.width() {
+                Some(width @ (1 | 2)) => {
+                    cell.push_glyph(&mut output);
+                    width
+                },
+                _ => {
+                    output.push(' ');
+                    1
+                },
+            };
+            cursor = Some((line, column + width));
+        }
+
+        // Reset attributes, to not leak them into the next frame's clearing.
+        if attributes != (Color::default(), Color::default(), TextStyle::empty()) {
+            output.push_str("\x1b[0m");
+        }
+
+        if !output.is_empty() {
+            self.frames.push((self.start.elapsed(), output));
+        }
+    }
+
+    /// Serialize the recording in the asciicast v2 format.
+    pub fn serialize(&self) -> String {
+        let mut asciicast = format!(
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"idle_time_limit\": {:.1}}}\n",
+            self.columns, self.lines, IDLE_TIME_LIMIT,
+        );
+
+        // Hide the cursor, since it does not reflect the cursor while drawing.
+        asciicast.push_str("[0.0, \"o\", \"\\u001b[?25l\"]\n");
+
+        for (time, output) in &self.frames {
+            let _ =
+                writeln!(asciicast, "[{:.6}, \"o\", {}]", time.as_secs_f64(), json_string(output));
+        }
+
+        asciicast
+    }
+}
+
+/// Number of columns and lines of a canvas.
+fn canvas_size(cells: &[Vec<Cell>]) -> (usize, usize) {
+    (cells.first().map_or(0, Vec::len), cells.len())
+}
+
+/// Check if a path should use the asciicast format.
+pub fn is_asciicast(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == EXTENSION)
+}
+
+/// Quote and escape text as JSON string.
+fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            },
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_diffs() {
+        let mut recording = Asciicast::default();
+
+        let mut cells = vec![vec![Cell::default(); 3]; 2];
+        cells[0][1].c = 'a';
+        recording.update(&cells, []);
+        cells[1][2] = Cell::new('b', Color::Indexed(1), Color::default(), TextStyle::empty());
+        recording.update(&cells, [Point { column: 3, line: 2 }]);
+        recording.update(&cells, []);
+
+        let outputs: Vec<_> = recording.frames.iter().map(|(_, output)| output.as_str()).collect();
+        assert_eq!(outputs, [
+            "\x1b[2J\x1b[1;2Ha",
+            "\x1b[2;3H\x1b[38;5;1m\x1b[49m\x1b[22;23mb\x1b[0m"
+        ]);
+
+        let asciicast = recording.serialize();
+        assert!(asciicast.starts_with("{\"version\": 2, \"width\": 3, \"height\": 2,"));
+        assert!(asciicast.contains(", \"o\", \"\\u001b[2J\\u001b[1;2Ha\"]\n"));
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt::Write as _;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use sketch::grid::{MAX_COLUMNS, MAX_LINES};
+use sketch::Cell;
+
+use crate::session::{cell_fields, parse_cell};
+
+/// First line of every autosave file, including the format version.
+const HEADER: &[u8] = b"sketch-autosave 1\n";
+
+/// Minimum time between two autosaves.
+const INTERVAL: Duration = Duration::from_secs(5);
+
+/// Incremental snapshots written before the file is compacted.
+const MAX_INCREMENTAL: usize = 64;
+
+/// Periodic crash recovery snapshots of the canvas.
+///
+/// Autosave files consist of zlib-compressed records, each prefixed by its
+/// length as a big-endian `u32`. The first record is a full snapshot, every
+/// following record only contains the cells changed since the previous one.
+/// Once too many incremental records have accumulated, the file is replaced by
+/// a single full snapshot.
+pub struct Autosave {
+    path: PathBuf,
+    file: File,
+    previous: Vec<Vec<Cell>>,
+    incremental: usize,
+    last_save: Instant,
+}
+
+impl Autosave {
+    /// Start autosaving to a file, replacing its current content.
+    pub fn create(path: &Path, cells: &[Vec<Cell>]) -> io::Result<Self> {
+        let file = write_full(path, cells)?;
+        Ok(Self {
+            path: path.into(),
+            file,
+            previous: cells.to_vec(),
+            incremental: 0,
+            last_save: Instant::now(),
+        })
+    }
+
+    /// Recover the canvas from an autosave file.
+    ///
+    /// A truncated last record, like it is left behind by a crash during a
+    /// write, is ignored.
+    pub fn load(path: &Path) -> io::Result<Vec<Vec<Cell>>> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "invalid autosave file");
+
+        let bytes = fs::read(path)?;
+        let mut records = bytes.strip_prefix(HEADER).ok_or_else(invalid)?;
+
+        let mut cells = Vec::new();
+        while let Some((length, rest)) = records.split_first_chunk::<4>() {
+            let Some(record) = rest.get(..u32::from_be_bytes(*length) as usize) else {
+                break;
+            };
+            records = &rest[record.len()..];
+
+            let mut text = String::new();
+            ZlibDecoder::new(record).read_to_string(&mut text)?;
+            apply_record(&mut cells, &text).ok_or_else(invalid)?;
+        }
+
+        Ok(cells)
+    }
+
+    /// Snapshot the canvas, if enough time has passed since the last snapshot.
+    ///
+    /// Autosaving is best effort, failed writes are ignored.
+    pub fn update(&mut self, cells: &[Vec<Cell>]) {
+        if self.last_save.elapsed() >= INTERVAL {
+            self.save(cells);
+        }
+    }
+
+    /// Snapshot the canvas immediately.
+    pub fn save(&mut self, cells: &[Vec<Cell>]) {
+        self.last_save = Instant::now();
+
+        if self.previous == cells {
+            return;
+        }
+
+        // Start over with a full snapshot after resizes or too many increments.
+        let resized = self.previous.len() != cells.len()
+            || self.previous.first().map(Vec::len) != cells.first().map(Vec::len);
+        if resized || self.incremental >= MAX_INCREMENTAL {
+            if let Ok(file) = write_full(&self.path, cells) {
+                self.file = file;
+                self.previous = cells.to_vec();
+                self.incremental = 0;
+            }
+            return;
+        }
+
+        let mut diff = String::from("diff\n");
+        for (line, (cells, previous)) in cells.iter().zip(&mut self.previous).enumerate() {
+            for (column, (cell, previous)) in cells.iter().zip(previous).enumerate() {
+                if cell != previous {
+                    let _ = writeln!(diff, "{} {} {}", column + 1, line + 1, cell_fields(cell));
+                    *previous = cell.clone();
+                }
+            }
+        }
+
+        if write_record(&mut self.file, &diff).is_ok() {
+            self.incremental += 1;
+        }
+    }
+}
+
+/// Replace an autosave file with a full snapshot.
+///
+/// Returns the new file, ready for appending incremental snapshots.
+fn write_full(path: &Path, cells: &[Vec<Cell>]) -> io::Result<File> {
+    let columns = cells.first().map_or(0, Vec::len);
+    let mut full = format!("full {columns} {}\n", cells.len());
+    for (line, cells) in cells.iter().enumerate() {
+        for (column, cell) in cells.iter().enumerate() {
+            if *cell != Cell::default() {
+                let _ = writeln!(full, "{} {} {}", column + 1, line + 1, cell_fields(cell));
+            }
+        }
+    }
+
+    // Write to a temporary file, to never leave a partial snapshot behind.
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(HEADER)?;
+    write_record(&mut file, &full)?;
+    fs::rename(&tmp_path, path)?;
+
+    OpenOptions::new().append(true).open(path)
+}
+
+/// Append a compressed record to an autosave file.
+fn write_record(file: &mut File, record: &str) -> io::Result<()> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::fast());
+    encoder.write_all(record.as_bytes())?;
+    let compressed = encoder.finish()?;
+
+    let mut bytes = (compressed.len() as u32).to_be_bytes().to_vec();
+    bytes.extend_from_slice(&compressed);
+    file.write_all(&bytes)
+}
+
+/// Apply a decompressed record to the recovered cells.
+///
+/// Returns `None` if the record is malformed.
+fn apply_record(cells: &mut Vec<Vec<Cell>>, record: &str) -> Option<()> {
+    let mut lines = record.lines();
+
+    let header = lines.next()?;
+    if let Some(size) = header.strip_prefix("full ") {
+        let (columns, lines) = size.split_once(' ')?;
+        let columns = columns.parse().ok().filter(|columns| *columns <= MAX_COLUMNS)?;
+        let lines = lines.parse().ok().filter(|lines| *lines <= MAX_LINES)?;
+        *cells = vec![vec![Cell::default(); columns]; lines];
+    } else if header != "diff" {
+        return None;
+    }
+
+    for line in lines {
+        let mut fields = line.split(' ');
+        let column: usize = fields.next()?.parse().ok()?;
+        let line: usize = fields.next()?.parse().ok()?;
+        let cell = parse_cell(fields.next()?)?;
+
+        *cells.get_mut(line.checked_sub(1)?)?.get_mut(column.checked_sub(1)?)? = cell;
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::{Color, TextStyle};
+
+    #[test]
+    fn incremental_roundtrip() {
+        let path = std::env::temp_dir().join(format!("sketch-autosave-{}", std::process::id()));
+
+        let mut cells = vec![vec![Cell::default(); 3]; 2];
+        let mut autosave = Autosave::create(&path, &cells).unwrap();
+
+        cells[1][2] = Cell::new('x', Color::Indexed(3), Color::default(), TextStyle::BOLD);
+        autosave.save(&cells);
+        cells[0][0].c = 'y';
+        autosave.save(&cells);
+        cells.push(vec![Cell::default(); 3]);
+        autosave.save(&cells);
+        cells[2][1].c = 'z';
+        autosave.save(&cells);
+
+        // Simulate a crash during a write.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0, 0, 1, 0, 42]).unwrap();
+
+        let recovered = Autosave::load(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(recovered.unwrap(), cells);
+    }
+
+    #[test]
+    fn reject_oversized() {
+        let mut cells = Vec::new();
+        assert!(apply_record(&mut cells, &format!("full {MAX_COLUMNS} 1\n")).is_some());
+        assert!(apply_record(&mut cells, &format!("full 1 {}\n", MAX_LINES + 1)).is_none());
+        assert!(apply_record(&mut cells, "full 99999999999 99999999999\n").is_none());
+    }
+}
@@ -0,0 +1,95 @@
+/// Height of banner letters in lines.
+pub const LETTER_HEIGHT: usize = 5;
+
+/// Horizontal scaling applied to the font to account for the cell aspect ratio.
+pub const HORIZONTAL_SCALE: usize = 2;
+
+/// Columns between two banner letters.
+pub const LETTER_SPACING: usize = 2;
+
+/// Bitmap font for banner letters.
+///
+/// Every letter is three pixels wide, with `#` marking occupied pixels.
+#[rustfmt::skip]
+const FONT: [(char, [&str; LETTER_HEIGHT]); 45] = [
+    ('A', ["###", "# #", "###", "# #", "# #"]),
+    ('B', ["## ", "# #", "## ", "# #", "## "]),
+    ('C', ["###", "#  ", "#  ", "#  ", "###"]),
+    ('D', ["## ", "# #", "# #", "# #", "## "]),
+    ('E', ["###", "#  ", "## ", "#  ", "###"]),
+    ('F', ["###", "#  ", "## ", "#  ", "#  "]),
+    ('G', ["###", "#  ", "# #", "# #", "###"]),
+    ('H', ["# #", "# #", "###", "# #", "# #"]),
+    ('I', ["###", " # ", " # ", " # ", "###"]),
+    ('J', ["  #", "  #", "  #", "# #", "###"]),
+    ('K', ["# #", "# #", "## ", "# #", "# #"]),
+    ('L', ["#  ", "#  ", "#  ", "#  ", "###"]),
+    ('M', ["# #", "###", "###", "# #", "# #"]),
+    ('N', ["###", "# #", "# #", "# #", "# #"]),
+    ('O', ["###", "# #", "# #", "# #", "###"]),
+    ('P', ["###", "# #", "###", "#  ", "#  "]),
+    ('Q', ["###", "# #", "# #", "###", "  #"]),
+    ('R', ["## ", "# #", "## ", "# #", "# #"]),
+    ('S', ["###", "#  ", "###", "  #", "###"]),
+    ('T', ["###", " # ", " # ", " # ", " # "]),
+    ('U', ["# #", "# #", "# #", "# #", "###"]),
+    ('V', ["# #", "# #", "# #", "# #", " # "]),
+    ('W', ["# #", "# #", "###", "###", "# #"]),
+    ('X', ["# #", "# #", " # ", "# #", "# #"]),
+    ('Y', ["# #", "# #", " # ", " # ", " # "]),
+    ('Z', ["###", "  #", " # ", "#  ", "###"]),
+    ('0', ["###", "# #", "# #", "# #", "###"]),
+    ('1', [" # ", "## ", " # ", " # ", "###"]),
+    ('2', ["###", "  #", "###", "#  ", "###"]),
+    ('3', ["###", "  #", " ##", "  #", "###"]),
+    ('4', ["# #", "# #", "###", "  #", "  #"]),
+    ('5', ["###", "#  ", "###", "  #", "###"]),
+    ('6', ["###", "#  ", "###", "# #", "###"]),
+    ('7', ["###", "  #", "  #", "  #", "  #"]),
+    ('8', ["###", "# #", "###", "# #", "###"]),
+    ('9', ["###", "# #", "###", "  #", "###"]),
+    (' ', ["   ", "   ", "   ", "   ", "   "]),
+    ('.', ["   ", "   ", "   ", "   ", " # "]),
+    (',', ["   ", "   ", "   ", " # ", "#  "]),
+    ('!', [" # ", " # ", " # ", "   ", " # "]),
+    ('?', ["###", "  #", " ##", "   ", " # "]),
+    ('-', ["   ", "   ", "###", "   ", "   "]),
+    (':', ["   ", " # ", "   ", " # ", "   "]),
+    ('/', ["  #", "  #", " # ", "#  ", "#  "]),
+    ('\'', [" # ", " # ", "   ", "   ", "   "]),
+];
+
+/// Get the bitmap of a banner letter.
+///
+/// Lowercase letters are rendered as uppercase.
+pub fn letter(c: char) -> Option<&'static [&'static str; LETTER_HEIGHT]> {
+    let c = c.to_ascii_uppercase();
+    FONT.iter().find(|(letter, _)| *letter == c).map(|(_, bitmap)| bitmap)
+}
+
+/// Width of a banner letter in columns.
+pub fn letter_width() -> usize {
+    FONT[0].1[0].len() * HORIZONTAL_SCALE
+}
+
+/// Occupied column ranges of a line in a banner letter's bitmap.
+///
+/// Returns the start column offset and the number of occupied columns for each
+/// consecutive run of pixels, already scaled horizontally.
+pub fn runs(row: &str) -> Vec<(usize, usize)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+
+    for (i, pixel) in row.chars().chain([' ']).enumerate() {
+        match (pixel, start) {
+            ('#', None) => start = Some(i),
+            (' ', Some(run_start)) => {
+                runs.push((run_start * HORIZONTAL_SCALE, (i - run_start) * HORIZONTAL_SCALE));
+                start = None;
+            },
+            _ => (),
+        }
+    }
+
+    runs
+}
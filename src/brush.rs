@@ -0,0 +1,490 @@
+use unicode_width::UnicodeWidthChar;
+
+use crate::color::{Color, Rgb, TextStyle};
+use crate::{Cell, Point};
+
+/// Drawing brush.
+pub struct Brush {
+    pub template: Vec<Vec<bool>>,
+    pub shape: BrushShape,
+    /// Custom cells replacing the template.
+    pub stamp: Option<Vec<Vec<Cell>>>,
+    /// Draw lines and boxes with increased thickness.
+    pub emphasis: bool,
+    /// Pick black or white foreground based on the background's brightness.
+    pub auto_contrast: bool,
+    pub foreground: Color,
+    pub background: Color,
+    pub style: TextStyle,
+    pub position: Point,
+    /// Grapheme clusters tiled across brush strokes.
+    pub pattern: Vec<String>,
+    pub size: u8,
+    /// Size of the eraser, matching the brush size if unset.
+    pub eraser_size: Option<u8>,
+    /// Cell attributes removed by the eraser.
+    pub eraser: EraserMode,
+    /// Cell attributes replaced by brush strokes.
+    pub paint: PaintMode,
+    /// Combination of stamp cells with the content below them.
+    pub blend: StampBlend,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self {
+            template: Self::create_template(BrushShape::default(), 1),
+            shape: BrushShape::default(),
+            stamp: None,
+            emphasis: false,
+            auto_contrast: false,
+            pattern: vec!["+".into()],
+            size: 1,
+            eraser_size: None,
+            eraser: EraserMode::default(),
+            paint: PaintMode::default(),
+            blend: StampBlend::default(),
+            foreground: Default::default(),
+            background: Default::default(),
+            position: Default::default(),
+            style: Default::default(),
+        }
+    }
+}
+
+impl Brush {
+    /// Update the brush's colors.
+    pub fn set_color(&mut self, position: ColorPosition, color: Color) {
+        match position {
+            ColorPosition::Foreground => self.foreground = color,
+            ColorPosition::Background => self.background = color,
+        }
+    }
+
+    /// Get the pattern's glyph for a grid column.
+    pub fn glyph(&self, column: usize) -> String {
+        let index = column.saturating_sub(1) / self.width() % self.pattern.len();
+        self.pattern[index].clone()
+    }
+
+    /// Foreground color used for writing.
+    ///
+    /// With automatic contrast enabled, this will be either black or white to
+    /// ensure legibility on top of the brush's background.
+    pub fn contrast_foreground(&self) -> Color {
+        self.contrast_with(self.background).unwrap_or(self.foreground)
+    }
+
+    /// Black or white foreground legible on top of a background.
+    ///
+    /// Returns `None` if automatic contrast is disabled or the background's
+    /// brightness is unknown.
+    pub fn contrast_with(&self, background: Color) -> Option<Color> {
+        let background = background.rgb().filter(|_| self.auto_contrast)?;
+        let value = if background.luminance() > 127 { 0 } else { 255 };
+        Some(Color::Rgb(Rgb { r: value, g: value, b: value }))
+    }
+
+    /// Width of the brush's glyphs.
+    pub fn width(&self) -> usize {
+        self.pattern[0].chars().next().and_then(|c| c.width()).unwrap_or(1)
+    }
+
+    /// Create a new brush template.
+    ///
+    /// The resulting template is a matrix that stores `true` for every cell
+    /// that contains a brush glyph and `false` for all empty cells.
+    pub fn create_template(shape: BrushShape, size: u8) -> Vec<Vec<bool>> {
+        // Special case the default 1x1 cursor.
+        if size == 1 {
+            return vec![vec![true]];
+        }
+
+        let size = size as usize;
+        match shape {
+            BrushShape::Hexagon => Self::hexagon_template(size),
+            BrushShape::Square => vec![vec![true; size]; size.div_ceil(2)],
+            BrushShape::HorizontalLine => vec![vec![true; size]],
+            BrushShape::VerticalLine => vec![vec![true]; size],
+            BrushShape::Diamond => Self::diamond_template(size),
+        }
+    }
+
+    /// Create a hexagon shaped brush template.
+    ///
+    /// A brush with size 6 might look like this (`+`: `true`, `-`: `false`):
+    ///
+    /// ```text
+    /// --++++++--
+    /// -++++++++-
+    /// ++++++++++
+    /// -++++++++-
+    /// --++++++--
+    /// ```
+    pub fn hexagon_template(size: usize) -> Vec<Vec<bool>> {
+        let width = size + (size / 2 - 1) * 2;
+        let height = size - 1;
+
+        // Initialize an empty cursor.
+        let mut cursor = vec![vec![false; width]; height];
+
+        let mid_point = (size - 1) as f32 / 2.;
+        let mut num_occupied = size;
+        for (i, line) in cursor.iter_mut().enumerate().take(height) {
+            // Set all occupied bits in the current line.
+            for column in 0..num_occupied {
+                let column = (width - num_occupied) / 2 + column;
+                line[column] = true;
+            }
+
+            // Increment/Decrement based on current line in hexagon.
+            if i as f32 + 1. < mid_point {
+                num_occupied += 2;
+            } else if i as f32 + 1. > mid_point {
+                num_occupied -= 2;
+            }
+        }
+
+        cursor
+    }
+
+    /// Create a diamond shaped brush template.
+    ///
+    /// A brush with size 9 might look like this (`+`: `true`, `-`: `false`):
+    ///
+    /// ```text
+    /// ----+----
+    /// --+++++--
+    /// +++++++++
+    /// --+++++--
+    /// ----+----
+    /// ```
+    pub fn diamond_template(size: usize) -> Vec<Vec<bool>> {
+        // Use two columns per line to account for the cell aspect ratio.
+        let half_width = (size - 1) / 2;
+        let half_height = half_width / 2;
+
+        (0..=half_height * 2)
+            .map(|line| {
+                let distance = line.abs_diff(half_height);
+                let line_half_width = half_width - distance * 2;
+                (0..=half_width * 2)
+                    .map(|column| column.abs_diff(half_width) <= line_half_width)
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Outline of the brush template.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BrushShape {
+    #[default]
+    Hexagon,
+    Square,
+    HorizontalLine,
+    VerticalLine,
+    Diamond,
+}
+
+impl BrushShape {
+    /// Get the shape following this one.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Hexagon => Self::Square,
+            Self::Square => Self::HorizontalLine,
+            Self::HorizontalLine => Self::VerticalLine,
+            Self::VerticalLine => Self::Diamond,
+            Self::Diamond => Self::Hexagon,
+        }
+    }
+
+    /// Get human-readable name of the shape.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hexagon => "hexagon",
+            Self::Square => "square",
+            Self::HorizontalLine => "horizontal line",
+            Self::VerticalLine => "vertical line",
+            Self::Diamond => "diamond",
+        }
+    }
+}
+
+/// Cell attributes replaced by brush strokes.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PaintMode {
+    #[default]
+    All,
+    Background,
+    Foreground,
+    Style,
+}
+
+impl PaintMode {
+    /// Get the paint mode following this one.
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Self::Background,
+            Self::Background => Self::Foreground,
+            Self::Foreground => Self::Style,
+            Self::Style => Self::All,
+        }
+    }
+
+    /// Get human-readable name of the painted attributes.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::All => "glyphs and colors",
+            Self::Background => "background only",
+            Self::Foreground => "foreground only",
+            Self::Style => "text style only",
+        }
+    }
+}
+
+/// Combination of stamp cells with the content below them.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum StampBlend {
+    #[default]
+    Overwrite,
+    KeepGlyph,
+    KeepColors,
+    SkipNonEmpty,
+}
+
+impl StampBlend {
+    /// Get the blend mode following this one.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Overwrite => Self::KeepGlyph,
+            Self::KeepGlyph => Self::KeepColors,
+            Self::KeepColors => Self::SkipNonEmpty,
+            Self::SkipNonEmpty => Self::Overwrite,
+        }
+    }
+
+    /// Get human-readable name of the blend mode.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Overwrite => "overwrite",
+            Self::KeepGlyph => "colors only",
+            Self::KeepColors => "glyphs only",
+            Self::SkipNonEmpty => "empty cells only",
+        }
+    }
+
+    /// Combine a stamp cell with the existing cell below it.
+    ///
+    /// Returns `None` if the existing cell should be left untouched.
+    pub fn apply(self, existing: &Cell, stamp: &Cell) -> Option<Cell> {
+        match self {
+            Self::Overwrite => Some(stamp.clone()),
+            Self::KeepGlyph => Some(Cell {
+                c: if existing.c == '\0' { ' ' } else { existing.c },
+                combining: existing.combining.clone(),
+                ..stamp.clone()
+            }),
+            Self::KeepColors => Some(Cell {
+                foreground: existing.foreground,
+                background: existing.background,
+                style: existing.style,
+                ..stamp.clone()
+            }),
+            Self::SkipNonEmpty if !existing.is_empty() => None,
+            Self::SkipNonEmpty => Some(stamp.clone()),
+        }
+    }
+}
+
+/// Cell attributes removed by the eraser.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum EraserMode {
+    #[default]
+    All,
+    Glyphs,
+    Colors,
+}
+
+impl EraserMode {
+    /// Get the eraser mode following this one.
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Self::Glyphs,
+            Self::Glyphs => Self::Colors,
+            Self::Colors => Self::All,
+        }
+    }
+
+    /// Get human-readable name of the erased attributes.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::All => "glyphs and colors",
+            Self::Glyphs => "glyphs only",
+            Self::Colors => "colors only",
+        }
+    }
+}
+
+/// Axes brush strokes are mirrored across, centered on the canvas.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Symmetry {
+    #[default]
+    None,
+    Vertical,
+    Both,
+}
+
+impl Symmetry {
+    /// Get the symmetry following this one.
+    pub fn next(self) -> Self {
+        match self {
+            Self::None => Self::Vertical,
+            Self::Vertical => Self::Both,
+            Self::Both => Self::None,
+        }
+    }
+
+    /// Get human-readable name of the symmetry.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Vertical => "vertical axis",
+            Self::Both => "vertical and horizontal axes",
+        }
+    }
+
+    /// Get all positions of a point mirrored on a canvas of the specified size.
+    ///
+    /// The original point is always the first position, duplicates on the
+    /// axes themselves are omitted.
+    pub fn mirror(self, point: Point, columns: usize, lines: usize) -> Vec<Point> {
+        let mirrored_column = (columns + 1).checked_sub(point.column).filter(|c| *c > 0);
+        let mirrored_line = (lines + 1).checked_sub(point.line).filter(|l| *l > 0);
+
+        let mut points = vec![point];
+        let mut push = |column: Option<usize>, line: Option<usize>| {
+            if let (Some(column), Some(line)) = (column, line) {
+                let point = Point { column, line };
+                if !points.contains(&point) {
+                    points.push(point);
+                }
+            }
+        };
+
+        if self != Self::None {
+            push(mirrored_column, Some(point.line));
+        }
+        if self == Self::Both {
+            push(Some(point.column), mirrored_line);
+            push(mirrored_column, mirrored_line);
+        }
+
+        points
+    }
+}
+
+/// Brush color affected by a change.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ColorPosition {
+    Foreground,
+    Background,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[rustfmt::skip]
+    fn cursor() {
+        let cursor = Brush::create_template(BrushShape::Hexagon, 1);
+        assert_eq!(cursor, vec![vec![true]]);
+
+        let cursor = Brush::create_template(BrushShape::Hexagon, 2);
+        assert_eq!(cursor, vec![
+            vec![true, true],
+        ]);
+
+        let cursor = Brush::create_template(BrushShape::Hexagon, 3);
+        assert_eq!(cursor, vec![
+            vec![true, true, true],
+            vec![true, true, true],
+        ]);
+
+        let cursor = Brush::create_template(BrushShape::Hexagon, 6);
+        assert_eq!(cursor, vec![
+            vec![false, false, true, true, true, true, true, true, false, false],
+            vec![false, true,  true, true, true, true, true, true, true,  false],
+            vec![true,  true,  true, true, true, true, true, true, true,  true ],
+            vec![false, true,  true, true, true, true, true, true, true,  false],
+            vec![false, false, true, true, true, true, true, true, false, false],
+        ]);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn cursor_shapes() {
+        let cursor = Brush::create_template(BrushShape::Square, 4);
+        assert_eq!(cursor, vec![
+            vec![true, true, true, true],
+            vec![true, true, true, true],
+        ]);
+
+        let cursor = Brush::create_template(BrushShape::VerticalLine, 3);
+        assert_eq!(cursor, vec![vec![true], vec![true], vec![true]]);
+
+        let cursor = Brush::create_template(BrushShape::Diamond, 5);
+        assert_eq!(cursor, vec![
+            vec![false, false, true, false, false],
+            vec![true,  true,  true, true,  true ],
+            vec![false, false, true, false, false],
+        ]);
+    }
+
+    #[test]
+    fn mirror_points() {
+        let point = Point { column: 2, line: 1 };
+        let mirrored = Point { column: 9, line: 1 };
+        assert_eq!(Symmetry::None.mirror(point, 10, 4), vec![point]);
+        assert_eq!(Symmetry::Vertical.mirror(point, 10, 4), vec![point, mirrored]);
+
+        let flipped = Point { column: 2, line: 4 };
+        let rotated = Point { column: 9, line: 4 };
+        assert_eq!(Symmetry::Both.mirror(point, 10, 4), vec![point, mirrored, flipped, rotated]);
+
+        // Points on the axes are not duplicated.
+        let center = Point { column: 3, line: 2 };
+        assert_eq!(Symmetry::Both.mirror(center, 5, 3), vec![center]);
+    }
+
+    #[test]
+    fn background_contrast() {
+        let mut brush = Brush::default();
+        let white = Color::Rgb(Rgb { r: 255, g: 255, b: 255 });
+        let black = Color::Rgb(Rgb { r: 0, g: 0, b: 0 });
+        assert_eq!(brush.contrast_with(white), None);
+
+        brush.auto_contrast = true;
+        assert_eq!(brush.contrast_with(white), Some(black));
+        assert_eq!(brush.contrast_with(Color::Indexed(4)), Some(white));
+        assert_eq!(brush.contrast_with(Color::default()), None);
+    }
+
+    #[test]
+    fn stamp_blending() {
+        let red = Color::Indexed(1);
+        let existing = Cell::new('x', red, Color::default(), TextStyle::default());
+        let stamp = Cell::new('o', Color::default(), red, TextStyle::default());
+
+        let blended = StampBlend::KeepGlyph.apply(&existing, &stamp).unwrap();
+        assert_eq!(blended, Cell::new('x', Color::default(), red, TextStyle::default()));
+
+        let blended = StampBlend::KeepColors.apply(&existing, &stamp).unwrap();
+        assert_eq!(blended, Cell::new('o', red, Color::default(), TextStyle::default()));
+
+        assert_eq!(StampBlend::SkipNonEmpty.apply(&existing, &stamp), None);
+        assert_eq!(StampBlend::SkipNonEmpty.apply(&Cell::default(), &stamp), Some(stamp.clone()));
+        assert_eq!(StampBlend::Overwrite.apply(&existing, &stamp), Some(stamp));
+    }
+}
@@ -0,0 +1,928 @@
+//! Drawing operations on the sketch grid.
+
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::mem;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::brush::{EraserMode, PaintMode, Symmetry};
+use crate::color::{Color, TextStyle};
+use crate::geometry::{self, LineDirection};
+use crate::junction::{self, Weight};
+use crate::{Brush, Cell, Grid, Point};
+
+/// Glyph used for the shadow of emphasized boxes.
+pub const SHADOW_GLYPH: &str = "▒";
+
+/// Regular box drawing characters.
+const LIGHT_BOX_GLYPHS: BoxGlyphs = BoxGlyphs {
+    horizontal: '─',
+    vertical: '│',
+    top_left: '┌',
+    top_right: '┐',
+    bottom_left: '└',
+    bottom_right: '┘',
+    tee_right: '├',
+    tee_left: '┤',
+    tee_down: '┬',
+    tee_up: '┴',
+    cross: '┼',
+};
+
+/// Box drawing characters for emphasized boxes.
+const HEAVY_BOX_GLYPHS: BoxGlyphs = BoxGlyphs {
+    horizontal: '━',
+    vertical: '┃',
+    top_left: '┏',
+    top_right: '┓',
+    bottom_left: '┗',
+    bottom_right: '┛',
+    tee_right: '┣',
+    tee_left: '┫',
+    tee_down: '┳',
+    tee_up: '┻',
+    cross: '╋',
+};
+
+/// Sketch grid with the brush drawing on it.
+///
+/// Every write is reported as [`Stroke`], allowing interfaces to mirror the
+/// changes without redrawing the entire grid. Writes never commit to the undo
+/// history, that is left to the caller.
+#[derive(Default)]
+pub struct Canvas {
+    pub grid: Grid,
+
+    /// Brush used for all drawing operations.
+    pub brush: Brush,
+
+    /// Regions protected from modification.
+    pub locked: Vec<(Point, Point)>,
+
+    /// Axes brush strokes are mirrored across.
+    pub symmetry: Symmetry,
+
+    /// Whether erasing box borders repairs the remaining junctions.
+    pub smart_erase: bool,
+
+    /// Whether circles ignore the cell aspect ratio.
+    pub true_geometry: bool,
+
+    /// Writes since the strokes were last taken.
+    strokes: Vec<Stroke>,
+
+    /// Queue used for color fills.
+    fill_queue: VecDeque<(usize, usize, usize, isize)>,
+}
+
+impl Canvas {
+    pub fn new(grid: Grid, brush: Brush) -> Self {
+        Self { grid, brush, ..Self::default() }
+    }
+
+    /// Take all strokes written since the last call.
+    pub fn take_strokes(&mut self) -> Vec<Stroke> {
+        mem::take(&mut self.strokes)
+    }
+
+    /// Check if a cell is part of a locked region.
+    pub fn is_locked(&self, point: Point) -> bool {
+        self.locked.iter().any(|(start, end)| point.within(*start, *end))
+    }
+
+    /// Write character at the specified position.
+    ///
+    /// Unless `persist` is set, the write is only reported as preview stroke
+    /// without modifying the grid.
+    pub fn write(&mut self, at: Point, c: char, persist: bool) -> Point {
+        self.write_many(at, c.encode_utf8(&mut [0; 4]), 1, persist)
+    }
+
+    /// Write the same grapheme cluster multiple times.
+    ///
+    /// Returns the point after the last written cell.
+    pub fn write_many(&mut self, at: Point, glyph: &str, count: usize, persist: bool) -> Point {
+        if count == 0 {
+            return at;
+        }
+
+        let foreground = self.brush.contrast_foreground();
+        let background = self.brush.background;
+        let Some(cell) = Cell::with_glyph(glyph, foreground, background, self.brush.style) else {
+            return at;
+        };
+
+        // Verify that the glyph is a printable character.
+        let width = match cell.c.width() {
+            Some(width) if width > 0 => width,
+            _ => return at,
+        };
+
+        // Skip over locked cells, writing only the unlocked ones.
+        let locked = |canvas: &Self, column| {
+            (column..column + width).any(|column| canvas.is_locked(Point { column, ..at }))
+        };
+        if persist && (0..count).any(|i| locked(self, at.column + i * width)) {
+            let mut point = at;
+            for _ in 0..count {
+                point = match locked(self, point.column) {
+                    true => Point { column: point.column + width, ..point },
+                    false => self.write_many(point, glyph, 1, persist),
+                };
+            }
+            return point;
+        }
+
+        let Point { column, line } = at;
+
+        // Verify the first cell write is within the grid.
+        if self.grid.len() < line || self.grid[line - 1].len() + 1 < column + width {
+            return at;
+        }
+
+        let max = min(column + (count - 1) * width, self.grid[line - 1].len());
+        let end = Point { column: column + width * count, line };
+
+        // Keep temporary content out of the grid.
+        if !persist {
+            let count = (max - column) / width + 1;
+            self.strokes.push(Stroke::Preview { start: at, cell, count });
+            return end;
+        }
+
+        // Store character in the grid state.
+        for column in (column..=max).step_by(width) {
+            // Replace the glyph itself.
+            self.grid.replace(Point { column, line }, cell.clone());
+
+            // Reset the following character when writing fullwidth characters.
+            if width == 2 {
+                self.grid.clear(Point { column: column + 1, line });
+            }
+
+            // Replace previous fullwidth character if we're writing inside its spacer.
+            if column >= 2 && self.grid[line - 1][column - 2].c.width() == Some(2) {
+                self.grid.clear(Point { column: column - 1, line });
+            }
+        }
+
+        self.strokes.push(Stroke::Write { start: at, cell, count });
+
+        end
+    }
+
+    /// Write the brush pattern multiple times.
+    ///
+    /// Patterns with multiple glyphs are tiled based on the absolute grid
+    /// column, which ensures that adjacent strokes line up seamlessly.
+    pub fn write_pattern(&mut self, at: Point, count: usize, persist: bool) -> Point {
+        // Write single-glyph patterns as a single run.
+        if let [glyph] = &self.brush.pattern[..] {
+            return self.write_many(at, &glyph.clone(), count, persist);
+        }
+
+        let mut point = at;
+        for _ in 0..count {
+            let next_point = self.write_many(point, &self.brush.glyph(point.column), 1, persist);
+
+            // Stop once we've left the grid.
+            if next_point == point {
+                break;
+            }
+            point = next_point;
+        }
+
+        point
+    }
+
+    /// Write a character, merging box drawing characters with the existing
+    /// content.
+    pub fn write_merged(&mut self, at: Point, c: char, persist: bool) -> Point {
+        let c = junction::merge(self.grid.get(at).c, c);
+        self.write(at, c, persist)
+    }
+
+    /// Write a brush glyph, merging box drawing characters with the existing
+    /// content.
+    pub fn write_merged_glyph(&mut self, at: Point, glyph: &str, persist: bool) -> Point {
+        // Grapheme clusters never form box drawing junctions.
+        match glyph.parse() {
+            Ok(c) => self.write_merged(at, c, persist),
+            Err(_) => self.write_many(at, glyph, 1, persist),
+        }
+    }
+
+    /// Write the brush pattern multiple times, merging box drawing characters
+    /// with the existing content.
+    pub fn write_merged_pattern(&mut self, at: Point, count: usize, persist: bool) -> Point {
+        let mut point = at;
+        for _ in 0..count {
+            let glyph = self.brush.glyph(point.column);
+            let next_point = self.write_merged_glyph(point, &glyph, persist);
+
+            // Stop once we've left the grid.
+            if next_point == point {
+                break;
+            }
+            point = next_point;
+        }
+
+        point
+    }
+
+    /// Write a cell's glyph using the cell's colors and text style.
+    pub fn write_cell(&mut self, at: Point, cell: &Cell, persist: bool) -> Point {
+        let foreground = mem::replace(&mut self.brush.foreground, cell.foreground);
+        let background = mem::replace(&mut self.brush.background, cell.background);
+        let style = mem::replace(&mut self.brush.style, cell.style);
+        let auto_contrast = mem::replace(&mut self.brush.auto_contrast, false);
+
+        let point = self.write_many(at, &cell.glyph(), 1, persist);
+
+        self.brush.foreground = foreground;
+        self.brush.background = background;
+        self.brush.style = style;
+        self.brush.auto_contrast = auto_contrast;
+
+        point
+    }
+
+    /// Reset a cell to its transparent default.
+    ///
+    /// Locked cells and cells outside of the grid are ignored.
+    pub fn clear_cell(&mut self, point: Point) {
+        if !self.grid.contains(point) || self.is_locked(point) {
+            return;
+        }
+
+        // Clear the spacer of fullwidth characters with them.
+        let mut points = vec![point];
+        let spacer = Point { column: point.column + 1, ..point };
+        if self.grid.get(point).c.width() == Some(2) && self.grid.contains(spacer) {
+            points.push(spacer);
+        }
+
+        for point in &points {
+            self.grid.clear(*point);
+        }
+
+        self.strokes.push(Stroke::Clear(points));
+    }
+
+    /// Write the brush's content at its current location.
+    pub fn write_brush(&mut self, mode: WriteMode) {
+        // Erase with the eraser's own size, ignoring custom stamps.
+        let eraser_size = self.brush.eraser_size.filter(|_| mode == WriteMode::Erase);
+        let brush = eraser_size.map(|size| {
+            let template = Brush::create_template(self.brush.shape, size);
+            (mem::replace(&mut self.brush.template, template), self.brush.stamp.take())
+        });
+
+        // Repeat the stroke at every position mirrored across the symmetry axes.
+        let position = self.brush.position;
+        let columns = self.grid.first().map_or(0, Vec::len);
+        for mirrored in self.symmetry.mirror(position, columns, self.grid.len()) {
+            self.brush.position = mirrored;
+            self.write_brush_once(mode);
+        }
+        self.brush.position = position;
+
+        if let Some((template, stamp)) = brush {
+            self.brush.template = template;
+            self.brush.stamp = stamp;
+        }
+    }
+
+    /// Write the brush's content at its current location, ignoring symmetry.
+    fn write_brush_once(&mut self, mode: WriteMode) {
+        // Remember box drawing characters which might get erased.
+        let borders = match mode {
+            WriteMode::Erase if self.smart_erase => self.brush_borders(),
+            _ => Vec::new(),
+        };
+
+        // Write custom brush stamps instead of the template when available.
+        match self.brush.stamp.take() {
+            Some(stamp) => {
+                self.write_stamp(&stamp, mode);
+                self.brush.stamp = Some(stamp);
+            },
+            None => self.write_template(mode),
+        }
+
+        // Remove junction segments leading into erased borders.
+        let erased: Vec<_> = borders
+            .into_iter()
+            .filter(|point| junction::segments(self.grid.get(*point).c).is_none())
+            .collect();
+        for (point, c) in self.grid.border_repairs(&erased) {
+            let Cell { foreground, background, style, .. } = *self.grid.get(point);
+            self.write_cell(point, &Cell::new(c, foreground, background, style), true);
+        }
+    }
+
+    /// Get all box drawing characters below the brush.
+    fn brush_borders(&self) -> Vec<Point> {
+        let (columns, lines) = match &self.brush.stamp {
+            Some(stamp) => (stamp[0].len(), stamp.len()),
+            None => (self.brush.template[0].len(), self.brush.template.len()),
+        };
+
+        // Cover at least the entire brush, the exact bounds don't matter.
+        let position = self.brush.position;
+        let start = Point {
+            column: max(position.column.saturating_sub(columns / 2), 1),
+            line: max(position.line.saturating_sub(lines / 2), 1),
+        };
+        let end = Point { column: position.column + columns, line: position.line + lines };
+
+        let mut borders = self.grid.region(start, end);
+        borders.retain(|point| junction::segments(self.grid.get(*point).c).is_some());
+        borders
+    }
+
+    /// Write a custom brush stamp centered around the brush position.
+    pub fn write_stamp(&mut self, stamp: &[Vec<Cell>], mode: WriteMode) {
+        let cursor_position = self.brush.position;
+
+        // Find the top left corner of the stamp.
+        let origin_column = cursor_position.column as isize - stamp[0].len() as isize / 2;
+        let origin_line = cursor_position.line as isize - stamp.len() as isize / 2;
+
+        for (line_offset, line) in stamp.iter().enumerate() {
+            for (column_offset, cell) in line.iter().enumerate() {
+                // Keep the content below transparent stamp cells.
+                if cell.transparent {
+                    continue;
+                }
+
+                // Skip cells outside the top left of the grid.
+                let column = usize::try_from(origin_column + column_offset as isize);
+                let line = usize::try_from(origin_line + line_offset as isize);
+                let point = match (column, line) {
+                    (Ok(column), Ok(line)) if column > 0 && line > 0 => Point { column, line },
+                    _ => continue,
+                };
+
+                if mode == WriteMode::Erase {
+                    self.erase(point, 1);
+                    continue;
+                }
+
+                // Combine the stamp with existing content, based on the blend mode.
+                if !self.grid.contains(point) {
+                    continue;
+                }
+                let Some(cell) = self.brush.blend.apply(self.grid.get(point), cell) else {
+                    continue;
+                };
+
+                self.write_cell(point, &cell, mode == WriteMode::Write);
+            }
+        }
+    }
+
+    /// Write the brush's template at its current location.
+    fn write_template(&mut self, mode: WriteMode) {
+        let last_line = self.grid.len() as isize;
+        let cursor_position = self.brush.position;
+
+        // Find the top left corner of the cursor.
+        let brush_width = self.brush.template[0].len();
+        let brush_height = self.brush.template.len();
+        let origin_column = cursor_position.column as isize - brush_width as isize / 2;
+        let origin_line = cursor_position.line as isize - brush_height as isize / 2;
+
+        // Write the cursor characters.
+        for line in 0..brush_height {
+            let target_line = origin_line + line as isize;
+            let skip = usize::try_from(-origin_column + 1).unwrap_or_default();
+            let first_occupied = self.brush.template[line].iter().skip(skip).position(|b| *b);
+
+            // Skip this line if there is no occupied cell within the grid.
+            let first_occupied = match first_occupied {
+                Some(first_occupied) if target_line > 0 && target_line <= last_line => {
+                    first_occupied + skip
+                },
+                _ => continue,
+            };
+
+            // Get write target start location.
+            let write_location = Point {
+                column: (origin_column + first_occupied as isize) as usize,
+                line: target_line as usize,
+            };
+
+            // Get the last non-empty cell in the brush.
+            let last_occupied = self.brush.template[line].iter().rposition(|occ| *occ).unwrap_or(0);
+
+            // Ignore every second cell for fullwidth brushes.
+            let width = self.brush.width();
+            let columns = (last_occupied + width - first_occupied) / width;
+
+            match mode {
+                // Recolor existing content without replacing its glyphs.
+                WriteMode::WriteVolatile | WriteMode::Write
+                    if self.brush.paint != PaintMode::All =>
+                {
+                    self.paint(write_location, columns * width, mode == WriteMode::Write);
+                },
+                WriteMode::WriteVolatile => {
+                    self.write_pattern(write_location, columns, false);
+                },
+                WriteMode::Write => {
+                    self.write_pattern(write_location, columns, true);
+                },
+                WriteMode::Erase => self.erase(write_location, columns * width),
+            }
+        }
+    }
+
+    /// Erase a run of cells, based on the eraser mode.
+    pub fn erase(&mut self, start: Point, count: usize) {
+        let mode = self.brush.eraser;
+        if mode == EraserMode::All {
+            for point in self.grid.run(start, count) {
+                self.clear_cell(point);
+            }
+            return;
+        }
+
+        for point in self.grid.run(start, count) {
+            let cell = self.grid.get(point).clone();
+            let erased = match mode {
+                EraserMode::Glyphs => Cell { c: ' ', combining: String::new(), ..cell },
+                _ => Cell { foreground: Color::default(), background: Color::default(), ..cell },
+            };
+            self.write_cell(point, &erased, true);
+        }
+    }
+
+    /// Recolor a run of cells, based on the paint mode.
+    ///
+    /// Only the brush's background, foreground, or text style is applied,
+    /// keeping the glyphs intact. Foregrounds and text styles are only painted
+    /// for cells with visible glyphs.
+    ///
+    /// With automatic contrast enabled, glyphs below a painted background are
+    /// recolored to stay legible.
+    pub fn paint(&mut self, start: Point, count: usize, persist: bool) {
+        for point in self.grid.run(start, count) {
+            let cell = self.grid.get(point);
+            let painted = match self.brush.paint {
+                PaintMode::Background => {
+                    let painted = cell.with_background(self.brush.background);
+
+                    // Keep existing glyphs legible on top of the new background.
+                    match self.brush.contrast_with(self.brush.background) {
+                        Some(foreground) if !cell.is_blank() => painted.with_foreground(foreground),
+                        _ => painted,
+                    }
+                },
+                PaintMode::Foreground if !cell.is_blank() => {
+                    cell.with_foreground(self.brush.foreground)
+                },
+                PaintMode::Style if !cell.is_blank() => cell.with_style(self.brush.style),
+                _ => continue,
+            };
+            self.write_cell(point, &painted, persist);
+        }
+    }
+
+    /// Write a box.
+    pub fn write_box(&mut self, mut start: Point, mut end: Point, mode: WriteMode) {
+        // Erasing line drawing mode does not exist.
+        if mode == WriteMode::Erase {
+            return;
+        }
+        let persistent = mode == WriteMode::Write;
+
+        // Ensure start is always at the top left corner of the box.
+        if start.column > end.column {
+            mem::swap(&mut start.column, &mut end.column);
+        }
+        if start.line > end.line {
+            mem::swap(&mut start.line, &mut end.line);
+        }
+
+        // Use heavy box drawing characters for emphasized boxes.
+        let glyphs = if self.brush.emphasis { &HEAVY_BOX_GLYPHS } else { &LIGHT_BOX_GLYPHS };
+
+        // Write a new box char, taking combinations into consideration.
+        let mut write_line_char = |point, c| self.write_merged(point, c, persistent);
+
+        // Write box drawing characters for first and last line.
+        if start.column == end.column && start.line == end.line {
+            // Single cell box.
+            write_line_char(start, glyphs.cross);
+        } else if start.column == end.column {
+            // Vertical line.
+            write_line_char(start, glyphs.tee_down);
+            let point = Point { column: start.column, line: end.line };
+            write_line_char(point, glyphs.tee_up);
+        } else if start.line == end.line {
+            // Horizontal line.
+            let mut point = write_line_char(start, glyphs.tee_right);
+            for _ in 0..end.column - start.column - 1 {
+                point = write_line_char(point, glyphs.horizontal);
+            }
+            write_line_char(point, glyphs.tee_left);
+        } else {
+            // Full box.
+            let mut point = write_line_char(start, glyphs.top_left);
+            for _ in 0..end.column - start.column - 1 {
+                point = write_line_char(point, glyphs.horizontal);
+            }
+            write_line_char(point, glyphs.top_right);
+
+            let mut point = Point { column: start.column, line: end.line };
+            point = write_line_char(point, glyphs.bottom_left);
+            for _ in 0..end.column - start.column - 1 {
+                point = write_line_char(point, glyphs.horizontal);
+            }
+            write_line_char(point, glyphs.bottom_right);
+        };
+
+        // Draw the sides of the box.
+        for line in (start.line..end.line).skip(1) {
+            // Write left border.
+            let point = Point { column: start.column, line };
+            write_line_char(point, glyphs.vertical);
+
+            // Write right border.
+            if end.column != start.column {
+                let point = Point { column: end.column, line };
+                write_line_char(point, glyphs.vertical);
+            }
+        }
+
+        // Cast a shadow below and to the right of emphasized boxes.
+        if self.brush.emphasis {
+            let point = Point { column: start.column + 1, line: end.line + 1 };
+            self.write_many(point, SHADOW_GLYPH, end.column - start.column + 1, persistent);
+
+            for line in start.line + 1..=end.line {
+                let point = Point { column: end.column + 1, line };
+                self.write_many(point, SHADOW_GLYPH, 1, persistent);
+            }
+        }
+    }
+
+    /// Write a table with evenly distributed rows and columns.
+    ///
+    /// Rows and columns which do not fit into the table are dropped.
+    pub fn write_table(&mut self, start: Point, end: Point, size: (usize, usize), mode: WriteMode) {
+        // Erasing table drawing mode does not exist.
+        if mode == WriteMode::Erase {
+            return;
+        }
+        let persistent = mode == WriteMode::Write;
+
+        let (start, end) = Point::rect(start, end);
+        let dividers = |start: usize, end: usize, count: usize| {
+            let mut dividers: Vec<_> =
+                (0..=count).map(|index| start + (end - start) * index / count).collect();
+            dividers.dedup();
+            dividers
+        };
+        let row_dividers = dividers(start.line, end.line, size.0);
+        let column_dividers = dividers(start.column, end.column, size.1);
+
+        // Connect the borders and dividers with the matching junctions.
+        let weight = if self.brush.emphasis { Weight::Heavy } else { Weight::Light };
+        let segment = |present: bool| if present { weight } else { Weight::None };
+        for line in start.line..=end.line {
+            let horizontal = row_dividers.contains(&line);
+            for column in start.column..=end.column {
+                let vertical = column_dividers.contains(&column);
+                let segments = [
+                    segment(vertical && line > start.line),
+                    segment(horizontal && column < end.column),
+                    segment(vertical && line < end.line),
+                    segment(horizontal && column > start.column),
+                ];
+
+                if let Some(c) = junction::glyph(segments) {
+                    self.write_merged(Point { column, line }, c, persistent);
+                }
+            }
+        }
+    }
+
+    /// Write a one-dimensional line.
+    ///
+    /// If `snap` is set, the line is constrained to the closest horizontal,
+    /// vertical, or diagonal direction. Otherwise only horizontal and vertical
+    /// lines are drawn.
+    pub fn write_line(&mut self, start: Point, end: Point, snap: bool, mode: WriteMode) {
+        // Erasing line drawing mode does not exist.
+        if mode == WriteMode::Erase {
+            return;
+        }
+        let persistent = mode == WriteMode::Write;
+
+        // Check the brush travel in X and Y direction.
+        let min_column = min(start.column, end.column);
+        let column_delta = max(start.column, end.column) - min_column;
+        let min_line = min(start.line, end.line);
+        let max_line = max(start.line, end.line);
+        let line_delta = max_line - min_line;
+
+        // Emphasized lines are two cells thick.
+        let thickness = if self.brush.emphasis { 2 } else { 1 };
+
+        let dx = end.column as isize - start.column as isize;
+        let dy = end.line as isize - start.line as isize;
+        let direction = if snap {
+            LineDirection::snapped(dx, dy)
+        } else if column_delta >= line_delta * 2 {
+            LineDirection::Horizontal
+        } else {
+            LineDirection::Vertical
+        };
+
+        // Write the line.
+        match direction {
+            LineDirection::Horizontal => {
+                let count = (column_delta + 1) / self.brush.width();
+                for line in start.line..start.line + thickness {
+                    let point = Point { column: min_column, line };
+                    self.write_merged_pattern(point, count, persistent);
+                }
+            },
+            LineDirection::Vertical => {
+                for line in min_line..=max_line {
+                    for column in start.column..start.column + thickness * self.brush.width() {
+                        let point = Point { column, line };
+                        self.write_merged_glyph(point, &self.brush.glyph(column), persistent);
+                    }
+                }
+            },
+            LineDirection::Diagonal => {
+                // Move multiple columns per line to keep a 45 degree angle on screen.
+                let step_width = geometry::CELL_ASPECT_RATIO as isize;
+                let steps = (dx.abs() / step_width + dy.abs() + 1) / 2;
+
+                for step in 0..=steps {
+                    // Write each step to the left of the line's origin when moving left.
+                    let mut column = start.column as isize + dx.signum() * step * step_width;
+                    if dx < 0 {
+                        column -= step_width - 1;
+                    }
+                    let line = start.line as isize + dy.signum() * step;
+
+                    for line in line..line + thickness as isize {
+                        let point = match (usize::try_from(column), usize::try_from(line)) {
+                            (Ok(column), Ok(line)) if column > 0 && line > 0 => {
+                                Point { column, line }
+                            },
+                            _ => continue,
+                        };
+                        self.write_merged_pattern(
+                            point,
+                            step_width as usize / self.brush.width(),
+                            persistent,
+                        );
+                    }
+                }
+            },
+        }
+    }
+
+    /// Move cells by an offset.
+    ///
+    /// Vacated cells are cleared, unless the cells are duplicated. Cells moved
+    /// outside of the grid are dropped.
+    pub fn move_region(
+        &mut self,
+        points: &[Point],
+        offset: (isize, isize),
+        duplicate: bool,
+        mode: WriteMode,
+    ) {
+        let persistent = mode == WriteMode::Write;
+
+        // Copy the cells before clearing, since regions might overlap after moving.
+        let cells: Vec<_> = points
+            .iter()
+            .map(|point| {
+                let cell = self.grid.get(*point);
+                let c = if cell.c == '\0' { ' ' } else { cell.c };
+                (*point, Cell { c, ..cell.clone() })
+            })
+            .collect();
+
+        let blank = Cell::new(' ', Color::default(), Color::default(), TextStyle::default());
+        for point in points.iter().filter(|_| !duplicate) {
+            match persistent {
+                true => self.clear_cell(*point),
+                false => _ = self.write_cell(*point, &blank, false),
+            }
+        }
+
+        // Keep the content below transparent cells at the new location.
+        for (point, cell) in cells.iter().filter(|(_, cell)| !cell.transparent) {
+            if let Some(point) = point.offset(offset) {
+                self.write_cell(point, cell, persistent);
+            }
+        }
+    }
+
+    /// Write an ellipse around a center point.
+    ///
+    /// The `end` point determines the ellipse's radii. Unless `free` is set,
+    /// the ellipse is constrained to a circle passing through `end`.
+    pub fn write_ellipse(&mut self, center: Point, end: Point, free: bool, mode: WriteMode) {
+        // Erasing ellipse drawing mode does not exist.
+        if mode == WriteMode::Erase {
+            return;
+        }
+        let persistent = mode == WriteMode::Write;
+
+        let dx = end.column as isize - center.column as isize;
+        let dy = end.line as isize - center.line as isize;
+        let (radius_x, radius_y) = if free {
+            (dx.unsigned_abs(), dy.unsigned_abs())
+        } else {
+            geometry::circle_radii(dx, dy, !self.true_geometry)
+        };
+
+        for (x, y) in geometry::ellipse(radius_x, radius_y) {
+            // Skip cells outside the top left of the grid.
+            let column = usize::try_from(center.column as isize + x);
+            let line = usize::try_from(center.line as isize + y);
+            let point = match (column, line) {
+                (Ok(column), Ok(line)) if column > 0 && line > 0 => Point { column, line },
+                _ => continue,
+            };
+
+            self.write_many(point, &self.brush.glyph(point.column), 1, persistent);
+        }
+    }
+
+    /// Flood-fill the region around a point with the brush pattern.
+    pub fn fill(&mut self, origin: Point) {
+        // Use cell at the origin as template for filling.
+        let template = self.grid.get(origin).clone();
+
+        // Setup the initial fill queue ranges.
+        let Point { mut column, line } = origin;
+        self.fill_queue.clear();
+        self.fill_queue.push_back((column, column, line, 1));
+        self.fill_queue.push_back((column, column, line - 1, -1));
+
+        while let Some((mut start_column, end_column, line, line_delta)) =
+            self.fill_queue.pop_front()
+        {
+            // Fill all empty cells to the left of this range's start.
+            column = start_column;
+            if self.fillable(column, line, &template) {
+                // Fill empty cells until we've reached a boundary on the left.
+                while self.fillable(column - 1, line, &template) {
+                    let glyph = self.brush.glyph(column - 1);
+                    self.write_many(Point { line, column: column - 1 }, &glyph, 1, true);
+                    column -= 1;
+                }
+
+                // Add new queue element to search in the opposite vertical direction if our
+                // horizontal range expanded to the left.
+                if column < start_column {
+                    let next_line = (line as isize - line_delta) as usize;
+                    self.fill_queue.push_back((column, start_column - 1, next_line, -line_delta));
+                }
+            }
+
+            // Fill all empty cells to the right of this range's start.
+            while start_column <= end_column {
+                // Fill empty cells until we've reached a boundary on the right.
+                while self.fillable(start_column, line, &template) {
+                    let glyph = self.brush.glyph(start_column);
+                    self.write_many(Point { line, column: start_column }, &glyph, 1, true);
+                    start_column += 1;
+                }
+
+                // If the range isn't empty, search it again on the next line.
+                if start_column > column {
+                    let next_line = (line as isize + line_delta) as usize;
+                    self.fill_queue.push_back((column, start_column - 1, next_line, line_delta));
+                }
+
+                // Add new queue element to search in the opposite vertical direction if our
+                // horizontal range expanded to the right.
+                if start_column - 1 > end_column {
+                    let next_line = (line as isize - line_delta) as usize;
+                    let start = end_column + 1;
+                    let end = start_column - 1;
+                    self.fill_queue.push_back((start, end, next_line, -line_delta));
+                }
+
+                // Skip over occupied cells if we're not yet at the right boundary of the range.
+                start_column += 1;
+                while start_column < end_column && !self.fillable(start_column, line, &template) {
+                    start_column += 1;
+                }
+
+                // Update right boundary of the last added range.
+                column = start_column;
+            }
+        }
+    }
+
+    /// Check if a cell can be filled, without leaking into locked regions.
+    fn fillable(&self, column: usize, line: usize, template: &Cell) -> bool {
+        self.grid.cell_matches(column, line, template) && !self.is_locked(Point { column, line })
+    }
+}
+
+/// Cells written to the canvas.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Stroke {
+    /// Run of identical cells shown without modifying the grid.
+    Preview { start: Point, cell: Cell, count: usize },
+    /// Run of identical cells stored in the grid.
+    Write { start: Point, cell: Cell, count: usize },
+    /// Cells reset to their transparent default.
+    Clear(Vec<Point>),
+}
+
+/// Modes for writing text to the grid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WriteMode {
+    /// Preview the write without storing the result.
+    WriteVolatile,
+    /// Write to the grid.
+    Write,
+    /// Write whitespace to erase content from the grid.
+    Erase,
+}
+
+/// Glyphs used for drawing boxes.
+struct BoxGlyphs {
+    horizontal: char,
+    vertical: char,
+    top_left: char,
+    top_right: char,
+    bottom_left: char,
+    bottom_right: char,
+    tee_right: char,
+    tee_left: char,
+    tee_down: char,
+    tee_up: char,
+    cross: char,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::journal::Journal;
+
+    /// Create a canvas with an empty grid.
+    fn canvas(columns: usize, lines: usize) -> Canvas {
+        let grid = Grid::new(vec![vec![Cell::default(); columns]; lines], Journal::default());
+        Canvas::new(grid, Brush::default())
+    }
+
+    /// Get the glyphs of a grid line.
+    fn text(canvas: &Canvas, line: usize) -> String {
+        canvas.grid[line - 1]
+            .iter()
+            .map(|cell| if cell.is_empty() { ' ' } else { cell.c })
+            .collect()
+    }
+
+    #[test]
+    fn strokes() {
+        let mut canvas = canvas(5, 3);
+
+        let end = Point { column: 3, line: 2 };
+        canvas.write_box(Point { column: 1, line: 1 }, end, WriteMode::Write);
+        assert_eq!(text(&canvas, 1), "┌─┐  ");
+        assert_eq!(text(&canvas, 2), "└─┘  ");
+        assert!(canvas.take_strokes().iter().all(|stroke| matches!(stroke, Stroke::Write { .. })));
+
+        // Previews are only reported, without modifying the grid.
+        let start = Point { column: 1, line: 3 };
+        canvas.write_pattern(start, 5, false);
+        assert_eq!(text(&canvas, 3), "     ");
+        assert!(matches!(
+            &canvas.take_strokes()[..],
+            [Stroke::Preview { start: point, count: 5, .. }] if *point == start
+        ));
+
+        canvas.clear_cell(Point { column: 2, line: 1 });
+        assert_eq!(text(&canvas, 1), "┌ ┐  ");
+        assert_eq!(canvas.take_strokes(), [Stroke::Clear(vec![Point { column: 2, line: 1 }])]);
+    }
+
+    #[test]
+    fn locked_fill() {
+        let mut canvas = canvas(4, 2);
+        canvas.locked.push((Point { column: 3, line: 1 }, Point { column: 4, line: 2 }));
+
+        canvas.fill(Point { column: 1, line: 1 });
+        assert_eq!(text(&canvas, 1), "++  ");
+        assert_eq!(text(&canvas, 2), "++  ");
+    }
+}
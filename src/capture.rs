@@ -0,0 +1,21 @@
+use std::io;
+use std::process::Command;
+
+/// Read the visible screen of the terminal this process is running in.
+///
+/// The screen content includes SGR escapes for colors and text styles. There
+/// is no widely supported escape sequence for dumping the screen, so this is
+/// only supported inside tmux.
+pub fn screen() -> io::Result<String> {
+    let pane = std::env::var("TMUX_PANE").map_err(|_| {
+        io::Error::new(io::ErrorKind::Unsupported, "screen capture is only supported in tmux")
+    })?;
+
+    let output = Command::new("tmux").args(["capture-pane", "-p", "-e", "-t", &pane]).output()?;
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!("tmux capture-pane failed: {}", error.trim())));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
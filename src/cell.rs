@@ -0,0 +1,125 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::color::{Color, TextStyle};
+
+/// Content of a cell in the grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    // Cell contents.
+    pub c: char,
+    /// Characters joined with `c` into a single grapheme cluster.
+    ///
+    /// This includes combining marks, variation selectors, and emoji joined by
+    /// zero width joiners. These never affect the width of the cell.
+    pub combining: String,
+    pub foreground: Color,
+    pub background: Color,
+    pub style: TextStyle,
+    /// Whether the cell was never written to.
+    ///
+    /// Unlike blank cells deliberately painted with the default background,
+    /// transparent cells let the content below them show through when
+    /// compositing layers or pasting selections.
+    pub transparent: bool,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        let cell = Self::new('\0', Color::default(), Color::default(), TextStyle::default());
+        Self { transparent: true, ..cell }
+    }
+}
+
+impl Cell {
+    pub fn new(c: char, foreground: Color, background: Color, style: TextStyle) -> Self {
+        Self { c, style, foreground, background, combining: String::new(), transparent: false }
+    }
+
+    /// Create a cell from a grapheme cluster.
+    ///
+    /// Returns `None` if the glyph is empty.
+    pub fn with_glyph(
+        glyph: &str,
+        foreground: Color,
+        background: Color,
+        style: TextStyle,
+    ) -> Option<Self> {
+        let mut chars = glyph.chars();
+        let mut cell = Self::new(chars.next()?, foreground, background, style);
+        cell.combining = chars.as_str().into();
+        Some(cell)
+    }
+
+    /// Full grapheme cluster of the cell.
+    pub fn glyph(&self) -> String {
+        let mut glyph = String::new();
+        self.push_glyph(&mut glyph);
+        glyph
+    }
+
+    /// Append the cell's grapheme cluster to a string.
+    pub fn push_glyph(&self, text: &mut String) {
+        text.push(self.c);
+        text.push_str(&self.combining);
+    }
+
+    /// Join a character with the cell's grapheme cluster.
+    ///
+    /// Returns `false` without modifying the cell if the character would start
+    /// a new grapheme cluster.
+    pub fn combine(&mut self, c: char) -> bool {
+        if self.c == '\0' {
+            return false;
+        }
+
+        let mut glyph = self.glyph();
+        glyph.push(c);
+        if glyph.graphemes(true).count() != 1 {
+            return false;
+        }
+
+        self.combining.push(c);
+        true
+    }
+
+    /// Check if the cell contains a grapheme cluster.
+    pub fn is_glyph(&self, glyph: &str) -> bool {
+        let mut chars = glyph.chars();
+        chars.next() == Some(self.c) && chars.as_str() == self.combining
+    }
+
+    /// Check if cell has any visible content.
+    pub fn is_empty(&self) -> bool {
+        (self.c.is_whitespace() || self.c == '\0') && self.background == Color::default()
+    }
+
+    /// Check if this cell has no visible glyph.
+    pub fn is_blank(&self) -> bool {
+        self.c.is_whitespace() || self.c == '\0'
+    }
+
+    /// Copy of this cell with a different background, keeping the glyph.
+    pub fn with_background(&self, background: Color) -> Self {
+        Self { background, ..self.clone() }
+    }
+
+    /// Copy of this cell with a different foreground, keeping the glyph.
+    pub fn with_foreground(&self, foreground: Color) -> Self {
+        Self { foreground, ..self.clone() }
+    }
+
+    /// Copy of this cell with a different text style, keeping glyph and colors.
+    pub fn with_style(&self, style: TextStyle) -> Self {
+        Self { style, ..self.clone() }
+    }
+
+    /// Check if this cell's content matches another's.
+    pub fn content_eq(&self, other: &Cell) -> bool {
+        let both_whitespace = (self.c.is_whitespace() || self.c == '\0')
+            && (other.c.is_whitespace() || other.c == '\0');
+        let c_matches = self.c == other.c || both_whitespace;
+        c_matches
+            && self.background == other.background
+            && (self.foreground == other.foreground || both_whitespace)
+    }
+}
@@ -0,0 +1,177 @@
+/// Block characters for each eighth of a cell's height.
+const EIGHTHS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Chart style.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum ChartKind {
+    /// Vertical bars with a value axis and numbered bars.
+    #[default]
+    Bars,
+    /// Single line of bars between the minimum and maximum value.
+    Sparkline,
+}
+
+impl ChartKind {
+    /// Switch to the other chart style.
+    pub fn toggle(self) -> Self {
+        match self {
+            Self::Bars => Self::Sparkline,
+            Self::Sparkline => Self::Bars,
+        }
+    }
+
+    /// Human-readable name of the chart style.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bars => "bar chart",
+            Self::Sparkline => "sparkline",
+        }
+    }
+}
+
+/// Parse a list of numbers separated by commas or whitespace.
+///
+/// Returns `None` if any number is malformed or the list is empty.
+pub fn parse(values: &str) -> Option<Vec<f64>> {
+    let values = values
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse().ok().filter(|value: &f64| value.is_finite()))
+        .collect::<Option<Vec<_>>>()?;
+    (!values.is_empty()).then_some(values)
+}
+
+/// Render values as a chart filling `width` columns and `lines` lines.
+///
+/// Returns `None` if the chart does not fit into the available space.
+pub fn render(values: &[f64], kind: ChartKind, width: usize, lines: usize) -> Option<Vec<String>> {
+    match kind {
+        ChartKind::Bars => bars(values, width, lines),
+        ChartKind::Sparkline => sparkline(values, width),
+    }
+}
+
+/// Render values as vertical bars, labeling the value axis and every bar.
+fn bars(values: &[f64], width: usize, lines: usize) -> Option<Vec<String>> {
+    let (base, top) = range(values);
+    let (base_label, top_label) = (label(base), label(top));
+    let label_width = base_label.len().max(top_label.len());
+
+    // Reserve space for axis labels, axes, and bar numbers.
+    let plot_width = width.checked_sub(label_width + 2)?;
+    let plot_lines = lines.checked_sub(2).filter(|lines| *lines > 0)?;
+    let slot = plot_width / values.len();
+    if slot == 0 {
+        return None;
+    }
+
+    // Leave a gap between bars when there is room for it.
+    let bar_width = if slot > 1 { slot - 1 } else { 1 };
+    let eighths: Vec<usize> = values
+        .iter()
+        .map(|value| ((value - base) / (top - base) * (plot_lines * 8) as f64).round() as usize)
+        .collect();
+
+    let mut chart = Vec::with_capacity(lines);
+    for line in 0..plot_lines {
+        let axis_label = if line == 0 { top_label.as_str() } else { "" };
+        let axis = if line == 0 { '┤' } else { '│' };
+        let mut text = format!("{axis_label:>label_width$} {axis}");
+
+        let floor = (plot_lines - 1 - line) * 8;
+        for eighths in &eighths {
+            let glyph = EIGHTHS[eighths.saturating_sub(floor).min(8)];
+            text.push_str(&" ".repeat(slot - bar_width));
+            text.push_str(&glyph.to_string().repeat(bar_width));
+        }
+        chart.push(text.trim_end().into());
+    }
+
+    chart.push(format!("{base_label:>label_width$} └{}", "─".repeat(plot_width)));
+
+    // Number the bars, skipping labels which would overlap their neighbors.
+    let mut numbers = " ".repeat(label_width + 2);
+    for index in 0..values.len() {
+        let number = (index + 1).to_string();
+        let column = label_width + 2 + index * slot + slot - bar_width;
+        if index == 0 || column > numbers.len() {
+            numbers.push_str(&" ".repeat(column - numbers.len()));
+            numbers.push_str(&number);
+        }
+    }
+    chart.push(numbers.trim_end().into());
+
+    Some(chart)
+}
+
+/// Render values as a sparkline, enclosed by the minimum and maximum value.
+fn sparkline(values: &[f64], width: usize) -> Option<Vec<String>> {
+    let (min, max) = values
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(min, max), value| (min.min(*value), max.max(*value)));
+    let (min_label, max_label) = (label(min), label(max));
+    if min_label.len() + values.len() + max_label.len() + 2 > width {
+        return None;
+    }
+
+    let span = if max > min { max - min } else { 1. };
+    let bars: String = values
+        .iter()
+        .map(|value| EIGHTHS[1 + ((value - min) / span * 7.).round() as usize])
+        .collect();
+
+    Some(vec![format!("{min_label} {bars} {max_label}")])
+}
+
+/// Value range covered by the bar chart, always including zero.
+fn range(values: &[f64]) -> (f64, f64) {
+    let base = values.iter().copied().fold(0., f64::min);
+    let top = values.iter().copied().fold(0., f64::max);
+    if top > base {
+        (base, top)
+    } else {
+        (base, base + 1.)
+    }
+}
+
+/// Format a value with at most two decimal places.
+fn label(value: f64) -> String {
+    let label = format!("{value:.2}");
+    let label = label.trim_end_matches('0').trim_end_matches('.');
+    if label == "-0" {
+        "0".into()
+    } else {
+        label.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(parse("1, 2.5 -3\t4"), Some(vec![1., 2.5, -3., 4.]));
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("1,x"), None);
+        assert_eq!(parse("inf"), None);
+    }
+
+    #[test]
+    fn bar_chart() {
+        let chart = render(&[1., 4., 2.], ChartKind::Bars, 12, 4).unwrap();
+        let expected = ["4 ┤    ██", "  │ ▄▄ ██ ██", "0 └─────────", "    1  2  3"];
+        assert_eq!(chart, expected);
+
+        assert_eq!(render(&[1., 4., 2.], ChartKind::Bars, 5, 4), None);
+        assert_eq!(render(&[1., 4., 2.], ChartKind::Bars, 11, 2), None);
+    }
+
+    #[test]
+    fn sparkline_chart() {
+        let chart = render(&[0., 3.5, 7., 1.], ChartKind::Sparkline, 20, 1).unwrap();
+        assert_eq!(chart, ["0 ▁▅█▂ 7"]);
+
+        assert_eq!(render(&[0., 3.5, 7., 1.], ChartKind::Sparkline, 7, 1), None);
+    }
+}
@@ -1,14 +1,323 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::builder::{PossibleValue, PossibleValuesParser, RangedU64ValueParser};
+use clap::{Parser, Subcommand, ValueEnum};
+use sketch::export::{self, Background, ExportSettings, SgrEncoding};
+use sketch::palette::Palette;
+
+use crate::terminal::{Color, NamedColor};
 
 #[derive(Parser, Debug)]
 #[command(author, about, version)]
 pub struct Options {
+    /// Process a sketch without starting the interface.
+    #[command(subcommand)]
+    pub command: Option<Command>,
     /// Existing sketch file.
     #[clap(short, long)]
     pub file: Option<PathBuf>,
+    /// Start with the visible screen of the terminal, only supported in tmux.
+    #[clap(long, conflicts_with_all = ["file", "replay_input"])]
+    pub capture: bool,
+    /// Sketch shown as dimmed tracing layer below the canvas.
+    #[clap(long, value_name = "FILE")]
+    pub trace: Option<PathBuf>,
     /// Output file.
     #[clap(short, long)]
     pub output: Option<PathBuf>,
+    /// Write the sketch to STDOUT on exit, using the controlling terminal for
+    /// the interface.
+    #[clap(long, conflicts_with = "output")]
+    pub stdout: bool,
+    /// Print a short summary of the sketch after exiting.
+    #[clap(long)]
+    pub summary: bool,
+    /// Record the drawing process, to allow saving it as asciicast (.cast).
+    /// Always enabled when the output file is an asciicast.
+    #[clap(long)]
+    pub record: bool,
+    /// Draw in this many lines below the shell prompt, keeping the rest of the
+    /// screen intact.
+    #[clap(
+        long,
+        value_name = "LINES",
+        value_parser = clap::value_parser!(u16).range(1..),
+        conflicts_with = "replay_input"
+    )]
+    pub inline: Option<u16>,
+    /// Fixed canvas width, independent of the terminal size.
+    #[clap(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub width: Option<usize>,
+    /// Fixed canvas height, independent of the terminal size.
+    #[clap(long, value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub height: Option<usize>,
+    /// Ruler marking guide columns with `|` or `+`, like "+-------+-------+".
+    #[clap(long)]
+    pub ruler: Option<String>,
+    /// Part of the sketch that stays in place when the terminal is resized.
+    #[clap(long, value_enum, default_value_t)]
+    pub anchor: Anchor,
+    /// Separator between the parameters of exported 256 and truecolor escapes.
+    #[clap(long, value_enum, default_value_t)]
+    pub sgr_separator: SgrSeparator,
+    /// Reduce exported colors to a palette, for viewing sketches in terminals
+    /// with limited color support.
+    #[clap(long, value_enum, value_name = "PALETTE")]
+    pub export_palette: Option<ExportPalette>,
+    /// Background of cells without background color in HTML and SVG exports.
+    #[clap(long, value_enum, default_value_t)]
+    pub export_background: ExportBackground,
+    /// Periodically save the canvas to a file, recovering it on startup when
+    /// no sketch file is passed.
+    #[clap(long, value_name = "FILE", conflicts_with = "replay_input")]
+    pub autosave: Option<PathBuf>,
+    /// Record all terminal input to a file.
+    #[clap(long, value_name = "FILE")]
+    pub record_input: Option<PathBuf>,
+    /// Replay recorded terminal input without any interface.
+    #[clap(long, value_name = "FILE", conflicts_with_all = ["record_input", "stdout"])]
+    pub replay_input: Option<PathBuf>,
+    /// Compare the replayed sketch against a file, failing if they differ.
+    #[clap(long, value_name = "FILE", requires = "replay_input")]
+    pub assert_output: Option<PathBuf>,
+    /// Paste service used for sharing sketches.
+    #[clap(long, value_name = "HOST:PORT", default_value = "termbin.com:9999")]
+    pub share_host: String,
+    /// Drawing mode of the left mouse button.
+    #[clap(long, value_enum, default_value_t)]
+    pub mode: DrawingMode,
+    /// Brush glyph, or a pattern of multiple glyphs.
+    #[clap(long)]
+    pub glyph: Option<String>,
+    /// Brush foreground color, as name, palette index, or `#rrggbb`.
+    #[clap(long, value_parser = parse_color)]
+    pub fg: Option<Color>,
+    /// Brush background color, as name, palette index, or `#rrggbb`.
+    #[clap(long, value_parser = parse_color)]
+    pub bg: Option<Color>,
+    /// Brush size.
+    #[clap(long, value_parser = clap::value_parser!(u8).range(1..))]
+    pub size: Option<u8>,
+    /// Accept drawing commands from other programs on a Unix domain socket.
+    #[clap(long, value_name = "FILE", conflicts_with = "replay_input")]
+    pub control_socket: Option<PathBuf>,
+}
+
+/// Non-interactive operations on sketch files.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Convert a sketch to another format.
+    Convert {
+        /// Sketch file, `-` for STDIN.
+        input: PathBuf,
+        /// Output format.
+        #[clap(long, value_parser = export_formats())]
+        to: String,
+        /// Output file, STDOUT if omitted.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Remove empty lines and columns surrounding a sketch.
+    Trim {
+        /// Sketch file, `-` for STDIN.
+        input: PathBuf,
+        /// Output file, STDOUT if omitted.
+        #[clap(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+impl Options {
+    /// Canvas columns marked as guides by the ruler.
+    pub fn guides(&self) -> Vec<usize> {
+        let ruler = self.ruler.as_deref().unwrap_or_default();
+        ruler
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| matches!(c, '|' | '+'))
+            .map(|(i, _)| i + 1)
+            .collect()
+    }
+
+    /// Encoding of colors in exported escapes.
+    pub fn sgr_encoding(&self) -> SgrEncoding {
+        let palette = self.export_palette.map(ExportPalette::palette);
+        SgrEncoding { separator: self.sgr_separator.char(), palette }
+    }
+
+    /// Settings of all export formats.
+    pub fn export_settings(&self) -> ExportSettings {
+        let background = self.export_background.background();
+        ExportSettings { encoding: self.sgr_encoding(), background }
+    }
+}
+
+/// Separator between the parameters of extended SGR color escapes.
+#[derive(ValueEnum, Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SgrSeparator {
+    /// ITU T.416 style, like `38:2:r:g:b`.
+    #[default]
+    Colon,
+    /// Legacy style used by tmux and screen, like `38;2;r;g;b`.
+    Semicolon,
+}
+
+impl SgrSeparator {
+    /// Separator character.
+    pub fn char(self) -> char {
+        match self {
+            Self::Colon => ':',
+            Self::Semicolon => ';',
+        }
+    }
+}
+
+/// Background of cells without background color in HTML and SVG exports.
+#[derive(ValueEnum, Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ExportBackground {
+    /// Let the document below the export show through.
+    #[default]
+    Transparent,
+    /// Mark transparent cells with a checkerboard pattern.
+    Checkerboard,
+}
+
+impl ExportBackground {
+    /// Background rendered by the exporters.
+    pub fn background(self) -> Background {
+        match self {
+            Self::Transparent => Background::Transparent,
+            Self::Checkerboard => Background::Checkerboard,
+        }
+    }
+}
+
+/// Palette exported colors can be reduced to.
+#[derive(ValueEnum, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ExportPalette {
+    /// The 16 CTerm colors.
+    Ansi16,
+    /// XTerm's 256 color palette.
+    Xterm256,
+}
+
+impl ExportPalette {
+    /// Palette used for color matching.
+    pub fn palette(self) -> Palette {
+        match self {
+            Self::Ansi16 => Palette::Ansi16,
+            Self::Xterm256 => Palette::Xterm256,
+        }
+    }
+}
+
+/// Extensions of all export formats, accepted as output format.
+fn export_formats() -> PossibleValuesParser {
+    let exporters = export::exporters(ExportSettings::default());
+    let formats = exporters
+        .iter()
+        .map(|exporter| PossibleValue::new(exporter.extension()).help(exporter.name()));
+    PossibleValuesParser::new(formats)
+}
+
+/// Action performed by clicking or dragging the left mouse button.
+#[derive(ValueEnum, Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DrawingMode {
+    /// Freehand drawing with the brush.
+    #[default]
+    Brush,
+    /// Box drawing by clicking two corners.
+    Box,
+    /// Line drawing by dragging.
+    Line,
+    /// Text cursor placement.
+    Text,
+}
+
+impl DrawingMode {
+    /// Get the mode following this one.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Brush => Self::Box,
+            Self::Box => Self::Line,
+            Self::Line => Self::Text,
+            Self::Text => Self::Brush,
+        }
+    }
+
+    /// Human-readable name of the mode.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Brush => "brush",
+            Self::Box => "box",
+            Self::Line => "line",
+            Self::Text => "text",
+        }
+    }
+
+    /// Whether the mode draws lines and boxes.
+    pub fn draws_lines(self) -> bool {
+        matches!(self, Self::Box | Self::Line)
+    }
+}
+
+/// Parse a color name, 256-color palette index, or hex color.
+pub fn parse_color(text: &str) -> Result<Color, String> {
+    let named = match text.to_ascii_lowercase().as_str() {
+        "default" => Some(NamedColor::Default),
+        "black" => Some(NamedColor::Black),
+        "red" => Some(NamedColor::Red),
+        "green" => Some(NamedColor::Green),
+        "yellow" => Some(NamedColor::Yellow),
+        "blue" => Some(NamedColor::Blue),
+        "magenta" => Some(NamedColor::Magenta),
+        "cyan" => Some(NamedColor::Cyan),
+        "white" => Some(NamedColor::White),
+        _ => None,
+    };
+
+    if let Some(named) = named {
+        Ok(Color::Named(named))
+    } else if let Ok(index) = text.parse() {
+        Ok(Color::Indexed(index))
+    } else if let Ok(rgb) = text.strip_prefix('#').unwrap_or(text).parse() {
+        Ok(Color::Rgb(rgb))
+    } else {
+        Err(String::from("expected color name, palette index, or #rrggbb"))
+    }
+}
+
+/// Position of the sketch content within the terminal.
+#[derive(ValueEnum, Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Share of added or removed space that is placed before the content.
+    ///
+    /// Returns the horizontal and vertical share in halves, so `1` puts half
+    /// of the space before the content and the other half after it.
+    pub fn leading_share(self) -> (usize, usize) {
+        match self {
+            Self::TopLeft => (0, 0),
+            Self::Top => (1, 0),
+            Self::TopRight => (2, 0),
+            Self::Left => (0, 1),
+            Self::Center => (1, 1),
+            Self::Right => (2, 1),
+            Self::BottomLeft => (0, 2),
+            Self::Bottom => (1, 2),
+            Self::BottomRight => (2, 2),
+        }
+    }
 }
@@ -0,0 +1,238 @@
+use std::str::FromStr;
+
+use bitflags::bitflags;
+
+/// Terminal color.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Color {
+    Named(NamedColor),
+    Indexed(u8),
+    Rgb(Rgb),
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::Named(NamedColor::Default)
+    }
+}
+
+impl Color {
+    pub fn escape(&self, foreground: bool) -> String {
+        self.escape_with_separator(foreground, ':')
+    }
+
+    /// SGR escape using a custom separator for indexed and RGB parameters.
+    pub fn escape_with_separator(&self, foreground: bool, s: char) -> String {
+        match (self, foreground) {
+            // Foreground:
+            (Color::Named(color), true) => format!("\x1b[3{}m", *color as u8),
+            (Color::Indexed(color), true) => format!("\x1b[38{s}5{s}{}m", color),
+            (Color::Rgb(Rgb { r, g, b }), true) => format!("\x1b[38{s}2{s}{}{s}{}{s}{}m", r, g, b),
+            // Background:
+            (Color::Named(color), false) => format!("\x1b[4{}m", *color as u8),
+            (Color::Indexed(color), false) => format!("\x1b[48{s}5{s}{}m", color),
+            (Color::Rgb(Rgb { r, g, b }), false) => format!("\x1b[48{s}2{s}{}{s}{}{s}{}m", r, g, b),
+        }
+    }
+
+    /// Approximate RGB value of the color.
+    ///
+    /// Named and indexed colors are based on XTerm's default palette. Since the
+    /// default color depends on the terminal, it has no RGB value.
+    pub fn rgb(&self) -> Option<Rgb> {
+        let index = match self {
+            Color::Rgb(rgb) => return Some(*rgb),
+            Color::Named(NamedColor::Default) => return None,
+            Color::Named(color) => *color as u8,
+            Color::Indexed(index) => *index,
+        };
+
+        let rgb = match index {
+            // Regular and bright CTerm colors.
+            0..=15 => {
+                let (r, g, b) = ANSI_COLORS[index as usize];
+                Rgb { r, g, b }
+            },
+            // 6x6x6 color cube.
+            16..=231 => {
+                let index = index - 16;
+                let level = |value: u8| if value == 0 { 0 } else { value * 40 + 55 };
+                Rgb { r: level(index / 36), g: level(index / 6 % 6), b: level(index % 6) }
+            },
+            // Grayscale ramp.
+            232..=255 => {
+                let value = (index - 232) * 10 + 8;
+                Rgb { r: value, g: value, b: value }
+            },
+        };
+
+        Some(rgb)
+    }
+}
+
+/// RGB values of the 16 CTerm colors.
+const ANSI_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// CTerm color.
+#[allow(unused)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NamedColor {
+    Black = 0,
+    Red = 1,
+    Green = 2,
+    Yellow = 3,
+    Blue = 4,
+    Magenta = 5,
+    Cyan = 6,
+    White = 7,
+    Default = 9,
+}
+
+/// RGB color.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    /// Perceived brightness of the color from `0` to `255`.
+    pub fn luminance(&self) -> u8 {
+        let luminance = 0.299 * self.r as f32 + 0.587 * self.g as f32 + 0.114 * self.b as f32;
+        luminance.round() as u8
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 6 {
+            return Err(());
+        }
+
+        Ok(Rgb {
+            r: u8::from_str_radix(&s[0..2], 16).map_err(|_| ())?,
+            g: u8::from_str_radix(&s[2..4], 16).map_err(|_| ())?,
+            b: u8::from_str_radix(&s[4..6], 16).map_err(|_| ())?,
+        })
+    }
+}
+
+bitflags! {
+    /// Text character style.
+    #[derive(PartialEq, Eq, Copy, Clone, Debug)]
+    pub struct TextStyle: u8 {
+        const BOLD    = 1 << 0;
+        const ITALICS = 1 << 1;
+        const DIM     = 1 << 2;
+    }
+}
+
+impl TextStyle {
+    /// Get the escape sequence to enable this style.
+    ///
+    /// Intensity is always reset first, since bold and dim are independent
+    /// attributes in most terminals.
+    pub fn escape(&self) -> &'static str {
+        let italics = self.contains(Self::ITALICS);
+        match (self.intensity(), italics) {
+            (Intensity::Bold, true) => "\x1b[22;1;3m",
+            (Intensity::Bold, false) => "\x1b[22;1;23m",
+            (Intensity::Dim, true) => "\x1b[22;2;3m",
+            (Intensity::Dim, false) => "\x1b[22;2;23m",
+            (Intensity::Normal, true) => "\x1b[22;3m",
+            (Intensity::Normal, false) => "\x1b[22;23m",
+        }
+    }
+
+    /// Get human-readable name of the current mode.
+    pub fn name(&self) -> &'static str {
+        let italics = self.contains(Self::ITALICS);
+        match (self.intensity(), italics) {
+            (Intensity::Bold, true) => "bold italic",
+            (Intensity::Bold, false) => "bold",
+            (Intensity::Dim, true) => "dim italic",
+            (Intensity::Dim, false) => "dim",
+            (Intensity::Normal, true) => "italic",
+            (Intensity::Normal, false) => "default",
+        }
+    }
+
+    /// Get the glyph intensity of this style.
+    ///
+    /// Bold takes precedence if both bold and dim are set.
+    pub fn intensity(&self) -> Intensity {
+        if self.contains(Self::BOLD) {
+            Intensity::Bold
+        } else if self.contains(Self::DIM) {
+            Intensity::Dim
+        } else {
+            Intensity::Normal
+        }
+    }
+
+    /// Copy of this style with a different glyph intensity.
+    pub fn with_intensity(&self, intensity: Intensity) -> Self {
+        let mut style = self.difference(Self::BOLD | Self::DIM);
+        match intensity {
+            Intensity::Bold => style.insert(Self::BOLD),
+            Intensity::Dim => style.insert(Self::DIM),
+            Intensity::Normal => (),
+        }
+        style
+    }
+}
+
+/// Glyph intensity, rendered with the bold and dim SGR attributes.
+#[derive(Default, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Intensity {
+    #[default]
+    Normal,
+    Dim,
+    Bold,
+}
+
+impl Intensity {
+    /// Get the intensity following this one.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Normal => Self::Dim,
+            Self::Dim => Self::Bold,
+            Self::Bold => Self::Normal,
+        }
+    }
+
+    /// Get human-readable name of the intensity.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Dim => "dim",
+            Self::Bold => "bold",
+        }
+    }
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle::empty()
+    }
+}
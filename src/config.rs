@@ -0,0 +1,144 @@
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use serde::Deserialize;
+use sketch::palette::Palette;
+
+/// Name of the per-project configuration file.
+const PROJECT_CONFIG_NAME: &str = ".sketch.toml";
+
+/// User and project configuration.
+#[derive(Deserialize, Default, Clone, PartialEq, Eq, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Fixed canvas width.
+    pub width: Option<usize>,
+    /// Fixed canvas height.
+    pub height: Option<usize>,
+    /// Palette preselected when quantizing colors.
+    pub palette: Option<Palette>,
+    /// Line weight of lines and boxes.
+    pub box_style: Option<BoxStyle>,
+    /// Directory suggested when saving sketches.
+    pub output_directory: Option<PathBuf>,
+    /// Maximum number of undoable revisions.
+    pub undo_depth: Option<usize>,
+    /// Use the kitty keyboard protocol when the terminal supports it.
+    pub kitty_keyboard: Option<bool>,
+    /// Repeat glyphs with the REP escape, instead of writing them literally.
+    pub repeat_escape: Option<bool>,
+    /// Restore the brush of the previous session on startup.
+    pub remember_brush: Option<bool>,
+    /// Distance in cells between the lines of the alignment grid.
+    pub grid_spacing: Option<usize>,
+    /// Empty cells kept around the sketch when cropping the canvas.
+    pub crop_padding: Option<usize>,
+    /// Ruler marking guide columns with `|` or `+`, like "+-------+-------+".
+    pub ruler: Option<String>,
+}
+
+impl Config {
+    /// Load the user configuration, overridden by the closest project
+    /// configuration.
+    ///
+    /// The project configuration is searched for in the current directory and
+    /// all its parents.
+    pub fn load() -> Self {
+        let user_config = user_config_path().and_then(|path| Self::from_file(&path));
+
+        let current_dir = env::current_dir().unwrap_or_default();
+        let project_config = current_dir
+            .ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG_NAME))
+            .find(|path| path.is_file())
+            .and_then(|path| Self::from_file(&path));
+
+        let config = user_config.unwrap_or_default();
+        match project_config {
+            Some(project_config) => config.merge(project_config),
+            None => config,
+        }
+    }
+
+    /// Read the configuration from a file.
+    ///
+    /// Invalid configuration files are reported and ignored.
+    fn from_file(path: &Path) -> Option<Self> {
+        let config = fs::read_to_string(path).ok()?;
+
+        match toml::from_str(&config) {
+            Ok(config) => Some(config),
+            Err(err) => {
+                eprintln!("Ignoring invalid config {}: {}", path.display(), err.message());
+                None
+            },
+        }
+    }
+
+    /// Combine two configurations, preferring options set in `other`.
+    fn merge(self, other: Self) -> Self {
+        Self {
+            width: other.width.or(self.width),
+            height: other.height.or(self.height),
+            palette: other.palette.or(self.palette),
+            box_style: other.box_style.or(self.box_style),
+            output_directory: other.output_directory.or(self.output_directory),
+            undo_depth: other.undo_depth.or(self.undo_depth),
+            kitty_keyboard: other.kitty_keyboard.or(self.kitty_keyboard),
+            repeat_escape: other.repeat_escape.or(self.repeat_escape),
+            remember_brush: other.remember_brush.or(self.remember_brush),
+            grid_spacing: other.grid_spacing.or(self.grid_spacing),
+            crop_padding: other.crop_padding.or(self.crop_padding),
+            ruler: other.ruler.or(self.ruler),
+        }
+    }
+}
+
+/// Line weight of lines and boxes.
+#[derive(Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum BoxStyle {
+    Light,
+    Heavy,
+}
+
+/// Location of the brush state shared between sessions.
+pub fn brush_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("brush"))
+}
+
+/// Location of the user configuration file.
+fn user_config_path() -> Option<PathBuf> {
+    Some(config_dir()?.join("sketch.toml"))
+}
+
+/// Directory of the user configuration.
+fn config_dir() -> Option<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(config_dir) if !config_dir.is_empty() => PathBuf::from(config_dir),
+        _ => home::home_dir()?.join(".config"),
+    };
+    Some(config_dir.join("sketch"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_overrides_user() {
+        let user: Config = toml::from_str("width = 80\npalette = \"xterm256\"").unwrap();
+        let project: Config = toml::from_str("width = 100\nbox_style = \"heavy\"").unwrap();
+
+        let config = user.merge(project);
+        assert_eq!(config.width, Some(100));
+        assert_eq!(config.palette, Some(Palette::Xterm256));
+        assert_eq!(config.box_style, Some(BoxStyle::Heavy));
+        assert_eq!(config.output_directory, None);
+
+        let project: Config = toml::from_str("ruler = \"+---+\"").unwrap();
+        assert_eq!(config.merge(project).ruler.as_deref(), Some("+---+"));
+
+        assert!(toml::from_str::<Config>("colour = 3").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use sketch::brush::ColorPosition;
+use sketch::Point;
+
+use crate::cli;
+use crate::terminal::Color;
+
+/// Command received over the control socket.
+#[derive(PartialEq, Eq, Debug)]
+pub enum ControlCommand {
+    /// Stroke the brush at a canvas position.
+    Draw(Point),
+    /// Write text starting at a canvas position.
+    Text(Point, String),
+    /// Change one of the brush colors.
+    Color(ColorPosition, Color),
+    /// Save the sketch, to the output file unless another path is given.
+    Save(Option<PathBuf>),
+    /// Crop the canvas to the sketch, with an optional padding.
+    Crop(Option<usize>),
+}
+
+impl ControlCommand {
+    /// Parse a command line, like `text 3 4 Hello, World!`.
+    ///
+    /// Returns a description of the problem if the command is invalid.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let (name, args) = line.split_once(' ').unwrap_or((line, ""));
+        match name {
+            "draw" => Ok(Self::Draw(parse_point(args)?.0)),
+            "text" => {
+                let (point, text) = parse_point(args)?;
+                Ok(Self::Text(point, text.into()))
+            },
+            "color" => {
+                let usage = || String::from("usage: color fg|bg COLOR");
+                let (position, color) = match args.split_once(' ').ok_or_else(usage)? {
+                    ("fg", color) => (ColorPosition::Foreground, color),
+                    ("bg", color) => (ColorPosition::Background, color),
+                    _ => return Err(usage()),
+                };
+                Ok(Self::Color(position, cli::parse_color(color.trim())?))
+            },
+            "save" => {
+                let path = args.trim();
+                Ok(Self::Save((!path.is_empty()).then(|| PathBuf::from(path))))
+            },
+            "crop" => match args.trim() {
+                "" => Ok(Self::Crop(None)),
+                padding => match padding.parse() {
+                    Ok(padding) => Ok(Self::Crop(Some(padding))),
+                    Err(_) => Err(String::from("usage: crop [PADDING]")),
+                },
+            },
+            _ => Err(format!("unknown command {name:?}")),
+        }
+    }
+}
+
+/// Parse a canvas position given as `COLUMN LINE`.
+///
+/// Returns the position and all text following it.
+fn parse_point(args: &str) -> Result<(Point, &str), String> {
+    let mut fields = args.splitn(3, ' ');
+    let mut coordinate = || fields.next().and_then(|field| field.parse().ok());
+    let (Some(column), Some(line)) = (coordinate(), coordinate()) else {
+        return Err(String::from("expected COLUMN LINE"));
+    };
+
+    Ok((Point { column, line }, fields.next().unwrap_or_default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::NamedColor;
+
+    #[test]
+    fn parse_commands() {
+        let text = ControlCommand::parse("text 3 4 Hello,  World!");
+        assert_eq!(
+            text,
+            Ok(ControlCommand::Text(Point { column: 3, line: 4 }, "Hello,  World!".into()))
+        );
+
+        let color = ControlCommand::parse("color bg red");
+        let red = Color::Named(NamedColor::Red);
+        assert_eq!(color, Ok(ControlCommand::Color(ColorPosition::Background, red)));
+
+        assert_eq!(ControlCommand::parse("save"), Ok(ControlCommand::Save(None)));
+        assert_eq!(ControlCommand::parse("crop 2"), Ok(ControlCommand::Crop(Some(2))));
+        assert!(ControlCommand::parse("crop -1").is_err());
+        assert!(ControlCommand::parse("draw 3").is_err());
+        assert!(ControlCommand::parse("erase 3 4").is_err());
+    }
+}
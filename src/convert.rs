@@ -0,0 +1,97 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use sketch::export::{self, Ansi, ExportSettings, Exporter};
+use sketch::import::SketchParser;
+use sketch::journal::Journal;
+use sketch::{Cell, Grid};
+use unicode_width::UnicodeWidthChar;
+
+use crate::cli::Command;
+
+/// Run a subcommand without starting the interface.
+pub fn run(command: &Command, settings: ExportSettings) -> io::Result<()> {
+    match command {
+        Command::Convert { input, to, output } => {
+            let grid = Grid::new(load(input)?, Journal::default());
+            let exporter = export::for_extension(to, settings)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "unknown format"))?;
+            write(&grid, &*exporter, output.as_deref())
+        },
+        Command::Trim { input, output } => {
+            let grid = Grid::new(trim(load(input)?), Journal::default());
+            write(&grid, &Ansi { encoding: settings.encoding }, output.as_deref())
+        },
+    }
+}
+
+/// Parse a sketch file, reading from STDIN for `-`.
+fn load(path: &Path) -> io::Result<Vec<Vec<Cell>>> {
+    let sketch = match path.as_os_str() == "-" {
+        true => {
+            let mut sketch = String::new();
+            io::stdin().read_to_string(&mut sketch)?;
+            sketch
+        },
+        false => fs::read_to_string(path)?,
+    };
+    Ok(SketchParser::parse(&sketch))
+}
+
+/// Export a sketch to a file, or STDOUT if no path is given.
+fn write(grid: &Grid, exporter: &dyn Exporter, path: Option<&Path>) -> io::Result<()> {
+    match path {
+        Some(path) => exporter.export(grid, &mut File::create(path)?),
+        None => {
+            let mut stdout = io::stdout().lock();
+            exporter.export(grid, &mut stdout)?;
+            stdout.flush()
+        },
+    }
+}
+
+/// Remove all empty lines and columns surrounding the sketch.
+fn trim(mut cells: Vec<Vec<Cell>>) -> Vec<Vec<Cell>> {
+    let is_empty = |line: &Vec<Cell>| line.iter().all(Cell::is_empty);
+    let start = cells.iter().position(|line| !is_empty(line)).unwrap_or(cells.len());
+    let end = cells.iter().rposition(|line| !is_empty(line)).map_or(start, |end| end + 1);
+    cells.truncate(end);
+    cells.drain(..start);
+
+    let first_column =
+        cells.iter().filter_map(|line| line.iter().position(|c| !c.is_empty())).min();
+    let last_column =
+        cells.iter().filter_map(|line| line.iter().rposition(|c| !c.is_empty())).max();
+    let (Some(first_column), Some(last_column)) = (first_column, last_column) else {
+        return Vec::new();
+    };
+
+    // Keep the second half of fullwidth glyphs in the last column.
+    let last_column = match cells.iter().any(|line| line[last_column].c.width() == Some(2)) {
+        true => last_column + 1,
+        false => last_column,
+    };
+
+    for line in &mut cells {
+        line.truncate(last_column + 1);
+        line.drain(..first_column);
+    }
+
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trim_surrounding_space() {
+        let cells = SketchParser::parse("\n\n   ab\n    c  \n\n");
+        let text: Vec<String> = trim(cells)
+            .iter()
+            .map(|line| line.iter().map(|cell| if cell.c == '\0' { ' ' } else { cell.c }).collect())
+            .collect();
+        assert_eq!(text, ["ab", " c"]);
+    }
+}
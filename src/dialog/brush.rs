@@ -0,0 +1,197 @@
+use sketch::brush::BrushShape;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::dialog::{self, Dialog, DialogLine};
+use crate::terminal::Terminal;
+
+/// Message prompt of the brush settings dialog.
+const BRUSH_DIALOG_PROMPT: &str = "Brush settings (TAB to switch fields):";
+
+/// Width of the field labels, including padding.
+const LABEL_WIDTH: usize = 10;
+
+/// Dialog for editing all brush settings at once.
+#[derive(PartialEq, Eq)]
+pub struct BrushDialog {
+    size: String,
+    shape: BrushShape,
+    pattern: Vec<String>,
+    field: Field,
+
+    /// Whether the pattern is still the one the dialog was opened with.
+    unmodified_pattern: bool,
+}
+
+impl BrushDialog {
+    /// Create a new brush settings dialog, starting with the current settings.
+    pub fn new(size: u8, shape: BrushShape, pattern: Vec<String>) -> Self {
+        Self {
+            pattern,
+            shape,
+            size: size.to_string(),
+            field: Field::Size,
+            unmodified_pattern: true,
+        }
+    }
+
+    /// Process a keystroke.
+    ///
+    /// Returns `true` if the dialog shrunk and a full redraw is required.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> bool {
+        match (self.field, glyph) {
+            (field, '\t') => self.field = field.next(),
+            (Field::Size, '\x7f') => {
+                let _ = self.size.pop();
+                return true;
+            },
+            (Field::Size, '0'..='9') if self.size.len() < 3 => self.size.push(glyph),
+            (Field::Shape, ' ') => {
+                self.shape = self.shape.next();
+                return true;
+            },
+            (Field::Pattern, '\x7f') => {
+                self.unmodified_pattern = false;
+                let _ = self.pattern.pop();
+                return true;
+            },
+            (Field::Pattern, glyph) => {
+                // Replace the original pattern with the first printable keystroke.
+                if self.unmodified_pattern && glyph.width().unwrap_or_default() > 0 {
+                    self.unmodified_pattern = false;
+                    self.pattern.clear();
+                }
+
+                if !dialog::push_pattern_glyph(&mut self.pattern, glyph) {
+                    return false;
+                }
+            },
+            _ => return false,
+        }
+
+        self.render(terminal);
+        false
+    }
+
+    /// The submitted brush size.
+    ///
+    /// Returns `None` if the size is not a valid number.
+    pub fn size(&self) -> Option<u8> {
+        self.size.parse().ok().filter(|size| *size > 0)
+    }
+
+    /// The selected brush shape.
+    pub fn shape(&self) -> BrushShape {
+        self.shape
+    }
+
+    /// The submitted brush pattern.
+    ///
+    /// Returns `None` if no glyph was picked.
+    pub fn pattern(&self) -> Option<Vec<String>> {
+        (!self.pattern.is_empty()).then(|| self.pattern.clone())
+    }
+
+    /// Format a field, highlighting its label if it is focused.
+    fn field_line(&self, field: Field, value: &str) -> String {
+        let label = format!("{:LABEL_WIDTH$}", field.label());
+        match field == self.field {
+            true => format!("\x1b[32m{label}\x1b[39m{value}"),
+            false => format!("{label}{value}"),
+        }
+    }
+}
+
+impl Dialog for BrushDialog {
+    fn lines(&self) -> Vec<String> {
+        let pattern = self.pattern.concat();
+        vec![
+            BRUSH_DIALOG_PROMPT.into(),
+            String::new(),
+            self.field_line(Field::Size, &self.size),
+            self.field_line(Field::Shape, &format!("{} (SPACE to cycle)", self.shape.name())),
+            self.field_line(Field::Pattern, &pattern),
+        ]
+    }
+
+    fn cursor_position(&self, _lines: &[DialogLine]) -> Option<(usize, usize)> {
+        let pattern = self.pattern.concat();
+        let position = match self.field {
+            Field::Size => (LABEL_WIDTH + self.size.len(), 2),
+            Field::Shape => (LABEL_WIDTH, 3),
+            Field::Pattern => (LABEL_WIDTH + pattern.width(), 4),
+        };
+        Some(position)
+    }
+}
+
+/// Editable brush setting.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Field {
+    Size,
+    Shape,
+    Pattern,
+}
+
+impl Field {
+    /// Get the field following this one.
+    fn next(self) -> Self {
+        match self {
+            Self::Size => Self::Shape,
+            Self::Shape => Self::Pattern,
+            Self::Pattern => Self::Size,
+        }
+    }
+
+    /// Label of the field.
+    fn label(self) -> &'static str {
+        match self {
+            Self::Size => "Size",
+            Self::Shape => "Shape",
+            Self::Pattern => "Pattern",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_size() {
+        let mut terminal = Terminal::headless(Default::default());
+        let mut dialog = BrushDialog::new(1, BrushShape::Hexagon, vec!["+".into()]);
+
+        dialog.keyboard_input(&mut terminal, '\x7f');
+        assert_eq!(dialog.size(), None);
+
+        for glyph in ['1', '2', 'x', '3', '4'] {
+            dialog.keyboard_input(&mut terminal, glyph);
+        }
+        assert_eq!(dialog.size(), Some(123));
+
+        dialog.keyboard_input(&mut terminal, '\t');
+        dialog.keyboard_input(&mut terminal, ' ');
+        assert!(dialog.shape() == BrushShape::Square);
+    }
+
+    #[test]
+    fn grapheme_pattern() {
+        let mut terminal = Terminal::headless(Default::default());
+        let mut dialog = BrushDialog::new(1, BrushShape::Hexagon, vec!["+".into()]);
+        dialog.keyboard_input(&mut terminal, '\t');
+        dialog.keyboard_input(&mut terminal, '\t');
+
+        for glyph in "👩\u{200d}💻🎨x".chars() {
+            dialog.keyboard_input(&mut terminal, glyph);
+        }
+        assert_eq!(dialog.pattern().unwrap(), ["👩\u{200d}💻", "🎨"]);
+
+        let mut dialog = BrushDialog::new(1, BrushShape::Hexagon, vec!["+".into()]);
+        dialog.keyboard_input(&mut terminal, '\t');
+        dialog.keyboard_input(&mut terminal, '\t');
+        for glyph in "e\u{301}x".chars() {
+            dialog.keyboard_input(&mut terminal, glyph);
+        }
+        assert_eq!(dialog.pattern().unwrap(), ["e\u{301}", "x"]);
+    }
+}
@@ -1,52 +1,79 @@
-use unicode_width::UnicodeWidthChar;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
-use crate::dialog::{Dialog, DialogLine};
+use crate::dialog::{self, Dialog, DialogLine};
 use crate::terminal::Terminal;
 
 /// Message prompt of the brush character picker dialog.
-const BRUSH_CHARACTER_DIALOG_PROMPT: &str = "Pick a brush character: ";
+const BRUSH_CHARACTER_DIALOG_PROMPT: &str = "Pick a brush pattern: ";
 
-/// Dialog for picking a new brush glyph.
+/// Dialog for picking a new brush pattern.
 #[derive(PartialEq, Eq)]
 pub struct BrushCharacterDialog {
-    glyph: char,
+    pattern: Vec<String>,
+
+    /// Whether the pattern is still the one the dialog was opened with.
+    unmodified: bool,
 }
 
 impl BrushCharacterDialog {
     /// Create a new brush character dialog.
     ///
-    /// The brush character `glyph` will be rendered at the end of the prompt to
-    /// indicate to the user what the active glyph for the brush is.
-    pub fn new(glyph: char) -> Self {
-        Self { glyph }
+    /// The brush `pattern` will be rendered at the end of the prompt to
+    /// indicate to the user what the active glyphs for the brush are.
+    pub fn new(pattern: Vec<String>) -> Self {
+        Self { pattern, unmodified: true }
     }
 
     /// Process a keystroke.
-    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) {
-        // Only accept renderable glyphs.
-        if glyph.width().unwrap_or_default() == 0 {
-            return;
+    ///
+    /// Returns `true` if the dialog shrunk and a full redraw is required.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> bool {
+        // Remove the last glyph on backspace.
+        if glyph == '\x7f' {
+            self.unmodified = false;
+            let _ = self.pattern.pop();
+            return true;
+        }
+
+        // Replace the original pattern with the first printable keystroke.
+        if self.unmodified && glyph.width().unwrap_or_default() > 0 {
+            self.unmodified = false;
+            self.pattern.clear();
         }
 
-        // Switch to the new glyph.
-        self.glyph = glyph;
+        // Add the new glyph to the pattern.
+        if !dialog::push_pattern_glyph(&mut self.pattern, glyph) {
+            return false;
+        }
 
         // Update the dialog.
         self.render(terminal);
+        false
     }
 
-    /// The selected brush glyph.
-    pub fn glyph(&self) -> char {
-        self.glyph
+    /// The selected brush pattern.
+    ///
+    /// Returns `None` if no glyph was picked.
+    pub fn pattern(&self) -> Option<Vec<String>> {
+        (!self.pattern.is_empty()).then(|| self.pattern.clone())
     }
 }
 
 impl Dialog for BrushCharacterDialog {
     fn lines(&self) -> Vec<String> {
-        vec![format!("{}{}", BRUSH_CHARACTER_DIALOG_PROMPT, self.glyph)]
+        let pattern = self.pattern.concat();
+        vec![format!("{}{}", BRUSH_CHARACTER_DIALOG_PROMPT, pattern)]
     }
 
     fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
-        Some((lines.first().map(|line| line.width()).unwrap_or_default() - 1, 0))
+        let line_width = lines.first().map(|line| line.width()).unwrap_or_default();
+
+        // Put the cursor on the last glyph, or after the prompt when it's empty.
+        let cursor_column = match self.pattern.last() {
+            Some(glyph) => line_width - glyph.width(),
+            None => line_width,
+        };
+
+        Some((cursor_column, 0))
     }
 }
@@ -0,0 +1,92 @@
+use crate::dialog::Dialog;
+use crate::terminal::Terminal;
+
+/// Message prompt of the buffer list dialog.
+const BUFFER_DIALOG_PROMPT: &str = "Open buffers:";
+/// Message prompt of the buffer list dialog while picking a split view buffer.
+const SPLIT_DIALOG_PROMPT: &str = "Buffer shown in split view:";
+/// Help text of the buffer list dialog.
+const BUFFER_DIALOG_HELP: &str = "[1-9] Switch    [N] New    [D] Close active    [S] Split view";
+
+/// Dialog listing all open buffers.
+#[derive(PartialEq, Eq)]
+pub struct BufferListDialog {
+    names: Vec<String>,
+    active: usize,
+
+    /// Whether the selected buffer is shown in the split view.
+    split: bool,
+}
+
+impl BufferListDialog {
+    /// Create a new buffer list dialog.
+    pub fn new(names: Vec<String>, active: usize) -> Self {
+        Self { names, active, split: false }
+    }
+
+    /// Process a keystroke.
+    ///
+    /// Returns the selected action, if any.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> Option<BufferAction> {
+        match glyph {
+            's' => {
+                self.split = !self.split;
+                self.render(terminal);
+                None
+            },
+            'n' => Some(BufferAction::New),
+            'd' => Some(BufferAction::Close),
+            '1'..='9' => {
+                let index = glyph as usize - '1' as usize;
+                let action = if self.split { BufferAction::Split } else { BufferAction::Switch };
+                (index < self.names.len()).then(|| action(index))
+            },
+            _ => None,
+        }
+    }
+}
+
+impl Dialog for BufferListDialog {
+    fn lines(&self) -> Vec<String> {
+        let prompt = if self.split { SPLIT_DIALOG_PROMPT } else { BUFFER_DIALOG_PROMPT };
+        let mut lines = vec![prompt.into(), String::new()];
+
+        for (i, name) in self.names.iter().enumerate() {
+            match i == self.active {
+                true => lines.push(format!("\x1b[32m{}  {name}\x1b[39m", i + 1)),
+                false => lines.push(format!("{}  {name}", i + 1)),
+            }
+        }
+
+        lines.push(String::new());
+        lines.push(BUFFER_DIALOG_HELP.into());
+
+        lines
+    }
+}
+
+/// Buffer list operation.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BufferAction {
+    Switch(usize),
+    Split(usize),
+    New,
+    Close,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_selection() {
+        let mut terminal = Terminal::headless(Default::default());
+        let mut dialog = BufferListDialog::new(vec!["a".into(), "b".into()], 0);
+
+        assert_eq!(dialog.keyboard_input(&mut terminal, '2'), Some(BufferAction::Switch(1)));
+        assert_eq!(dialog.keyboard_input(&mut terminal, '3'), None);
+
+        assert_eq!(dialog.keyboard_input(&mut terminal, 's'), None);
+        assert_eq!(dialog.keyboard_input(&mut terminal, '2'), Some(BufferAction::Split(1)));
+    }
+}
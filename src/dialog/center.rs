@@ -0,0 +1,45 @@
+use crate::dialog::Dialog;
+
+/// Message prompt of the center dialog.
+const CENTER_DIALOG_PROMPT: &str = "Center the sketch on the canvas:";
+
+/// Dialog for picking the axes the sketch is centered along.
+#[derive(Default, PartialEq, Eq)]
+pub struct CenterDialog;
+
+impl CenterDialog {
+    /// Create a new center dialog.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Get the axes selected by a keystroke.
+    pub fn axes(&self, glyph: char) -> Option<CenterAxes> {
+        match glyph {
+            'h' => Some(CenterAxes::Horizontal),
+            'v' => Some(CenterAxes::Vertical),
+            'b' => Some(CenterAxes::Both),
+            _ => None,
+        }
+    }
+}
+
+impl Dialog for CenterDialog {
+    fn lines(&self) -> Vec<String> {
+        vec![
+            CENTER_DIALOG_PROMPT.into(),
+            String::new(),
+            String::from("\x1b[32mh\x1b[39m  horizontally"),
+            String::from("\x1b[32mv\x1b[39m  vertically"),
+            String::from("\x1b[32mb\x1b[39m  both"),
+        ]
+    }
+}
+
+/// Axes along which the sketch is centered.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum CenterAxes {
+    Horizontal,
+    Vertical,
+    Both,
+}
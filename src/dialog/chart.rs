@@ -0,0 +1,108 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::chart::{self, ChartKind};
+use crate::dialog::{Dialog, DialogLine};
+use crate::terminal::{Color, NamedColor, Terminal};
+
+/// Message prompt of the chart dialog.
+const CHART_DIALOG_PROMPT: &str = "Chart values (numbers separated by commas):";
+
+/// Dialog for entering the values of a new chart.
+#[derive(Default, PartialEq, Eq)]
+pub struct ChartDialog {
+    values: String,
+    kind: ChartKind,
+    error: bool,
+}
+
+impl ChartDialog {
+    /// Create a new chart dialog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a keystroke.
+    ///
+    /// Returns `true` if the dialog shrunk and a full redraw is required.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> bool {
+        // Switch between chart styles on tab.
+        if glyph == '\t' {
+            self.kind = self.kind.toggle();
+            self.render(terminal);
+            return false;
+        }
+
+        // Only accept renderable glyphs.
+        if glyph != '\x7f' && glyph.width().unwrap_or_default() == 0 {
+            return false;
+        }
+
+        // Clear error when the values are changed.
+        self.error = false;
+
+        match glyph {
+            '\x7f' => {
+                let _ = self.values.pop();
+
+                // Redraw everything if backspace caused dialog to shrink.
+                if self.values.width() + 1 > CHART_DIALOG_PROMPT.len() {
+                    return true;
+                }
+            },
+            c => self.values.push(c),
+        }
+
+        // Redraw just the dialog.
+        self.render(terminal);
+        false
+    }
+
+    /// Append pasted values, with one value per line or separated by commas.
+    pub fn paste(&mut self, terminal: &mut Terminal, text: &str) {
+        for value in text.lines().map(str::trim).filter(|value| !value.is_empty()) {
+            if !self.values.trim().is_empty() && !self.values.trim_end().ends_with(',') {
+                self.values.push_str(", ");
+            }
+            self.values.push_str(value);
+        }
+
+        self.error = false;
+        self.render(terminal);
+    }
+
+    /// The submitted chart values.
+    pub fn values(&self) -> Option<Vec<f64>> {
+        chart::parse(&self.values)
+    }
+
+    /// The selected chart style.
+    pub fn kind(&self) -> ChartKind {
+        self.kind
+    }
+
+    /// Indicate an error to the user.
+    pub fn mark_failed(&mut self, terminal: &mut Terminal) {
+        self.error = true;
+        self.render(terminal);
+    }
+}
+
+impl Dialog for ChartDialog {
+    fn lines(&self) -> Vec<String> {
+        vec![
+            CHART_DIALOG_PROMPT.into(),
+            self.values.clone(),
+            String::new(),
+            format!("\x1b[32mTab\x1b[39m  {}", self.kind.name()),
+        ]
+    }
+
+    fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
+        Some((lines.get(1).map(|line| line.width()).unwrap_or_default(), 1))
+    }
+
+    fn box_color(&self) -> (Color, Color) {
+        let fg = if self.error { Color::Named(NamedColor::Red) } else { Color::default() };
+        (fg, Color::default())
+    }
+}
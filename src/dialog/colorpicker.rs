@@ -1,6 +1,8 @@
 use std::fmt::{self, Display, Formatter};
 use std::str::FromStr;
 
+use sketch::brush::ColorPosition;
+
 use crate::dialog::{Dialog, DialogLine};
 use crate::terminal::{Color, NamedColor, Rgb, Terminal};
 
@@ -81,12 +83,6 @@ impl Dialog for ColorpickerDialog {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
-pub enum ColorPosition {
-    Foreground,
-    Background,
-}
-
 #[derive(PartialEq, Eq)]
 enum ColorpickerMode {
     Rgb(String),
@@ -138,10 +134,8 @@ impl ColorpickerMode {
 
         match glyph {
             '\x7f' => color.truncate(color.len().saturating_sub(1)),
-            glyph if color.len() < 6 => {
-                if u8::from_str_radix(&glyph.to_string(), 16).is_ok() {
-                    color.push(glyph);
-                }
+            glyph if color.len() < 6 && u8::from_str_radix(&glyph.to_string(), 16).is_ok() => {
+                color.push(glyph);
             },
             _ => (),
         }
@@ -15,13 +15,84 @@ impl Dialog for HelpDialog {
     fn lines(&self) -> Vec<String> {
         vec![
             String::from("MOUSE WHEEL        \x1b[32mbrush size\x1b[39m change"),
+            String::from("SHIFT + WHEEL      \x1b[32meraser size\x1b[39m change"),
+            String::from("DRAG MMB           \x1b[32mcanvas\x1b[39m panning"),
+            String::from("ALT + ARROWS       \x1b[32mcanvas\x1b[39m scrolling"),
+            String::from("ALT + SHIFT + ←↑↓→ \x1b[32mcontent\x1b[39m shifting by one cell"),
             String::from("CTRL + LMB         \x1b[32mbox drawing\x1b[39m mode"),
-            String::from("CTRL + DRAG LMB    \x1b[32mline drawing\x1b[39m mode"),
-            String::from("CTRL + G           \x1b[32mgrapheme\x1b[39m picker"),
+            String::from("CTRL + DRAG LMB    \x1b[32mline drawing\x1b[39m mode (SHIFT to snap)"),
+            String::from(
+                "ALT + LMB          \x1b[32mcircle drawing\x1b[39m mode (CTRL for ellipses)",
+            ),
+            String::from("ALT + A            \x1b[32mcircle aspect\x1b[39m correction toggle"),
+            String::from("ALT + Q            \x1b[32mquantize\x1b[39m colors to palette"),
+            String::from("ALT + S            \x1b[32mbrush shape\x1b[39m toggle"),
+            String::from("ALT + K            \x1b[32mbrush settings\x1b[39m with exact size"),
+            String::from("ALT + T            \x1b[32mtext along path\x1b[39m under brush"),
+            String::from("ALT + B            \x1b[32mbanner text\x1b[39m at brush position"),
+            String::from("ALT + C            \x1b[32mauto contrast\x1b[39m foreground toggle"),
+            String::from("ALT + |            \x1b[32mmirror\x1b[39m drawing axes cycling"),
+            String::from("ALT + L            \x1b[32mstamp library\x1b[39m picker"),
+            String::from("ALT + D            \x1b[32mpacket diagram\x1b[39m as brush stamp"),
+            String::from("ALT + E            \x1b[32memphasis\x1b[39m for lines/boxes toggle"),
+            String::from("ALT + R            \x1b[32mborder repair\x1b[39m when erasing toggle"),
+            String::from("ALT + SHIFT + R    \x1b[32mshape recognition\x1b[39m for strokes toggle"),
+            String::from("ALT + SHIFT + E    \x1b[32meraser\x1b[39m glyphs/colors cycling"),
+            String::from(
+                "ALT + SHIFT + C    \x1b[32mpaint\x1b[39m background/foreground/style only cycling",
+            ),
+            String::from(
+                "ALT + SHIFT + V    \x1b[32mstamp blending\x1b[39m colors/glyphs/empty only \
+                 cycling",
+            ),
+            String::from("ALT + SHIFT + U    \x1b[32mintensity\x1b[39m dim/bold/normal cycling"),
+            String::from(
+                "ALT + SHIFT + M    \x1b[32mdrawing mode\x1b[39m brush/box/line/text cycling",
+            ),
+            String::from("CTRL + G           \x1b[32mbrush pattern\x1b[39m picker"),
             String::from("CTRL + F           \x1b[32mforeground color\x1b[39m picker"),
             String::from("CTRL + B           \x1b[32mbackground color\x1b[39m picker"),
-            String::from("CTRL + E           \x1b[32mfill\x1b[39m at brush position"),
-            String::from("CTRL + T           \x1b[32mtext styles\x1b[39m toggle"),
+            String::from("CTRL + E           \x1b[32mfill\x1b[39m region on click"),
+            String::from("CTRL + T           \x1b[32mtext style\x1b[39m dialog"),
+            String::from("CTRL + Y           \x1b[32mbrush stamp\x1b[39m capture/drop"),
+            String::from("ALT + W            \x1b[32mconnector\x1b[39m between box borders"),
+            String::from(
+                "ALT + SHIFT + W    \x1b[32mmeasure\x1b[39m size and aspect ratio of regions",
+            ),
+            String::from("ALT + SHIFT + K    \x1b[32mlock\x1b[39m region against changes toggle"),
+            String::from("ALT + G            \x1b[32mtable row\x1b[39m below the cursor"),
+            String::from("ALT + SHIFT + G    \x1b[32mtable\x1b[39m with rows and columns"),
+            String::from("ALT + SHIFT + N    \x1b[32mchart\x1b[39m from a list of numbers"),
+            String::from("ALT + SHIFT + I    \x1b[32mtree diagram\x1b[39m from indented list"),
+            String::from(
+                "ALT + M            \x1b[32mmove\x1b[39m content or selection, ALT to duplicate",
+            ),
+            String::from(
+                "ALT + Y            \x1b[32mcopy\x1b[39m sketch or selection to clipboard",
+            ),
+            String::from("ALT + SHIFT + A    \x1b[32mbox drawing\x1b[39m to ASCII and back"),
+            String::from("ALT + SHIFT + T    \x1b[32mcrop\x1b[39m canvas to the sketch"),
+            String::from("ALT + U            \x1b[32mshare\x1b[39m sketch via paste service"),
+            String::from("ALT + F            \x1b[32mjump\x1b[39m to brush color or glyph"),
+            String::from(
+                "ALT + X            \x1b[32msearch\x1b[39m text, then n/N between matches",
+            ),
+            String::from("ALT + P            \x1b[32mruler\x1b[39m and cursor position toggle"),
+            String::from("ALT + Z            \x1b[32mgrid\x1b[39m overlay toggle"),
+            String::from("ALT + SHIFT + Z    \x1b[32mcenter\x1b[39m sketch on the canvas"),
+            String::from("ALT + V            \x1b[32mlast save\x1b[39m comparison toggle"),
+            String::from("ALT + I            \x1b[32mlint\x1b[39m overlay toggle"),
+            String::from("ALT + H            \x1b[32mundo history\x1b[39m stats and compaction"),
+            String::from("ALT + N            \x1b[32msnapshot\x1b[39m of the canvas"),
+            String::from("ALT + SHIFT + F    \x1b[32mduplicate\x1b[39m animation frame"),
+            String::from("ALT + SHIFT + D    \x1b[32mdelete\x1b[39m animation frame"),
+            String::from("ALT + < / >        \x1b[32mprevious/next\x1b[39m animation frame"),
+            String::from("ALT + { / }        \x1b[32mprevious/next\x1b[39m buffer"),
+            String::from("ALT + SHIFT + B    \x1b[32mbuffer list\x1b[39m dialog"),
+            String::from("ALT + SHIFT + S    \x1b[32msplit view\x1b[39m load/close"),
+            String::from("ALT + SHIFT + H/L  \x1b[32msplit view\x1b[39m divider moving"),
+            String::from("ALT + J            \x1b[32mrestore snapshot\x1b[39m picker"),
+            String::from("ALT + O            \x1b[32mtracing layer\x1b[39m load/discard"),
             String::from("CTRL + S           \x1b[32msave\x1b[39m sketch"),
             String::from("CTRL + O           \x1b[32mopen\x1b[39m existing sketch"),
             String::from("CTRL + U           \x1b[32mundo\x1b[39m last action"),
@@ -0,0 +1,67 @@
+use crate::dialog::{Dialog, DialogLine};
+use crate::terminal::{Color, NamedColor, Terminal};
+
+/// Message prompt of the history dialog.
+const HISTORY_DIALOG_PROMPT: &str = "Undo steps to keep:";
+
+/// Dialog for inspecting and compacting the undo history.
+#[derive(Default, PartialEq, Eq)]
+pub struct HistoryDialog {
+    summary: String,
+    revisions: String,
+    error: bool,
+}
+
+impl HistoryDialog {
+    /// Create a new history dialog.
+    pub fn new(summary: String) -> Self {
+        Self { summary, ..Self::default() }
+    }
+
+    /// Process a keystroke.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) {
+        match glyph {
+            '\x7f' => {
+                let _ = self.revisions.pop();
+            },
+            c if c.is_ascii_digit() => self.revisions.push(c),
+            _ => return,
+        }
+
+        // Clear error when the input is changed.
+        self.error = false;
+
+        self.render(terminal);
+    }
+
+    /// The submitted number of revisions to keep.
+    pub fn revisions(&self) -> Option<usize> {
+        self.revisions.parse().ok()
+    }
+
+    /// Indicate an error to the user.
+    pub fn mark_failed(&mut self, terminal: &mut Terminal) {
+        self.error = true;
+        self.render(terminal);
+    }
+}
+
+impl Dialog for HistoryDialog {
+    fn lines(&self) -> Vec<String> {
+        vec![
+            self.summary.clone(),
+            String::new(),
+            HISTORY_DIALOG_PROMPT.into(),
+            self.revisions.clone(),
+        ]
+    }
+
+    fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
+        Some((lines.get(3).map(|line| line.width()).unwrap_or_default(), 3))
+    }
+
+    fn box_color(&self) -> (Color, Color) {
+        let fg = if self.error { Color::Named(NamedColor::Red) } else { Color::default() };
+        (fg, Color::default())
+    }
+}
@@ -0,0 +1,67 @@
+use std::cmp::min;
+use std::path::{Path, PathBuf};
+
+use sketch::grid::MAX_COLUMNS;
+
+use crate::dialog::{Dialog, DialogLine};
+use crate::terminal::Terminal;
+
+/// Message prompt of the image import dialog.
+const IMAGE_DIALOG_PROMPT: &str = "Image width in columns:";
+
+/// Maximum number of digits in the image width.
+const MAX_WIDTH_DIGITS: usize = 4;
+
+/// Dialog for picking the size of an imported image.
+#[derive(PartialEq, Eq)]
+pub struct ImageDialog {
+    path: PathBuf,
+    width: String,
+}
+
+impl ImageDialog {
+    /// Create a new image import dialog, suggesting an initial width.
+    pub fn new(path: PathBuf, width: usize) -> Self {
+        Self { path, width: width.to_string() }
+    }
+
+    /// Process a keystroke.
+    ///
+    /// Returns `true` if the dialog shrunk and a full redraw is required.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> bool {
+        match glyph {
+            '\x7f' => {
+                let _ = self.width.pop();
+                return true;
+            },
+            '0'..='9' if self.width.len() < MAX_WIDTH_DIGITS => self.width.push(glyph),
+            _ => return false,
+        }
+
+        self.render(terminal);
+        false
+    }
+
+    /// Path of the imported image.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The submitted image width.
+    ///
+    /// Returns `None` if the width is not a valid number. Widths beyond
+    /// [`MAX_COLUMNS`] are reduced to it.
+    pub fn width(&self) -> Option<usize> {
+        self.width.parse().ok().filter(|width| *width > 0).map(|width| min(width, MAX_COLUMNS))
+    }
+}
+
+impl Dialog for ImageDialog {
+    fn lines(&self) -> Vec<String> {
+        vec![IMAGE_DIALOG_PROMPT.into(), self.width.clone()]
+    }
+
+    fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
+        Some((lines.get(1).map(|line| line.width()).unwrap_or_default(), 1))
+    }
+}
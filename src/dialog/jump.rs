@@ -0,0 +1,52 @@
+use crate::dialog::Dialog;
+use crate::terminal::{Color, Terminal};
+
+/// Message prompt of the jump dialog.
+const JUMP_DIALOG_PROMPT: &str = "Jump to the next cell with the brush's:";
+
+/// Dialog for picking the brush attribute to jump to.
+#[derive(PartialEq, Eq)]
+pub struct JumpDialog {
+    foreground: Color,
+    background: Color,
+    glyph: String,
+}
+
+impl JumpDialog {
+    /// Create a new jump dialog, showing the brush's current attributes.
+    pub fn new(foreground: Color, background: Color, glyph: String) -> Self {
+        Self { foreground, background, glyph }
+    }
+
+    /// Get the attribute selected by a keystroke.
+    pub fn target(&self, glyph: char) -> Option<JumpTarget> {
+        match glyph {
+            'f' => Some(JumpTarget::Foreground),
+            'b' => Some(JumpTarget::Background),
+            'g' => Some(JumpTarget::Glyph),
+            _ => None,
+        }
+    }
+}
+
+impl Dialog for JumpDialog {
+    fn lines(&self) -> Vec<String> {
+        let foreground = Terminal::supported_color(self.foreground).escape(true);
+        let background = Terminal::supported_color(self.background).escape(false);
+        vec![
+            JUMP_DIALOG_PROMPT.into(),
+            String::new(),
+            format!("\x1b[32mf\x1b[39m  foreground color {foreground}██\x1b[39m"),
+            format!("\x1b[32mb\x1b[39m  background color {background}  \x1b[49m"),
+            format!("\x1b[32mg\x1b[39m  glyph            {}", self.glyph),
+        ]
+    }
+}
+
+/// Cell attribute to jump to.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum JumpTarget {
+    Foreground,
+    Background,
+    Glyph,
+}
@@ -0,0 +1,102 @@
+use std::mem;
+
+use sketch::lint::Issue;
+use sketch::Point;
+
+use crate::dialog::Dialog;
+
+/// Message prompt of the lint dialog.
+const LINT_DIALOG_PROMPT: &str = "Pick an issue to jump to:";
+
+/// Number of issues listed on every page of the dialog.
+const PAGE_SIZE: usize = 9;
+
+/// Dialog listing diagram consistency issues.
+#[derive(PartialEq, Eq)]
+pub struct LintDialog {
+    issues: Vec<Issue>,
+    page: usize,
+}
+
+impl LintDialog {
+    /// Create a new lint dialog.
+    pub fn new(issues: Vec<Issue>) -> Self {
+        Self { issues, page: 0 }
+    }
+
+    /// Get the location of the issue selected by a keystroke on the current
+    /// page.
+    pub fn issue(&self, glyph: char) -> Option<Point> {
+        let index = glyph.to_digit(10)?.checked_sub(1)? as usize;
+        self.issues.get(self.page * PAGE_SIZE + index).map(|issue| issue.point)
+    }
+
+    /// Switch to the next or previous page of issues with `n` and `p`.
+    ///
+    /// Returns `true` if the page changed and a full redraw is required.
+    pub fn keyboard_input(&mut self, glyph: char) -> bool {
+        let last_page = self.pages().saturating_sub(1);
+        let page = match glyph {
+            'n' => (self.page + 1).min(last_page),
+            'p' => self.page.saturating_sub(1),
+            _ => return false,
+        };
+        mem::replace(&mut self.page, page) != page
+    }
+
+    /// Number of pages required to list all issues.
+    fn pages(&self) -> usize {
+        self.issues.len().div_ceil(PAGE_SIZE)
+    }
+}
+
+impl Dialog for LintDialog {
+    fn lines(&self) -> Vec<String> {
+        if self.issues.is_empty() {
+            return vec!["No issues found".into()];
+        }
+
+        let mut lines = vec![LINT_DIALOG_PROMPT.into(), String::new()];
+        let page = self.issues.iter().skip(self.page * PAGE_SIZE).take(PAGE_SIZE);
+        for (i, issue) in page.enumerate() {
+            let Point { column, line } = issue.point;
+            let description = issue.kind.description();
+            lines.push(format!("\x1b[32m{}\x1b[39m  {description} at {line}:{column}", i + 1));
+        }
+
+        // Explain navigation for issues that do not fit on a single page.
+        if self.pages() > 1 {
+            lines.push(String::new());
+            lines.push(format!(
+                "Page {}/{}, \x1b[32mn\x1b[39m/\x1b[32mp\x1b[39m for next/previous",
+                self.page + 1,
+                self.pages(),
+            ));
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sketch::lint::IssueKind;
+
+    use super::*;
+
+    #[test]
+    fn paging() {
+        let issues = (1..=12)
+            .map(|line| Issue { point: Point { column: 1, line }, kind: IssueKind::DanglingEnd })
+            .collect();
+        let mut dialog = LintDialog::new(issues);
+        assert_eq!(dialog.issue('9'), Some(Point { column: 1, line: 9 }));
+
+        assert!(!dialog.keyboard_input('p'));
+        assert!(dialog.keyboard_input('n'));
+        assert!(!dialog.keyboard_input('n'));
+        assert_eq!(dialog.issue('3'), Some(Point { column: 1, line: 12 }));
+        assert_eq!(dialog.issue('4'), None);
+        assert!(dialog.lines().last().unwrap().starts_with("Page 2/2"));
+    }
+}
@@ -1,12 +1,33 @@
-use unicode_width::UnicodeWidthStr;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 use crate::terminal::{Color, CursorShape, EscapeStripper, Terminal, TerminalMode};
 
+pub mod brush;
 pub mod brush_character;
+pub mod buffers;
+pub mod center;
+pub mod chart;
 pub mod colorpicker;
 pub mod help;
+pub mod history;
+#[cfg(feature = "image")]
+pub mod image;
+pub mod jump;
+pub mod lint;
 pub mod open;
+pub mod packet;
+pub mod quantize;
 pub mod save;
+pub mod search;
+pub mod snapshot;
+pub mod stamp_library;
+pub mod style;
+pub mod table;
+pub mod tree;
+
+/// Maximum number of glyphs in a brush pattern.
+const MAX_PATTERN_LEN: usize = 8;
 
 pub trait Dialog {
     fn lines(&self) -> Vec<String>;
@@ -29,8 +50,9 @@ pub trait Dialog {
         let lines: Vec<_> = self.lines().into_iter().map(DialogLine::from).collect();
 
         let max_width = lines.iter().map(|line| line.width()).max().unwrap_or(0) + 4;
-        let column = (terminal.dimensions.columns as usize - max_width) / 2;
-        let mut line_index = (terminal.dimensions.lines as usize - 5) / 2;
+        let column = (terminal.dimensions.columns as usize).saturating_sub(max_width) / 2;
+        let mut line_index =
+            (terminal.dimensions.lines as usize).saturating_sub(lines.len() + 2) / 2;
 
         // Setup the colored box drawing characters.
         let box_color = self.box_color();
@@ -82,6 +104,40 @@ pub trait Dialog {
     }
 }
 
+/// Add a typed character to a brush pattern.
+///
+/// Characters extending the last grapheme cluster, like combining marks or
+/// zero width joiner sequences, are joined with the last glyph. Other glyphs
+/// are rejected if they are not printable, exceed the maximum pattern length,
+/// or differ in width from the rest of the pattern.
+///
+/// Returns `true` if the pattern was changed.
+pub fn push_pattern_glyph(pattern: &mut Vec<String>, glyph: char) -> bool {
+    if let Some(last) = pattern.last_mut() {
+        let mut joined = last.clone();
+        joined.push(glyph);
+        if joined.graphemes(true).count() == 1 {
+            *last = joined;
+            return true;
+        }
+    }
+
+    // Only accept renderable glyphs.
+    let width = glyph.width().unwrap_or_default();
+    if width == 0 {
+        return false;
+    }
+
+    // Mixing glyphs of different widths would break the brush grid alignment.
+    let pattern_width = pattern.first().and_then(|glyph| glyph.chars().next()?.width());
+    if pattern.len() >= MAX_PATTERN_LEN || pattern_width.is_some_and(|w| w != width) {
+        return false;
+    }
+
+    pattern.push(glyph.into());
+    true
+}
+
 /// Line of text in the dialog.
 pub struct DialogLine {
     pub original: String,
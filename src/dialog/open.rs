@@ -8,11 +8,18 @@ use crate::terminal::{Color, NamedColor, Terminal};
 /// Message prompt of the open dialog.
 const OPEN_DIALOG_PROMPT: &str = "Sketch path:";
 
+/// Message prompt of the open dialog for tracing layers.
+const TRACE_DIALOG_PROMPT: &str = "Tracing layer path:";
+
+/// Message prompt of the open dialog for split view references.
+const SPLIT_DIALOG_PROMPT: &str = "Split view path:";
+
 /// Dialog for loading sketches.
 #[derive(Default, PartialEq, Eq)]
 pub struct OpenDialog {
     path: String,
     error: bool,
+    target: OpenTarget,
 }
 
 impl OpenDialog {
@@ -21,6 +28,30 @@ impl OpenDialog {
         Self::default()
     }
 
+    /// Create a new dialog for loading a tracing layer.
+    pub fn tracing() -> Self {
+        Self { target: OpenTarget::Tracing, ..Self::default() }
+    }
+
+    /// Create a new dialog for loading a split view reference.
+    pub fn split() -> Self {
+        Self { target: OpenTarget::Split, ..Self::default() }
+    }
+
+    /// Where the file is opened.
+    pub fn target(&self) -> OpenTarget {
+        self.target
+    }
+
+    /// Message prompt of the dialog.
+    fn prompt(&self) -> &'static str {
+        match self.target {
+            OpenTarget::Canvas => OPEN_DIALOG_PROMPT,
+            OpenTarget::Tracing => TRACE_DIALOG_PROMPT,
+            OpenTarget::Split => SPLIT_DIALOG_PROMPT,
+        }
+    }
+
     /// Process a keystroke.
     ///
     /// Returns `true` if the dialog shrunk and a full redraw is required.
@@ -39,7 +70,7 @@ impl OpenDialog {
                 let _ = self.path.pop();
 
                 // Redraw everything if backspace caused dialog to shrink.
-                if self.path.width() + 1 > OPEN_DIALOG_PROMPT.len() {
+                if self.path.width() + 1 > self.prompt().len() {
                     return true;
                 }
             },
@@ -81,7 +112,7 @@ impl OpenDialog {
 
 impl Dialog for OpenDialog {
     fn lines(&self) -> Vec<String> {
-        vec![OPEN_DIALOG_PROMPT.into(), self.path.clone()]
+        vec![self.prompt().into(), self.path.clone()]
     }
 
     fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
@@ -93,3 +124,15 @@ impl Dialog for OpenDialog {
         (fg, Color::default())
     }
 }
+
+/// Destination of an opened file.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum OpenTarget {
+    /// Import into the canvas.
+    #[default]
+    Canvas,
+    /// Dimmed tracing layer below the canvas.
+    Tracing,
+    /// Read-only split pane next to the canvas.
+    Split,
+}
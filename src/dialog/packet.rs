@@ -0,0 +1,77 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::dialog::{Dialog, DialogLine};
+use crate::packet::{self, Field};
+use crate::terminal::{Color, NamedColor, Terminal};
+
+/// Message prompt of the packet diagram dialog.
+const PACKET_DIALOG_PROMPT: &str = "Packet fields (name:bits, ...):";
+
+/// Dialog for generating packet diagrams.
+#[derive(Default, PartialEq, Eq)]
+pub struct PacketDialog {
+    fields: String,
+    error: bool,
+}
+
+impl PacketDialog {
+    /// Create a new packet diagram dialog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a keystroke.
+    ///
+    /// Returns `true` if the dialog shrunk and a full redraw is required.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> bool {
+        // Only accept renderable glyphs.
+        if glyph != '\x7f' && glyph.width().unwrap_or_default() == 0 {
+            return false;
+        }
+
+        // Clear error when the fields are changed.
+        self.error = false;
+
+        match glyph {
+            '\x7f' => {
+                let _ = self.fields.pop();
+
+                // Redraw everything if backspace caused dialog to shrink.
+                if self.fields.width() + 1 > PACKET_DIALOG_PROMPT.len() {
+                    return true;
+                }
+            },
+            c => self.fields.push(c),
+        }
+
+        // Redraw just the dialog.
+        self.render(terminal);
+        false
+    }
+
+    /// The submitted packet fields.
+    pub fn fields(&self) -> Option<Vec<Field>> {
+        packet::parse(&self.fields)
+    }
+
+    /// Indicate an error to the user.
+    pub fn mark_failed(&mut self, terminal: &mut Terminal) {
+        self.error = true;
+        self.render(terminal);
+    }
+}
+
+impl Dialog for PacketDialog {
+    fn lines(&self) -> Vec<String> {
+        vec![PACKET_DIALOG_PROMPT.into(), self.fields.clone()]
+    }
+
+    fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
+        Some((lines.get(1).map(|line| line.width()).unwrap_or_default(), 1))
+    }
+
+    fn box_color(&self) -> (Color, Color) {
+        let fg = if self.error { Color::Named(NamedColor::Red) } else { Color::default() };
+        (fg, Color::default())
+    }
+}
@@ -0,0 +1,41 @@
+use sketch::palette::Palette;
+
+use crate::dialog::Dialog;
+
+/// Message prompt of the quantization dialog.
+const QUANTIZE_DIALOG_PROMPT: &str = "Quantize colors to palette: ";
+/// Help text of the quantization dialog.
+const QUANTIZE_DIALOG_HELP: &str = "[TAB] Switch palette    [ENTER] Apply";
+
+/// Dialog for reducing the sketch's colors to a palette.
+#[derive(Default, PartialEq, Eq)]
+pub struct QuantizeDialog {
+    palette: Palette,
+}
+
+impl QuantizeDialog {
+    /// Create a new quantization dialog.
+    pub fn new(palette: Palette) -> Self {
+        Self { palette }
+    }
+
+    /// Switch to the next palette.
+    pub fn next_palette(&mut self) {
+        self.palette = self.palette.next();
+    }
+
+    /// The selected palette.
+    pub fn palette(&self) -> Palette {
+        self.palette
+    }
+}
+
+impl Dialog for QuantizeDialog {
+    fn lines(&self) -> Vec<String> {
+        vec![
+            format!("{}\x1b[32m{}\x1b[39m", QUANTIZE_DIALOG_PROMPT, self.palette.name()),
+            String::new(),
+            QUANTIZE_DIALOG_HELP.to_string(),
+        ]
+    }
+}
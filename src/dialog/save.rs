@@ -15,25 +15,39 @@ pub struct SaveDialog {
     path: String,
     error: bool,
     shutdown: bool,
+
+    /// Question waiting for an answer before saving.
+    question: Option<Confirmation>,
+    /// Question answered with yes for the current path.
+    confirmed: Option<Confirmation>,
 }
 
 impl SaveDialog {
     /// Create a new save dialog.
     pub fn new(path: String, error: bool, shutdown: bool) -> Self {
-        Self { path, error, shutdown }
+        Self { path, error, shutdown, question: None, confirmed: None }
     }
 
     /// Process a keystroke.
     ///
     /// Returns `true` if the dialog shrunk and a full redraw is required.
     pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> bool {
+        // Dismiss pending questions with anything but `y`.
+        if let Some(question) = self.question.take() {
+            if glyph == 'y' {
+                self.confirmed = Some(question);
+            }
+            return true;
+        }
+
         // Only accept renderable glyphs.
         if glyph != '\x7f' && glyph.width().unwrap_or_default() == 0 {
             return false;
         }
 
-        // Clear error when the path is changed.
+        // Clear error and previous confirmations when the path is changed.
         self.error = false;
+        self.confirmed = None;
 
         // Add the new glyph to the path.
         match glyph {
@@ -80,6 +94,22 @@ impl SaveDialog {
         self.render(terminal);
     }
 
+    /// Ask for confirmation before saving.
+    pub fn ask(&mut self, terminal: &mut Terminal, question: Confirmation) {
+        self.question = Some(question);
+        self.render(terminal);
+    }
+
+    /// Check if a question is waiting for an answer.
+    pub fn has_question(&self) -> bool {
+        self.question.is_some()
+    }
+
+    /// Check if a question was confirmed for the current path.
+    pub fn is_confirmed(&self, question: Confirmation) -> bool {
+        self.confirmed == Some(question)
+    }
+
     /// Whether Sketch should terminate after successfully saving.
     pub fn shutdown_on_save(&self) -> bool {
         self.shutdown
@@ -97,7 +127,12 @@ impl SaveDialog {
 
 impl Dialog for SaveDialog {
     fn lines(&self) -> Vec<String> {
-        vec![self.prompt().into(), self.path.clone()]
+        let mut lines = vec![self.prompt().into(), self.path.clone()];
+        if let Some(question) = self.question {
+            lines.push(String::new());
+            lines.push(format!("\x1b[33m{}\x1b[39m [y/N]", question.text()));
+        }
+        lines
     }
 
     fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
@@ -109,3 +144,22 @@ impl Dialog for SaveDialog {
         (fg, Color::default())
     }
 }
+
+/// Question confirming an unusual save operation.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Confirmation {
+    /// Replace a file which is not the current output.
+    Overwrite,
+    /// Create missing parent directories.
+    CreateDirectory,
+}
+
+impl Confirmation {
+    /// Text of the question.
+    fn text(self) -> &'static str {
+        match self {
+            Self::Overwrite => "File exists, overwrite it?",
+            Self::CreateDirectory => "Directory does not exist, create it?",
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::dialog::{Dialog, DialogLine};
+use crate::terminal::Terminal;
+
+/// Message prompt of the search dialog.
+const SEARCH_DIALOG_PROMPT: &str = "Search text (empty to clear):";
+
+/// Dialog for searching text on the canvas.
+#[derive(Default, PartialEq, Eq)]
+pub struct SearchDialog {
+    query: String,
+}
+
+impl SearchDialog {
+    /// Create a new search dialog, starting with the previous query.
+    pub fn new(query: String) -> Self {
+        Self { query }
+    }
+
+    /// Process a keystroke.
+    ///
+    /// Returns `true` if the dialog shrunk and a full redraw is required.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> bool {
+        // Only accept renderable glyphs.
+        if glyph != '\x7f' && glyph.width().unwrap_or_default() == 0 {
+            return false;
+        }
+
+        match glyph {
+            '\x7f' => {
+                let _ = self.query.pop();
+
+                // Redraw everything if backspace caused dialog to shrink.
+                if self.query.width() + 1 > SEARCH_DIALOG_PROMPT.len() {
+                    return true;
+                }
+            },
+            c => self.query.push(c),
+        }
+
+        self.render(terminal);
+        false
+    }
+
+    /// The submitted search query.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+}
+
+impl Dialog for SearchDialog {
+    fn lines(&self) -> Vec<String> {
+        vec![SEARCH_DIALOG_PROMPT.into(), self.query.clone()]
+    }
+
+    fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
+        Some((lines.get(1).map(|line| line.width()).unwrap_or_default(), 1))
+    }
+}
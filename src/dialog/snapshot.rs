@@ -0,0 +1,108 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::dialog::{Dialog, DialogLine};
+use crate::terminal::Terminal;
+
+/// Message prompt of the snapshot dialog.
+const SNAPSHOT_DIALOG_PROMPT: &str = "Snapshot name:";
+
+/// Message prompt of the snapshot list dialog.
+const SNAPSHOT_LIST_DIALOG_PROMPT: &str = "Pick a snapshot to restore:";
+
+/// Maximum number of snapshots listed in the dialog.
+const MAX_SNAPSHOTS: usize = 9;
+
+/// Dialog for naming a new snapshot.
+#[derive(Default, PartialEq, Eq)]
+pub struct SnapshotDialog {
+    name: String,
+}
+
+impl SnapshotDialog {
+    /// Create a new snapshot dialog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a keystroke.
+    ///
+    /// Returns `true` if the dialog shrunk and a full redraw is required.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> bool {
+        // Only accept renderable glyphs.
+        if glyph != '\x7f' && glyph.width().unwrap_or_default() == 0 {
+            return false;
+        }
+
+        match glyph {
+            '\x7f' => {
+                let _ = self.name.pop();
+
+                // Redraw everything if backspace caused dialog to shrink.
+                if self.name.width() + 1 > SNAPSHOT_DIALOG_PROMPT.len() {
+                    return true;
+                }
+            },
+            c => self.name.push(c),
+        }
+
+        self.render(terminal);
+        false
+    }
+
+    /// The submitted snapshot name.
+    pub fn name(&self) -> Option<&str> {
+        let name = self.name.trim();
+        (!name.is_empty()).then_some(name)
+    }
+}
+
+impl Dialog for SnapshotDialog {
+    fn lines(&self) -> Vec<String> {
+        vec![SNAPSHOT_DIALOG_PROMPT.into(), self.name.clone()]
+    }
+
+    fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
+        Some((lines.get(1).map(|line| line.width()).unwrap_or_default(), 1))
+    }
+}
+
+/// Dialog listing snapshots for restoring them.
+#[derive(PartialEq, Eq)]
+pub struct SnapshotListDialog {
+    names: Vec<String>,
+}
+
+impl SnapshotListDialog {
+    /// Create a new snapshot list dialog.
+    ///
+    /// Snapshots are listed in the order of `names`.
+    pub fn new(names: Vec<String>) -> Self {
+        Self { names }
+    }
+
+    /// Get the name of the snapshot selected by a keystroke.
+    pub fn snapshot(&self, glyph: char) -> Option<&str> {
+        let index = glyph.to_digit(10)?.checked_sub(1)? as usize;
+        self.names.iter().take(MAX_SNAPSHOTS).nth(index).map(String::as_str)
+    }
+}
+
+impl Dialog for SnapshotListDialog {
+    fn lines(&self) -> Vec<String> {
+        if self.names.is_empty() {
+            return vec!["No snapshots saved".into()];
+        }
+
+        let mut lines = vec![SNAPSHOT_LIST_DIALOG_PROMPT.into(), String::new()];
+        for (i, name) in self.names.iter().take(MAX_SNAPSHOTS).enumerate() {
+            lines.push(format!("\x1b[32m{}\x1b[39m  {name}", i + 1));
+        }
+
+        // Indicate snapshots that do not fit into the dialog.
+        if self.names.len() > MAX_SNAPSHOTS {
+            lines.push(format!("   ... and {} older", self.names.len() - MAX_SNAPSHOTS));
+        }
+
+        lines
+    }
+}
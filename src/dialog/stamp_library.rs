@@ -0,0 +1,44 @@
+use crate::dialog::Dialog;
+
+/// Message prompt of the stamp library dialog.
+const STAMP_LIBRARY_DIALOG_PROMPT: &str = "Pick a stamp:";
+
+/// Built-in stamps with their names.
+const STAMPS: [(&str, &str); 9] = [
+    ("database", " _______\n(_______)\n|       |\n|       |\n(_______)"),
+    ("cloud", "   .--.\n .(    ).\n(___.__)__)"),
+    ("person", " o\n/|\\\n/ \\"),
+    ("right arrow", "    |\\\n----+ \\\n----+ /\n    |/"),
+    ("left arrow", " /|\n/ +----\n\\ +----\n \\|"),
+    ("up arrow", "  /\\\n /  \\\n/_  _\\\n |  |\n |  |"),
+    ("down arrow", " |  |\n |  |\n_|  |_\n\\    /\n \\  /\n  \\/"),
+    ("left brace", " /\n |\n<\n |\n \\"),
+    ("right brace", "\\\n|\n >\n|\n/"),
+];
+
+/// Dialog for picking a stamp from the built-in library.
+#[derive(Default, PartialEq, Eq)]
+pub struct StampLibraryDialog;
+
+impl StampLibraryDialog {
+    /// Create a new stamp library dialog.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Get the stamp selected by a keystroke.
+    pub fn stamp(&self, glyph: char) -> Option<&'static str> {
+        let index = glyph.to_digit(10)?.checked_sub(1)?;
+        STAMPS.get(index as usize).map(|(_, stamp)| *stamp)
+    }
+}
+
+impl Dialog for StampLibraryDialog {
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![STAMP_LIBRARY_DIALOG_PROMPT.into(), String::new()];
+        for (i, (name, _)) in STAMPS.iter().enumerate() {
+            lines.push(format!("\x1b[32m{}\x1b[39m  {}", i + 1, name));
+        }
+        lines
+    }
+}
@@ -0,0 +1,81 @@
+use crate::dialog::Dialog;
+use crate::terminal::{Terminal, TextStyle};
+
+/// Message prompt of the text style dialog.
+const STYLE_DIALOG_PROMPT: &str = "Text style (press key to toggle):";
+/// Help text of the text style dialog.
+const STYLE_DIALOG_HELP: &str = "[ENTER] Apply";
+
+/// Text rendered with the selected styles.
+const PREVIEW_TEXT: &str = "Sketch 0123 ┌─┐";
+
+/// Available text styles, with their toggle key and label.
+const STYLES: [(TextStyle, char, &str); 2] =
+    [(TextStyle::BOLD, 'b', "Bold"), (TextStyle::ITALICS, 'i', "Italic")];
+
+/// Dialog for toggling individual text styles.
+#[derive(PartialEq, Eq)]
+pub struct StyleDialog {
+    style: TextStyle,
+}
+
+impl StyleDialog {
+    /// Create a new text style dialog, starting with the current style.
+    pub fn new(style: TextStyle) -> Self {
+        Self { style }
+    }
+
+    /// Process a keystroke.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) {
+        let glyph = glyph.to_ascii_lowercase();
+        if let Some((style, ..)) = STYLES.iter().find(|(_, key, _)| *key == glyph) {
+            self.style.toggle(*style);
+            self.render(terminal);
+        }
+    }
+
+    /// The selected text style.
+    pub fn style(&self) -> TextStyle {
+        self.style
+    }
+}
+
+impl Dialog for StyleDialog {
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![STYLE_DIALOG_PROMPT.into(), String::new()];
+
+        for (style, key, label) in STYLES {
+            let checkbox = if self.style.contains(style) { "\x1b[32m[x]\x1b[39m" } else { "[ ]" };
+            lines.push(format!("{checkbox} {} {label}", key.to_ascii_uppercase()));
+        }
+
+        lines.push(String::new());
+        lines.push(format!(
+            "Preview: {}{PREVIEW_TEXT}{}",
+            self.style.escape(),
+            TextStyle::empty().escape()
+        ));
+        lines.push(String::new());
+        lines.push(STYLE_DIALOG_HELP.into());
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_styles() {
+        let mut terminal = Terminal::headless(Default::default());
+        let mut dialog = StyleDialog::new(TextStyle::BOLD);
+
+        dialog.keyboard_input(&mut terminal, 'i');
+        assert_eq!(dialog.style(), TextStyle::BOLD | TextStyle::ITALICS);
+
+        dialog.keyboard_input(&mut terminal, 'B');
+        dialog.keyboard_input(&mut terminal, 'x');
+        assert_eq!(dialog.style(), TextStyle::ITALICS);
+    }
+}
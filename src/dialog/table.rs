@@ -0,0 +1,79 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::dialog::{Dialog, DialogLine};
+use crate::terminal::{Color, NamedColor, Terminal};
+
+/// Message prompt of the table dialog.
+const TABLE_DIALOG_PROMPT: &str = "Table size (rows x columns):";
+
+/// Dialog for picking the number of rows and columns of a new table.
+#[derive(Default, PartialEq, Eq)]
+pub struct TableDialog {
+    size: String,
+    error: bool,
+}
+
+impl TableDialog {
+    /// Create a new table dialog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a keystroke.
+    ///
+    /// Returns `true` if the dialog shrunk and a full redraw is required.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> bool {
+        // Only accept renderable glyphs.
+        if glyph != '\x7f' && glyph.width().unwrap_or_default() == 0 {
+            return false;
+        }
+
+        // Clear error when the size is changed.
+        self.error = false;
+
+        match glyph {
+            '\x7f' => {
+                let _ = self.size.pop();
+
+                // Redraw everything if backspace caused dialog to shrink.
+                if self.size.width() + 1 > TABLE_DIALOG_PROMPT.len() {
+                    return true;
+                }
+            },
+            c => self.size.push(c),
+        }
+
+        // Redraw just the dialog.
+        self.render(terminal);
+        false
+    }
+
+    /// The submitted number of rows and columns.
+    pub fn size(&self) -> Option<(usize, usize)> {
+        let (rows, columns) = self.size.split_once('x')?;
+        let rows = rows.trim().parse().ok().filter(|rows| *rows > 0)?;
+        let columns = columns.trim().parse().ok().filter(|columns| *columns > 0)?;
+        Some((rows, columns))
+    }
+
+    /// Indicate an error to the user.
+    pub fn mark_failed(&mut self, terminal: &mut Terminal) {
+        self.error = true;
+        self.render(terminal);
+    }
+}
+
+impl Dialog for TableDialog {
+    fn lines(&self) -> Vec<String> {
+        vec![TABLE_DIALOG_PROMPT.into(), self.size.clone()]
+    }
+
+    fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
+        Some((lines.get(1).map(|line| line.width()).unwrap_or_default(), 1))
+    }
+
+    fn box_color(&self) -> (Color, Color) {
+        let fg = if self.error { Color::Named(NamedColor::Red) } else { Color::default() };
+        (fg, Color::default())
+    }
+}
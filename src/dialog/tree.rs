@@ -0,0 +1,119 @@
+use unicode_width::UnicodeWidthChar;
+
+use crate::dialog::{Dialog, DialogLine};
+use crate::terminal::{Color, NamedColor, Terminal};
+use crate::tree::{self, Item};
+
+/// Message prompt of the tree diagram dialog.
+const TREE_DIALOG_PROMPT: &str = "Indented list (empty line to finish):";
+
+/// Indentation inserted for every tab keystroke.
+const TAB_INDENT: &str = "  ";
+
+/// Dialog for generating tree diagrams from indented lists.
+#[derive(PartialEq, Eq)]
+pub struct TreeDialog {
+    lines: Vec<String>,
+    error: bool,
+}
+
+impl Default for TreeDialog {
+    fn default() -> Self {
+        Self { lines: vec![String::new()], error: false }
+    }
+}
+
+impl TreeDialog {
+    /// Create a new tree diagram dialog.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a keystroke.
+    ///
+    /// Returns `true` if the dialog shrunk and a full redraw is required.
+    pub fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) -> bool {
+        // Only accept renderable glyphs.
+        if !matches!(glyph, '\x7f' | '\t' | '\n') && glyph.width().unwrap_or_default() == 0 {
+            return false;
+        }
+
+        // Clear error when the list is changed.
+        self.error = false;
+
+        let lines = self.lines.len();
+        let line = self.lines.last_mut().unwrap();
+        match glyph {
+            // Remove empty lines, to allow going back to the previous one.
+            '\x7f' if line.is_empty() && lines > 1 => {
+                self.lines.pop();
+                return true;
+            },
+            '\x7f' => {
+                let _ = line.pop();
+                return true;
+            },
+            '\t' => line.push_str(TAB_INDENT),
+            '\n' => self.lines.push(String::new()),
+            c => line.push(c),
+        }
+
+        // Redraw just the dialog.
+        self.render(terminal);
+        false
+    }
+
+    /// Append pasted text to the list.
+    pub fn paste(&mut self, terminal: &mut Terminal, text: &str) {
+        for (i, pasted) in text.lines().filter(|line| !line.trim().is_empty()).enumerate() {
+            let line = self.lines.last_mut().unwrap();
+            if i > 0 || !line.is_empty() {
+                self.lines.push(String::new());
+            }
+            self.lines.last_mut().unwrap().push_str(pasted);
+        }
+        self.lines.push(String::new());
+
+        self.error = false;
+        self.render(terminal);
+    }
+
+    /// Whether the line being edited is empty.
+    ///
+    /// Submitting an empty line finishes the list.
+    pub fn line_empty(&self) -> bool {
+        self.lines.last().map(|line| line.trim().is_empty()).unwrap_or(true)
+    }
+
+    /// The submitted list items.
+    ///
+    /// Returns `None` if the list is empty.
+    pub fn items(&self) -> Option<Vec<Item>> {
+        let items = tree::parse(&self.lines.join("\n"));
+        (!items.is_empty()).then_some(items)
+    }
+
+    /// Indicate an error to the user.
+    pub fn mark_failed(&mut self, terminal: &mut Terminal) {
+        self.error = true;
+        self.render(terminal);
+    }
+}
+
+impl Dialog for TreeDialog {
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![TREE_DIALOG_PROMPT.into()];
+        lines.extend(self.lines.iter().cloned());
+        lines
+    }
+
+    fn cursor_position(&self, lines: &[DialogLine]) -> Option<(usize, usize)> {
+        let line = lines.len() - 1;
+        Some((lines.last().map(|line| line.width()).unwrap_or_default(), line))
+    }
+
+    fn box_color(&self) -> (Color, Color) {
+        let fg = if self.error { Color::Named(NamedColor::Red) } else { Color::default() };
+        (fg, Color::default())
+    }
+}
@@ -0,0 +1,615 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::color::{Color, Intensity, TextStyle};
+use crate::palette::Palette;
+use crate::{Cell, Grid};
+
+/// Width of a cell in SVG exports, in pixels.
+const SVG_CELL_WIDTH: usize = 8;
+
+/// Height of a cell in SVG exports, in pixels.
+const SVG_CELL_HEIGHT: usize = 16;
+
+/// Distance between the top of a cell and the text baseline in SVG exports.
+const SVG_BASELINE: usize = 12;
+
+/// Font size of SVG exports, in pixels.
+const SVG_FONT_SIZE: usize = 13;
+
+/// Opacity of dim text in HTML and SVG exports.
+const DIM_OPACITY: f32 = 0.5;
+
+/// Light and dark color of the checkerboard background.
+const CHECKERBOARD_COLORS: (&str, &str) = ("#ffffff", "#cccccc");
+
+/// Size of a single checkerboard square, in pixels.
+const CHECKERBOARD_SIZE: usize = 8;
+
+/// Number of lines rendered together in parallel exports.
+const STRIPE_LINES: usize = 64;
+
+/// Encoding of colors in text with SGR escapes.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SgrEncoding {
+    /// Separator between the parameters of extended color escapes.
+    pub separator: char,
+    /// Palette all colors are reduced to, for terminals with limited color
+    /// support.
+    pub palette: Option<Palette>,
+}
+
+impl Default for SgrEncoding {
+    fn default() -> Self {
+        Self { separator: ':', palette: None }
+    }
+}
+
+/// Settings applied by all exporters.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub struct ExportSettings {
+    /// Encoding of colors in text with SGR escapes.
+    pub encoding: SgrEncoding,
+    /// Rendering of cells without background color in HTML and SVG exports.
+    pub background: Background,
+}
+
+/// Rendering of cells without background color in HTML and SVG exports.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum Background {
+    /// Let the document below the export show through.
+    #[default]
+    Transparent,
+    /// Mark transparent cells with a checkerboard pattern.
+    Checkerboard,
+}
+
+impl SgrEncoding {
+    /// Get the color written for a cell's color.
+    pub fn map_color(&self, color: Color) -> Color {
+        self.palette.map_or(color, |palette| palette.nearest(color))
+    }
+}
+
+/// Output format for sketches.
+pub trait Exporter: Send + Sync {
+    /// Human-readable name of the format.
+    fn name(&self) -> &'static str;
+
+    /// File extension of the format, without the leading dot.
+    fn extension(&self) -> &'static str;
+
+    /// Whether files with this format's extension are exported in it.
+    ///
+    /// Formats which are not picked by extension can only be requested
+    /// explicitly.
+    fn by_extension(&self) -> bool {
+        true
+    }
+
+    /// Write the content of a grid in this format.
+    fn export(&self, grid: &Grid, output: &mut dyn Write) -> io::Result<()>;
+
+    /// Render a range of the content lines independently of all other lines.
+    ///
+    /// Formats supporting this are exported in parallel by [`export_parallel`],
+    /// placing the stripes between [`Exporter::header`] and
+    /// [`Exporter::footer`].
+    fn stripe(&self, _lines: &[Vec<Cell>], _range: Range<usize>) -> Option<String> {
+        None
+    }
+
+    /// Document start, written before the first stripe.
+    fn header(&self, _lines: &[Vec<Cell>]) -> String {
+        String::new()
+    }
+
+    /// Document end, written after the last stripe.
+    fn footer(&self) -> String {
+        String::new()
+    }
+}
+
+/// All available export formats.
+///
+/// The first exporter is the default, used for all unknown extensions.
+pub fn exporters(settings: ExportSettings) -> Vec<Box<dyn Exporter>> {
+    let ExportSettings { encoding, background } = settings;
+    vec![
+        Box::new(Ansi { encoding }),
+        Box::new(Html { background }),
+        Box::new(Svg { background }),
+        Box::new(Text),
+    ]
+}
+
+/// Get the exporter for a format's file extension.
+pub fn for_extension(extension: &str, settings: ExportSettings) -> Option<Box<dyn Exporter>> {
+    exporters(settings).into_iter().find(|exporter| exporter.extension() == extension)
+}
+
+/// Get the exporter matching the extension of a path.
+pub fn for_path(path: &Path, settings: ExportSettings) -> Box<dyn Exporter> {
+    let extension = path.extension().unwrap_or_default();
+    let mut exporters = exporters(settings);
+    let index = exporters
+        .iter()
+        .position(|exporter| exporter.by_extension() && extension == exporter.extension());
+    exporters.swap_remove(index.unwrap_or(0))
+}
+
+/// Write the content of a grid, rendering stripes of lines on all available
+/// threads.
+///
+/// The `progress` callback receives the number of finished and total stripes.
+/// Setting `cancel` aborts the export with an [`io::ErrorKind::Interrupted`]
+/// error, before anything is written to the output. Formats without stripe
+/// support are exported in one piece.
+pub fn export_parallel(
+    exporter: &dyn Exporter,
+    grid: &Grid,
+    output: &mut dyn Write,
+    progress: &(dyn Fn(usize, usize) + Sync),
+    cancel: &AtomicBool,
+) -> io::Result<()> {
+    let lines = content_lines(grid);
+    let ranges: Vec<_> = (0..lines.len())
+        .step_by(STRIPE_LINES)
+        .map(|start| start..(start + STRIPE_LINES).min(lines.len()))
+        .collect();
+    if ranges.is_empty() {
+        return exporter.export(grid, output);
+    }
+
+    // Let each thread grab the next stripe until all are rendered.
+    let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+    let stripes = Mutex::new(vec![None; ranges.len()]);
+    let next = AtomicUsize::new(0);
+    let done = AtomicUsize::new(0);
+    let unsupported = AtomicBool::new(false);
+    thread::scope(|scope| {
+        for _ in 0..threads.min(ranges.len()) {
+            scope.spawn(|| {
+                while !cancel.load(Ordering::Relaxed) && !unsupported.load(Ordering::Relaxed) {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(range) = ranges.get(index) else { break };
+
+                    match exporter.stripe(lines, range.clone()) {
+                        Some(stripe) => stripes.lock().unwrap()[index] = Some(stripe),
+                        None => unsupported.store(true, Ordering::Relaxed),
+                    }
+
+                    progress(done.fetch_add(1, Ordering::Relaxed) + 1, ranges.len());
+                }
+            });
+        }
+    });
+
+    if cancel.load(Ordering::Relaxed) {
+        return Err(io::Error::new(io::ErrorKind::Interrupted, "export cancelled"));
+    }
+
+    if unsupported.into_inner() {
+        exporter.export(grid, output)?;
+        progress(ranges.len(), ranges.len());
+        return Ok(());
+    }
+
+    // Stitch the stripes together in their original order.
+    output.write_all(exporter.header(lines).as_bytes())?;
+    for stripe in stripes.into_inner().unwrap().into_iter().flatten() {
+        output.write_all(stripe.as_bytes())?;
+    }
+    output.write_all(exporter.footer().as_bytes())
+}
+
+/// Write all content lines of a grid as a single stripe.
+fn export_stripes(exporter: &dyn Exporter, grid: &Grid, output: &mut dyn Write) -> io::Result<()> {
+    let lines = content_lines(grid);
+    let stripe = exporter.stripe(lines, 0..lines.len()).unwrap_or_default();
+    let document = [exporter.header(lines), stripe, exporter.footer()].concat();
+    output.write_all(document.as_bytes())
+}
+
+/// Text with SGR escapes for colors and styles.
+pub struct Ansi {
+    /// Encoding of colors in the exported escapes.
+    pub encoding: SgrEncoding,
+}
+
+impl Exporter for Ansi {
+    fn name(&self) -> &'static str {
+        "ANSI text"
+    }
+
+    fn extension(&self) -> &'static str {
+        "ans"
+    }
+
+    fn export(&self, grid: &Grid, output: &mut dyn Write) -> io::Result<()> {
+        output.write_all(grid.trimmed_text(self.encoding).as_bytes())
+    }
+}
+
+/// Preformatted HTML text, with inline styles for colors.
+#[derive(Default)]
+pub struct Html {
+    /// Rendering of cells without background color.
+    pub background: Background,
+}
+
+impl Exporter for Html {
+    fn name(&self) -> &'static str {
+        "HTML"
+    }
+
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn export(&self, grid: &Grid, output: &mut dyn Write) -> io::Result<()> {
+        export_stripes(self, grid, output)
+    }
+
+    fn header(&self, _lines: &[Vec<Cell>]) -> String {
+        match self.background {
+            Background::Transparent => String::from("<pre>\n"),
+            Background::Checkerboard => {
+                let (light, dark) = CHECKERBOARD_COLORS;
+                format!(
+                    "<pre style=\"background: repeating-conic-gradient({dark} 0% 25%, {light} 0% \
+                     50%) 0 0 / {size}px {size}px;\">\n",
+                    size = 2 * CHECKERBOARD_SIZE,
+                )
+            },
+        }
+    }
+
+    fn stripe(&self, lines: &[Vec<Cell>], range: Range<usize>) -> Option<String> {
+        let mut html = String::new();
+        for line in &lines[range] {
+            let end = line_end(line);
+
+            // Group cells with identical styling into a single span.
+            let mut span = String::new();
+            let mut column = 0;
+            while column < end {
+                let cell = &line[column];
+
+                let style = css(cell);
+                if style != span {
+                    if !span.is_empty() {
+                        html.push_str("</span>");
+                    }
+                    if !style.is_empty() {
+                        let _ = write!(html, "<span style=\"{style}\">");
+                    }
+                    span = style;
+                }
+
+                column += push_escaped_glyph(&mut html, cell);
+            }
+
+            if !span.is_empty() {
+                html.push_str("</span>");
+            }
+            html.push('\n');
+        }
+
+        Some(html)
+    }
+
+    fn footer(&self) -> String {
+        String::from("</pre>\n")
+    }
+}
+
+/// Scalable vector graphics, with one text element per run of styled cells.
+#[derive(Default)]
+pub struct Svg {
+    /// Rendering of cells without background color.
+    pub background: Background,
+}
+
+impl Exporter for Svg {
+    fn name(&self) -> &'static str {
+        "SVG"
+    }
+
+    fn extension(&self) -> &'static str {
+        "svg"
+    }
+
+    fn export(&self, grid: &Grid, output: &mut dyn Write) -> io::Result<()> {
+        export_stripes(self, grid, output)
+    }
+
+    fn header(&self, lines: &[Vec<Cell>]) -> String {
+        let columns = lines.iter().map(|line| line_end(line)).max().unwrap_or_default();
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             font-family=\"monospace\" font-size=\"{SVG_FONT_SIZE}\">\n",
+            columns * SVG_CELL_WIDTH,
+            lines.len() * SVG_CELL_HEIGHT,
+        );
+
+        // Underlay the sketch with a repeating checkerboard pattern.
+        if self.background == Background::Checkerboard {
+            let (light, dark) = CHECKERBOARD_COLORS;
+            let size = CHECKERBOARD_SIZE;
+            let _ = writeln!(
+                svg,
+                "<defs><pattern id=\"checkerboard\" width=\"{}\" height=\"{0}\" \
+                 patternUnits=\"userSpaceOnUse\"><rect width=\"{0}\" height=\"{0}\" \
+                 fill=\"{light}\"/><rect width=\"{size}\" height=\"{size}\" \
+                 fill=\"{dark}\"/><rect x=\"{size}\" y=\"{size}\" width=\"{size}\" \
+                 height=\"{size}\" fill=\"{dark}\"/></pattern></defs>",
+                2 * size,
+            );
+            svg.push_str("<rect width=\"100%\" height=\"100%\" fill=\"url(#checkerboard)\"/>\n");
+        }
+
+        svg
+    }
+
+    fn stripe(&self, lines: &[Vec<Cell>], range: Range<usize>) -> Option<String> {
+        let mut svg = String::new();
+        for (i, line) in lines.iter().enumerate().take(range.end).skip(range.start) {
+            let y = i * SVG_CELL_HEIGHT;
+            let end = line_end(line);
+
+            // Group cells with identical styling into a single element.
+            let mut column = 0;
+            while column < end {
+                let first = &line[column];
+                let styling = |cell: &Cell| (cell.foreground, cell.background, cell.style);
+                let start = column;
+                let mut text = String::new();
+                while column < end && styling(&line[column]) == styling(first) {
+                    column += push_escaped_glyph(&mut text, &line[column]);
+                }
+
+                let x = start * SVG_CELL_WIDTH;
+                let width = (column - start) * SVG_CELL_WIDTH;
+                if let Some(rgb) = first.background.rgb() {
+                    let _ = writeln!(
+                        svg,
+                        "<rect x=\"{x}\" y=\"{y}\" width=\"{width}\" height=\"{SVG_CELL_HEIGHT}\" \
+                         fill=\"#{:02x}{:02x}{:02x}\"/>",
+                        rgb.r, rgb.g, rgb.b,
+                    );
+                }
+
+                if text.trim().is_empty() {
+                    continue;
+                }
+
+                let mut attributes = String::new();
+                if let Some(rgb) = first.foreground.rgb() {
+                    let _ =
+                        write!(attributes, " fill=\"#{:02x}{:02x}{:02x}\"", rgb.r, rgb.g, rgb.b);
+                }
+                match first.style.intensity() {
+                    Intensity::Bold => attributes.push_str(" font-weight=\"bold\""),
+                    Intensity::Dim => {
+                        let _ = write!(attributes, " fill-opacity=\"{DIM_OPACITY}\"");
+                    },
+                    Intensity::Normal => (),
+                }
+                if first.style.contains(TextStyle::ITALICS) {
+                    attributes.push_str(" font-style=\"italic\"");
+                }
+                let _ = writeln!(
+                    svg,
+                    "<text x=\"{x}\" y=\"{}\" textLength=\"{width}\" \
+                     lengthAdjust=\"spacingAndGlyphs\" \
+                     xml:space=\"preserve\"{attributes}>{text}</text>",
+                    y + SVG_BASELINE,
+                );
+            }
+        }
+
+        Some(svg)
+    }
+
+    fn footer(&self) -> String {
+        String::from("</svg>\n")
+    }
+}
+
+/// Plain text, without any colors or styles.
+pub struct Text;
+
+impl Exporter for Text {
+    fn name(&self) -> &'static str {
+        "plain text"
+    }
+
+    fn extension(&self) -> &'static str {
+        "txt"
+    }
+
+    /// Plain text is only available explicitly, since sketches with escapes
+    /// are commonly stored as `.txt` files.
+    fn by_extension(&self) -> bool {
+        false
+    }
+
+    fn export(&self, grid: &Grid, output: &mut dyn Write) -> io::Result<()> {
+        export_stripes(self, grid, output)
+    }
+
+    fn stripe(&self, lines: &[Vec<Cell>], range: Range<usize>) -> Option<String> {
+        let mut text = String::new();
+        for line in &lines[range] {
+            let mut line_text = String::new();
+            let mut column = 0;
+            while column < line.len() {
+                let cell = &line[column];
+                match cell.c.width() {
+                    Some(1) | Some(2) => cell.push_glyph(&mut line_text),
+                    _ => line_text.push(' '),
+                }
+                column += cell.c.width().filter(|w| *w != 0).unwrap_or(1);
+            }
+
+            text.push_str(line_text.trim_end());
+            text.push('\n');
+        }
+
+        Some(text)
+    }
+}
+
+/// Lines of a grid, without the empty lines above and below the sketch.
+fn content_lines(grid: &Grid) -> &[Vec<Cell>] {
+    let is_empty = |line: &Vec<Cell>| line.iter().all(|cell| *cell == Cell::default());
+    let start = grid.iter().position(|line| !is_empty(line)).unwrap_or(grid.len());
+    let end = grid.iter().rposition(|line| !is_empty(line)).map_or(start, |end| end + 1);
+    &grid[start..end]
+}
+
+/// Column after the last non-empty cell of a line.
+fn line_end(line: &[Cell]) -> usize {
+    line.iter().rposition(|cell| *cell != Cell::default()).map_or(0, |i| i + 1)
+}
+
+/// Append a cell's glyph with XML special characters escaped.
+///
+/// Returns the number of columns occupied by the cell.
+fn push_escaped_glyph(text: &mut String, cell: &Cell) -> usize {
+    let width = cell.c.width();
+    match (width, cell.c) {
+        (_, '&') => text.push_str("&amp;"),
+        (_, '<') => text.push_str("&lt;"),
+        (_, '>') => text.push_str("&gt;"),
+        (Some(1) | Some(2), _) => cell.push_glyph(text),
+        _ => text.push(' '),
+    }
+
+    // Skip columns when dealing with fullwidth characters.
+    width.filter(|w| *w != 0).unwrap_or(1)
+}
+
+/// Inline CSS for the colors and text style of a cell.
+fn css(cell: &Cell) -> String {
+    let mut css = String::new();
+    let colors = [("color", cell.foreground), ("background-color", cell.background)];
+    for (property, color) in colors {
+        if let Some(rgb) = color.rgb() {
+            let _ = write!(css, "{property}: #{:02x}{:02x}{:02x}; ", rgb.r, rgb.g, rgb.b);
+        }
+    }
+    match cell.style.intensity() {
+        Intensity::Bold => css.push_str("font-weight: bold; "),
+        Intensity::Dim => {
+            let _ = write!(css, "opacity: {DIM_OPACITY}; ");
+        },
+        Intensity::Normal => (),
+    }
+    if cell.style.contains(TextStyle::ITALICS) {
+        css.push_str("font-style: italic; ");
+    }
+    css.truncate(css.trim_end().len());
+    css
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::{NamedColor, Rgb};
+    use crate::journal::Journal;
+
+    #[test]
+    fn html_export() {
+        let red = Color::Named(NamedColor::Red);
+        let mut cells = vec![vec![Cell::default(); 4]; 3];
+        cells[1][0] = Cell::new('<', red, Color::default(), TextStyle::BOLD);
+        cells[1][1] = Cell::new('a', Color::default(), Color::default(), TextStyle::empty());
+        let grid = Grid::new(cells, Journal::default());
+
+        let mut html = Vec::new();
+        Html { background: Background::Transparent }.export(&grid, &mut html).unwrap();
+        assert_eq!(
+            String::from_utf8(html).unwrap(),
+            "<pre>\n<span style=\"color: #cd0000; font-weight: bold;\">&lt;</span>a\n</pre>\n"
+        );
+
+        let exporter = for_path(Path::new("sketch.html"), ExportSettings::default());
+        assert_eq!(exporter.name(), "HTML");
+        let exporter = for_path(Path::new("sketch.txt"), ExportSettings::default());
+        assert_eq!(exporter.name(), "ANSI text");
+        let exporter = for_extension("txt", ExportSettings::default()).unwrap();
+        assert_eq!(exporter.name(), "plain text");
+    }
+
+    #[test]
+    fn checkerboard_background() {
+        let cells =
+            vec![vec![Cell::new('x', Color::default(), Color::default(), TextStyle::empty())]];
+        let grid = Grid::new(cells, Journal::default());
+
+        let mut html = Vec::new();
+        Html { background: Background::Checkerboard }.export(&grid, &mut html).unwrap();
+        assert!(String::from_utf8(html).unwrap().starts_with("<pre style=\"background: "));
+
+        let mut svg = Vec::new();
+        Svg { background: Background::Checkerboard }.export(&grid, &mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert!(svg.contains("<rect width=\"100%\" height=\"100%\" fill=\"url(#checkerboard)\"/>"));
+
+        let mut svg = Vec::new();
+        Svg::default().export(&grid, &mut svg).unwrap();
+        assert!(!String::from_utf8(svg).unwrap().contains("checkerboard"));
+    }
+
+    #[test]
+    fn parallel_export() {
+        let red = Color::Named(NamedColor::Red);
+        let mut cells = vec![vec![Cell::default(); 8]; 3 * STRIPE_LINES];
+        for (i, line) in cells.iter_mut().enumerate() {
+            line[i % 8] = Cell::new('x', red, Color::default(), TextStyle::empty());
+        }
+        let grid = Grid::new(cells, Journal::default());
+
+        let cancel = AtomicBool::new(false);
+        for exporter in exporters(ExportSettings::default()) {
+            let mut expected = Vec::new();
+            exporter.export(&grid, &mut expected).unwrap();
+
+            let mut stitched = Vec::new();
+            export_parallel(&*exporter, &grid, &mut stitched, &|_, _| (), &cancel).unwrap();
+            assert_eq!(stitched, expected, "{}", exporter.name());
+        }
+
+        let cancel = AtomicBool::new(true);
+        let mut output = Vec::new();
+        let result = export_parallel(&Svg::default(), &grid, &mut output, &|_, _| (), &cancel);
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::Interrupted);
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn palette_encoding() {
+        let orange = Color::Rgb(Rgb { r: 250, g: 130, b: 0 });
+        let cells = vec![vec![Cell::new('x', orange, Color::default(), TextStyle::empty())]];
+        let grid = Grid::new(cells, Journal::default());
+
+        let encoding = SgrEncoding { separator: ';', palette: Some(Palette::Xterm256) };
+        let mut text = Vec::new();
+        Ansi { encoding }.export(&grid, &mut text).unwrap();
+        assert_eq!(String::from_utf8(text).unwrap(), "\x1b[38;5;208mx\n");
+
+        let encoding = SgrEncoding { palette: Some(Palette::Ansi16), ..encoding };
+        assert_eq!(grid.trimmed_text(encoding), "\x1b[33mx\n");
+    }
+}
@@ -0,0 +1,235 @@
+/// Approximate ratio between a terminal cell's height and its width.
+pub const CELL_ASPECT_RATIO: f64 = 2.;
+
+/// Minimum share of stroke cells which must be close to a recognized shape.
+const SHAPE_COVERAGE: f64 = 0.9;
+
+/// Rasterize the outline of an ellipse.
+///
+/// The returned cells are `(column, line)` offsets relative to the center of
+/// the ellipse, with `radius_x` and `radius_y` in cells.
+pub fn ellipse(radius_x: usize, radius_y: usize) -> Vec<(isize, isize)> {
+    let (rx, ry) = (radius_x as isize, radius_y as isize);
+
+    // Degenerate ellipses are just straight lines.
+    if rx == 0 || ry == 0 {
+        let horizontal = (-rx..=rx).map(|x| (x, 0));
+        let vertical = (-ry..=ry).map(|y| (0, y));
+        let mut points: Vec<_> = horizontal.chain(vertical).collect();
+        points.sort_unstable();
+        points.dedup();
+        return points;
+    }
+
+    let mut points = Vec::new();
+    let mut plot = |x: isize, y: isize| {
+        points.extend_from_slice(&[(x, y), (-x, y), (x, -y), (-x, -y)]);
+    };
+
+    // Midpoint ellipse algorithm.
+    let (rx2, ry2) = ((rx * rx) as f64, (ry * ry) as f64);
+    let (mut x, mut y) = (0, ry);
+    let mut dx = 0.;
+    let mut dy = 2. * rx2 * y as f64;
+
+    // Region with a slope flatter than -1.
+    let mut p = ry2 - rx2 * ry as f64 + rx2 / 4.;
+    while dx < dy {
+        plot(x, y);
+
+        x += 1;
+        dx += 2. * ry2;
+        if p < 0. {
+            p += dx + ry2;
+        } else {
+            y -= 1;
+            dy -= 2. * rx2;
+            p += dx - dy + ry2;
+        }
+    }
+
+    // Region with a slope steeper than -1.
+    let mut p = ry2 * (x as f64 + 0.5).powi(2) + rx2 * (y as f64 - 1.).powi(2) - rx2 * ry2;
+    while y >= 0 {
+        plot(x, y);
+
+        y -= 1;
+        dy -= 2. * rx2;
+        if p > 0. {
+            p += rx2 - dy;
+        } else {
+            x += 1;
+            dx += 2. * ry2;
+            p += dx - dy + rx2;
+        }
+    }
+
+    points.sort_unstable();
+    points.dedup();
+    points
+}
+
+/// Radii of a circle centered at the origin that passes through a point.
+///
+/// With `aspect_correction`, the horizontal radius is scaled by the
+/// [`CELL_ASPECT_RATIO`] so the circle appears round on screen.
+pub fn circle_radii(dx: isize, dy: isize, aspect_correction: bool) -> (usize, usize) {
+    let (dx, dy) = (dx as f64, dy as f64);
+
+    if aspect_correction {
+        let radius = (dx / CELL_ASPECT_RATIO).hypot(dy);
+        ((radius * CELL_ASPECT_RATIO).round() as usize, radius.round() as usize)
+    } else {
+        let radius = dx.hypot(dy).round() as usize;
+        (radius, radius)
+    }
+}
+
+/// Direction of a straight line.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum LineDirection {
+    Horizontal,
+    Vertical,
+    /// Line with a 45 degree angle on screen.
+    Diagonal,
+}
+
+impl LineDirection {
+    /// Snap a line's travel to the closest horizontal, vertical, or diagonal
+    /// direction.
+    ///
+    /// The angles are measured on screen, taking the cell aspect ratio into
+    /// account.
+    pub fn snapped(dx: isize, dy: isize) -> Self {
+        let dx = dx.unsigned_abs() as f64;
+        let dy = dy.unsigned_abs() as f64 * CELL_ASPECT_RATIO;
+
+        // Split the quadrant into three sections at 22.5 and 67.5 degrees.
+        let tan = std::f64::consts::FRAC_PI_8.tan();
+        if dy <= dx * tan {
+            Self::Horizontal
+        } else if dx <= dy * tan {
+            Self::Vertical
+        } else {
+            Self::Diagonal
+        }
+    }
+}
+
+/// Clean shape approximated by a freehand stroke.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Shape {
+    /// Straight line between the start and end of the stroke.
+    Line((isize, isize), (isize, isize)),
+    /// Rectangle between its top left and bottom right corner.
+    Rectangle((isize, isize), (isize, isize)),
+}
+
+/// Recognize a straight line or rectangle in a rough freehand stroke.
+///
+/// The stroke's cells are `(column, line)` positions in the order they were
+/// drawn. Distances are measured on screen, taking the cell aspect ratio into
+/// account.
+pub fn recognize_shape(stroke: &[(isize, isize)]) -> Option<Shape> {
+    let (&first, &last) = (stroke.first()?, stroke.last()?);
+
+    // Convert cells to screen coordinates measured in lines.
+    let screen = |(column, line): (isize, isize)| (column as f64 / CELL_ASPECT_RATIO, line as f64);
+    let points: Vec<_> = stroke.iter().copied().map(screen).collect();
+    let covered = |distance: &dyn Fn((f64, f64)) -> f64, tolerance: f64| {
+        let close = points.iter().filter(|point| distance(**point) <= tolerance).count();
+        close as f64 >= points.len() as f64 * SHAPE_COVERAGE
+    };
+
+    let top_left =
+        (stroke.iter().map(|cell| cell.0).min()?, stroke.iter().map(|cell| cell.1).min()?);
+    let bottom_right =
+        (stroke.iter().map(|cell| cell.0).max()?, stroke.iter().map(|cell| cell.1).max()?);
+    let ((left, top), (right, bottom)) = (screen(top_left), screen(bottom_right));
+    let (width, height) = (right - left, bottom - top);
+    let size = width.max(height);
+
+    // Leave small scribbles alone.
+    if size < 2. {
+        return None;
+    }
+
+    let ((x0, y0), (x1, y1)) = (screen(first), screen(last));
+    let length = (x1 - x0).hypot(y1 - y0);
+
+    // Open strokes following the line between their ends are lines.
+    if length >= size * 0.8 {
+        let distance =
+            |(x, y): (f64, f64)| ((x1 - x0) * (y0 - y) - (x0 - x) * (y1 - y0)).abs() / length;
+        let tolerance = (length * 0.1).max(1.);
+        return covered(&distance, tolerance).then_some(Shape::Line(first, last));
+    }
+
+    // Closed strokes following the edges of their bounding box are rectangles.
+    if length <= size * 0.3 && width >= 1. && height >= 1. {
+        let distance = |(x, y): (f64, f64)| {
+            let horizontal = (x - left).abs().min((right - x).abs());
+            horizontal.min((y - top).abs()).min((bottom - y).abs())
+        };
+        let tolerance = (width.min(height) * 0.1).max(1.);
+        if covered(&distance, tolerance) {
+            return Some(Shape::Rectangle(top_left, bottom_right));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ellipse_outline() {
+        assert_eq!(ellipse(0, 0), vec![(0, 0)]);
+        assert_eq!(ellipse(1, 0), vec![(-1, 0), (0, 0), (1, 0)]);
+
+        let points = ellipse(4, 2);
+        assert!(points.contains(&(4, 0)) && points.contains(&(-4, 0)));
+        assert!(points.contains(&(0, 2)) && points.contains(&(0, -2)));
+        assert!(!points.contains(&(0, 0)));
+    }
+
+    #[test]
+    fn circle_aspect() {
+        assert_eq!(circle_radii(0, 3, true), (6, 3));
+        assert_eq!(circle_radii(0, 3, false), (3, 3));
+        assert_eq!(circle_radii(8, 0, true), (8, 4));
+    }
+
+    #[test]
+    fn shape_recognition() {
+        // Wobbly horizontal line.
+        let stroke: Vec<_> = (2..30).map(|column| (column, 5 + column % 3 / 2)).collect();
+        assert_eq!(recognize_shape(&stroke), Some(Shape::Line((2, 6), (29, 6))));
+
+        // Rectangle drawn clockwise, overshooting its start slightly.
+        let mut stroke: Vec<_> = (10..=30).map(|column| (column, 2)).collect();
+        stroke.extend((3..=10).map(|line| (30, line)));
+        stroke.extend((10..30).rev().map(|column| (column, 10)));
+        stroke.extend((2..10).rev().map(|line| (10 + line % 2, line)));
+        stroke.push((12, 2));
+        assert_eq!(recognize_shape(&stroke), Some(Shape::Rectangle((10, 2), (30, 10))));
+
+        // Circles, corners, and scribbles are left alone.
+        let circle: Vec<_> = ellipse(20, 10).into_iter().map(|(x, y)| (x + 30, y + 20)).collect();
+        assert_eq!(recognize_shape(&circle), None);
+        let mut corner: Vec<_> = (2..=20).map(|column| (column, 2)).collect();
+        corner.extend((3..=12).map(|line| (20, line)));
+        assert_eq!(recognize_shape(&corner), None);
+        assert_eq!(recognize_shape(&[(1, 1), (2, 1), (2, 2)]), None);
+    }
+
+    #[test]
+    fn line_snapping() {
+        assert_eq!(LineDirection::snapped(10, 1), LineDirection::Horizontal);
+        assert_eq!(LineDirection::snapped(1, 5), LineDirection::Vertical);
+        assert_eq!(LineDirection::snapped(-8, 4), LineDirection::Diagonal);
+        assert_eq!(LineDirection::snapped(6, -2), LineDirection::Diagonal);
+    }
+}
@@ -0,0 +1,1192 @@
+use std::cmp::{max, min, Ordering};
+use std::collections::HashSet;
+use std::fs::File;
+use std::ops::{Deref, RangeInclusive};
+use std::path::Path;
+use std::{io, iter, mem};
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::color::{Color, TextStyle};
+use crate::export::{self, ExportSettings, SgrEncoding};
+use crate::journal::{Change, Journal};
+use crate::junction::{self, Segments, Weight};
+use crate::lint::{self, Issue};
+use crate::Cell;
+
+/// Maximum number of columns of loaded sketches and fixed canvas sizes.
+pub const MAX_COLUMNS: usize = 2048;
+
+/// Maximum number of lines of loaded sketches and fixed canvas sizes.
+pub const MAX_LINES: usize = 2048;
+
+/// Sketch content grid.
+#[derive(Default)]
+pub struct Grid {
+    pub cells: Vec<Vec<Cell>>,
+
+    /// Undo history of all cell changes.
+    pub journal: Journal,
+
+    /// Cells which changed since the last frame.
+    pub damage: Damage,
+}
+
+impl Grid {
+    pub fn new(cells: Vec<Vec<Cell>>, journal: Journal) -> Self {
+        Self { cells, journal, damage: Damage::Full }
+    }
+
+    /// Mark a cell for redrawing with the next frame.
+    pub fn damage(&mut self, point: Point) {
+        if let Damage::Partial(points) = &mut self.damage {
+            points.insert(point);
+        }
+    }
+
+    /// Take all cells which need to be redrawn, resetting the damage.
+    pub fn take_damage(&mut self) -> Damage {
+        mem::replace(&mut self.damage, Damage::Partial(HashSet::new()))
+    }
+
+    /// Replace the cell at the specified point.
+    ///
+    /// This should be used over replacing the cell directly, since it correctly
+    /// records the change for undoing it in the future.
+    pub fn replace(&mut self, point: Point, cell: Cell) {
+        let target = &mut self.cells[point.line - 1][point.column - 1];
+        self.journal.record(point, target);
+        *target = cell;
+
+        self.damage(point);
+    }
+
+    /// Get all points in a run of columns.
+    ///
+    /// Points outside of the grid and spacers of fullwidth characters are
+    /// skipped.
+    pub fn run(&self, start: Point, count: usize) -> Vec<Point> {
+        let is_spacer = |point: &Point| {
+            let left = Point { column: point.column - 1, ..*point };
+            point.column > 1 && self.get(left).c.width() == Some(2)
+        };
+        (start.column..start.column + count)
+            .map(|column| Point { column, ..start })
+            .filter(|point| self.contains(*point) && !is_spacer(point))
+            .collect()
+    }
+
+    /// Reset the cell at the specified point to the default content.
+    pub fn clear(&mut self, point: Point) {
+        self.replace(point, Cell::default());
+    }
+
+    /// Undo the last revision.
+    ///
+    /// Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let mut changes = match self.journal.pop_undo() {
+            Some(changes) => changes,
+            None => return false,
+        };
+
+        self.swap(&mut changes);
+        self.journal.push_redo(changes);
+
+        true
+    }
+
+    /// Redo the last undone revision.
+    ///
+    /// Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let mut changes = match self.journal.pop_redo() {
+            Some(changes) => changes,
+            None => return false,
+        };
+
+        self.swap(&mut changes);
+        self.journal.push_undo(changes);
+
+        true
+    }
+
+    /// Swap cells in the grid with the content stored in a revision.
+    pub fn swap(&mut self, changes: &mut [Change]) {
+        for (point, cell) in changes {
+            mem::swap(&mut self.cells[point.line - 1][point.column - 1], cell);
+            self.damage(*point);
+        }
+    }
+
+    /// Get a trimmed version of the sketch.
+    ///
+    /// This will remove all empty lines from the top and bottom of the sketch.
+    /// Colors are written using the SGR `encoding`.
+    pub fn trimmed_text(&self, encoding: SgrEncoding) -> String {
+        let columns = self.first().map_or(0, Vec::len);
+        let viewport = Viewport { columns, lines: self.len(), ..Viewport::default() };
+        let mut text =
+            self.render(&viewport, encoding.separator, |color| encoding.map_color(color));
+
+        // Find the first non-empty line.
+        let start_offset = text
+            .chars()
+            .enumerate()
+            .take_while(|&(_, c)| c.is_whitespace())
+            .fold(0, |acc, (i, c)| if c == '\n' { i + 1 } else { acc });
+
+        // Remove empty lines above or below the sketch.
+        text = text[start_offset..].trim_end().to_owned();
+        text.push('\n');
+
+        text
+    }
+
+    /// Find the next cell matching a predicate in reading order.
+    ///
+    /// The search starts after `start` and wraps around at the end of the grid.
+    pub fn find_next(&self, start: Point, predicate: impl Fn(&Cell) -> bool) -> Option<Point> {
+        let columns = self.first().map_or(0, Vec::len);
+        let cells = columns * self.len();
+        let start = (start.line.saturating_sub(1) * columns + start.column.saturating_sub(1))
+            .min(cells.saturating_sub(1));
+
+        (1..=cells)
+            .map(|offset| (start + offset) % cells)
+            .map(|index| Point { column: index % columns + 1, line: index / columns + 1 })
+            .find(|point| predicate(self.get(*point)))
+    }
+
+    /// Find all occurrences of a string in the grid's text.
+    ///
+    /// Returns the first cell and the number of columns of every match.
+    pub fn search(&self, query: &str) -> Vec<(Point, usize)> {
+        let query: Vec<char> = query.chars().collect();
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches = Vec::new();
+        for (line, cells) in self.iter().enumerate() {
+            // Collect glyphs with their columns, skipping spacers of fullwidth characters.
+            let mut glyphs = Vec::new();
+            let mut column = 0;
+            while column < cells.len() {
+                let c = cells[column].c;
+                glyphs.push((column, if c == '\0' { ' ' } else { c }));
+                column += c.width().filter(|w| *w != 0).unwrap_or(1);
+            }
+
+            for window in glyphs.windows(query.len()) {
+                if window.iter().map(|(_, c)| c).eq(&query) {
+                    let (start, _) = window[0];
+                    let (end, c) = window[window.len() - 1];
+                    let columns = end + c.width().filter(|w| *w != 0).unwrap_or(1) - start;
+                    matches.push((Point { column: start + 1, line: line + 1 }, columns));
+                }
+            }
+        }
+
+        matches
+    }
+
+    /// Get the text of a rectangular region.
+    ///
+    /// Colors are written using the SGR `encoding`.
+    pub fn region_text(&self, start: Point, end: Point, encoding: SgrEncoding) -> String {
+        let (start, end) = Point::rect(start, end);
+        let viewport = Viewport {
+            column_offset: start.column - 1,
+            line_offset: start.line - 1,
+            columns: end.column + 1 - start.column,
+            lines: end.line + 1 - start.line,
+        };
+        let mut text =
+            self.render(&viewport, encoding.separator, |color| encoding.map_color(color));
+        text.push('\n');
+        text
+    }
+
+    /// Columns and lines covered by the trimmed sketch.
+    ///
+    /// Like [`Self::trimmed_text`], this includes empty columns to the left of
+    /// the sketch.
+    pub fn content_size(&self) -> (usize, usize) {
+        let width = |line: &Vec<Cell>| line.iter().rposition(|cell| !cell.is_empty());
+        let columns = self.iter().filter_map(width).max().map_or(0, |column| column + 1);
+
+        let start = self.iter().position(|line| width(line).is_some());
+        let end = self.iter().rposition(|line| width(line).is_some());
+        let lines = start.zip(end).map_or(0, |(start, end)| end - start + 1);
+
+        (columns, lines)
+    }
+
+    /// Top left and bottom right corner of the smallest rectangle containing
+    /// all non-empty cells.
+    ///
+    /// Returns `None` if the grid is empty.
+    pub fn bounding_box(&self) -> Option<(Point, Point)> {
+        let mut corners: Option<(Point, Point)> = None;
+        for (line, cells) in self.iter().enumerate() {
+            for (column, cell) in cells.iter().enumerate().filter(|(_, cell)| !cell.is_empty()) {
+                // Include the spacer of fullwidth characters.
+                let width = cell.c.width().filter(|w| *w != 0).unwrap_or(1);
+                let start = Point { column: column + 1, line: line + 1 };
+                let end = Point { column: column + width, line: line + 1 };
+
+                corners = Some(match corners {
+                    Some((top_left, bottom_right)) => {
+                        (Point::rect(top_left, start).0, Point::rect(bottom_right, end).1)
+                    },
+                    None => (start, end),
+                });
+            }
+        }
+        corners
+    }
+
+    /// Crop the grid to a rectangle, surrounded by `padding` empty cells.
+    ///
+    /// Like translations, crops are not part of the undo history.
+    pub fn crop(&mut self, start: Point, end: Point, padding: usize) {
+        let (start, end) = Point::rect(start, end);
+        let width = self.first().map_or(0, Vec::len);
+        let end = Point { column: min(end.column, width), line: min(end.line, self.len()) };
+        let columns = end.column.saturating_sub(start.column - 1) + 2 * padding;
+        let lines = end.line.saturating_sub(start.line - 1) + 2 * padding;
+
+        let mut cells = vec![vec![Cell::default(); columns]; padding];
+        for line in self.cells.get(start.line - 1..end.line).unwrap_or_default() {
+            let mut cropped = vec![Cell::default(); padding];
+            cropped.extend_from_slice(line.get(start.column - 1..end.column).unwrap_or_default());
+            cropped.resize(columns, Cell::default());
+            cells.push(cropped);
+        }
+        cells.resize(lines, vec![Cell::default(); columns]);
+        self.cells = cells;
+
+        // Keep the undo history pointing to the moved content.
+        let offset = (
+            padding as isize - (start.column - 1) as isize,
+            padding as isize - (start.line - 1) as isize,
+        );
+        self.journal.shift(offset, columns, lines);
+
+        self.damage = Damage::Full;
+    }
+
+    /// Try to write the Sketch to a file.
+    ///
+    /// The export format is picked based on the file extension.
+    pub fn persist(&self, path: &Path, settings: ExportSettings) -> io::Result<()> {
+        let exporter = export::for_path(path, settings);
+        let mut file = File::create(path)?;
+        exporter.export(self, &mut file)
+    }
+
+    /// Get cell at the specified point.
+    pub fn get(&self, point: Point) -> &Cell {
+        &self.cells[point.line - 1][point.column - 1]
+    }
+
+    /// Get all distinct colors used in the grid.
+    ///
+    /// The colors are sorted by their first occurrence, the default color is
+    /// ignored.
+    pub fn used_colors(&self) -> Vec<Color> {
+        let mut colors = Vec::new();
+
+        for cell in self.iter().flatten() {
+            // Ignore foreground colors of cells without visible glyphs.
+            let has_glyph = !cell.c.is_whitespace() && cell.c != '\0';
+            let foreground = has_glyph.then_some(cell.foreground);
+
+            for color in foreground.into_iter().chain([cell.background]) {
+                if color != Color::default() && !colors.contains(&color) {
+                    colors.push(color);
+                }
+            }
+        }
+
+        colors
+    }
+
+    /// Direction a connector leaves a box border in.
+    ///
+    /// Returns `Some(true)` for vertical borders, which are left horizontally,
+    /// `Some(false)` for horizontal borders, and `None` if there is no straight
+    /// border at `point`.
+    pub fn border_exit(&self, point: Point) -> Option<bool> {
+        let [up, right, down, left] = junction::segments(self.get(point).c)?;
+        if up != Weight::None && down != Weight::None {
+            Some(true)
+        } else if left != Weight::None && right != Weight::None {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    /// Find the box with its right border at `point`.
+    ///
+    /// Returns the first and last line of the box, or `None` if `point` is not
+    /// on the right border of a box enclosing the cell to its left.
+    pub fn box_right_border(&self, point: Point) -> Option<(usize, usize)> {
+        if !self.contains(point) || point.column == 1 || self.border_exit(point) != Some(true) {
+            return None;
+        }
+
+        // Ignore lines running into the border from the inside.
+        let inside = self.get(Point { column: point.column - 1, ..point });
+        if junction::segments(inside.c).is_some_and(|segments| segments[1] != Weight::None) {
+            return None;
+        }
+
+        // Follow the border outward until reaching a corner which turns left.
+        let corner = |lines: &mut dyn Iterator<Item = usize>, outward: usize| {
+            for line in lines {
+                let segments = junction::segments(self.get(Point { line, ..point }).c)?;
+                if segments[(outward + 2) % 4] == Weight::None {
+                    return None;
+                } else if segments[outward] == Weight::None {
+                    return (segments[3] != Weight::None).then_some(line);
+                }
+            }
+            None
+        };
+        let top = corner(&mut (1..point.line).rev(), 0)?;
+        let bottom = corner(&mut (point.line + 1..=self.len()), 2)?;
+
+        Some((top, bottom))
+    }
+
+    /// Insert an empty column at `column` for the specified lines.
+    ///
+    /// Cells at and after `column` are shifted to the right, dropping the last
+    /// cell of every line.
+    pub fn insert_column(&mut self, column: usize, lines: RangeInclusive<usize>) {
+        for line in lines {
+            for i in (column + 1..=self[line - 1].len()).rev() {
+                let previous = self.get(Point { column: i - 1, line }).clone();
+                self.replace(Point { column: i, line }, previous);
+            }
+            self.clear(Point { column, line });
+        }
+    }
+
+    /// Insert `count` empty lines at `line` for the specified columns.
+    ///
+    /// Cells at and below `line` are shifted down, dropping the last cells of
+    /// every column.
+    pub fn insert_lines(&mut self, line: usize, count: usize, columns: RangeInclusive<usize>) {
+        for column in columns {
+            for i in (line + count..=self.len()).rev() {
+                let previous = self.get(Point { column, line: i - count }).clone();
+                self.replace(Point { column, line: i }, previous);
+            }
+            for line in line..min(line + count, self.len() + 1) {
+                self.clear(Point { column, line });
+            }
+        }
+    }
+
+    /// Find the table enclosing `point`.
+    ///
+    /// Tables are boxes split into rows by horizontal separators. Returns the
+    /// columns of the table's outer borders, the line of its bottom border, and
+    /// the height of its last row.
+    pub fn table_bottom(&self, point: Point) -> Option<(RangeInclusive<usize>, usize, usize)> {
+        if !self.contains(point) {
+            return None;
+        }
+
+        // Find the closest vertical border to the left.
+        let segments = |column, line| junction::segments(self.get(Point { column, line }).c);
+        let vertical = (1..=point.column).rev().find(|column| {
+            segments(*column, point.line)
+                .is_some_and(|[up, _, down, _]| up != Weight::None && down != Weight::None)
+        })?;
+
+        // Follow it down to the bottom border.
+        let mut lines = point.line..=self.len();
+        let bottom = lines.find(|line| {
+            segments(vertical, *line)
+                .map_or(true, |[up, _, down, _]| up == Weight::None || down == Weight::None)
+        })?;
+        if segments(vertical, bottom).map_or(true, |[up, ..]| up == Weight::None) {
+            return None;
+        }
+
+        // Follow the bottom border to both corners.
+        let line_width = self[bottom - 1].len();
+        let corner = |columns: &mut dyn Iterator<Item = usize>, outward: usize| {
+            for column in columns {
+                if segments(column, bottom)?[outward] == Weight::None {
+                    return Some(column);
+                }
+            }
+            None
+        };
+        let left = corner(&mut (1..=vertical).rev(), 3)?;
+        let right = corner(&mut (vertical..=line_width), 1)?;
+        let is_corner = |column, inward: usize| {
+            segments(column, bottom).is_some_and(|segments| {
+                segments[0] != Weight::None && segments[inward] != Weight::None
+            })
+        };
+        if !is_corner(left, 1) || !is_corner(right, 3) || point.column > right {
+            return None;
+        }
+
+        // Measure the last row up to the previous separator.
+        let separator = (1..bottom).rev().find(|line| {
+            segments(left, *line).map_or(true, |[_, right, ..]| right != Weight::None)
+        })?;
+        if segments(left, separator)
+            .map_or(true, |[_, right, down, _]| right == Weight::None || down == Weight::None)
+        {
+            return None;
+        }
+        let height = bottom - separator - 1;
+
+        (height > 0).then_some((left..=right, bottom, height))
+    }
+
+    /// Get the glyphs replacing junctions next to erased box borders.
+    ///
+    /// Segments leading into an erased cell are removed from junctions, turning
+    /// them back into corners or edges. Cells which would keep only a single
+    /// segment are left untouched, so lines and corners don't turn into stubs.
+    pub fn border_repairs(&self, erased: &[Point]) -> Vec<(Point, char)> {
+        let mut repairs: Vec<(Point, Segments)> = Vec::new();
+
+        for point in erased {
+            for (direction, offset) in [(0, -1), (1, 0), (0, 1), (-1, 0)].into_iter().enumerate() {
+                let neighbor = match point.offset(offset) {
+                    Some(neighbor) if self.contains(neighbor) => neighbor,
+                    _ => continue,
+                };
+
+                // Apply repairs on top of previous ones for the same cell.
+                let index = match repairs.iter().position(|(point, _)| *point == neighbor) {
+                    Some(index) => index,
+                    None => match junction::segments(self.get(neighbor).c) {
+                        Some(segments) => {
+                            repairs.push((neighbor, segments));
+                            repairs.len() - 1
+                        },
+                        None => continue,
+                    },
+                };
+
+                let segments = &mut repairs[index].1;
+                let connected = segments.iter().filter(|weight| **weight != Weight::None).count();
+                if connected > 2 {
+                    segments[(direction + 2) % 4] = Weight::None;
+                }
+            }
+        }
+
+        repairs
+            .into_iter()
+            .filter_map(|(point, segments)| Some((point, junction::glyph(segments)?)))
+            .filter(|(point, c)| self.get(*point).c != *c)
+            .collect()
+    }
+
+    /// Find diagram consistency issues.
+    pub fn lint(&self) -> Vec<Issue> {
+        let lines: Vec<Vec<char>> =
+            self.iter().map(|line| line.iter().map(|cell| cell.c).collect()).collect();
+        lint::lint(&lines)
+    }
+
+    /// Route a connector between two box borders.
+    ///
+    /// Returns `None` if either point is not on a box border.
+    pub fn connector_path(&self, start: Point, end: Point) -> Option<Vec<Point>> {
+        let horizontal = (self.border_exit(start)?, self.border_exit(end)?);
+        (start != end).then(|| route_connector(start, end, horizontal))
+    }
+
+    /// Grow the grid to at least the specified size.
+    ///
+    /// New cells are added to the right and bottom of the existing content.
+    pub fn grow(&mut self, columns: usize, lines: usize) {
+        let columns = max(columns, self.first().map_or(0, Vec::len));
+        if lines > self.len() || columns > self.first().map_or(0, Vec::len) {
+            self.damage = Damage::Full;
+        }
+
+        if lines > self.len() {
+            self.cells.resize(lines, Vec::new());
+        }
+
+        for line in &mut self.cells {
+            if columns > line.len() {
+                line.resize(columns, Cell::default());
+            }
+        }
+    }
+
+    /// Replace all content with a copy of `cells`, growing the grid to fit.
+    pub fn restore_cells(&mut self, cells: &[Vec<Cell>]) {
+        self.grow(cells.first().map_or(0, Vec::len), cells.len());
+
+        for line in 1..=self.len() {
+            for column in 1..=self[line - 1].len() {
+                let point = Point { column, line };
+                let cell = cells.get(line - 1).and_then(|cells| cells.get(column - 1));
+                let cell = cell.cloned().unwrap_or_default();
+                if *self.get(point) != cell {
+                    self.replace(point, cell);
+                }
+            }
+        }
+    }
+
+    /// Insert empty columns and lines before the existing content.
+    pub fn insert_leading(&mut self, columns: usize, lines: usize) {
+        let width = self.first().map_or(0, Vec::len);
+        let new_lines = iter::repeat(vec![Cell::default(); width]).take(lines);
+        self.cells.splice(0..0, new_lines);
+
+        for line in &mut self.cells {
+            line.splice(0..0, iter::repeat(Cell::default()).take(columns));
+        }
+
+        // Keep the undo history pointing to the moved content.
+        let offset = (columns as isize, lines as isize);
+        self.journal.shift(offset, width + columns, self.len());
+
+        if columns > 0 || lines > 0 {
+            self.damage = Damage::Full;
+        }
+    }
+
+    /// Move all content by an offset.
+    ///
+    /// Cells moved past the edge of the grid wrap around to the other side.
+    pub fn translate(&mut self, columns: isize, lines: isize) {
+        let width = self.first().map_or(0, Vec::len);
+        if width == 0 {
+            return;
+        }
+
+        let height = self.len();
+        for line in &mut self.cells {
+            line.rotate_right(columns.rem_euclid(width as isize) as usize);
+        }
+        self.cells.rotate_right(lines.rem_euclid(height as isize) as usize);
+
+        // Keep the undo history pointing to the moved content.
+        self.journal.shift((columns, lines), width, height);
+
+        self.damage = Damage::Full;
+    }
+
+    /// Move all content by an offset, as a single undoable revision.
+    ///
+    /// Unlike [`Self::translate`], content moved past the edge of the grid is
+    /// discarded. Returns the number of discarded non-empty cells.
+    pub fn shift(&mut self, columns: isize, lines: isize) -> usize {
+        let width = self.first().map_or(0, Vec::len);
+        let points: Vec<Point> = (1..=self.len())
+            .flat_map(|line| (1..=width).map(move |column| Point { column, line }))
+            .collect();
+
+        let lost = points
+            .iter()
+            .filter(|point| !self.get(**point).is_empty())
+            .filter(|point| !point.offset((columns, lines)).is_some_and(|p| self.contains(p)))
+            .count();
+
+        let shifted: Vec<Cell> = points
+            .iter()
+            .map(|point| match point.offset((-columns, -lines)) {
+                Some(source) if self.contains(source) => self.get(source).clone(),
+                _ => Cell::default(),
+            })
+            .collect();
+        for (point, cell) in points.into_iter().zip(shifted) {
+            if *self.get(point) != cell {
+                self.replace(point, cell);
+            }
+        }
+
+        lost
+    }
+
+    /// Get all non-empty cells within a rectangle.
+    pub fn region(&self, start: Point, end: Point) -> Vec<Point> {
+        (start.line..=end.line)
+            .flat_map(|line| (start.column..=end.column).map(move |column| Point { column, line }))
+            .filter(|point| self.contains(*point) && !self.get(*point).is_empty())
+            .collect()
+    }
+
+    /// Get all non-empty cells connected to `start`.
+    ///
+    /// Cells are considered connected if they touch horizontally, vertically,
+    /// or diagonally.
+    pub fn connected_region(&self, start: Point) -> Vec<Point> {
+        if !self.contains(start) || self.get(start).is_empty() {
+            return Vec::new();
+        }
+
+        let mut visited = HashSet::new();
+        visited.insert(start);
+        let mut queue = vec![start];
+        let mut region = Vec::new();
+
+        while let Some(point) = queue.pop() {
+            region.push(point);
+
+            for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+                let neighbor = match point.offset((dx, dy)) {
+                    Some(neighbor) if self.contains(neighbor) => neighbor,
+                    _ => continue,
+                };
+
+                if !self.get(neighbor).is_empty() && visited.insert(neighbor) {
+                    queue.push(neighbor);
+                }
+            }
+        }
+
+        region
+    }
+
+    /// Check if a point is within the grid.
+    pub fn contains(&self, point: Point) -> bool {
+        point.line > 0
+            && point.column > 0
+            && point.line <= self.len()
+            && point.column <= self[point.line - 1].len()
+    }
+
+    /// Trace a path of connected non-empty cells.
+    ///
+    /// The path keeps moving in the same direction for as long as possible,
+    /// turning only when it cannot continue straight. This makes it possible
+    /// to follow lines and box borders from any starting point.
+    pub fn trace_path(&self, start: Point) -> Vec<Point> {
+        // Try continuing straight before checking other directions.
+        const DIRECTIONS: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+        // Get the neighboring point in a direction, if it's part of the path.
+        let neighbor = |point: Point, (dx, dy): (isize, isize)| {
+            let column = usize::try_from(point.column as isize + dx).ok()?;
+            let line = usize::try_from(point.line as isize + dy).ok()?;
+            let point = Point { column, line };
+            let in_grid = line > 0 && column > 0 && line <= self.len() && column <= self[0].len();
+            (in_grid && !self.get(point).is_empty()).then_some(point)
+        };
+
+        if self.get(start).is_empty() {
+            return Vec::new();
+        }
+
+        let mut visited = HashSet::new();
+        visited.insert(start);
+        let mut path = vec![start];
+
+        let mut point = start;
+        let mut direction = DIRECTIONS[0];
+        loop {
+            let next = [direction]
+                .into_iter()
+                .chain(DIRECTIONS)
+                .filter_map(|direction| Some((neighbor(point, direction)?, direction)))
+                .find(|(next, _)| !visited.contains(next));
+
+            match next {
+                Some((next, next_direction)) => {
+                    visited.insert(next);
+                    path.push(next);
+                    point = next;
+                    direction = next_direction;
+                },
+                None => break,
+            }
+        }
+
+        path
+    }
+
+    /// Check if the content in a grid cell matches a template.
+    pub fn cell_matches(&self, column: usize, line: usize, template: &Cell) -> bool {
+        let try_index = |column, line| {
+            let column = usize::try_from(column as isize - 1).ok()?;
+            let line = usize::try_from(line as isize - 1).ok()?;
+            let grid_line = (line < self.len()).then(|| &self[line])?;
+            (column < grid_line.len()).then(|| &grid_line[column])
+        };
+        try_index(column, line).is_some_and(|cell| cell.content_eq(template))
+    }
+
+    /// Render the grid within a viewport, with a transformation applied to all
+    /// colors.
+    ///
+    /// The text assumes all colors and styles are reset before it is printed.
+    /// Extended colors use `separator` between their SGR parameters.
+    pub fn render(
+        &self,
+        viewport: &Viewport,
+        separator: char,
+        map_color: impl Fn(Color) -> Color,
+    ) -> String {
+        let mut text = String::new();
+        if self.cells.is_empty() {
+            return text;
+        }
+
+        let first_column = viewport.column_offset;
+        let last_line = min(viewport.line_offset + viewport.lines, self.len());
+
+        // Store colors/styles to reduce number of writes.
+        let mut foreground = Color::default();
+        let mut background = Color::default();
+        let mut style = TextStyle::empty();
+
+        for line in &self.cells[viewport.line_offset..last_line] {
+            let last_column = min(first_column + viewport.columns, line.len());
+            let mut column = first_column;
+
+            // Replace fullwidth characters cut off by the viewport with whitespace.
+            if column > 0 && column < last_column && line[column - 1].c.width() == Some(2) {
+                text.push(' ');
+                column += 1;
+            }
+
+            while column < last_column {
+                let cell = &line[column];
+
+                // Set the cell's colors, keeping the foreground for transparent cells.
+                let cell_foreground = map_color(cell.foreground);
+                if cell_foreground != foreground && !cell.transparent {
+                    text.push_str(&cell_foreground.escape_with_separator(true, separator));
+                    foreground = cell_foreground;
+                }
+                let cell_background = map_color(cell.background);
+                if cell_background != background {
+                    text.push_str(&cell_background.escape_with_separator(false, separator));
+                    background = cell_background;
+                }
+
+                // Set the cell's text style.
+                if cell.style != style {
+                    text.push_str(cell.style.escape());
+                    style = cell.style;
+                }
+
+                // Render empty cells as whitespace.
+                let width = cell.c.width();
+                match width {
+                    Some(1) | Some(2) => cell.push_glyph(&mut text),
+                    _ => text.push(' '),
+                }
+
+                // Skip columns when dealing with fullwidth characters.
+                column += width.filter(|w| *w != 0).unwrap_or(1);
+            }
+            text.push('\n');
+        }
+
+        text.truncate(text.trim_end_matches('\n').len());
+        text
+    }
+}
+
+impl Deref for Grid {
+    type Target = Vec<Vec<Cell>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cells
+    }
+}
+
+/// Grid cells which need to be redrawn.
+#[derive(Default)]
+pub enum Damage {
+    /// Only the listed cells have changed.
+    Partial(HashSet<Point>),
+    /// The entire screen needs to be redrawn.
+    #[default]
+    Full,
+}
+
+/// Visible section of the canvas.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub struct Viewport {
+    /// Canvas columns left of the viewport.
+    pub column_offset: usize,
+    /// Canvas lines above the viewport.
+    pub line_offset: usize,
+    /// Width of the viewport.
+    pub columns: usize,
+    /// Height of the viewport.
+    pub lines: usize,
+}
+
+impl Viewport {
+    /// Convert a point on the screen to a point on the canvas.
+    pub fn canvas_point(&self, column: usize, line: usize) -> Point {
+        Point { column: column + self.column_offset, line: line + self.line_offset }
+    }
+
+    /// Resize the viewport, keeping the anchored part of the canvas in place.
+    ///
+    /// The `leading_share` is the horizontal and vertical share of added or
+    /// removed space placed before the content, in halves.
+    ///
+    /// Returns the number of columns and lines that must be inserted at the
+    /// start of the canvas, since the viewport would leave it otherwise.
+    pub fn resize(
+        &mut self,
+        columns: usize,
+        lines: usize,
+        leading_share: (usize, usize),
+    ) -> (usize, usize) {
+        let (horizontal_share, vertical_share) = leading_share;
+        let column_shift =
+            (columns as isize - self.columns as isize) * horizontal_share as isize / 2;
+        let line_shift = (lines as isize - self.lines as isize) * vertical_share as isize / 2;
+        let column_offset = self.column_offset as isize - column_shift;
+        let line_offset = self.line_offset as isize - line_shift;
+
+        *self = Self {
+            column_offset: column_offset.max(0) as usize,
+            line_offset: line_offset.max(0) as usize,
+            columns,
+            lines,
+        };
+
+        (column_offset.min(0).unsigned_abs(), line_offset.min(0).unsigned_abs())
+    }
+
+    /// Canvas column and line just past the bottom right corner of the
+    /// viewport.
+    pub fn end(&self) -> (usize, usize) {
+        (self.column_offset + self.columns, self.line_offset + self.lines)
+    }
+
+    /// Clip a run of `count` glyphs with `width` columns each to the viewport.
+    ///
+    /// Returns the screen position of the first visible glyph and the number of
+    /// visible glyphs, or `None` if no glyph is visible.
+    pub fn clip(&self, at: Point, width: usize, count: usize) -> Option<(Point, usize)> {
+        let (end_column, end_line) = self.end();
+        if at.line <= self.line_offset || at.line > end_line || width == 0 {
+            return None;
+        }
+
+        // Skip glyphs left of the viewport.
+        let skipped = (self.column_offset + 1).saturating_sub(at.column).div_ceil(width);
+        let column = at.column + skipped * width;
+
+        // Drop glyphs which don't fit within the viewport.
+        let visible = (end_column + 1).saturating_sub(column) / width;
+        let count = min(count.saturating_sub(skipped), visible);
+
+        let line = at.line - self.line_offset;
+        (count > 0).then_some((Point { column: column - self.column_offset, line }, count))
+    }
+}
+
+/// Coordinate in the terminal grid.
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+pub struct Point {
+    pub column: usize,
+    pub line: usize,
+}
+
+impl Point {
+    /// Normalize two corners of a rectangle to its top left and bottom right.
+    pub fn rect(start: Point, end: Point) -> (Point, Point) {
+        let top_left =
+            Point { column: min(start.column, end.column), line: min(start.line, end.line) };
+        let bottom_right =
+            Point { column: max(start.column, end.column), line: max(start.line, end.line) };
+        (top_left, bottom_right)
+    }
+
+    /// Check if the point is inside the rectangle between two corners.
+    pub fn within(&self, top_left: Point, bottom_right: Point) -> bool {
+        (top_left.column..=bottom_right.column).contains(&self.column)
+            && (top_left.line..=bottom_right.line).contains(&self.line)
+    }
+
+    /// Get the offset from this point to another.
+    pub fn offset_to(&self, other: Point) -> (isize, isize) {
+        (other.column as isize - self.column as isize, other.line as isize - self.line as isize)
+    }
+
+    /// Move the point by an offset.
+    ///
+    /// Returns `None` if the point would leave the grid at the top or left.
+    pub fn offset(&self, (dx, dy): (isize, isize)) -> Option<Point> {
+        let column =
+            usize::try_from(self.column as isize + dx).ok().filter(|column| *column > 0)?;
+        let line = usize::try_from(self.line as isize + dy).ok().filter(|line| *line > 0)?;
+        Some(Point { column, line })
+    }
+}
+
+impl Default for Point {
+    fn default() -> Self {
+        Self { column: 1, line: 1 }
+    }
+}
+
+/// Route an orthogonal path between two box borders.
+///
+/// Borders are left horizontally when `horizontal` is set for them and
+/// vertically otherwise. This produces an L-shaped path when both are left in
+/// different directions, and a Z-shaped path with its middle segment halfway
+/// between the borders otherwise.
+pub fn route_connector(start: Point, end: Point, horizontal: (bool, bool)) -> Vec<Point> {
+    let waypoints = match horizontal {
+        (true, true) => {
+            let column = (start.column + end.column) / 2;
+            [Point { column, line: start.line }, Point { column, line: end.line }, end]
+        },
+        (false, false) => {
+            let line = (start.line + end.line) / 2;
+            [Point { column: start.column, line }, Point { column: end.column, line }, end]
+        },
+        (true, false) => [Point { column: end.column, line: start.line }, end, end],
+        (false, true) => [Point { column: start.column, line: end.line }, end, end],
+    };
+
+    // Walk to each waypoint one cell at a time.
+    let mut path = vec![start];
+    let mut point = start;
+    for waypoint in waypoints {
+        while point != waypoint {
+            match point.column.cmp(&waypoint.column) {
+                Ordering::Less => point.column += 1,
+                Ordering::Greater => point.column -= 1,
+                Ordering::Equal => (),
+            }
+            match point.line.cmp(&waypoint.line) {
+                Ordering::Less => point.line += 1,
+                Ordering::Greater => point.line -= 1,
+                Ordering::Equal => (),
+            }
+            path.push(point);
+        }
+    }
+
+    path
+}
+
+/// Box drawing character connecting a cell to its neighbours on a path.
+pub fn connector_glyph<'a>(
+    point: Point,
+    neighbours: impl Iterator<Item = &'a Point>,
+    weight: Weight,
+) -> char {
+    let mut segments = Segments::default();
+    for neighbour in neighbours {
+        let index = match (neighbour.column.cmp(&point.column), neighbour.line.cmp(&point.line)) {
+            (_, Ordering::Less) => 0,
+            (Ordering::Greater, _) => 1,
+            (_, Ordering::Greater) => 2,
+            _ => 3,
+        };
+        segments[index] = weight;
+    }
+
+    junction::glyph(segments).unwrap_or(' ')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a grid with default colors from lines of text.
+    fn grid_from_text(text: &[&str]) -> Grid {
+        let cell = |c| Cell::new(c, Color::default(), Color::default(), TextStyle::empty());
+        let cells = text.iter().map(|line| line.chars().map(cell).collect()).collect();
+        Grid::new(cells, Journal::default())
+    }
+
+    /// Get the characters of every line in the grid.
+    fn grid_text(grid: &Grid) -> Vec<String> {
+        grid.iter().map(|line| line.iter().map(|cell| cell.c).collect()).collect()
+    }
+
+    /// Create a point in the grid.
+    fn point(column: usize, line: usize) -> Point {
+        Point { column, line }
+    }
+
+    #[test]
+    fn viewport_clipping() {
+        let viewport = Viewport { column_offset: 10, line_offset: 5, columns: 20, lines: 10 };
+
+        assert_eq!(viewport.clip(point(15, 6), 1, 3), Some((point(5, 1), 3)));
+        assert_eq!(viewport.clip(point(8, 6), 1, 5), Some((point(1, 1), 2)));
+        assert_eq!(viewport.clip(point(9, 6), 2, 3), Some((point(1, 1), 2)));
+        assert_eq!(viewport.clip(point(28, 6), 2, 3), Some((point(18, 1), 1)));
+        assert_eq!(viewport.clip(point(15, 5), 1, 3), None);
+        assert_eq!(viewport.clip(point(31, 6), 1, 3), None);
+    }
+
+    #[test]
+    fn viewport_resize_roundtrip() {
+        let mut viewport = Viewport { columns: 80, lines: 24, ..Viewport::default() };
+
+        // Shrinking moves the viewport without touching the canvas.
+        assert_eq!(viewport.resize(37, 11, (1, 1)), (0, 0));
+        assert_eq!((viewport.column_offset, viewport.line_offset), (21, 6));
+
+        // Growing back restores the original view.
+        assert_eq!(viewport.resize(80, 24, (1, 1)), (0, 0));
+        assert_eq!(viewport, Viewport { columns: 80, lines: 24, ..Viewport::default() });
+
+        // Growing past the canvas origin requires new leading cells.
+        assert_eq!(viewport.resize(100, 30, (2, 2)), (20, 6));
+        assert_eq!((viewport.column_offset, viewport.line_offset), (0, 0));
+    }
+
+    #[test]
+    fn connector_routing() {
+        let path = route_connector(point(1, 1), point(5, 3), (true, true));
+        assert_eq!(path, vec![
+            point(1, 1),
+            point(2, 1),
+            point(3, 1),
+            point(3, 2),
+            point(3, 3),
+            point(4, 3),
+            point(5, 3),
+        ]);
+
+        let path = route_connector(point(3, 1), point(1, 3), (false, true));
+        assert_eq!(path, vec![point(3, 1), point(3, 2), point(3, 3), point(2, 3), point(1, 3)]);
+
+        let glyph =
+            |neighbours: &[Point]| connector_glyph(point(3, 3), neighbours.iter(), Weight::Light);
+        assert_eq!(glyph(&[point(3, 2), point(2, 3)]), '┘');
+        assert_eq!(glyph(&[point(4, 3)]), '╶');
+    }
+
+    #[test]
+    fn widen_box() {
+        let text = ["┌──┐ x", "│ab│ y", "└──┘  "];
+        let mut grid = grid_from_text(&text);
+
+        assert_eq!(grid.box_right_border(point(4, 2)), Some((1, 3)));
+        assert_eq!(grid.box_right_border(point(3, 2)), None);
+        assert_eq!(grid.box_right_border(point(1, 2)), None);
+
+        grid.insert_column(4, 1..=2);
+        assert_eq!(grid_text(&grid), ["┌──\0┐ ", "│ab\0│ ", "└──┘  "]);
+
+        // Widening is undone as a single revision.
+        assert!(grid.undo());
+        assert_eq!(grid_text(&grid), ["┌──┐ x", "│ab│ y", "└──┘  "]);
+        assert!(!grid.undo());
+    }
+
+    #[test]
+    fn crop_to_content() {
+        let text = ["      ", "  ab  ", "   c  ", "      "];
+        let mut grid = grid_from_text(&text);
+
+        let (start, end) = grid.bounding_box().unwrap();
+        assert_eq!((start, end), (point(3, 2), point(4, 3)));
+
+        grid.crop(start, end, 1);
+        assert_eq!(grid_text(&grid), ["\0\0\0\0", "\0ab\0", "\0 c\0", "\0\0\0\0"]);
+
+        grid.crop(point(2, 2), point(3, 3), 0);
+        assert_eq!(grid_text(&grid), ["ab", " c"]);
+
+        let grid = Grid::new(vec![vec![Cell::default(); 3]; 2], Journal::default());
+        assert_eq!(grid.bounding_box(), None);
+    }
+
+    #[test]
+    fn shift_content() {
+        let text = ["ab ", " c "];
+        let mut grid = grid_from_text(&text);
+
+        assert_eq!(grid.shift(1, 0), 0);
+        grid.journal.commit();
+        assert_eq!(grid_text(&grid), ["\0ab", "\0 c"]);
+
+        assert_eq!(grid.shift(0, 1), 1);
+        grid.journal.commit();
+        assert_eq!(grid_text(&grid), ["\0\0\0", "\0ab"]);
+
+        // Every shift is undone as a single revision.
+        assert!(grid.undo());
+        assert_eq!(grid_text(&grid), ["\0ab", "\0 c"]);
+        assert!(grid.undo());
+        assert_eq!(grid_text(&grid), ["ab ", " c "]);
+    }
+
+    #[test]
+    fn table_rows() {
+        let text = ["┌─┬──┐ ", "│a│bc│x", "├─┼──┤ ", "│d│  │ ", "│ │  │ ", "└─┴──┘y"];
+        let mut grid = grid_from_text(&text);
+
+        assert_eq!(grid.table_bottom(point(4, 2)), Some((1..=6, 6, 2)));
+        assert_eq!(grid.table_bottom(point(3, 3)), Some((1..=6, 6, 2)));
+        assert_eq!(grid.table_bottom(point(7, 2)), None);
+
+        grid.insert_lines(5, 2, 1..=6);
+        assert_eq!(grid_text(&grid), [
+            "┌─┬──┐ ",
+            "│a│bc│x",
+            "├─┼──┤ ",
+            "│d│  │ ",
+            "\0\0\0\0\0\0 ",
+            "\0\0\0\0\0\0y",
+        ]);
+    }
+
+    #[test]
+    fn transparent_escapes() {
+        let red = Cell::new('a', Color::Indexed(1), Color::default(), TextStyle::empty());
+        let blank = Cell::new(' ', Color::default(), Color::default(), TextStyle::empty());
+        let grid = Grid::new(
+            vec![vec![red.clone(), Cell::default(), red.clone(), blank, red]],
+            Journal::default(),
+        );
+
+        let viewport = Viewport { columns: 5, lines: 1, ..Viewport::default() };
+        let text = grid.render(&viewport, ':', |color| color);
+        assert_eq!(text, "\x1b[38:5:1ma a\x1b[39m \x1b[38:5:1ma");
+    }
+
+    #[test]
+    fn text_search() {
+        let text = ["ab 猫\0ab", "aab   "];
+        let grid = grid_from_text(&text);
+
+        assert_eq!(grid.search("ab"), [(point(1, 1), 2), (point(6, 1), 2), (point(2, 2), 2)]);
+        assert_eq!(grid.search("猫a"), [(point(4, 1), 3)]);
+        assert_eq!(grid.search(" 猫"), [(point(3, 1), 3)]);
+        assert_eq!(grid.search("b  "), [(point(3, 2), 3)]);
+        assert!(grid.search("").is_empty());
+    }
+
+    #[test]
+    fn repair_borders() {
+        let text = ["┌─┬─┐", "│ │ │", "├─┼─┤"];
+        let grid = grid_from_text(&text);
+
+        // Erasing the middle of the center line.
+        let repairs = grid.border_repairs(&[point(3, 2)]);
+        assert_eq!(repairs, [(point(3, 1), '─'), (point(3, 3), '┬')]);
+
+        // Corners are never reduced to stubs.
+        assert_eq!(grid.border_repairs(&[point(2, 1)]), [(point(3, 1), '┌')]);
+    }
+}
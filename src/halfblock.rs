@@ -0,0 +1,103 @@
+use std::ffi::OsStr;
+use std::io;
+use std::path::Path;
+
+use image::imageops::{self, FilterType};
+use image::Rgba;
+use sketch::grid::{MAX_COLUMNS, MAX_LINES};
+use sketch::Cell;
+
+use crate::terminal::{Color, Rgb, TextStyle};
+
+/// File extensions of supported images.
+const EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// Minimum alpha value of pixels which are not treated as transparent.
+const ALPHA_THRESHOLD: u8 = 128;
+
+/// Check if a file is an importable image, based on its extension.
+pub fn is_image(path: &Path) -> bool {
+    let extension = path.extension().and_then(OsStr::to_str).unwrap_or_default();
+    EXTENSIONS.iter().any(|image_extension| extension.eq_ignore_ascii_case(image_extension))
+}
+
+/// Load an image as half-block cells.
+///
+/// The image is scaled to `columns` and every cell covers two vertically
+/// stacked pixels, which roughly keeps the aspect ratio with common fonts.
+/// Transparent pixels are left empty.
+///
+/// The result is limited to [`MAX_COLUMNS`] and [`MAX_LINES`].
+pub fn load(path: &Path, columns: usize) -> io::Result<Vec<Vec<Cell>>> {
+    let image = image::open(path).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let image = image.to_rgba8();
+
+    let (width, height) = image.dimensions();
+    if width == 0 || height == 0 || columns == 0 {
+        return Ok(Vec::new());
+    }
+
+    let columns = columns.min(MAX_COLUMNS) as u32;
+    let lines = (u64::from(height) * u64::from(columns)).div_ceil(u64::from(width) * 2);
+    let lines = lines.clamp(1, MAX_LINES as u64) as u32;
+    let image = imageops::resize(&image, columns, lines * 2, FilterType::Triangle);
+
+    let cells = (0..lines)
+        .map(|line| {
+            (0..columns)
+                .map(|column| {
+                    let top = image.get_pixel(column, line * 2);
+                    let bottom = image.get_pixel(column, line * 2 + 1);
+                    half_block(*top, *bottom)
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(cells)
+}
+
+/// Get the cell showing two vertically stacked pixels.
+fn half_block(top: Rgba<u8>, bottom: Rgba<u8>) -> Cell {
+    let color = |Rgba([r, g, b, a]): Rgba<u8>| {
+        (a >= ALPHA_THRESHOLD).then_some(Color::Rgb(Rgb { r, g, b }))
+    };
+
+    match (color(top), color(bottom)) {
+        (Some(top), Some(bottom)) => Cell::new('▀', top, bottom, TextStyle::empty()),
+        (Some(top), None) => Cell::new('▀', top, Color::default(), TextStyle::empty()),
+        (None, Some(bottom)) => Cell::new('▄', bottom, Color::default(), TextStyle::empty()),
+        (None, None) => Cell::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transparent_halves() {
+        let red = Rgba([255, 0, 0, 255]);
+        let clear = Rgba([0, 0, 255, 0]);
+        let rgb = Color::Rgb(Rgb { r: 255, g: 0, b: 0 });
+
+        assert_eq!(half_block(red, red), Cell::new('▀', rgb, rgb, TextStyle::empty()));
+        assert_eq!(
+            half_block(clear, red),
+            Cell::new('▄', rgb, Color::default(), TextStyle::empty())
+        );
+        assert_eq!(half_block(clear, clear), Cell::default());
+    }
+
+    #[test]
+    fn bounded_size() {
+        let path = std::env::temp_dir().join(format!("sketch-image-{}.png", std::process::id()));
+        image::RgbaImage::new(1, 10000).save(&path).unwrap();
+
+        let cells = load(&path, 9999);
+        let _ = std::fs::remove_file(&path);
+        let cells = cells.unwrap();
+        assert_eq!(cells.len(), MAX_LINES);
+        assert_eq!(cells[0].len(), MAX_COLUMNS);
+    }
+}
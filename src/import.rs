@@ -1,32 +1,134 @@
+use std::cmp::max;
 use std::iter;
 
-use vte::{Params, ParamsIter, Perform};
+use unicode_width::UnicodeWidthChar;
+use vte::{Params, ParamsIter, Parser, Perform};
 
-use crate::terminal::{Color, NamedColor, Rgb};
-use crate::{Point, Sketch, TextStyle};
+use crate::color::{Color, NamedColor, Rgb, TextStyle};
+use crate::grid::{MAX_COLUMNS, MAX_LINES};
+use crate::Cell;
+
+/// Columns between tab stops.
+const TAB_WIDTH: usize = 8;
 
 /// Parser for importing existing sketches.
-pub struct SketchParser<'a> {
-    sketch: &'a mut Sketch,
-    origin: Point,
-    point: Point,
+#[derive(Default)]
+pub struct SketchParser {
+    /// Imported cells, with one vector per line.
+    cells: Vec<Vec<Cell>>,
+
+    /// Zero-based line and column of the next written glyph.
+    cursor: (usize, usize),
+    /// Cursor position stored by DECSC or SCOSC.
+    saved_cursor: (usize, usize),
+
+    /// Whether the end of the file was reached.
+    ///
+    /// Everything after the SUB control character is metadata like SAUCE
+    /// records and should not be imported.
+    eof: bool,
+
+    // Active SGR attributes.
+    foreground: Color,
+    background: Color,
+    style: TextStyle,
 }
 
-impl<'a> SketchParser<'a> {
-    pub fn new(sketch: &'a mut Sketch, origin: Point) -> Self {
-        Self { sketch, origin, point: origin }
+impl SketchParser {
+    /// Parse a sketch into a block of cells.
+    ///
+    /// All lines are padded with empty cells to the width of the widest line.
+    /// Content beyond [`MAX_COLUMNS`] and [`MAX_LINES`] is discarded.
+    pub fn parse(sketch: &str) -> Vec<Vec<Cell>> {
+        let mut sketch_parser = Self::default();
+        let mut parser = Parser::new();
+
+        for byte in sketch.as_bytes() {
+            parser.advance(&mut sketch_parser, *byte);
+        }
+
+        let mut cells = sketch_parser.cells;
+        let width = cells.iter().map(Vec::len).max().unwrap_or_default();
+        for line in &mut cells {
+            line.resize(width, Cell::default());
+        }
+
+        cells
+    }
+
+    /// Write a cell at the cursor position.
+    fn write(&mut self, cell: Cell) {
+        let (line, column) = self.cursor;
+        self.cursor.1 += 1;
+
+        // Avoid unbounded allocations from huge cursor movements.
+        if line >= MAX_LINES || column >= MAX_COLUMNS {
+            return;
+        }
+
+        if self.cells.len() <= line {
+            self.cells.resize(line + 1, Vec::new());
+        }
+
+        let line = &mut self.cells[line];
+        if line.len() <= column {
+            line.resize(column + 1, Cell::default());
+        }
+        line[column] = cell;
+    }
+
+    /// Get the glyph left of the cursor, skipping spacers of fullwidth
+    /// characters.
+    fn previous_glyph(&mut self) -> Option<&mut Cell> {
+        let (line, column) = self.cursor;
+        let cells = self.cells.get_mut(line)?;
+        let mut column = column.checked_sub(1)?;
+        if column > 0 && cells.get(column - 1)?.c.width() == Some(2) {
+            column -= 1;
+        }
+        cells.get_mut(column)
+    }
+
+    /// Move the cursor relative to its current position.
+    fn move_cursor(&mut self, lines: isize, columns: isize) {
+        let (line, column) = self.cursor;
+        self.cursor = (line.saturating_add_signed(lines), column.saturating_add_signed(columns));
     }
 }
 
-impl<'a> Perform for SketchParser<'a> {
+impl Perform for SketchParser {
     fn print(&mut self, c: char) {
-        self.point = self.sketch.write(self.point, c, true);
+        if self.eof {
+            return;
+        }
+
+        // Join combining characters with the previous glyph.
+        if self.previous_glyph().is_some_and(|cell| cell.combine(c)) {
+            return;
+        }
+
+        // Ignore glyphs which cannot be rendered.
+        let width = match c.width() {
+            Some(width) if width > 0 => width,
+            _ => return,
+        };
+
+        self.write(Cell::new(c, self.foreground, self.background, self.style));
+
+        // Reserve the cell covered by fullwidth characters.
+        if width == 2 {
+            self.write(Cell::default());
+        }
     }
 
     fn execute(&mut self, byte: u8) {
-        if byte == b'\n' {
-            self.point.column = self.origin.column;
-            self.point.line += 1;
+        match byte {
+            b'\n' => self.cursor = (self.cursor.0 + 1, 0),
+            b'\r' => self.cursor.1 = 0,
+            b'\t' => self.cursor.1 = (self.cursor.1 / TAB_WIDTH + 1) * TAB_WIDTH,
+            // Stop at the SUB end of file marker.
+            0x1a => self.eof = true,
+            _ => (),
         }
     }
 
@@ -37,77 +139,115 @@ impl<'a> Perform for SketchParser<'a> {
         has_ignored_intermediates: bool,
         action: char,
     ) {
-        if has_ignored_intermediates || intermediates.len() > 2 {
+        if has_ignored_intermediates || !intermediates.is_empty() {
+            return;
+        }
+
+        // Get the first two parameters, with `0` replaced by the default `1`.
+        let mut numbers = params.iter().map(|param| max(param[0], 1) as usize);
+        let first = numbers.next().unwrap_or(1);
+        let second = numbers.next().unwrap_or(1);
+
+        match action {
+            'm' if params.is_empty() => {
+                self.style = TextStyle::empty();
+                self.foreground = Color::default();
+                self.background = Color::default();
+            },
+            'm' => handle_sgr(self, &mut params.into_iter()),
+            // Cursor up, down, forward, and backward.
+            'A' => self.move_cursor(-(first as isize), 0),
+            'B' => self.move_cursor(first as isize, 0),
+            'C' => self.move_cursor(0, first as isize),
+            'D' => self.move_cursor(0, -(first as isize)),
+            // Cursor next and previous line.
+            'E' => self.cursor = (self.cursor.0 + first, 0),
+            'F' => self.cursor = (self.cursor.0.saturating_sub(first), 0),
+            // Absolute cursor column and line.
+            'G' | '`' => self.cursor.1 = first - 1,
+            'd' => self.cursor.0 = first - 1,
+            // Absolute cursor position.
+            'H' | 'f' => self.cursor = (first - 1, second - 1),
+            // Save and restore cursor position.
+            's' => self.saved_cursor = self.cursor,
+            'u' => self.cursor = self.saved_cursor,
+            _ => (),
+        }
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
+        if !intermediates.is_empty() {
             return;
         }
 
-        if action == 'm' && intermediates.is_empty() {
-            if params.is_empty() {
-                self.sketch.brush.style = TextStyle::empty();
-                self.sketch.brush.foreground = Color::default();
-                self.sketch.brush.background = Color::default();
-            } else {
-                handle_sgr(self.sketch, &mut params.into_iter());
-            }
+        match byte {
+            b'7' => self.saved_cursor = self.cursor,
+            b'8' => self.cursor = self.saved_cursor,
+            _ => (),
         }
     }
 }
 
-/// Parse SGR modes and update the brush accordingly.
+/// Parse SGR modes and update the active attributes accordingly.
 ///
 /// Based on Alacritty's VTE crate ansi module.
-fn handle_sgr(sketch: &mut Sketch, params: &mut ParamsIter<'_>) {
+fn handle_sgr(parser: &mut SketchParser, params: &mut ParamsIter<'_>) {
     while let Some(param) = params.next() {
         match param {
             [0] => {
-                sketch.brush.style = TextStyle::empty();
-                sketch.brush.foreground = Color::default();
-                sketch.brush.background = Color::default();
+                parser.style = TextStyle::empty();
+                parser.foreground = Color::default();
+                parser.background = Color::default();
             },
-            [1] => sketch.brush.style.insert(TextStyle::BOLD),
-            [3] => sketch.brush.style.insert(TextStyle::ITALICS),
-            [21] => sketch.brush.style.remove(TextStyle::BOLD),
-            [23] => sketch.brush.style.remove(TextStyle::ITALICS),
-            [30] => sketch.brush.foreground = Color::Named(NamedColor::Black),
-            [31] => sketch.brush.foreground = Color::Named(NamedColor::Red),
-            [32] => sketch.brush.foreground = Color::Named(NamedColor::Green),
-            [33] => sketch.brush.foreground = Color::Named(NamedColor::Yellow),
-            [34] => sketch.brush.foreground = Color::Named(NamedColor::Blue),
-            [35] => sketch.brush.foreground = Color::Named(NamedColor::Magenta),
-            [36] => sketch.brush.foreground = Color::Named(NamedColor::Cyan),
-            [37] => sketch.brush.foreground = Color::Named(NamedColor::White),
+            [1] => parser.style.insert(TextStyle::BOLD),
+            [2] => parser.style.insert(TextStyle::DIM),
+            [3] => parser.style.insert(TextStyle::ITALICS),
+            [21] => parser.style.remove(TextStyle::BOLD),
+            [22] => parser.style.remove(TextStyle::BOLD | TextStyle::DIM),
+            [23] => parser.style.remove(TextStyle::ITALICS),
+            [30] => parser.foreground = Color::Named(NamedColor::Black),
+            [31] => parser.foreground = Color::Named(NamedColor::Red),
+            [32] => parser.foreground = Color::Named(NamedColor::Green),
+            [33] => parser.foreground = Color::Named(NamedColor::Yellow),
+            [34] => parser.foreground = Color::Named(NamedColor::Blue),
+            [35] => parser.foreground = Color::Named(NamedColor::Magenta),
+            [36] => parser.foreground = Color::Named(NamedColor::Cyan),
+            [37] => parser.foreground = Color::Named(NamedColor::White),
             [38] => {
                 let mut iter = params.map(|param| param[0]);
                 if let Some(color) = parse_sgr_color(&mut iter) {
-                    sketch.brush.foreground = color;
+                    parser.foreground = color;
                 }
             },
             [38, params @ ..] => {
                 if let Some(color) = handle_colon_rgb(params) {
-                    sketch.brush.foreground = color;
+                    parser.foreground = color;
                 }
             },
-            [39] => sketch.brush.foreground = Color::Named(NamedColor::Default),
-            [40] => sketch.brush.background = Color::Named(NamedColor::Black),
-            [41] => sketch.brush.background = Color::Named(NamedColor::Red),
-            [42] => sketch.brush.background = Color::Named(NamedColor::Green),
-            [43] => sketch.brush.background = Color::Named(NamedColor::Yellow),
-            [44] => sketch.brush.background = Color::Named(NamedColor::Blue),
-            [45] => sketch.brush.background = Color::Named(NamedColor::Magenta),
-            [46] => sketch.brush.background = Color::Named(NamedColor::Cyan),
-            [47] => sketch.brush.background = Color::Named(NamedColor::White),
+            [39] => parser.foreground = Color::Named(NamedColor::Default),
+            [40] => parser.background = Color::Named(NamedColor::Black),
+            [41] => parser.background = Color::Named(NamedColor::Red),
+            [42] => parser.background = Color::Named(NamedColor::Green),
+            [43] => parser.background = Color::Named(NamedColor::Yellow),
+            [44] => parser.background = Color::Named(NamedColor::Blue),
+            [45] => parser.background = Color::Named(NamedColor::Magenta),
+            [46] => parser.background = Color::Named(NamedColor::Cyan),
+            [47] => parser.background = Color::Named(NamedColor::White),
             [48] => {
                 let mut iter = params.map(|param| param[0]);
                 if let Some(color) = parse_sgr_color(&mut iter) {
-                    sketch.brush.background = color;
+                    parser.background = color;
                 }
             },
             [48, params @ ..] => {
                 if let Some(color) = handle_colon_rgb(params) {
-                    sketch.brush.background = color;
+                    parser.background = color;
                 }
             },
-            [49] => sketch.brush.background = Color::Named(NamedColor::Default),
+            [49] => parser.background = Color::Named(NamedColor::Default),
+            // Bright colors used by tmux and screen hardcopies.
+            [param @ 90..=97] => parser.foreground = Color::Indexed((param - 90 + 8) as u8),
+            [param @ 100..=107] => parser.background = Color::Indexed((param - 100 + 8) as u8),
             _ => (),
         }
     }
@@ -138,3 +278,81 @@ fn parse_sgr_color(params: &mut dyn Iterator<Item = u16>) -> Option<Color> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cells() {
+        let cells = SketchParser::parse("a\x1b[31mb\n\x1b[1m界\x1b[0mc\n");
+
+        assert_eq!(cells.len(), 2);
+        assert!(cells.iter().all(|line| line.len() == 3));
+        assert_eq!(cells[0][1].foreground, Color::Named(NamedColor::Red));
+        assert_eq!(cells[1][0].c, '界');
+        assert_eq!(cells[1][0].style, TextStyle::BOLD);
+        assert_eq!(cells[1][1], Cell::default());
+        assert_eq!(
+            cells[1][2],
+            Cell::new('c', Color::default(), Color::default(), TextStyle::empty())
+        );
+    }
+
+    #[test]
+    fn semicolon_colors() {
+        let cells = SketchParser::parse("\x1b[38;2;1;2;3;48;5;208ma\x1b[22;91;104mb");
+
+        assert_eq!(cells[0][0].foreground, Color::Rgb(Rgb { r: 1, g: 2, b: 3 }));
+        assert_eq!(cells[0][0].background, Color::Indexed(208));
+        assert_eq!(cells[0][1].foreground, Color::Indexed(9));
+        assert_eq!(cells[0][1].background, Color::Indexed(12));
+    }
+
+    #[test]
+    fn cursor_movement() {
+        let text = |cells: Vec<Vec<Cell>>| -> Vec<String> {
+            cells.iter().map(|line| line.iter().map(|cell| cell.c).collect()).collect()
+        };
+
+        let cells = SketchParser::parse("\x1b[2;3Hx\x1b[Ay\x1b[2Cz\x1b7\x1b[3;1Hw\x1b8!");
+        assert_eq!(text(cells), ["\0\0\0y\0\0z!", "\0\0x\0\0\0\0\0", "w\0\0\0\0\0\0\0"]);
+
+        let cells = SketchParser::parse("ab\rc\x1b[sd\x1b[5Ge\x1b[u\x1b[Bf\x1a\x1b[1;1HSAUCE");
+        assert_eq!(text(cells), ["cd\0\0e", "\0f\0\0\0"]);
+
+        // Content far outside the canvas is dropped.
+        let cells = SketchParser::parse("a\x1b[9999;9999Hx\x1b[1;9999Hy");
+        assert_eq!(text(cells), ["a"]);
+    }
+
+    #[test]
+    fn combining_characters() {
+        let cells = SketchParser::parse("e\u{301}\x1b[31mx界\u{302}\u{303}\n\u{301}y");
+
+        assert_eq!(cells[0].len(), 4);
+        assert_eq!(cells[0][0].glyph(), "e\u{301}");
+        assert_eq!(cells[0][1].glyph(), "x");
+        assert_eq!(cells[0][2].glyph(), "界\u{302}\u{303}");
+        assert_eq!(cells[1][0].glyph(), "y");
+    }
+
+    #[test]
+    fn intensity_escapes() {
+        let bold_italics = TextStyle::BOLD | TextStyle::ITALICS;
+        let styles = [TextStyle::DIM, bold_italics, TextStyle::DIM | TextStyle::ITALICS];
+
+        // Switching between intensities must not leak the previous one.
+        let mut text = String::new();
+        for style in styles {
+            text.push_str(style.escape());
+            text.push('a');
+        }
+        text.push_str(TextStyle::empty().escape());
+        text.push('b');
+
+        let cells = SketchParser::parse(&text);
+        let parsed: Vec<_> = cells[0].iter().map(|cell| cell.style).collect();
+        assert_eq!(parsed, [styles[0], styles[1], styles[2], TextStyle::empty()]);
+    }
+}
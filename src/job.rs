@@ -0,0 +1,75 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::Duration;
+use std::{io, thread};
+
+/// Interval for checking on running jobs.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Work running on a background thread, off the event loop.
+pub struct Job<T> {
+    result: Receiver<io::Result<T>>,
+    progress: Arc<Progress>,
+}
+
+impl<T: Send + 'static> Job<T> {
+    /// Start running `work` on a new thread.
+    pub fn spawn<F>(work: F) -> Self
+    where
+        F: FnOnce(&Progress) -> io::Result<T> + Send + 'static,
+    {
+        let (sender, result) = mpsc::channel();
+        let progress = Arc::new(Progress::default());
+
+        let job_progress = progress.clone();
+        thread::spawn(move || {
+            let _ = sender.send(work(&job_progress));
+        });
+
+        Self { result, progress }
+    }
+
+    /// Get the result of the job, once it is done.
+    pub fn poll(&self) -> Option<io::Result<T>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(io::Error::other("job crashed"))),
+        }
+    }
+
+    /// Block until the job is done.
+    pub fn wait(&self) -> io::Result<T> {
+        self.result.recv().unwrap_or_else(|_| Err(io::Error::other("job crashed")))
+    }
+
+    /// Ask the job to stop early.
+    pub fn cancel(&self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Completion of the job, in percent.
+    pub fn percent(&self) -> usize {
+        self.progress.percent.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress shared between a job and its thread.
+#[derive(Default)]
+pub struct Progress {
+    percent: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    /// Report the number of finished out of all steps.
+    pub fn update(&self, done: usize, total: usize) {
+        self.percent.store(done * 100 / total.max(1), Ordering::Relaxed);
+    }
+
+    /// Flag set once the job should stop early.
+    pub fn cancelled(&self) -> &AtomicBool {
+        &self.cancelled
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::{HashSet, VecDeque};
+use std::mem;
+
+use crate::{Cell, Point};
+
+/// Maximum number of undoable revisions, unless configured otherwise.
+pub const DEFAULT_DEPTH: usize = 1000;
+
+/// Cell content at a point before or after a change.
+pub type Change = (Point, Cell);
+
+/// Bounded undo/redo journal.
+///
+/// Every revision stores the previous content of all cells it changed. Undoing
+/// a revision swaps these cells with the grid, which leaves the revision
+/// holding the content required to redo it.
+pub struct Journal {
+    /// Revisions which can be undone, oldest first.
+    undo: VecDeque<Vec<Change>>,
+    /// Revisions which can be redone, most recently undone last.
+    redo: Vec<Vec<Change>>,
+
+    /// Changes of the revision in progress.
+    pending: Vec<Change>,
+    /// Points already changed by the revision in progress.
+    recorded: HashSet<Point>,
+
+    /// Maximum number of undoable revisions.
+    depth: usize,
+}
+
+impl Default for Journal {
+    fn default() -> Self {
+        Self::new(Vec::new(), Vec::new())
+    }
+}
+
+impl Journal {
+    /// Create a journal from existing undo and redo revisions.
+    pub fn new(undo: Vec<Vec<Change>>, redo: Vec<Vec<Change>>) -> Self {
+        Self {
+            undo: undo.into(),
+            redo,
+            depth: DEFAULT_DEPTH,
+            recorded: Default::default(),
+            pending: Default::default(),
+        }
+    }
+
+    /// Record the content of a cell before it is changed.
+    ///
+    /// Only the first change to every cell within a revision is stored. Any
+    /// change discards the redo history.
+    pub fn record(&mut self, point: Point, cell: &Cell) {
+        if self.recorded.insert(point) {
+            self.redo.clear();
+            self.pending.push((point, cell.clone()));
+        }
+    }
+
+    /// Finish the revision in progress.
+    ///
+    /// Revisions without any changes are ignored.
+    pub fn commit(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        self.recorded.clear();
+        self.undo.push_back(mem::take(&mut self.pending));
+        self.truncate(self.depth);
+    }
+
+    /// Take the most recent revision for undoing it.
+    ///
+    /// The revision in progress is committed first. Swapped changes must be
+    /// returned using [`Self::push_redo`].
+    pub fn pop_undo(&mut self) -> Option<Vec<Change>> {
+        self.commit();
+        self.undo.pop_back()
+    }
+
+    /// Take the most recently undone revision for redoing it.
+    ///
+    /// Swapped changes must be returned using [`Self::push_undo`].
+    pub fn pop_redo(&mut self) -> Option<Vec<Change>> {
+        self.redo.pop()
+    }
+
+    /// Store an undone revision.
+    pub fn push_redo(&mut self, changes: Vec<Change>) {
+        self.redo.push(changes);
+    }
+
+    /// Store a redone revision.
+    pub fn push_undo(&mut self, changes: Vec<Change>) {
+        self.undo.push_back(changes);
+    }
+
+    /// Drop the oldest revisions until at most `depth` can be undone.
+    pub fn truncate(&mut self, depth: usize) {
+        if self.undo.len() <= depth {
+            return;
+        }
+
+        self.undo.drain(..self.undo.len() - depth);
+        self.undo.shrink_to_fit();
+    }
+
+    /// Change the maximum number of undoable revisions.
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        self.truncate(depth);
+    }
+
+    /// Move all recorded points by an offset.
+    ///
+    /// This must be called whenever content is moved within the grid. Changes
+    /// moved outside of a grid with the specified dimensions are dropped.
+    pub fn shift(&mut self, offset: (isize, isize), columns: usize, lines: usize) {
+        let revisions = self.undo.iter_mut().chain(&mut self.redo).chain([&mut self.pending]);
+        for revision in revisions {
+            revision.retain_mut(|(point, _)| match point.offset(offset) {
+                Some(new_point) if new_point.column <= columns && new_point.line <= lines => {
+                    *point = new_point;
+                    true
+                },
+                _ => false,
+            });
+        }
+
+        self.recorded = self.pending.iter().map(|(point, _)| *point).collect();
+    }
+
+    /// Changes of the revision in progress.
+    pub fn pending(&self) -> &[Change] {
+        &self.pending
+    }
+
+    /// All revisions which can be undone, including the one in progress.
+    pub fn undo_revisions(&self) -> impl Iterator<Item = &[Change]> {
+        let pending = (!self.pending.is_empty()).then_some(&self.pending[..]);
+        self.undo.iter().map(Vec::as_slice).chain(pending)
+    }
+
+    /// All revisions which can be redone, most recently undone last.
+    pub fn redo_revisions(&self) -> impl Iterator<Item = &[Change]> {
+        self.redo.iter().map(Vec::as_slice)
+    }
+
+    /// Get the number of stored cell changes and their approximate memory
+    /// usage in bytes.
+    pub fn size(&self) -> (usize, usize) {
+        let revisions = self.undo.iter().chain(&self.redo).chain([&self.pending]);
+        revisions.fold((0, 0), |(changes, bytes), revision| {
+            let size =
+                revision.capacity() * mem::size_of::<Change>() + mem::size_of::<Vec<Change>>();
+            (changes + revision.len(), bytes + size)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounded_depth() {
+        let mut journal = Journal::default();
+        journal.set_depth(2);
+
+        for column in 1..=3 {
+            journal.record(Point { column, line: 1 }, &Cell::default());
+            journal.record(Point { column, line: 1 }, &Cell::default());
+            journal.commit();
+        }
+        journal.commit();
+
+        let columns: Vec<Vec<usize>> = journal
+            .undo_revisions()
+            .map(|changes| changes.iter().map(|(point, _)| point.column).collect())
+            .collect();
+        assert_eq!(columns, [vec![2], vec![3]]);
+
+        // New changes discard the redo history.
+        let changes = journal.pop_undo().unwrap();
+        journal.push_redo(changes);
+        assert_eq!(journal.redo_revisions().count(), 1);
+        journal.record(Point::default(), &Cell::default());
+        assert_eq!(journal.redo_revisions().count(), 0);
+    }
+}
@@ -0,0 +1,206 @@
+use Weight::{Heavy as H, Light as L, None as N};
+
+/// Weight of a box drawing line segment.
+#[derive(Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Weight {
+    #[default]
+    None,
+    Light,
+    Heavy,
+}
+
+/// Line segments of a box drawing character.
+///
+/// Every box drawing character is made up of up to four segments reaching from
+/// the center of the cell to its edges. Segments are ordered clockwise,
+/// starting with the one pointing up.
+pub type Segments = [Weight; 4];
+
+/// Light and heavy box drawing characters with their line segments.
+#[rustfmt::skip]
+const GLYPHS: [(char, Segments); 80] = [
+    ('─', [N, L, N, L]),
+    ('━', [N, H, N, H]),
+    ('│', [L, N, L, N]),
+    ('┃', [H, N, H, N]),
+    ('┌', [N, L, L, N]),
+    ('┍', [N, H, L, N]),
+    ('┎', [N, L, H, N]),
+    ('┏', [N, H, H, N]),
+    ('┐', [N, N, L, L]),
+    ('┑', [N, N, L, H]),
+    ('┒', [N, N, H, L]),
+    ('┓', [N, N, H, H]),
+    ('└', [L, L, N, N]),
+    ('┕', [L, H, N, N]),
+    ('┖', [H, L, N, N]),
+    ('┗', [H, H, N, N]),
+    ('┘', [L, N, N, L]),
+    ('┙', [L, N, N, H]),
+    ('┚', [H, N, N, L]),
+    ('┛', [H, N, N, H]),
+    ('├', [L, L, L, N]),
+    ('┝', [L, H, L, N]),
+    ('┞', [H, L, L, N]),
+    ('┟', [L, L, H, N]),
+    ('┠', [H, L, H, N]),
+    ('┡', [H, H, L, N]),
+    ('┢', [L, H, H, N]),
+    ('┣', [H, H, H, N]),
+    ('┤', [L, N, L, L]),
+    ('┥', [L, N, L, H]),
+    ('┦', [H, N, L, L]),
+    ('┧', [L, N, H, L]),
+    ('┨', [H, N, H, L]),
+    ('┩', [H, N, L, H]),
+    ('┪', [L, N, H, H]),
+    ('┫', [H, N, H, H]),
+    ('┬', [N, L, L, L]),
+    ('┭', [N, L, L, H]),
+    ('┮', [N, H, L, L]),
+    ('┯', [N, H, L, H]),
+    ('┰', [N, L, H, L]),
+    ('┱', [N, L, H, H]),
+    ('┲', [N, H, H, L]),
+    ('┳', [N, H, H, H]),
+    ('┴', [L, L, N, L]),
+    ('┵', [L, L, N, H]),
+    ('┶', [L, H, N, L]),
+    ('┷', [L, H, N, H]),
+    ('┸', [H, L, N, L]),
+    ('┹', [H, L, N, H]),
+    ('┺', [H, H, N, L]),
+    ('┻', [H, H, N, H]),
+    ('┼', [L, L, L, L]),
+    ('┽', [L, L, L, H]),
+    ('┾', [L, H, L, L]),
+    ('┿', [L, H, L, H]),
+    ('╀', [H, L, L, L]),
+    ('╁', [L, L, H, L]),
+    ('╂', [H, L, H, L]),
+    ('╃', [H, L, L, H]),
+    ('╄', [H, H, L, L]),
+    ('╅', [L, L, H, H]),
+    ('╆', [L, H, H, L]),
+    ('╇', [H, H, L, H]),
+    ('╈', [L, H, H, H]),
+    ('╉', [H, L, H, H]),
+    ('╊', [H, H, H, L]),
+    ('╋', [H, H, H, H]),
+    ('╴', [N, N, N, L]),
+    ('╵', [L, N, N, N]),
+    ('╶', [N, L, N, N]),
+    ('╷', [N, N, L, N]),
+    ('╸', [N, N, N, H]),
+    ('╹', [H, N, N, N]),
+    ('╺', [N, H, N, N]),
+    ('╻', [N, N, H, N]),
+    ('╼', [N, H, N, L]),
+    ('╽', [L, N, H, N]),
+    ('╾', [N, L, N, H]),
+    ('╿', [H, N, L, N]),
+];
+
+/// Get the line segments of a box drawing character.
+pub fn segments(c: char) -> Option<Segments> {
+    GLYPHS.iter().find(|(glyph, _)| *glyph == c).map(|(_, segments)| *segments)
+}
+
+/// Get the box drawing character made up of the given line segments.
+pub fn glyph(segments: Segments) -> Option<char> {
+    GLYPHS.iter().find(|(_, glyph_segments)| *glyph_segments == segments).map(|(glyph, _)| *glyph)
+}
+
+/// Get the ASCII approximation of a box drawing character.
+pub fn to_ascii(c: char) -> Option<char> {
+    let [up, right, down, left] = segments(c)?;
+    let ascii = match (up != N || down != N, right != N || left != N) {
+        (false, true) => '-',
+        (true, false) => '|',
+        _ => '+',
+    };
+    Some(ascii)
+}
+
+/// Get the box drawing character for an ASCII line or corner.
+///
+/// The neighbors are ordered clockwise, starting with the cell above. Only
+/// glyphs connected to a neighboring line are translated, to keep text like
+/// hyphenated words intact.
+pub fn from_ascii(c: char, neighbors: [char; 4]) -> Option<char> {
+    let reach = ascii_segments(c)?;
+
+    // Connect segments reaching neighbors with a segment pointing back.
+    let mut connected = [N; 4];
+    for (i, neighbor) in neighbors.into_iter().enumerate() {
+        let neighbor_segments = segments(neighbor).or_else(|| ascii_segments(neighbor));
+        if reach[i] != N && neighbor_segments.is_some_and(|segments| segments[(i + 2) % 4] != N) {
+            connected[i] = L;
+        }
+    }
+
+    match c {
+        _ if connected == [N; 4] => None,
+        '+' => glyph(connected),
+        _ => glyph(reach),
+    }
+}
+
+/// Get the line segments an ASCII line or corner can connect to.
+fn ascii_segments(c: char) -> Option<Segments> {
+    match c {
+        '-' => Some([N, L, N, L]),
+        '|' => Some([L, N, L, N]),
+        '+' => Some([L, L, L, L]),
+        _ => None,
+    }
+}
+
+/// Merge a new glyph with the existing content of a cell.
+///
+/// If both glyphs are box drawing characters, the result contains the line
+/// segments of both. Otherwise the new glyph replaces the existing one.
+pub fn merge(existing: char, new: char) -> char {
+    let (existing, new_segments) = match (segments(existing), segments(new)) {
+        (Some(existing), Some(new)) => (existing, new),
+        _ => return new,
+    };
+
+    // Keep the heavier weight of overlapping segments.
+    let mut merged = existing;
+    for (segment, new_segment) in merged.iter_mut().zip(new_segments) {
+        *segment = (*segment).max(new_segment);
+    }
+
+    glyph(merged).unwrap_or(new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_glyphs() {
+        assert_eq!(merge('─', '│'), '┼');
+        assert_eq!(merge('┌', '└'), '├');
+        assert_eq!(merge('│', '╶'), '├');
+        assert_eq!(merge('━', '│'), '┿');
+        assert_eq!(merge('┃', '┏'), '┣');
+        assert_eq!(merge('a', '─'), '─');
+        assert_eq!(merge('─', 'a'), 'a');
+    }
+
+    #[test]
+    fn ascii_translation() {
+        assert_eq!(to_ascii('┏'), Some('+'));
+        assert_eq!(to_ascii('━'), Some('-'));
+        assert_eq!(to_ascii('╷'), Some('|'));
+        assert_eq!(to_ascii('a'), None);
+
+        assert_eq!(from_ascii('+', [' ', '-', '|', ' ']), Some('┌'));
+        assert_eq!(from_ascii('+', ['|', '┼', '|', '-']), Some('┼'));
+        assert_eq!(from_ascii('-', [' ', '+', ' ', 'a']), Some('─'));
+        assert_eq!(from_ascii('-', ['|', 'b', '|', 'a']), None);
+        assert_eq!(from_ascii('|', [' ', ' ', '┐', ' ']), None);
+    }
+}
@@ -0,0 +1,22 @@
+//! Canvas editing engine of the sketch terminal painting program.
+//!
+//! Everything required for creating, editing, importing, and exporting
+//! sketches lives here, without any dependency on the terminal interface. This
+//! allows embedding the canvas into other applications.
+
+pub mod brush;
+pub mod canvas;
+pub mod cell;
+pub mod color;
+pub mod export;
+pub mod geometry;
+pub mod grid;
+pub mod import;
+pub mod journal;
+pub mod junction;
+pub mod lint;
+pub mod palette;
+
+pub use crate::brush::Brush;
+pub use crate::cell::Cell;
+pub use crate::grid::{Grid, Point, Viewport};
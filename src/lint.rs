@@ -0,0 +1,141 @@
+use crate::junction::{self, Weight};
+use crate::Point;
+
+/// Offsets to the neighbouring cells, in the order of the line segments.
+const DIRECTIONS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+/// Diagram consistency issue.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Issue {
+    pub point: Point,
+    pub kind: IssueKind,
+}
+
+/// Type of a diagram consistency issue.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum IssueKind {
+    /// Line ending in empty space instead of a box.
+    DanglingEnd,
+    /// Box corner which isn't connected to the rest of the box.
+    OpenCorner,
+    /// Text overwriting a vertical box border.
+    TextOverflow,
+    /// Light and heavy segments on the same line.
+    MixedWeights,
+}
+
+impl IssueKind {
+    /// Get human-readable description of the issue.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::DanglingEnd => "dangling line end",
+            Self::OpenCorner => "unclosed box corner",
+            Self::TextOverflow => "text overflowing its box",
+            Self::MixedWeights => "mixed line weights",
+        }
+    }
+}
+
+/// Find consistency issues in a diagram.
+///
+/// Every cell reports at most one issue. Issues are ordered by their position,
+/// line by line.
+pub fn lint(lines: &[Vec<char>]) -> Vec<Issue> {
+    let glyph = |column: usize, line: usize, (dx, dy): (isize, isize)| {
+        let line = lines.get(line.checked_add_signed(dy)?)?;
+        line.get(column.checked_add_signed(dx)?).copied()
+    };
+    let segments =
+        |column, line, direction| glyph(column, line, direction).and_then(junction::segments);
+    let is_empty = |c: Option<char>| c.map_or(true, |c| c.is_whitespace() || c == '\0');
+
+    let mut issues = Vec::new();
+    for (line, glyphs) in lines.iter().enumerate() {
+        for (column, c) in glyphs.iter().enumerate() {
+            let kind = match junction::segments(*c) {
+                Some(own) => {
+                    let connected = |direction: usize| own[direction] != Weight::None;
+
+                    // Check for lines leading into empty cells.
+                    let open = (0..4)
+                        .find(|&i| connected(i) && is_empty(glyph(column, line, DIRECTIONS[i])));
+
+                    // Check for weight changes within the cell or to its right/bottom neighbour.
+                    let mixed = [(0, 2), (3, 1)]
+                        .iter()
+                        .any(|&(a, b)| connected(a) && connected(b) && own[a] != own[b])
+                        || [1, 2].iter().any(|&i| {
+                            let neighbour = segments(column, line, DIRECTIONS[i]);
+                            let opposite =
+                                neighbour.map_or(Weight::None, |segments| segments[(i + 2) % 4]);
+                            connected(i) && opposite != Weight::None && own[i] != opposite
+                        });
+
+                    let is_corner = own.iter().filter(|weight| **weight != Weight::None).count()
+                        == 2
+                        && own[0] != own[2]
+                        && own[1] != own[3];
+
+                    match open {
+                        Some(_) if is_corner => IssueKind::OpenCorner,
+                        Some(_) => IssueKind::DanglingEnd,
+                        None if mixed => IssueKind::MixedWeights,
+                        None => continue,
+                    }
+                },
+                // Check for text replacing a vertical border.
+                None if !is_empty(Some(*c)) => {
+                    let above =
+                        segments(column, line, DIRECTIONS[0]).map_or(Weight::None, |s| s[2]);
+                    let below =
+                        segments(column, line, DIRECTIONS[2]).map_or(Weight::None, |s| s[0]);
+                    if above == Weight::None || below == Weight::None {
+                        continue;
+                    }
+                    IssueKind::TextOverflow
+                },
+                None => continue,
+            };
+
+            issues.push(Issue { point: Point { column: column + 1, line: line + 1 }, kind });
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint_text(text: &str) -> Vec<(usize, usize, IssueKind)> {
+        let lines: Vec<Vec<char>> = text.lines().map(|line| line.chars().collect()).collect();
+        lint(&lines)
+            .into_iter()
+            .map(|issue| (issue.point.column, issue.point.line, issue.kind))
+            .collect()
+    }
+
+    #[test]
+    fn clean_box() {
+        assert_eq!(lint_text("┌──┐\n│ab│\n└──┘"), []);
+    }
+
+    #[test]
+    fn box_issues() {
+        let overflow = [(4, 2, IssueKind::TextOverflow)];
+        assert_eq!(lint_text("┌──┐\n│abc\n└──┘"), overflow);
+        assert_eq!(lint_text("┌── \n│  │\n└──┘"), [
+            (3, 1, IssueKind::DanglingEnd),
+            (4, 2, IssueKind::DanglingEnd)
+        ]);
+        assert_eq!(lint_text("┌──┐\n   │\n└──┘"), [
+            (1, 1, IssueKind::OpenCorner),
+            (1, 3, IssueKind::OpenCorner)
+        ]);
+        assert_eq!(lint_text("┌━━┐\n│  │\n└──┘"), [
+            (1, 1, IssueKind::MixedWeights),
+            (3, 1, IssueKind::MixedWeights)
+        ]);
+    }
+}
@@ -1,129 +1,425 @@
-use std::cmp::{max, min, Ordering};
-use std::collections::{HashMap, VecDeque};
-use std::convert::TryFrom;
-use std::fmt::{self, Display, Formatter};
-use std::ops::{Deref, DerefMut};
-use std::path::Path;
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use std::{fs, io, mem};
 
 use clap::Parser as _;
-use unicode_width::UnicodeWidthChar;
-use vte::Parser;
-
-use crate::cli::Options;
+use sketch::brush::ColorPosition;
+use sketch::canvas::{Canvas, Stroke, WriteMode};
+use sketch::grid::{connector_glyph, Damage, MAX_COLUMNS, MAX_LINES};
+use sketch::import::SketchParser;
+use sketch::journal::{self, Journal};
+use sketch::junction::{self, Weight};
+use sketch::lint::Issue;
+use sketch::palette::Palette;
+use sketch::{export, geometry, Brush, Cell, Grid, Point, Viewport};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::asciicast::Asciicast;
+use crate::autosave::Autosave;
+use crate::chart::ChartKind;
+use crate::cli::{DrawingMode, Options};
+use crate::config::{BoxStyle, Config};
+use crate::control::ControlCommand;
+use crate::dialog::brush::BrushDialog;
 use crate::dialog::brush_character::BrushCharacterDialog;
-use crate::dialog::colorpicker::{ColorPosition, ColorpickerDialog};
+use crate::dialog::buffers::{BufferAction, BufferListDialog};
+use crate::dialog::center::{CenterAxes, CenterDialog};
+use crate::dialog::chart::ChartDialog;
+use crate::dialog::colorpicker::ColorpickerDialog;
 use crate::dialog::help::HelpDialog;
-use crate::dialog::open::OpenDialog;
-use crate::dialog::save::SaveDialog;
+use crate::dialog::history::HistoryDialog;
+#[cfg(feature = "image")]
+use crate::dialog::image::ImageDialog;
+use crate::dialog::jump::{JumpDialog, JumpTarget};
+use crate::dialog::lint::LintDialog;
+use crate::dialog::open::{OpenDialog, OpenTarget};
+use crate::dialog::packet::PacketDialog;
+use crate::dialog::quantize::QuantizeDialog;
+use crate::dialog::save::{Confirmation, SaveDialog};
+use crate::dialog::search::SearchDialog;
+use crate::dialog::snapshot::{SnapshotDialog, SnapshotListDialog};
+use crate::dialog::stamp_library::StampLibraryDialog;
+use crate::dialog::style::StyleDialog;
+use crate::dialog::table::TableDialog;
+use crate::dialog::tree::TreeDialog;
 use crate::dialog::Dialog;
-use crate::import::SketchParser;
-use crate::terminal::event::{ButtonState, EventHandler, Modifiers, MouseButton, MouseEvent};
-use crate::terminal::{Color, CursorShape, Dimensions, Terminal, TerminalMode, TextStyle};
-
+use crate::job::{Job, POLL_INTERVAL};
+use crate::session::Session;
+use crate::terminal::event::{
+    ArrowKey, ButtonState, EventHandler, Modifiers, MouseButton, MouseEvent,
+};
+use crate::terminal::recording::Recording;
+use crate::terminal::{
+    Color, CursorShape, Dimensions, EscapeStripper, NamedColor, Terminal, TerminalMode, TextStyle,
+};
+use crate::toast::Toast;
+use crate::tool::{
+    Action, ChartTool, ConnectorTool, FillTool, Freehand, ImportTool, LockTool, MeasureTool,
+    MoveTool, StampCapture, TableTool, TextTool, Tool,
+};
+
+mod animation;
+mod asciicast;
+mod autosave;
+mod banner;
+mod capture;
+mod chart;
 mod cli;
+mod config;
+mod control;
+mod convert;
 mod dialog;
-mod import;
+#[cfg(feature = "image")]
+mod halfblock;
+mod job;
+mod packet;
+mod session;
+mod share;
 mod terminal;
+mod toast;
+mod tool;
+mod tree;
 
 /// Help dialog binding information.
 const HELP: &str = "[CTRL + ?] Help";
 
+/// Width of a color swatch in columns, including padding.
+const SWATCH_WIDTH: usize = 3;
+
+/// Columns scrolled per keypress.
+const SCROLL_COLUMNS: isize = 4;
+
+/// Lines scrolled per keypress.
+const SCROLL_LINES: isize = 2;
+
+/// Glyph used to render guide columns.
+const GUIDE_GLYPH: char = '┊';
+
+/// Glyph used to render empty cells of locked regions.
+const LOCKED_GLYPH: char = '·';
+
+/// Maximum distance in columns for snapping to guides.
+const GUIDE_SNAP_DISTANCE: usize = 2;
+
+/// Distance in cells between alignment grid lines, unless configured otherwise.
+const DEFAULT_GRID_SPACING: usize = 8;
+
+/// Glyph separating the canvas from the split view pane.
+const SPLIT_GLYPH: &str = "│";
+
+/// Percentage of the terminal width initially occupied by the split view pane.
+const DEFAULT_SPLIT_SHARE: usize = 50;
+
+/// Percentage of the terminal width the split view pane is resized by per
+/// keypress.
+const SPLIT_SHARE_STEP: usize = 10;
+
 fn main() -> io::Result<()> {
+    let options = Options::parse();
+
+    // Run subcommands without any interface.
+    if let Some(command) = &options.command {
+        return convert::run(command, options.export_settings());
+    }
+
     // Launch the application.
-    Sketch::new().run()
+    Sketch::new(options)?.run()
 }
 
 /// Sketch application state.
 struct Sketch {
-    /// Content of the terminal grid.
-    content: Grid,
+    /// Sketch content with the brush drawing on it.
+    canvas: Canvas,
 
     /// CLI config.
     options: Options,
 
+    /// User and project configuration file options.
+    config: Config,
+
+    /// Original STDOUT while the interface uses the controlling terminal.
+    stdout: Option<File>,
+
     /// Current application mode.
     mode: SketchMode,
 
-    /// Mouse cursor brush used for drawing.
-    brush: Brush,
-
     /// Text cursor position.
     text_cursor: Option<Point>,
 
-    /// Current change revision for undo/redo tracking.
-    revision: usize,
-
-    /// Highest revision available for redo.
-    max_revision: usize,
-
     /// Whether the Sketch was successfully saved to a file.
     persisted: bool,
 
     /// Whether the output path was changed at runtime.
     output_modified: bool,
 
-    /// Whether there's currently text being pasted.
-    pasting: bool,
+    /// Whether the canvas changed since it was loaded or last saved.
+    modified: bool,
+
+    /// Named copies of the canvas, oldest first.
+    snapshots: Vec<(String, Vec<Vec<Cell>>)>,
+
+    /// Copy of the canvas as of the last successful save.
+    saved: Option<Grid>,
+
+    /// Visible section of the canvas.
+    viewport: Viewport,
+
+    /// Last screen position while panning with the middle mouse button.
+    pan_origin: Option<Point>,
+
+    /// Whether diagram consistency issues are highlighted.
+    lint_overlay: bool,
+
+    /// Whether column and line numbers are drawn along the screen edges.
+    ruler: bool,
+
+    /// Whether the alignment grid is drawn into empty cells.
+    grid: bool,
+
+    /// Notification shown at the bottom of the screen.
+    toast: Option<Toast>,
+
+    /// Query of the active text search, highlighted on the canvas.
+    search: Option<String>,
+
+    /// Whether freehand strokes resembling lines or boxes are replaced by them.
+    beautify: bool,
+
+    /// Action performed by the left mouse button without modifiers.
+    drawing_mode: DrawingMode,
 
-    /// Queue used for color fills.
-    fill_queue: VecDeque<(usize, usize, usize, isize)>,
+    /// Periodic crash recovery snapshots of the canvas.
+    autosave: Option<Autosave>,
+
+    /// Timestamped history of the drawing process, when it is recorded.
+    asciicast: Option<Asciicast>,
+
+    /// Temporary content drawn on top of the canvas.
+    overlay: Overlay,
+
+    /// Dimmed reference content shown in empty cells, for tracing.
+    trace: Option<Vec<Vec<Cell>>>,
+
+    /// Read-only reference sketch shown next to the canvas.
+    split: Option<Split>,
+
+    /// Animation frames, with a placeholder at the index of the active frame.
+    frames: Vec<Grid>,
+
+    /// Index of the frame shown on the canvas.
+    frame: usize,
+
+    /// Open buffers, with a placeholder at the index of the active buffer.
+    buffers: Vec<Buffer>,
+
+    /// Index of the buffer shown on the canvas.
+    buffer: usize,
+
+    /// Save running in the background.
+    export: Option<Export>,
+
+    /// Upload to the paste service running in the background.
+    upload: Option<Job<String>>,
 }
 
 impl Sketch {
     /// Setup the Sketch application state.
-    fn new() -> Self {
-        Self {
-            options: Options::parse(),
+    fn new(options: Options) -> io::Result<Self> {
+        // Ignore configuration files for deterministic replays.
+        let config = match options.replay_input {
+            Some(_) => Config::default(),
+            None => Config::load(),
+        };
+
+        Self::with_config(options, config)
+    }
+
+    /// Setup the Sketch application state without loading configuration files.
+    fn with_config(mut options: Options, config: Config) -> io::Result<Self> {
+        // CLI options take precedence over the configuration files.
+        options.width = options.width.or(config.width).map(|width| width.clamp(1, MAX_COLUMNS));
+        options.height = options.height.or(config.height).map(|height| height.clamp(1, MAX_LINES));
+        options.ruler = options.ruler.or_else(|| config.ruler.clone());
+
+        // Keep STDOUT free for the sketch, when requested.
+        let stdout = match options.stdout {
+            true => Some(terminal::use_controlling_tty()?),
+            false => None,
+        };
+
+        // Restore the brush of the previous session, unless disabled.
+        let mut brush = Sketch::remembers_brush(&options, &config)
+            .then(config::brush_path)
+            .flatten()
+            .and_then(|path| session::load_brush(&path))
+            .unwrap_or_default();
+
+        // Configured line weights take precedence over the restored brush.
+        if let Some(box_style) = config.box_style {
+            brush.emphasis = box_style == BoxStyle::Heavy;
+        }
+
+        // CLI brush presets take precedence over the restored brush.
+        if let Some(size) = options.size {
+            brush.size = size;
+            brush.template = Brush::create_template(brush.shape, size);
+        }
+        brush.foreground = options.fg.unwrap_or(brush.foreground);
+        brush.background = options.bg.unwrap_or(brush.background);
+        if let Some(glyph) = &options.glyph {
+            let mut pattern = Vec::new();
+            for c in glyph.chars() {
+                dialog::push_pattern_glyph(&mut pattern, c);
+            }
+            if !pattern.is_empty() {
+                brush.pattern = pattern;
+            }
+        }
+
+        let mut content = Grid::default();
+        content.journal.set_depth(config.undo_depth.unwrap_or(journal::DEFAULT_DEPTH));
+
+        // Only record the drawing process when it can be saved.
+        let record =
+            options.record || options.output.as_deref().is_some_and(asciicast::is_asciicast);
+
+        let mode = options.mode;
+        Ok(Self {
+            options,
+            config,
+            stdout,
             output_modified: Default::default(),
-            max_revision: Default::default(),
+            modified: Default::default(),
             text_cursor: Default::default(),
-            fill_queue: Default::default(),
+            pan_origin: Default::default(),
+            lint_overlay: Default::default(),
+            ruler: Default::default(),
+            grid: Default::default(),
+            beautify: Default::default(),
+            drawing_mode: mode,
+            search: Default::default(),
+            toast: Default::default(),
+            autosave: Default::default(),
+            asciicast: record.then(Asciicast::default),
+            overlay: Default::default(),
+            trace: Default::default(),
+            split: Default::default(),
+            frames: vec![Grid::default()],
+            frame: Default::default(),
+            buffers: vec![Buffer::default()],
+            buffer: Default::default(),
+            export: Default::default(),
+            upload: Default::default(),
+            snapshots: Default::default(),
+            saved: Default::default(),
             persisted: Default::default(),
-            revision: Default::default(),
-            content: Default::default(),
-            pasting: Default::default(),
-            brush: Default::default(),
+            viewport: Default::default(),
+            canvas: Canvas::new(content, brush),
             mode: Default::default(),
-        }
+        })
+    }
+
+    /// Check if the brush is stored between sessions.
+    ///
+    /// Replays never touch the stored brush, to keep them deterministic.
+    fn remembers_brush(options: &Options, config: &Config) -> bool {
+        options.replay_input.is_none() && config.remember_brush != Some(false)
     }
 
     /// Run the terminal event loop.
     fn run(mut self) -> io::Result<()> {
-        let mut terminal = Terminal::new();
+        // Capture the screen before the interface covers it.
+        let screen = if self.options.capture { Some(capture::screen()?) } else { None };
+
+        // Replay recorded input without any interface, if requested.
+        let recording = self.options.replay_input.as_deref().map(Recording::load).transpose()?;
+        let mut terminal = match &recording {
+            Some(recording) => Terminal::headless(recording.dimensions),
+            None => Terminal::new()?,
+        };
+
+        if let Some(path) = &self.options.record_input {
+            terminal.record_input(path)?;
+        }
+
+        if let Some(path) = &self.options.control_socket {
+            terminal.listen(path)?;
+        }
 
         // Perform terminal setup for the TUI.
         terminal.set_mode(TerminalMode::ShowCursor, false);
         terminal.set_mode(TerminalMode::LineWrap, false);
-        terminal.set_mode(TerminalMode::AltScreen, true);
-        terminal.set_mode(TerminalMode::SgrMouse, true);
-        terminal.set_mode(TerminalMode::MouseMotion, true);
+        match self.options.inline {
+            Some(lines) => terminal.set_inline(lines),
+            None => terminal.set_mode(TerminalMode::AltScreen, true),
+        }
+        terminal.enable_mouse();
         terminal.set_mode(TerminalMode::FocusInOut, true);
         terminal.set_mode(TerminalMode::BracketedPaste, true);
+        terminal.request_capabilities();
+        if self.config.kitty_keyboard != Some(false) {
+            terminal.request_kitty_keyboard();
+        }
         Terminal::goto(0, 0);
 
         // Resize internal buffer to fit terminal dimensions.
         let dimensions = terminal.dimensions();
         self.resize(&mut terminal, dimensions);
 
+        // Start with the captured screen, to annotate it.
+        if let Some(screen) = screen {
+            self.load(&mut terminal, &screen, false);
+            self.redraw(&mut terminal);
+        }
+
         // Import sketch file passed as CLI argument.
-        if let Some(sketch) =
-            self.options.file.as_ref().and_then(|path| fs::read_to_string(path).ok())
-        {
-            self.load(&mut terminal, &sketch, true);
+        if let Some(path) = self.options.file.clone() {
+            let _ = self.open(&mut terminal, &path, true);
+            self.modified = false;
+        }
+
+        // Start the recording with the initial canvas.
+        self.record_canvas();
+
+        // Load the tracing layer passed as CLI argument.
+        if let Some(path) = self.options.trace.clone() {
+            self.trace = Some(SketchParser::parse(&fs::read_to_string(path)?));
+            self.redraw(&mut terminal);
+        }
+
+        // Recover the last autosave and keep autosaving to the same file.
+        if let Some(path) = self.options.autosave.clone() {
+            if self.options.file.is_none() {
+                if let Ok(cells) = Autosave::load(&path) {
+                    self.canvas.grid.restore_cells(&cells);
+                    self.fit_canvas();
+                    self.bump_revision();
+                    self.redraw(&mut terminal);
+                }
+            }
+            self.autosave = Some(Autosave::create(&path, &self.canvas.grid.cells)?);
         }
 
         // Run the terminal event loop.
         terminal.set_event_handler(Box::new(self));
-        terminal.run()
+        match recording {
+            Some(recording) => terminal.replay(&recording.input),
+            None => terminal.run(),
+        }
     }
 
     /// Clear the entire screen, going back to an empty canvas.
     fn clear(&mut self, terminal: &mut Terminal) {
         // Reset storage.
-        for line in self.content.iter_mut() {
-            for cell in line {
-                cell.clear(self.revision);
+        for line in 1..=self.canvas.grid.len() {
+            for column in 1..=self.canvas.grid[line - 1].len() {
+                self.canvas.grid.clear(Point { column, line });
             }
         }
 
@@ -144,653 +440,2887 @@ impl Sketch {
     /// to Sketch's application state. This is used to clear things from the
     /// grid which are not part of the sketch (like the cursor preview).
     fn write(&mut self, at: Point, c: char, persist: bool) -> Point {
-        self.write_many(at, c, 1, persist)
+        let point = self.canvas.write(at, c, persist);
+        self.present_strokes();
+        point
     }
 
-    /// Write the same character multiple times.
+    /// Show the strokes written to the canvas since the last call.
     ///
-    /// This is a version of [`write`] optimized to repeat the same character
-    /// many times.
-    fn write_many(&mut self, at: Point, c: char, count: usize, persist: bool) -> Point {
-        if count == 0 {
-            return at;
+    /// Previews are added to the overlay using its current highlight, while
+    /// persistent writes are written to the terminal immediately.
+    fn present_strokes(&mut self) {
+        for stroke in self.canvas.take_strokes() {
+            match stroke {
+                Stroke::Preview { start, cell, count } => {
+                    let width = cell.c.width().unwrap_or(1);
+                    for index in 0..count {
+                        let point = Point { column: start.column + index * width, ..start };
+                        self.overlay.insert(point, &cell, width);
+                    }
+                },
+                Stroke::Write { start, cell, count } => self.present_run(start, &cell, count),
+                Stroke::Clear(points) => {
+                    let runs = self.screen_runs(points);
+                    self.redraw_runs(runs);
+                },
+            }
         }
+    }
 
-        // Verify that the glyph is a printable character.
-        let width = match c.width() {
-            Some(width) if width > 0 => width,
-            _ => return at,
+    /// Write the visible part of a run of identical cells to the terminal.
+    fn present_run(&self, start: Point, cell: &Cell, count: usize) {
+        let width = cell.c.width().unwrap_or(1);
+        let Some((screen_point, visible_count)) = self.viewport.clip(start, width, count) else {
+            return;
         };
 
-        let Point { column, line } = at;
+        let glyph = cell.glyph();
+        Terminal::set_style(cell.style);
+        Terminal::set_color(cell.foreground, cell.background);
+        Terminal::goto(screen_point.column, screen_point.line);
+        Terminal::write(&glyph);
+
+        // Use the terminal escape to repeat the character, if it is supported.
+        //
+        // Since the escape only repeats the last character, it cannot be used
+        // for grapheme clusters.
+        let literal = self.config.repeat_escape == Some(false) || !cell.combining.is_empty();
+        if visible_count > 1 && literal {
+            Terminal::write(glyph.repeat(visible_count - 1));
+        } else if visible_count > 1 {
+            Terminal::repeat(visible_count - 1);
+        }
+    }
+
+    /// Show the strokes of a canvas operation, adding persistent changes to
+    /// the undo history.
+    fn finish_write(&mut self, mode: WriteMode) {
+        self.present_strokes();
+
+        // Increment undo history.
+        if mode != WriteMode::WriteVolatile {
+            self.bump_revision();
+        }
+    }
 
-        // Verify the first cell write is within the grid.
-        if self.content.len() < line || self.content[line - 1].len() + 1 < column + width {
-            return at;
+    /// Move the terminal cursor to a point on the canvas.
+    ///
+    /// The cursor is not moved if the point is outside of the viewport.
+    fn goto(&self, point: Point) {
+        if let Some((point, _)) = self.viewport.clip(point, 1, 1) {
+            Terminal::goto(point.column, point.line);
         }
+    }
 
-        // Store character in the grid state.
-        let foreground = self.brush.foreground;
-        let background = self.brush.background;
-        if persist {
-            let line = &mut self.content[line - 1];
-            let max = min(column + (count - 1) * width, line.len());
-            for column in (column..=max).step_by(width) {
-                // Replace the glyph itself.
-                let cell = Cell::new(c, foreground, background, self.brush.style);
-                line[column - 1].replace(cell, self.revision);
+    /// Redraw the sketch within a rectangular area of the screen.
+    fn redraw_area(&self, columns: Range<usize>, lines: Range<usize>) {
+        if columns.is_empty() {
+            return;
+        }
 
-                // Reset the following character when writing fullwidth characters.
-                if width == 2 {
-                    line[column].clear(self.revision);
-                }
+        for line in lines {
+            let viewport = Viewport {
+                column_offset: self.viewport.column_offset + columns.start - 1,
+                line_offset: self.viewport.line_offset + line - 1,
+                columns: columns.len(),
+                lines: 1,
+            };
 
-                // Replace previous fullwidth character if we're writing inside its spacer.
-                if column >= 2 && line[column - 2].c.width() == Some(2) {
-                    line[column - 2].clear(self.revision);
-                }
-            }
+            Terminal::goto(columns.start, line);
+            Terminal::reset_sgr();
+            Terminal::write(self.displayed_grid().render(
+                &viewport,
+                ':',
+                Terminal::supported_color,
+            ));
         }
+    }
 
-        // Set the text style.
-        Terminal::set_style(self.brush.style);
+    /// Scroll the viewport by a number of columns and lines.
+    ///
+    /// The canvas is extended automatically when scrolling past its bottom or
+    /// right edge.
+    fn scroll(&mut self, terminal: &mut Terminal, columns: isize, lines: isize) {
+        let column_offset = self.viewport.column_offset as isize + columns;
+        let line_offset = self.viewport.line_offset as isize + lines;
+        self.viewport.column_offset = column_offset.max(0) as usize;
+        self.viewport.line_offset = line_offset.max(0) as usize;
 
-        // Set the correct colors for the terminal write.
-        Terminal::set_color(foreground, background);
+        self.fit_canvas();
 
-        // Write to the terminal.
-        Terminal::goto(column, line);
-        Terminal::write(c);
+        self.redraw(terminal);
+    }
 
-        // Use the terminal escape to repeat the character.
-        if count > 1 {
-            Terminal::repeat(count - 1);
+    /// Grow the canvas to cover the viewport.
+    ///
+    /// For canvases with fixed dimensions, the viewport is kept within the
+    /// canvas instead.
+    fn fit_canvas(&mut self) {
+        let Options { width, height, .. } = self.options;
+        let viewport = &mut self.viewport;
+        if let Some(width) = width {
+            viewport.column_offset =
+                min(viewport.column_offset, width.saturating_sub(viewport.columns));
+        }
+        if let Some(height) = height {
+            viewport.line_offset = min(viewport.line_offset, height.saturating_sub(viewport.lines));
         }
 
-        Point { column: column + width * count, line }
+        let (end_column, end_line) = viewport.end();
+        self.canvas.grid.grow(width.unwrap_or(end_column), height.unwrap_or(end_line));
     }
 
-    /// Write the brush's content at its current location.
-    fn write_brush(&mut self, mode: WriteMode) {
-        let last_line = self.content.len() as isize;
-        let cursor_position = self.brush.position;
-
-        // Find the top left corner of the cursor.
-        let brush_width = self.brush.template[0].len();
-        let brush_height = self.brush.template.len();
-        let origin_column = cursor_position.column as isize - brush_width as isize / 2;
-        let origin_line = cursor_position.line as isize - brush_height as isize / 2;
-
-        // Write the cursor characters.
-        for line in 0..brush_height {
-            let target_line = origin_line + line as isize;
-            let skip = usize::try_from(-origin_column + 1).unwrap_or_default();
-            let first_occupied = self.brush.template[line].iter().skip(skip).position(|b| *b);
-
-            // Skip this line if there is no occupied cell within the grid.
-            let first_occupied = match first_occupied {
-                Some(first_occupied) if target_line > 0 && target_line <= last_line => {
-                    first_occupied + skip
-                },
-                _ => continue,
-            };
+    /// Scroll the viewport to center it on a canvas point.
+    fn scroll_to(&mut self, point: Point) {
+        self.viewport.column_offset = point.column.saturating_sub(self.viewport.columns / 2);
+        self.viewport.line_offset = point.line.saturating_sub(self.viewport.lines / 2);
 
-            // Get write target start location.
-            let write_location = Point {
-                column: (origin_column + first_occupied as isize) as usize,
-                line: target_line as usize,
-            };
+        self.fit_canvas();
+    }
 
-            // Get the last non-empty cell in the brush.
-            let last_occupied = self.brush.template[line].iter().rposition(|occ| *occ).unwrap_or(0);
+    /// Highlight diagram consistency issues.
+    fn render_lint(&mut self) {
+        for Issue { point, .. } in self.canvas.grid.lint() {
+            let cell = self.canvas.grid.get(point);
+            let marker = cell.with_background(Color::Named(NamedColor::Red));
+            self.write_cell(point, &marker, false);
+        }
+    }
 
-            // Ignore every second cell for fullwidth brushes.
-            let width = self.brush.glyph.width().unwrap_or(1);
-            let columns = (last_occupied + width - first_occupied) / width;
+    /// Toggle the lint overlay, listing all issues when it is enabled.
+    fn toggle_lint(&mut self, terminal: &mut Terminal) {
+        self.lint_overlay = !self.lint_overlay;
 
-            match mode {
-                WriteMode::WriteVolatile => {
-                    self.write_many(write_location, self.brush.glyph, columns, false);
-                },
-                WriteMode::Write => {
-                    self.write_many(write_location, self.brush.glyph, columns, true);
-                },
-                WriteMode::Erase => {
-                    // Overwrite characters with default background set.
-                    let background = mem::take(&mut self.brush.background);
-                    self.write_many(write_location, ' ', columns * width, true);
-                    self.brush.background = background;
-                },
+        if self.lint_overlay {
+            self.mode = SketchMode::LintDialog(LintDialog::new(self.canvas.grid.lint()));
+        } else {
+            self.notify("Turned lint overlay \x1b[32moff");
+        }
+
+        self.redraw(terminal);
+    }
+
+    /// Render the ruler's guide columns into empty cells.
+    fn render_guides(&mut self) {
+        let guide = Cell::new(GUIDE_GLYPH, Color::default(), Color::default(), TextStyle::empty());
+        let (_, end_line) = self.viewport.end();
+
+        self.overlay.highlight = Highlight::Dim;
+        for column in self.options.guides() {
+            for line in self.viewport.line_offset + 1..=min(end_line, self.canvas.grid.len()) {
+                let point = Point { column, line };
+                if self.canvas.grid.contains(point) && self.canvas.grid.get(point).is_empty() {
+                    self.write_cell(point, &guide, false);
+                }
             }
         }
+        self.overlay.highlight = Highlight::None;
+    }
 
-        // Increment undo history.
-        if mode != WriteMode::WriteVolatile {
-            self.bump_revision();
+    /// Render locked regions with dim colors.
+    fn render_locked(&mut self) {
+        let (end_column, end_line) = self.viewport.end();
+        let locked =
+            Cell::new(LOCKED_GLYPH, Color::default(), Color::default(), TextStyle::empty());
+
+        self.overlay.highlight = Highlight::Dim;
+        for (start, end) in self.canvas.locked.clone() {
+            let columns =
+                max(start.column, self.viewport.column_offset + 1)..=min(end.column, end_column);
+            let lines = max(start.line, self.viewport.line_offset + 1)..=min(end.line, end_line);
+            for line in lines {
+                for column in columns.clone() {
+                    let point = Point { column, line };
+                    if !self.canvas.grid.contains(point) {
+                        continue;
+                    }
+
+                    match self.canvas.grid.get(point) {
+                        cell if cell.is_empty() => self.write_cell(point, &locked, false),
+                        cell if cell.c == '\0' => continue,
+                        cell => self.write_cell(point, &cell.clone(), false),
+                    };
+                }
+            }
         }
+        self.overlay.highlight = Highlight::None;
     }
 
-    // Preview the brush using dim colors.
-    fn preview_brush(&mut self) {
-        Terminal::set_dim();
-        self.write_brush(WriteMode::WriteVolatile);
-        Terminal::reset_sgr();
+    /// Start picking a region to lock or unlock.
+    fn start_lock(&mut self) {
+        self.mode = SketchMode::Tool(Box::<LockTool>::default());
+        self.notify("Drag to \x1b[32mlock\x1b[39m a region, click a locked region to unlock it");
     }
 
-    /// Write a box.
-    fn write_box(&mut self, mut start: Point, mut end: Point, mode: WriteMode) {
-        // Erasing line drawing mode does not exist.
-        if mode == WriteMode::Erase {
-            return;
+    /// Lock a region, or unlock the locked regions under a single clicked cell.
+    fn toggle_lock(&mut self, start: Point, end: Point) {
+        let (start, end) = Point::rect(start, end);
+        let len = self.canvas.locked.len();
+        if start == end {
+            self.canvas
+                .locked
+                .retain(|(locked_start, locked_end)| !start.within(*locked_start, *locked_end));
         }
-        let persistent = mode == WriteMode::Write;
 
-        // Ensure start is always at the top left corner of the box.
-        if start.column > end.column {
-            mem::swap(&mut start.column, &mut end.column);
-        }
-        if start.line > end.line {
-            mem::swap(&mut start.line, &mut end.line);
+        if self.canvas.locked.len() < len {
+            self.overlay.cells.clear();
+            self.render_locked();
+            self.notify("Unlocked \x1b[32mregion");
+        } else {
+            self.canvas.locked.push((start, end));
+            self.render_locked();
+            self.notify("Locked \x1b[32mregion");
         }
+    }
 
-        // Write a new box char, taking combinations into consideration.
-        let mut write_line_char = |point, c| {
-            let old_c = self.content.get(point).c;
-            let new_c = self.combine_line_chars(old_c, c);
-            self.write(point, new_c, persistent)
-        };
+    /// Render the alignment grid into empty cells.
+    fn render_grid(&mut self) {
+        if !self.grid {
+            return;
+        }
 
-        // Write box drawing characters for first and last line.
-        if start.column == end.column && start.line == end.line {
-            // Single cell box.
-            write_line_char(start, '┼');
-        } else if start.column == end.column {
-            // Vertical line.
-            write_line_char(start, '┬');
-            let point = Point { column: start.column, line: end.line };
-            write_line_char(point, '┴');
-        } else if start.line == end.line {
-            // Horizontal line.
-            let mut point = write_line_char(start, '├');
-            for _ in 0..end.column - start.column - 1 {
-                point = write_line_char(point, '─');
-            }
-            write_line_char(point, '┤');
-        } else {
-            // Full box.
-            let mut point = write_line_char(start, '┌');
-            for _ in 0..end.column - start.column - 1 {
-                point = write_line_char(point, '─');
-            }
-            write_line_char(point, '┐');
+        let spacing = self.config.grid_spacing.unwrap_or(DEFAULT_GRID_SPACING).max(1);
+        let (end_column, end_line) = self.viewport.end();
+        let columns = self.viewport.column_offset + 1..=end_column;
+        let lines = self.viewport.line_offset + 1..=end_line;
 
-            let mut point = Point { column: start.column, line: end.line };
-            point = write_line_char(point, '└');
-            for _ in 0..end.column - start.column - 1 {
-                point = write_line_char(point, '─');
-            }
-            write_line_char(point, '┘');
+        // Keep the help message legible.
+        let help_end = match self.help_visible() {
+            true => self.viewport.column_offset + HELP.len(),
+            false => 0,
         };
 
-        // Draw the sides of the box.
-        for line in (start.line..end.line).skip(1) {
-            // Write left border.
-            let point = Point { column: start.column, line };
-            write_line_char(point, '│');
+        self.overlay.highlight = Highlight::Dim;
+        for line in lines {
+            for column in columns.clone() {
+                let glyph = match (column % spacing == 0, line % spacing == 0) {
+                    (true, true) => '┼',
+                    (true, false) => '┊',
+                    (false, true) => '┈',
+                    (false, false) => continue,
+                };
+                if line == self.viewport.line_offset + 1 && column <= help_end {
+                    continue;
+                }
 
-            // Write right border.
-            if end.column != start.column {
-                let point = Point { column: end.column, line };
-                write_line_char(point, '│');
+                let point = Point { column, line };
+                if self.canvas.grid.contains(point) && self.canvas.grid.get(point).is_empty() {
+                    let cell =
+                        Cell::new(glyph, Color::default(), Color::default(), TextStyle::empty());
+                    self.overlay.insert(point, &cell, 1);
+                }
             }
         }
+        self.overlay.highlight = Highlight::None;
+    }
 
-        // Increment undo history.
-        if mode != WriteMode::WriteVolatile {
-            self.bump_revision();
-        }
+    /// Toggle the alignment grid.
+    fn toggle_grid(&mut self, terminal: &mut Terminal) {
+        self.grid = !self.grid;
+
+        let state = if self.grid { "on" } else { "off" };
+        self.notify(&format!("Turned grid \x1b[32m{}", state));
+
+        self.redraw(terminal);
     }
 
-    /// Preview the box using dim colors.
-    fn preview_box(&mut self, start: Point, end: Point) {
-        Terminal::set_dim();
-        self.write_box(start, end, WriteMode::WriteVolatile);
-        Terminal::reset_sgr();
+    /// Move a point to the closest guide column within snapping distance.
+    fn snap_to_guides(&self, point: Point) -> Point {
+        let closest = self.options.guides().into_iter().min_by_key(|c| c.abs_diff(point.column));
+        match closest {
+            Some(column) if column.abs_diff(point.column) <= GUIDE_SNAP_DISTANCE => {
+                Point { column, ..point }
+            },
+            _ => point,
+        }
     }
 
-    /// Write a one-dimensional line.
-    fn write_line(&mut self, start: Point, end: Point, mode: WriteMode) {
-        // Erasing line drawing mode does not exist.
-        if mode == WriteMode::Erase {
+    /// Draw column and line numbers along the screen edges.
+    ///
+    /// The position of the cursor is shown in the top right corner.
+    fn render_ruler(&mut self) {
+        if !self.ruler {
             return;
         }
-        let persistent = mode == WriteMode::Write;
 
-        // Check the brush travel in X and Y direction.
-        let min_column = min(start.column, end.column);
-        let column_delta = max(start.column, end.column) - min_column;
-        let min_line = min(start.line, end.line);
-        let max_line = max(start.line, end.line);
-        let line_delta = max_line - min_line;
-
-        // Write the line.
-        if column_delta >= line_delta * 2 {
-            let count = (column_delta + 1) / self.brush.glyph.width().unwrap_or(1);
-            let point = Point { column: min_column, line: start.line };
-            self.write_many(point, self.brush.glyph, count, persistent);
-        } else {
-            for line in min_line..=max_line {
-                let point = Point { column: start.column, line };
-                self.write(point, self.brush.glyph, persistent);
+        let Viewport { column_offset, line_offset, columns, lines } = self.viewport;
+        let put = |overlay: &mut Overlay, column: usize, line: usize, text: &str| {
+            for (column, c) in (column..=columns).zip(text.chars()) {
+                let cell = Cell::new(c, Color::default(), Color::default(), TextStyle::empty());
+                let point = Point { column: column + column_offset, line: line + line_offset };
+                overlay.insert(point, &cell, 1);
             }
+        };
+
+        self.overlay.highlight = Highlight::Dim;
+
+        // Number every line in a gutter along the left edge.
+        let gutter = (line_offset + lines).to_string().len();
+        put(&mut self.overlay, 1, 1, &" ".repeat(gutter));
+        for line in 2..=lines {
+            put(&mut self.overlay, 1, line, &format!("{:>gutter$}", line_offset + line));
         }
 
-        // Increment undo history.
-        if mode != WriteMode::WriteVolatile {
-            self.bump_revision();
+        // Number every tenth column along the top edge, with ticks in between.
+        let mut column = gutter + 1;
+        while column <= columns {
+            let text = match column_offset + column {
+                canvas_column if canvas_column % 10 == 0 => canvas_column.to_string(),
+                canvas_column if canvas_column % 5 == 0 => "+".into(),
+                _ => "·".into(),
+            };
+            put(&mut self.overlay, column, 1, &text);
+            column += text.chars().count();
         }
-    }
 
-    /// Preview the line using dim colors.
-    fn preview_line(&mut self, start: Point, end: Point) {
-        Terminal::set_dim();
-        self.write_line(start, end, WriteMode::WriteVolatile);
-        Terminal::reset_sgr();
+        // Show the cursor position on top of the ruler.
+        self.overlay.highlight = Highlight::Reverse;
+        let cursor = self.text_cursor.unwrap_or(self.canvas.brush.position);
+        let readout = format!(" {}:{} ", cursor.line, cursor.column);
+        put(&mut self.overlay, (columns + 1).saturating_sub(readout.len()).max(1), 1, &readout);
+
+        self.overlay.highlight = Highlight::None;
     }
 
-    /// Close all dialogs and go back to sketching mode.
-    fn close_dialog(&mut self, terminal: &mut Terminal) {
-        self.mode = SketchMode::Sketching;
+    /// Toggle the column and line number ruler.
+    fn toggle_ruler(&mut self, terminal: &mut Terminal) {
+        self.ruler = !self.ruler;
 
-        // Hide the terminal cursor.
-        terminal.set_mode(TerminalMode::ShowCursor, false);
+        let state = if self.ruler { "on" } else { "off" };
+        self.notify(&format!("Turned ruler \x1b[32m{}", state));
 
-        // Redraw everything.
         self.redraw(terminal);
-
-        self.preview_brush();
     }
 
-    /// Emulate backspace to delete the last character.
-    fn backspace(&mut self, terminal: &mut Terminal) {
-        // Ignore backspace in the first column.
-        let text_cursor = self.text_cursor.get_or_insert(self.brush.position);
-        if text_cursor.column <= 1 {
+    /// Mark the edge of a fixed size canvas and clear the screen beyond it.
+    fn render_canvas_border(&self) {
+        let Options { width, height, .. } = self.options;
+        if width.is_none() && height.is_none() {
             return;
         }
 
-        // Move cursor to the previous cell.
-        text_cursor.column -= 1;
+        // Get the screen position of the border, if it is visible.
+        let Viewport { column_offset, line_offset, columns, lines } = self.viewport;
+        let border_column = width.map(|width| width + 1 - column_offset).filter(|c| *c <= columns);
+        let border_line = height.map(|height| height + 1 - line_offset).filter(|l| *l <= lines);
 
-        // Overwrite cell with whitespace.
-        let point = *text_cursor;
-        self.write(point, ' ', true);
+        Terminal::reset_sgr();
+        Terminal::set_dim();
 
-        // Move terminal cursor to new location.
-        Terminal::goto(point.column, point.line);
+        if let Some(column) = border_column {
+            for line in 1..border_line.unwrap_or(lines + 1) {
+                Terminal::goto(column, line);
+                Terminal::write("│");
+                Terminal::clear_line_end();
+            }
+        }
 
-        // Ensure IBeam cursor is visible.
-        terminal.set_mode(TerminalMode::ShowCursor, true);
-        Terminal::set_cursor_shape(CursorShape::IBeam);
+        if let Some(line) = border_line {
+            Terminal::goto(1, line);
+            Terminal::write("─".repeat(border_column.unwrap_or(columns + 1) - 1));
+            if border_column.is_some() {
+                Terminal::write("┘");
+            }
+            Terminal::clear_screen_end();
+        }
 
-        self.bump_revision();
+        Terminal::reset_sgr();
     }
 
-    /// Open the dialog for color selection.
-    fn open_color_dialog(&mut self, terminal: &mut Terminal, color_position: ColorPosition) {
-        let dialog =
-            ColorpickerDialog::new(color_position, self.brush.foreground, self.brush.background);
-        dialog.render(terminal);
+    /// Join a character with the grapheme cluster before the text cursor.
+    ///
+    /// Returns `false` if the character does not extend the grapheme cluster.
+    fn combine_glyph(&mut self, c: char) -> bool {
+        let Some(text_cursor) = self.text_cursor.filter(|cursor| cursor.column > 1) else {
+            return false;
+        };
 
-        self.mode = SketchMode::ColorpickerDialog(dialog);
+        // Skip the spacer of fullwidth characters.
+        let mut point = Point { column: text_cursor.column - 1, ..text_cursor };
+        if point.column > 1
+            && self.canvas.grid.get(Point { column: point.column - 1, ..point }).c.width()
+                == Some(2)
+        {
+            point.column -= 1;
+        }
+
+        if !self.canvas.grid.contains(point) {
+            return false;
+        }
+
+        let mut cell = self.canvas.grid.get(point).clone();
+        if !cell.combine(c) {
+            return false;
+        }
+
+        self.write_cell(point, &cell, true);
+        true
     }
 
-    /// Open the dialog for brush character selection.
-    fn open_brush_character_dialog(&mut self, terminal: &mut Terminal) {
-        let dialog = BrushCharacterDialog::new(self.brush.glyph);
-        dialog.render(terminal);
+    /// Write a cell's glyph using the cell's colors and text style.
+    fn write_cell(&mut self, at: Point, cell: &Cell, persist: bool) -> Point {
+        let point = self.canvas.write_cell(at, cell, persist);
+        self.present_strokes();
+        point
+    }
 
-        self.mode = SketchMode::BrushCharacterDialog(dialog);
+    /// Write the brush's content at its current location.
+    fn write_brush(&mut self, mode: WriteMode) {
+        self.canvas.write_brush(mode);
+        self.finish_write(mode);
     }
 
-    /// Open the dialog for picking the save path.
-    fn open_save_dialog(&mut self, terminal: &mut Terminal, error: bool, shutdown: bool) {
-        let path = match &self.options.output {
-            Some(path) => path.to_string_lossy().into(),
-            None => String::new(),
-        };
-        self.mode = SketchMode::SaveDialog(SaveDialog::new(path, error, shutdown));
+    /// Write a custom brush stamp centered around the brush position.
+    fn write_stamp(&mut self, stamp: &[Vec<Cell>], mode: WriteMode) {
+        self.canvas.write_stamp(stamp, mode);
+        self.finish_write(mode);
+    }
 
-        // Redraw the entire terminal to clear previous dialogs.
-        self.redraw(terminal);
+    /// Reset a cell to its transparent default.
+    ///
+    /// Locked cells and cells outside of the grid are ignored.
+    fn clear_cell(&mut self, point: Point) {
+        self.canvas.clear_cell(point);
+        self.present_strokes();
     }
 
-    /// Open the dialog for importing sketches.
-    fn open_open_dialog(&mut self, terminal: &mut Terminal) {
-        self.mode = SketchMode::OpenDialog(OpenDialog::new());
+    /// Cycle through the cell attributes replaced by brush strokes.
+    fn cycle_paint_mode(&mut self) {
+        self.canvas.brush.paint = self.canvas.brush.paint.next();
+        self.notify(&format!("Changed brush to paint \x1b[32m{}", self.canvas.brush.paint.name()));
+    }
 
-        // Redraw the entire terminal to clear previous dialogs.
-        self.redraw(terminal);
+    /// Cycle through the glyph intensities of the brush's text style.
+    fn cycle_intensity(&mut self) {
+        let intensity = self.canvas.brush.style.intensity().next();
+        self.canvas.brush.style = self.canvas.brush.style.with_intensity(intensity);
+        self.notify(&format!("Changed brush intensity to \x1b[32m{}", intensity.name()));
     }
 
-    /// Open the dialog for showing keybarding and usage information.
+    /// Cycle through the combinations of stamps with existing content.
+    fn cycle_stamp_blend(&mut self) {
+        self.canvas.brush.blend = self.canvas.brush.blend.next();
+        self.notify(&format!(
+            "Changed stamp blending to \x1b[32m{}",
+            self.canvas.brush.blend.name()
+        ));
+    }
+
+    /// Cycle through the actions of the left mouse button.
+    fn cycle_drawing_mode(&mut self) {
+        self.drawing_mode = self.drawing_mode.next();
+        self.notify(&format!("Changed drawing mode to \x1b[32m{}", self.drawing_mode.name()));
+    }
+
+    /// Tool handling the mouse while no other tool is active.
+    ///
+    /// Unmodified mouse input in text mode places the text cursor, everything
+    /// else draws freehand.
+    fn default_tool(&self, event: &MouseEvent) -> Box<dyn Tool> {
+        if self.drawing_mode == DrawingMode::Text && event.modifiers.is_empty() {
+            Box::new(TextTool)
+        } else {
+            Box::new(Freehand)
+        }
+    }
+
+    /// Change the eraser size, starting from the brush size.
+    fn resize_eraser(&mut self, delta: i8) {
+        let size = self.canvas.brush.eraser_size.unwrap_or(self.canvas.brush.size);
+        let size = size.saturating_add_signed(delta).max(1);
+        self.canvas.brush.eraser_size = Some(size);
+
+        self.notify(&format!("Changed eraser size to \x1b[32m{size}"));
+    }
+
+    /// Cycle through the cell attributes removed by the eraser.
+    fn cycle_eraser_mode(&mut self) {
+        self.canvas.brush.eraser = self.canvas.brush.eraser.next();
+        self.notify(&format!(
+            "Changed eraser to clear \x1b[32m{}",
+            self.canvas.brush.eraser.name()
+        ));
+    }
+
+    // Preview the brush using dim colors.
+    fn preview_brush(&mut self) {
+        self.overlay.highlight = Highlight::Dim;
+        self.write_brush(WriteMode::WriteVolatile);
+        self.overlay.highlight = Highlight::None;
+    }
+
+    /// Write a box.
+    fn write_box(&mut self, start: Point, end: Point, mode: WriteMode) {
+        self.canvas.write_box(start, end, mode);
+        self.finish_write(mode);
+    }
+
+    /// Preview the box using dim colors.
+    fn preview_box(&mut self, start: Point, end: Point) {
+        self.overlay.highlight = Highlight::Dim;
+        self.write_box(start, end, WriteMode::WriteVolatile);
+        self.overlay.highlight = Highlight::None;
+    }
+
+    /// Write a table with evenly distributed rows and columns.
+    ///
+    /// Rows and columns which do not fit into the table are dropped.
+    fn write_table(&mut self, start: Point, end: Point, size: (usize, usize), mode: WriteMode) {
+        self.canvas.write_table(start, end, size, mode);
+        self.finish_write(mode);
+    }
+
+    /// Preview the table using dim colors.
+    fn preview_table(&mut self, start: Point, end: Point, size: (usize, usize)) {
+        self.overlay.highlight = Highlight::Dim;
+        self.write_table(start, end, size, WriteMode::WriteVolatile);
+        self.overlay.highlight = Highlight::None;
+    }
+
+    /// Write a chart filling the area between two corners.
+    ///
+    /// Returns `false` if the chart does not fit into the area.
+    fn write_chart(
+        &mut self,
+        start: Point,
+        end: Point,
+        values: &[f64],
+        kind: ChartKind,
+        mode: WriteMode,
+    ) -> bool {
+        // Erasing chart drawing mode does not exist.
+        if mode == WriteMode::Erase {
+            return false;
+        }
+        let persistent = mode == WriteMode::Write;
+
+        let (start, end) = Point::rect(start, end);
+        let width = end.column - start.column + 1;
+        let lines = end.line - start.line + 1;
+        let Some(chart) = chart::render(values, kind, width, lines) else {
+            return false;
+        };
+
+        for (line_offset, text) in chart.iter().enumerate() {
+            for (column_offset, c) in text.chars().enumerate().filter(|(_, c)| *c != ' ') {
+                let point =
+                    Point { column: start.column + column_offset, line: start.line + line_offset };
+                self.canvas.write(point, c, persistent);
+            }
+        }
+
+        self.finish_write(mode);
+
+        true
+    }
+
+    /// Preview the chart using dim colors, or its outline if the chart does not
+    /// fit.
+    fn preview_chart(&mut self, start: Point, end: Point, values: &[f64], kind: ChartKind) {
+        self.overlay.highlight = Highlight::Dim;
+        let fits = self.write_chart(start, end, values, kind, WriteMode::WriteVolatile);
+        self.overlay.highlight = Highlight::None;
+
+        if !fits {
+            self.preview_selection(start, end);
+        }
+    }
+
+    /// Write a one-dimensional line.
+    fn write_line(&mut self, start: Point, end: Point, snap: bool, mode: WriteMode) {
+        self.canvas.write_line(start, end, snap, mode);
+        self.finish_write(mode);
+    }
+
+    /// Preview a rectangular selection using reversed colors.
+    fn preview_selection(&mut self, start: Point, end: Point) {
+        let (start, end) = Point::rect(start, end);
+
+        self.overlay.highlight = Highlight::Reverse;
+        for line in start.line..=end.line {
+            for column in start.column..=end.column {
+                // Skip spacers of fullwidth characters.
+                let point = Point { column, line };
+                if column > 1
+                    && self.canvas.grid.get(Point { column: column - 1, line }).c.width() == Some(2)
+                {
+                    continue;
+                }
+
+                // Render empty cells as whitespace to make the selection visible.
+                let cell = self.canvas.grid.get(point);
+                let c = if cell.c == '\0' { ' ' } else { cell.c };
+                let cell = Cell { c, ..cell.clone() };
+
+                self.write_cell(point, &cell, false);
+            }
+        }
+        self.overlay.highlight = Highlight::None;
+    }
+
+    /// Preview a measured region, with its size and aspect ratio below it.
+    ///
+    /// The aspect ratio is measured on screen, unless circle aspect correction
+    /// is disabled.
+    fn preview_measure(&mut self, start: Point, end: Point) {
+        self.preview_selection(start, end);
+
+        let (start, end) = Point::rect(start, end);
+        let (width, height) = (end.column + 1 - start.column, end.line + 1 - start.line);
+        let cell_ratio = if self.canvas.true_geometry { 1. } else { geometry::CELL_ASPECT_RATIO };
+        let ratio = width as f64 / (height as f64 * cell_ratio);
+        let readout = format!(" {width}×{height} cells, {ratio:.2}:1 ");
+
+        // Put the readout below the region, or above it at the bottom of the screen.
+        let Viewport { column_offset, line_offset, columns, lines } = self.viewport;
+        let line = match end.line < line_offset + lines {
+            true => end.line + 1,
+            false => start.line.saturating_sub(1).max(line_offset + 1),
+        };
+        let max_column = (column_offset + columns + 1).saturating_sub(readout.chars().count());
+        let column = start.column.min(max_column).max(column_offset + 1);
+
+        self.overlay.highlight = Highlight::Reverse;
+        for (column, c) in (column..=column_offset + columns).zip(readout.chars()) {
+            let cell = Cell::new(c, Color::default(), Color::default(), TextStyle::empty());
+            self.overlay.insert(Point { column, line }, &cell, 1);
+        }
+        self.overlay.highlight = Highlight::None;
+    }
+
+    /// Start measuring regions, without drawing anything.
+    fn start_measure(&mut self) {
+        self.mode = SketchMode::Tool(Box::<MeasureTool>::default());
+        self.notify("Drag to \x1b[32mmeasure\x1b[39m a region, ESC to stop");
+    }
+
+    /// Capture a rectangular region of the canvas as custom brush stamp.
+    fn capture_stamp(&mut self, start: Point, end: Point) {
+        let (start, end) = Point::rect(start, end);
+
+        let stamp = (start.line..=end.line)
+            .map(|line| {
+                (start.column..=end.column)
+                    .map(|column| self.canvas.grid.get(Point { column, line }).clone())
+                    .collect()
+            })
+            .collect();
+        self.canvas.brush.stamp = Some(stamp);
+
+        self.notify("Captured selection as \x1b[32mbrush stamp");
+    }
+
+    /// Start selecting a region for a new brush stamp, or drop the active
+    /// stamp.
+    fn toggle_stamp_capture(&mut self) {
+        if self.canvas.brush.stamp.take().is_some() {
+            self.notify("Dropped \x1b[32mbrush stamp");
+        } else {
+            self.mode = SketchMode::Tool(Box::<StampCapture>::default());
+            self.notify("Drag to select the \x1b[32mbrush stamp");
+        }
+    }
+
+    /// Start picking a region to move.
+    fn start_move(&mut self) {
+        self.mode = SketchMode::Tool(Box::<MoveTool>::default());
+        self.notify("Drag content or a selection to \x1b[32mmove it\x1b[39m, ALT to duplicate");
+    }
+
+    /// Move cells by an offset.
+    ///
+    /// Vacated cells are cleared, unless the cells are duplicated. Cells moved
+    /// outside of the grid are dropped.
+    fn move_region(
+        &mut self,
+        points: &[Point],
+        offset: (isize, isize),
+        duplicate: bool,
+        mode: WriteMode,
+    ) {
+        self.canvas.move_region(points, offset, duplicate, mode);
+        self.finish_write(mode);
+    }
+
+    /// Start connecting two box borders.
+    fn start_connector(&mut self) {
+        self.mode = SketchMode::Tool(Box::<ConnectorTool>::default());
+        self.notify("Click two box borders to \x1b[32mconnect them");
+    }
+
+    /// Write an orthogonal connector between two box borders.
+    ///
+    /// Junctions are placed automatically where the connector meets the
+    /// borders.
+    fn write_connector(&mut self, start: Point, end: Point, mode: WriteMode) {
+        // Erasing connectors does not exist.
+        if mode == WriteMode::Erase {
+            return;
+        }
+        let persistent = mode == WriteMode::Write;
+
+        let path = match self.canvas.grid.connector_path(start, end) {
+            Some(path) => path,
+            None => return,
+        };
+
+        // Use heavy lines for emphasized connectors.
+        let weight = if self.canvas.brush.emphasis { Weight::Heavy } else { Weight::Light };
+
+        for (i, point) in path.iter().enumerate() {
+            let previous = i.checked_sub(1).and_then(|i| path.get(i));
+            let neighbours = previous.into_iter().chain(path.get(i + 1));
+            let glyph = connector_glyph(*point, neighbours, weight);
+
+            // Combine with the existing content to create junctions.
+            self.canvas.write_merged(*point, glyph, persistent);
+        }
+
+        self.finish_write(mode);
+    }
+
+    /// Preview the connector using dim colors.
+    fn preview_connector(&mut self, start: Point, end: Point) {
+        self.overlay.highlight = Highlight::Dim;
+        self.write_connector(start, end, WriteMode::WriteVolatile);
+        self.overlay.highlight = Highlight::None;
+    }
+
+    /// Preview the line using dim colors.
+    fn preview_line(&mut self, start: Point, end: Point, snap: bool) {
+        self.overlay.highlight = Highlight::Dim;
+        self.write_line(start, end, snap, WriteMode::WriteVolatile);
+        self.overlay.highlight = Highlight::None;
+    }
+
+    /// Write text using large banner letters.
+    fn write_banner(&mut self, origin: Point, text: &str, mode: WriteMode) {
+        // Erasing banner text does not exist.
+        if mode == WriteMode::Erase {
+            return;
+        }
+        let persistent = mode == WriteMode::Write;
+
+        let mut column = origin.column;
+        for c in text.chars() {
+            for (line_offset, row) in banner::letter(c).into_iter().flatten().enumerate() {
+                for (column_offset, columns) in banner::runs(row) {
+                    let point =
+                        Point { column: column + column_offset, line: origin.line + line_offset };
+                    self.canvas.write_pattern(
+                        point,
+                        columns / self.canvas.brush.width(),
+                        persistent,
+                    );
+                }
+            }
+
+            column += banner::letter_width() + banner::LETTER_SPACING;
+        }
+
+        self.finish_write(mode);
+    }
+
+    /// Preview the banner text using dim colors.
+    fn preview_banner(&mut self, origin: Point, text: &str) {
+        self.overlay.highlight = Highlight::Dim;
+        self.write_banner(origin, text, WriteMode::WriteVolatile);
+        self.overlay.highlight = Highlight::None;
+    }
+
+    /// Start typing banner text at the brush position.
+    fn start_banner_text(&mut self) {
+        self.mode = SketchMode::BannerText(self.canvas.brush.position, String::new());
+        self.notify("Type to write \x1b[32mbanner text\x1b[39m, confirm with enter");
+    }
+
+    /// Process keyboard input for writing banner text.
+    fn banner_text_input(&mut self, glyph: char) {
+        let (origin, mut text) = match mem::take(&mut self.mode) {
+            SketchMode::BannerText(origin, text) => (origin, text),
+            _ => return,
+        };
+
+        match glyph {
+            // Cancel banner text on escape.
+            '\x1b' => return,
+            // Write the banner text on enter.
+            '\n' => {
+                self.write_banner(origin, &text, WriteMode::Write);
+                return;
+            },
+            '\x7f' => {
+                let _ = text.pop();
+            },
+            glyph if banner::letter(glyph).is_some() => text.push(glyph),
+            _ => (),
+        }
+
+        self.preview_banner(origin, &text);
+        self.mode = SketchMode::BannerText(origin, text);
+    }
+
+    /// Write an ellipse around a center point.
+    fn write_ellipse(&mut self, center: Point, end: Point, free: bool, mode: WriteMode) {
+        self.canvas.write_ellipse(center, end, free, mode);
+        self.finish_write(mode);
+    }
+
+    /// Preview the ellipse using dim colors.
+    fn preview_ellipse(&mut self, center: Point, end: Point, free: bool) {
+        self.overlay.highlight = Highlight::Dim;
+        self.write_ellipse(center, end, free, WriteMode::WriteVolatile);
+        self.overlay.highlight = Highlight::None;
+    }
+
+    /// Toggle cell aspect ratio correction for circles.
+    fn toggle_true_geometry(&mut self) {
+        self.canvas.true_geometry = !self.canvas.true_geometry;
+
+        let state = if self.canvas.true_geometry { "off" } else { "on" };
+        self.notify(&format!("Turned circle aspect correction \x1b[32m{}", state));
+    }
+
+    /// Check if any dialog is open.
+    fn has_dialog(&self) -> bool {
+        #[cfg(feature = "image")]
+        if let SketchMode::ImageDialog(_) = self.mode {
+            return true;
+        }
+
+        matches!(
+            self.mode,
+            SketchMode::BrushCharacterDialog(_)
+                | SketchMode::BrushDialog(_)
+                | SketchMode::ColorpickerDialog(_)
+                | SketchMode::SaveDialog(_)
+                | SketchMode::OpenDialog(_)
+                | SketchMode::HelpDialog(_)
+                | SketchMode::StampLibraryDialog(_)
+                | SketchMode::QuantizeDialog(_)
+                | SketchMode::PacketDialog(_)
+                | SketchMode::TableDialog(_)
+                | SketchMode::ChartDialog(_)
+                | SketchMode::TreeDialog(_)
+                | SketchMode::LintDialog(_)
+                | SketchMode::HistoryDialog(_)
+                | SketchMode::SnapshotDialog(_)
+                | SketchMode::SnapshotListDialog(_)
+                | SketchMode::JumpDialog(_)
+                | SketchMode::CenterDialog(_)
+                | SketchMode::SearchDialog(_)
+                | SketchMode::BufferListDialog(_)
+                | SketchMode::StyleDialog(_)
+        )
+    }
+
+    /// Close all dialogs and go back to sketching mode.
+    fn close_dialog(&mut self, terminal: &mut Terminal) {
+        self.mode = SketchMode::Sketching;
+
+        // Hide the terminal cursor.
+        terminal.set_mode(TerminalMode::ShowCursor, false);
+
+        // Redraw everything.
+        self.redraw(terminal);
+
+        self.preview_brush();
+    }
+
+    /// Handle the answer to the box widening prompt.
+    ///
+    /// Confirming widens the box before writing the pending glyph, declining
+    /// writes it over the border. Any other key discards the glyph.
+    fn widen_prompt_input(&mut self, terminal: &mut Terminal, glyph: char) {
+        let (text_cursor, pending) = match self.mode {
+            SketchMode::WidenPrompt(text_cursor, pending) => (text_cursor, pending),
+            _ => return,
+        };
+        self.mode = SketchMode::Sketching;
+        self.clear_toast();
+
+        match glyph {
+            'y' | 'Y' => self.widen_box(text_cursor),
+            'n' | 'N' => (),
+            _ => {
+                self.redraw(terminal);
+                return;
+            },
+        }
+
+        self.text_cursor = Some(self.write(text_cursor, pending, true));
+        self.bump_revision();
+
+        // Clear the prompt and show the shifted content.
+        self.redraw(terminal);
+    }
+
+    /// Widen the box with its right border at `border` by one column.
+    ///
+    /// Everything to the right of the border on the lines of the box is shifted
+    /// along with it.
+    fn widen_box(&mut self, border: Point) {
+        let (top, bottom) = match self.canvas.grid.box_right_border(border) {
+            Some(lines) => lines,
+            None => return,
+        };
+
+        // Grow the canvas if content would be shifted out of it.
+        let columns = self.canvas.grid.first().map_or(0, Vec::len);
+        let overflow =
+            (top..=bottom).any(|line| !self.canvas.grid[line - 1][columns - 1].is_empty());
+        if overflow && self.options.width.is_none() {
+            self.canvas.grid.grow(columns + 1, 0);
+        }
+
+        self.canvas.grid.insert_column(border.column, top..=bottom);
+
+        // Extend the top and bottom border, and table row separators, into the new
+        // column.
+        for line in top..=bottom {
+            let corner = self.canvas.grid.get(Point { column: border.column + 1, line });
+            let weight = match junction::segments(corner.c) {
+                Some([.., weight]) if weight != Weight::None => weight,
+                _ if line == top || line == bottom => Weight::Light,
+                _ => continue,
+            };
+            let glyph = junction::glyph([Weight::None, weight, Weight::None, weight]);
+            let cell =
+                Cell::new(glyph.unwrap_or('─'), corner.foreground, corner.background, corner.style);
+            self.canvas.grid.replace(Point { line, ..border }, cell);
+        }
+    }
+
+    /// Extend the table under the text cursor or brush by one row.
+    ///
+    /// The new row copies the height and column separators of the table's last
+    /// row. Everything below the table is shifted down to make room for it.
+    fn append_table_row(&mut self) {
+        let cursor = self.text_cursor.unwrap_or(self.canvas.brush.position);
+        let (columns, bottom, height) = match self.canvas.grid.table_bottom(cursor) {
+            Some(table) => table,
+            None => {
+                self.notify("No \x1b[32mtable\x1b[39m under the cursor");
+                return;
+            },
+        };
+
+        // Grow the canvas if content would be shifted out of it.
+        //
+        // The row and its new bottom border shift all lines below the table.
+        let lines = self.canvas.grid.len();
+        let shifted = height + 1;
+        let overflow = (lines.saturating_sub(shifted).max(bottom)..lines).any(|line| {
+            columns.clone().any(|column| !self.canvas.grid[line][column - 1].is_empty())
+        });
+        if overflow || bottom + shifted > lines {
+            // Never drop content from canvases with a fixed height.
+            if self.options.height.is_some() {
+                self.notify("No room to grow the \x1b[32mtable\x1b[39m");
+                return;
+            }
+            self.canvas.grid.grow(0, lines + shifted);
+        }
+
+        let border: Vec<_> = columns
+            .clone()
+            .map(|column| self.canvas.grid[bottom - 1][column - 1].clone())
+            .collect();
+        self.canvas.grid.insert_lines(bottom + 1, shifted, columns.clone());
+
+        for (column, cell) in columns.zip(border) {
+            let [up, right, _, left] = junction::segments(cell.c).unwrap_or_default();
+
+            // Turn the old bottom border into a row separator.
+            let separator = junction::glyph([up, right, up, left]).unwrap_or(cell.c);
+            let separator = Cell { c: separator, ..cell.clone() };
+            self.canvas.grid.replace(Point { column, line: bottom }, separator);
+
+            // Continue column separators through the new row.
+            if up != Weight::None {
+                let glyph = junction::glyph([up, Weight::None, up, Weight::None]).unwrap_or('│');
+                for line in bottom + 1..=bottom + height {
+                    let cell = Cell { c: glyph, ..cell.clone() };
+                    self.canvas.grid.replace(Point { column, line }, cell);
+                }
+            }
+
+            self.canvas.grid.replace(Point { column, line: bottom + height + 1 }, cell);
+        }
+
+        // Continue typing in the new row.
+        if let Some(text_cursor) = &mut self.text_cursor {
+            text_cursor.line = bottom + 1;
+        }
+
+        self.bump_revision();
+    }
+
+    /// Start typing text along the path under the brush.
+    fn start_path_text(&mut self) {
+        let points = self.canvas.grid.trace_path(self.canvas.brush.position);
+        if points.is_empty() {
+            self.notify("No \x1b[32mpath\x1b[39m under the brush");
+            return;
+        }
+
+        // Store the original path content to allow restoring it with backspace.
+        let cells = points.iter().map(|point| self.canvas.grid.get(*point).clone()).collect();
+
+        self.text_cursor = Some(points[0]);
+        self.mode = SketchMode::PathText(PathText { points, cells, index: 0 });
+
+        self.notify("Type to write \x1b[32mtext along the path");
+    }
+
+    /// Process keyboard input for writing text along a path.
+    fn path_text_input(&mut self, terminal: &mut Terminal, glyph: char) {
+        let mut path_text = match mem::take(&mut self.mode) {
+            SketchMode::PathText(path_text) => path_text,
+            _ => return,
+        };
+
+        match glyph {
+            // Stop writing text on escape or enter.
+            '\x1b' | '\n' => {
+                self.text_cursor = None;
+                terminal.set_mode(TerminalMode::ShowCursor, false);
+                return;
+            },
+            // Restore the original path content on backspace.
+            '\x7f' if path_text.index > 0 => {
+                path_text.index -= 1;
+
+                let index = path_text.index;
+                self.write_cell(path_text.points[index], &path_text.cells[index], true);
+                self.bump_revision();
+            },
+            // Write glyph and advance along the path.
+            glyph if glyph.width() == Some(1) && path_text.index < path_text.points.len() => {
+                self.write(path_text.points[path_text.index], glyph, true);
+                path_text.index += 1;
+                self.bump_revision();
+            },
+            _ => (),
+        }
+
+        // Move the text cursor to the next position on the path.
+        match path_text.points.get(path_text.index) {
+            Some(point) => {
+                self.text_cursor = Some(*point);
+                self.goto(*point);
+
+                // Show IBeam cursor while typing.
+                terminal.set_mode(TerminalMode::ShowCursor, true);
+                Terminal::set_cursor_shape(CursorShape::IBeam);
+            },
+            None => terminal.set_mode(TerminalMode::ShowCursor, false),
+        }
+
+        self.mode = SketchMode::PathText(path_text);
+    }
+
+    /// Emulate backspace to delete the last character.
+    fn backspace(&mut self, terminal: &mut Terminal) {
+        // Ignore backspace in the first column.
+        let text_cursor = self.text_cursor.get_or_insert(self.canvas.brush.position);
+        if text_cursor.column <= 1 {
+            return;
+        }
+
+        // Move cursor to the previous cell.
+        text_cursor.column -= 1;
+
+        // Overwrite cell with whitespace.
+        let point = *text_cursor;
+        self.write(point, ' ', true);
+
+        // Move terminal cursor to new location.
+        self.goto(point);
+
+        // Ensure IBeam cursor is visible.
+        terminal.set_mode(TerminalMode::ShowCursor, true);
+        Terminal::set_cursor_shape(CursorShape::IBeam);
+
+        self.bump_revision();
+    }
+
+    /// Open the dialog for color selection.
+    fn open_color_dialog(&mut self, terminal: &mut Terminal, color_position: ColorPosition) {
+        let dialog = ColorpickerDialog::new(
+            color_position,
+            self.canvas.brush.foreground,
+            self.canvas.brush.background,
+        );
+        self.render_swatches(terminal);
+        dialog.render(terminal);
+
+        self.mode = SketchMode::ColorpickerDialog(dialog);
+    }
+
+    /// Render the colors used in the sketch as swatches into the last line.
+    fn render_swatches(&self, terminal: &Terminal) {
+        let line = terminal.dimensions().lines as usize;
+        let max_swatches = terminal.dimensions().columns as usize / SWATCH_WIDTH;
+
+        Terminal::goto(1, line);
+        for color in self.canvas.grid.used_colors().into_iter().take(max_swatches) {
+            Terminal::set_background(color);
+            Terminal::write(" ".repeat(SWATCH_WIDTH - 1));
+            Terminal::reset_sgr();
+            Terminal::write(" ");
+        }
+    }
+
+    /// Get the color of the swatch at a point.
+    fn swatch_at(&self, terminal: &Terminal, point: Point) -> Option<Color> {
+        let dimensions = terminal.dimensions();
+        let swatch_column = (point.column - 1) % SWATCH_WIDTH;
+        if point.line != dimensions.lines as usize || swatch_column == SWATCH_WIDTH - 1 {
+            return None;
+        }
+
+        let index = (point.column - 1) / SWATCH_WIDTH;
+        self.canvas.grid.used_colors().get(index).copied()
+    }
+
+    /// Open the dialog for brush character selection.
+    fn open_brush_character_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = BrushCharacterDialog::new(self.canvas.brush.pattern.clone());
+        dialog.render(terminal);
+
+        self.mode = SketchMode::BrushCharacterDialog(dialog);
+    }
+
+    /// Open the dialog for editing all brush settings.
+    fn open_brush_dialog(&mut self, terminal: &mut Terminal) {
+        let brush = &self.canvas.brush;
+        let dialog = BrushDialog::new(brush.size, brush.shape, brush.pattern.clone());
+        dialog.render(terminal);
+
+        self.mode = SketchMode::BrushDialog(dialog);
+    }
+
+    /// Open the dialog for picking a built-in stamp.
+    fn open_stamp_library_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = StampLibraryDialog::new();
+        dialog.render(terminal);
+
+        self.mode = SketchMode::StampLibraryDialog(dialog);
+    }
+
+    /// Use a text stamp as the brush stamp.
+    ///
+    /// All glyphs of the stamp will use the brush's colors and text style,
+    /// whitespace is transparent.
+    fn load_stamp(&mut self, stamp: &str) {
+        let brush = &self.canvas.brush;
+        let width = stamp.lines().map(|line| line.width()).max().unwrap_or_default();
+        let stamp = stamp
+            .lines()
+            .map(|line| {
+                let mut cells = Vec::with_capacity(width);
+                for glyph in line.graphemes(true) {
+                    let (foreground, background) = (brush.foreground, brush.background);
+                    match Cell::with_glyph(glyph, foreground, background, brush.style) {
+                        Some(cell) if glyph != " " => cells.push(cell),
+                        _ => cells.push(Cell::default()),
+                    }
+
+                    // Keep the spacer of fullwidth characters transparent.
+                    let spacers = glyph.width().saturating_sub(1);
+                    cells.extend((0..spacers).map(|_| Cell::default()));
+                }
+                cells.resize(width, Cell::default());
+                cells
+            })
+            .collect();
+        self.canvas.brush.stamp = Some(stamp);
+    }
+
+    /// Open the dialog for generating packet diagrams.
+    fn open_packet_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = PacketDialog::new();
+        dialog.render(terminal);
+
+        self.mode = SketchMode::PacketDialog(dialog);
+    }
+
+    /// Open the dialog for drawing tables.
+    fn open_table_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = TableDialog::new();
+        dialog.render(terminal);
+
+        self.mode = SketchMode::TableDialog(dialog);
+    }
+
+    /// Open the dialog for drawing charts.
+    fn open_chart_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = ChartDialog::new();
+        dialog.render(terminal);
+
+        self.mode = SketchMode::ChartDialog(dialog);
+    }
+
+    /// Open the dialog for generating tree diagrams.
+    fn open_tree_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = TreeDialog::new();
+        dialog.render(terminal);
+
+        self.mode = SketchMode::TreeDialog(dialog);
+    }
+
+    /// Ask for confirmation before uploading the sketch to the paste service.
+    fn share(&mut self) {
+        if self.upload.is_some() {
+            self.notify("Sketch is \x1b[33malready being shared");
+            return;
+        }
+
+        self.mode = SketchMode::SharePrompt;
+        self.notify_share_prompt();
+    }
+
+    /// Show the question for confirming uploads.
+    fn notify_share_prompt(&mut self) {
+        let host = &self.options.share_host;
+        self.notify(&format!("Upload the sketch to \x1b[33m{host}\x1b[39m? [y/N]"));
+    }
+
+    /// Handle the answer to the upload confirmation prompt.
+    ///
+    /// Only an explicit confirmation uploads the sketch, any other key cancels.
+    fn share_prompt_input(&mut self, glyph: char) {
+        self.mode = SketchMode::Sketching;
+
+        if !matches!(glyph, 'y' | 'Y') {
+            self.notify("Sharing \x1b[31mcancelled");
+            return;
+        }
+
+        // Upload in the background, since the paste service might be slow to respond.
+        let host = self.options.share_host.clone();
+        let text = self.canvas.grid.trimmed_text(self.options.sgr_encoding());
+        self.upload = Some(Job::spawn(move |_| share::upload(&host, &text)));
+        self.notify("Uploading sketch…");
+    }
+
+    /// Report the result of the upload, once it is done.
+    fn poll_upload(&mut self) {
+        let result = match self.upload.as_ref().and_then(Job::poll) {
+            Some(result) => result,
+            None => return,
+        };
+        self.upload = None;
+
+        match result {
+            Ok(url) => {
+                Terminal::set_clipboard(&url);
+                self.notify(&format!("Shared sketch at \x1b[32m{url}\x1b[39m (copied)"));
+            },
+            Err(err) => self.notify(&format!("Sharing failed: \x1b[31m{err}")),
+        }
+    }
+
+    /// Copy the active selection or the entire sketch to the clipboard.
+    fn copy_to_clipboard(&mut self) {
+        let encoding = self.options.sgr_encoding();
+        let selection = match &self.mode {
+            SketchMode::Tool(tool) => tool.selection(),
+            _ => None,
+        };
+
+        match selection {
+            Some((start, end)) => {
+                Terminal::set_clipboard(&self.canvas.grid.region_text(start, end, encoding));
+                self.notify("Copied selection to the \x1b[32mclipboard");
+            },
+            None => {
+                Terminal::set_clipboard(&self.canvas.grid.trimmed_text(encoding));
+                self.notify("Copied sketch to the \x1b[32mclipboard");
+            },
+        }
+    }
+
+    /// Translate box drawing characters between Unicode and ASCII.
+    ///
+    /// Only the selection of the active tool is translated, if there is one.
+    /// Regions with any Unicode box drawing characters are converted to ASCII,
+    /// otherwise ASCII lines and corners are converted to Unicode.
+    fn translate_box_drawing(&mut self) {
+        let columns = self.canvas.grid.first().map_or(0, Vec::len);
+        let canvas =
+            (Point { column: 1, line: 1 }, Point { column: columns, line: self.canvas.grid.len() });
+        let (start, end) = match &self.mode {
+            SketchMode::Tool(tool) => tool.selection().unwrap_or(canvas),
+            _ => canvas,
+        };
+
+        let points = self.canvas.grid.region(start, end);
+        let glyph = |point: Point| match self.canvas.grid.contains(point) {
+            true => self.canvas.grid.get(point).c,
+            false => ' ',
+        };
+        let to_ascii = points.iter().any(|point| junction::segments(glyph(*point)).is_some());
+
+        // Find all replacements before modifying the grid, to keep connections intact.
+        let replacements: Vec<_> = points
+            .into_iter()
+            .filter_map(|point| {
+                let Point { column, line } = point;
+                let c = if to_ascii {
+                    junction::to_ascii(glyph(point))?
+                } else {
+                    let neighbors = [
+                        glyph(Point { column, line: line - 1 }),
+                        glyph(Point { column: column + 1, line }),
+                        glyph(Point { column, line: line + 1 }),
+                        glyph(Point { column: column - 1, line }),
+                    ];
+                    junction::from_ascii(glyph(point), neighbors)?
+                };
+                Some((point, c))
+            })
+            .collect();
+
+        for (point, c) in &replacements {
+            let Cell { foreground, background, style, .. } = *self.canvas.grid.get(*point);
+            self.write_cell(*point, &Cell::new(*c, foreground, background, style), true);
+        }
+        self.bump_revision();
+
+        let target = if to_ascii { "ASCII" } else { "Unicode" };
+        let count = replacements.len();
+        self.notify(&format!("Translated {count} box drawing glyphs to \x1b[32m{target}"));
+    }
+
+    /// Open the dialog for quantizing colors to a palette.
+    fn open_quantize_dialog(&mut self, terminal: &mut Terminal) {
+        let palette = self.config.palette.unwrap_or_default();
+        self.mode = SketchMode::QuantizeDialog(QuantizeDialog::new(palette));
+
+        // Redraw the entire terminal to show the quantization preview.
+        self.redraw(terminal);
+    }
+
+    /// Remap all colors in the sketch to their closest match in a palette.
+    fn quantize(&mut self, palette: Palette) {
+        for line in 1..=self.canvas.grid.len() {
+            for column in 1..=self.canvas.grid[line - 1].len() {
+                let cell = self.canvas.grid.get(Point { column, line });
+                let foreground = palette.nearest(cell.foreground);
+                let background = palette.nearest(cell.background);
+                if foreground != cell.foreground || background != cell.background {
+                    let new_cell = Cell { foreground, background, ..cell.clone() };
+                    self.canvas.grid.replace(Point { column, line }, new_cell);
+                }
+            }
+        }
+
+        self.bump_revision();
+    }
+
+    /// Open the dialog for picking the save path.
+    fn open_save_dialog(&mut self, terminal: &mut Terminal, error: bool, shutdown: bool) {
+        let path = match (&self.options.output, &self.config.output_directory) {
+            (Some(path), _) => path.to_string_lossy().into(),
+            (None, Some(directory)) => directory.join("").to_string_lossy().into(),
+            (None, None) => String::new(),
+        };
+        self.mode = SketchMode::SaveDialog(SaveDialog::new(path, error, shutdown));
+
+        // Redraw the entire terminal to clear previous dialogs.
+        self.redraw(terminal);
+    }
+
+    /// Open the dialog for importing sketches.
+    fn open_open_dialog(&mut self, terminal: &mut Terminal) {
+        self.mode = SketchMode::OpenDialog(OpenDialog::new());
+
+        // Redraw the entire terminal to clear previous dialogs.
+        self.redraw(terminal);
+    }
+
+    /// Open the dialog for showing keybarding and usage information.
     fn open_help_dialog(&mut self, terminal: &mut Terminal) {
         let dialog = HelpDialog::new();
         dialog.render(terminal);
 
-        self.mode = SketchMode::HelpDialog(dialog);
+        self.mode = SketchMode::HelpDialog(dialog);
+    }
+
+    /// Render the help dialog message.
+    fn render_help(&mut self) {
+        if !self.help_visible() {
+            return;
+        }
+
+        // Write the help message into the last line.
+        Terminal::reset_sgr();
+        Terminal::goto(0, 0);
+        Terminal::write(HELP);
+    }
+
+    /// Whether the help message fits into the first visible line.
+    ///
+    /// The message is hidden as soon as the line has any content in it.
+    fn help_visible(&self) -> bool {
+        let Viewport { column_offset, line_offset, columns, .. } = self.viewport;
+        let mut first_line = self.canvas.grid[line_offset].iter().skip(column_offset).take(columns);
+        first_line.all(Cell::is_empty)
+    }
+
+    /// Redraw all cells which changed since the last frame.
+    ///
+    /// This falls back to a full redraw when the entire screen is damaged.
+    fn update(&mut self, terminal: &mut Terminal) {
+        let points = match self.canvas.grid.take_damage() {
+            // Quantization previews recolor the entire canvas.
+            Damage::Partial(_) if matches!(self.mode, SketchMode::QuantizeDialog(_)) => {
+                return self.redraw(terminal);
+            },
+            Damage::Partial(points) => points,
+            Damage::Full => return self.redraw(terminal),
+        };
+
+        self.overlay.clear();
+
+        // Always repaint the first line, since it might contain the help message.
+        let mut runs = self.screen_runs(points);
+        runs.push((1, 1..self.viewport.columns + 1));
+        self.redraw_runs(runs);
+
+        self.render_overlays(terminal);
+    }
+
+    /// Merge canvas cells into continuous runs on the screen.
+    fn screen_runs(&self, points: impl IntoIterator<Item = Point>) -> Vec<(usize, Range<usize>)> {
+        let mut points: Vec<_> = points
+            .into_iter()
+            .filter_map(|point| self.viewport.clip(point, 1, 1))
+            .map(|(point, _)| point)
+            .collect();
+        points.sort_unstable_by_key(|point| (point.line, point.column));
+
+        let mut runs: Vec<(usize, Range<usize>)> = Vec::new();
+        for Point { column, line } in points {
+            match runs.last_mut() {
+                Some((run_line, columns)) if *run_line == line && columns.end == column => {
+                    columns.end += 1;
+                },
+                _ => runs.push((line, column..column + 1)),
+            }
+        }
+        runs
+    }
+
+    /// Redraw the sketch within runs of screen cells.
+    fn redraw_runs(&mut self, runs: Vec<(usize, Range<usize>)>) {
+        let Viewport { column_offset, line_offset, .. } = self.viewport;
+        for (line, mut columns) in runs {
+            // Include fullwidth characters cut in half by the run.
+            let canvas_line = self.canvas.grid.cells.get(line_offset + line - 1);
+            let previous = (column_offset + columns.start).checked_sub(2);
+            let previous = previous.and_then(|column| canvas_line?.get(column));
+            if previous.is_some_and(|cell| cell.c.width() == Some(2)) {
+                columns.start -= 1;
+            }
+
+            self.redraw_area(columns.clone(), line..line + 1);
+
+            // Overlay cells in the area have been overwritten.
+            let canvas_columns = column_offset + columns.start..column_offset + columns.end;
+            self.overlay.invalidate(line_offset + line, canvas_columns);
+        }
+    }
+
+    /// Write all overlay cells which changed since the last frame.
+    ///
+    /// Removed overlay cells are restored from the canvas.
+    fn present_overlay(&mut self) {
+        let overlay = &self.overlay;
+        let removed = overlay.rendered.keys().filter(|point| !overlay.cells.contains_key(point));
+        let runs = self.screen_runs(removed.copied());
+        self.redraw_runs(runs);
+
+        let overlay = &self.overlay;
+        let mut changed: Vec<_> = overlay
+            .cells
+            .iter()
+            .filter(|(point, cell)| {
+                cell.cell.c != '\0' && overlay.rendered.get(point) != Some(cell)
+            })
+            .collect();
+        changed.sort_unstable_by_key(|(point, _)| (point.line, point.column));
+
+        // Skip cursor movement and SGR changes between similar adjacent cells.
+        let mut cursor = None;
+        let mut attributes = None;
+        for (point, overlay_cell) in changed {
+            let OverlayCell { cell, highlight } = overlay_cell;
+            let width = cell.c.width().unwrap_or(1);
+            let Some((screen_point, _)) = self.viewport.clip(*point, width, 1) else {
+                continue;
+            };
+
+            if cursor != Some(screen_point) {
+                Terminal::goto(screen_point.column, screen_point.line);
+            }
+            cursor = Some(Point { column: screen_point.column + width, ..screen_point });
+
+            let cell_attributes = (cell.foreground, cell.background, cell.style, *highlight);
+            if attributes != Some(cell_attributes) {
+                Terminal::reset_sgr();
+                match highlight {
+                    Highlight::Dim => Terminal::set_dim(),
+                    Highlight::Reverse => Terminal::set_reverse(),
+                    Highlight::None => (),
+                }
+                Terminal::set_style(cell.style);
+                Terminal::set_color(cell.foreground, cell.background);
+                attributes = Some(cell_attributes);
+            }
+
+            Terminal::write(cell.glyph());
+        }
+
+        if attributes.is_some() {
+            Terminal::reset_sgr();
+        }
+
+        // Restore the text cursor moved by overlay writes.
+        if let Some(text_cursor) = self.text_cursor.filter(|_| cursor.is_some()) {
+            self.goto(text_cursor);
+        }
+
+        self.overlay.rendered = self.overlay.cells.clone();
+    }
+
+    /// Re-print the canvas and all overlays.
+    fn redraw_screen(&mut self, terminal: &mut Terminal) {
+        // Everything is redrawn, so previous changes can be ignored.
+        self.canvas.grid.take_damage();
+        self.overlay.clear();
+        self.overlay.rendered.clear();
+
+        // Re-print the visible part of the stored buffer.
+        Terminal::goto(1, 1);
+        Terminal::reset_sgr();
+        match &self.mode {
+            // Preview the sketch with quantized colors.
+            SketchMode::QuantizeDialog(dialog) => {
+                let palette = dialog.palette();
+                Terminal::write(
+                    self.canvas.grid.render(&self.viewport, ':', |color| palette.nearest(color)),
+                );
+            },
+            _ => Terminal::write(self.displayed_grid().render(
+                &self.viewport,
+                ':',
+                Terminal::supported_color,
+            )),
+        }
+        self.render_split(terminal);
+
+        self.render_overlays(terminal);
+    }
+
+    /// Render everything drawn on top of the canvas.
+    fn render_overlays(&mut self, terminal: &mut Terminal) {
+        self.render_trace();
+        self.render_onion_skin();
+        self.render_canvas_border();
+        self.render_grid();
+        self.render_guides();
+        self.render_locked();
+        if self.lint_overlay {
+            self.render_lint();
+        }
+        self.render_search();
+        self.render_ruler();
+
+        self.render_help();
+
+        // Restore text cursor.
+        if let Some(text_cursor) = self.text_cursor {
+            self.goto(text_cursor);
+        }
+
+        // Show preview of the banner text that is being written.
+        if let SketchMode::BannerText(origin, text) = &self.mode {
+            let (origin, text) = (*origin, text.clone());
+            self.preview_banner(origin, &text);
+        }
+
+        // Keep asking until the box widening prompt is answered.
+        if let SketchMode::WidenPrompt(text_cursor, _) = self.mode {
+            self.notify("Widen the box? [y/n]");
+            self.goto(text_cursor);
+        }
+
+        // Keep asking until the upload confirmation is answered.
+        if let SketchMode::SharePrompt = self.mode {
+            self.notify_share_prompt();
+        }
+
+        // Restore previews of the active tool.
+        let mode = mem::take(&mut self.mode);
+        if let SketchMode::Tool(tool) = &mode {
+            tool.redraw(self);
+        }
+        self.mode = mode;
+
+        // Show color swatches while picking colors.
+        if let SketchMode::ColorpickerDialog(_) = self.mode {
+            self.render_swatches(terminal);
+        }
+
+        self.render_toast();
+
+        // Redraw dialogs.
+        match &mut self.mode {
+            SketchMode::BrushCharacterDialog(dialog) => dialog.render(terminal),
+            SketchMode::BrushDialog(dialog) => dialog.render(terminal),
+            SketchMode::ColorpickerDialog(dialog) => dialog.render(terminal),
+            SketchMode::SaveDialog(dialog) => dialog.render(terminal),
+            SketchMode::OpenDialog(dialog) => dialog.render(terminal),
+            SketchMode::HelpDialog(dialog) => dialog.render(terminal),
+            SketchMode::StampLibraryDialog(dialog) => dialog.render(terminal),
+            SketchMode::QuantizeDialog(dialog) => dialog.render(terminal),
+            SketchMode::PacketDialog(dialog) => dialog.render(terminal),
+            SketchMode::TableDialog(dialog) => dialog.render(terminal),
+            SketchMode::ChartDialog(dialog) => dialog.render(terminal),
+            SketchMode::TreeDialog(dialog) => dialog.render(terminal),
+            SketchMode::LintDialog(dialog) => dialog.render(terminal),
+            SketchMode::HistoryDialog(dialog) => dialog.render(terminal),
+            SketchMode::SnapshotDialog(dialog) => dialog.render(terminal),
+            SketchMode::SnapshotListDialog(dialog) => dialog.render(terminal),
+            SketchMode::JumpDialog(dialog) => dialog.render(terminal),
+            SketchMode::CenterDialog(dialog) => dialog.render(terminal),
+            SketchMode::SearchDialog(dialog) => dialog.render(terminal),
+            SketchMode::BufferListDialog(dialog) => dialog.render(terminal),
+            SketchMode::StyleDialog(dialog) => dialog.render(terminal),
+            #[cfg(feature = "image")]
+            SketchMode::ImageDialog(dialog) => dialog.render(terminal),
+            _ => (),
+        }
+    }
+
+    /// Show a notification, replacing the previous one.
+    ///
+    /// The message is cleared automatically after a few seconds.
+    fn notify(&mut self, message: &str) {
+        self.clear_toast();
+        self.toast = Some(Toast::new(message));
+        self.render_toast();
+    }
+
+    /// Show the active notification in the last line of the screen.
+    fn render_toast(&mut self) {
+        let toast = match &self.toast {
+            Some(toast) => toast,
+            None => return,
+        };
+
+        let mut column = 1;
+        for cell in toast.cells() {
+            let point = self.viewport.canvas_point(column, self.viewport.lines);
+            let width = cell.c.width().unwrap_or(1);
+            self.overlay.insert(point, cell, width);
+            column += width;
+        }
+    }
+
+    /// Remove the active notification, uncovering the cells below it.
+    fn clear_toast(&mut self) {
+        let toast = match self.toast.take() {
+            Some(toast) => toast,
+            None => return,
+        };
+
+        for column in 1..=toast.width() {
+            let point = self.viewport.canvas_point(column, self.viewport.lines);
+            self.overlay.cells.remove(&point);
+        }
+    }
+
+    /// Undo the last revision.
+    fn undo(&mut self, terminal: &mut Terminal) {
+        if self.canvas.grid.undo() {
+            self.modified = true;
+            if let Some(asciicast) = &mut self.asciicast {
+                let changes = self.canvas.grid.journal.redo_revisions().last().unwrap_or_default();
+                asciicast.update(&self.canvas.grid.cells, changes.iter().map(|(point, _)| *point));
+            }
+            self.update(terminal);
+        }
+    }
+
+    /// Redo the last undone revision.
+    fn redo(&mut self, terminal: &mut Terminal) {
+        if self.canvas.grid.redo() {
+            self.modified = true;
+            if let Some(asciicast) = &mut self.asciicast {
+                let changes = self.canvas.grid.journal.undo_revisions().last().unwrap_or_default();
+                asciicast.update(&self.canvas.grid.cells, changes.iter().map(|(point, _)| *point));
+            }
+            self.update(terminal);
+        }
+    }
+
+    /// Finish the current revision for undo/redo tracking.
+    fn bump_revision(&mut self) {
+        self.modified = true;
+        if let Some(asciicast) = &mut self.asciicast {
+            let changes = self.canvas.grid.journal.pending().iter().map(|(point, _)| *point);
+            asciicast.update(&self.canvas.grid.cells, changes);
+        }
+        self.canvas.grid.journal.commit();
+
+        if let Some(autosave) = &mut self.autosave {
+            autosave.update(&self.canvas.grid.cells);
+        }
+    }
+
+    /// Record the entire canvas, after it was replaced without a revision.
+    fn record_canvas(&mut self) {
+        if let Some(asciicast) = &mut self.asciicast {
+            asciicast.redraw(&self.canvas.grid.cells);
+        }
+    }
+
+    /// Maximum number of undoable revisions.
+    fn undo_depth(&self) -> usize {
+        self.config.undo_depth.unwrap_or(journal::DEFAULT_DEPTH)
+    }
+
+    /// Drop all undo history except for the last `revisions` revisions.
+    fn compact_history(&mut self, revisions: usize) {
+        self.canvas.grid.journal.commit();
+        self.canvas.grid.journal.truncate(revisions);
+    }
+
+    /// Describe the size of the undo history.
+    fn history_summary(&self) -> String {
+        let journal = &self.canvas.grid.journal;
+        let (changes, bytes) = journal.size();
+        format!(
+            "{} undo / {} redo steps, {} cell changes using {}",
+            journal.undo_revisions().count(),
+            journal.redo_revisions().count(),
+            changes,
+            format_bytes(bytes),
+        )
+    }
+
+    /// Open the undo history dialog.
+    fn open_history_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = HistoryDialog::new(self.history_summary());
+        dialog.render(terminal);
+
+        self.mode = SketchMode::HistoryDialog(dialog);
+    }
+
+    /// Open the dialog for naming a new snapshot.
+    fn open_snapshot_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = SnapshotDialog::new();
+        dialog.render(terminal);
+
+        self.mode = SketchMode::SnapshotDialog(dialog);
+    }
+
+    /// Open the dialog for picking the size of an imported image.
+    ///
+    /// The image width defaults to the width of the screen.
+    #[cfg(feature = "image")]
+    fn open_image_dialog(&mut self, terminal: &mut Terminal, path: &Path) {
+        self.mode = SketchMode::ImageDialog(ImageDialog::new(path.into(), self.viewport.columns));
+
+        // Redraw the entire terminal to clear the open dialog.
+        self.redraw(terminal);
+    }
+
+    /// Open the dialog for restoring a snapshot.
+    fn open_snapshot_list_dialog(&mut self, terminal: &mut Terminal) {
+        let names = self.snapshots.iter().rev().map(|(name, _)| name.clone()).collect();
+        let dialog = SnapshotListDialog::new(names);
+        dialog.render(terminal);
+
+        self.mode = SketchMode::SnapshotListDialog(dialog);
+    }
+
+    /// Open the dialog for jumping to cells matching the brush.
+    fn open_jump_dialog(&mut self, terminal: &mut Terminal) {
+        let glyph = self.canvas.brush.pattern.first().cloned().unwrap_or_else(|| " ".into());
+        let dialog =
+            JumpDialog::new(self.canvas.brush.foreground, self.canvas.brush.background, glyph);
+        dialog.render(terminal);
+
+        self.mode = SketchMode::JumpDialog(dialog);
     }
 
-    /// Render the help dialog message.
-    fn render_help(&mut self) {
-        // Skip drawing if the first line has any content in it.
-        if !self.content[0].iter().all(Cell::is_empty) {
+    /// Move the text cursor to the next cell matching the brush.
+    ///
+    /// Cells are searched in reading order, starting after the text cursor or
+    /// brush, and wrapping around at the end of the canvas.
+    fn jump_to(&mut self, terminal: &mut Terminal, target: JumpTarget) {
+        let brush = &self.canvas.brush;
+        let start = self.text_cursor.unwrap_or(brush.position);
+        let point = self.canvas.grid.find_next(start, |cell| {
+            !cell.is_empty()
+                && match target {
+                    JumpTarget::Foreground => cell.foreground == brush.foreground,
+                    JumpTarget::Background => cell.background == brush.background,
+                    JumpTarget::Glyph => brush.pattern.iter().any(|glyph| cell.is_glyph(glyph)),
+                }
+        });
+
+        match point {
+            Some(point) => self.move_text_cursor(terminal, point),
+            None => self.notify("No \x1b[32mmatching cell\x1b[39m found"),
+        }
+    }
+
+    /// Open the dialog for searching text on the canvas.
+    fn open_search_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = SearchDialog::new(self.search.clone().unwrap_or_default());
+        dialog.render(terminal);
+
+        self.mode = SketchMode::SearchDialog(dialog);
+    }
+
+    /// Move the text cursor to the next or previous match of the active search.
+    ///
+    /// Matches are searched in reading order, starting at the text cursor or
+    /// brush, and wrapping around at the edges of the canvas.
+    fn search_next(&mut self, terminal: &mut Terminal, forward: bool) {
+        let query = match &self.search {
+            Some(query) => query,
+            None => return,
+        };
+
+        let start = self.text_cursor.unwrap_or(self.canvas.brush.position);
+        let start = (start.line, start.column);
+        let matches = self.canvas.grid.search(query);
+        let index = if forward {
+            matches.iter().position(|(point, _)| (point.line, point.column) > start).unwrap_or(0)
+        } else {
+            matches
+                .iter()
+                .rposition(|(point, _)| (point.line, point.column) < start)
+                .unwrap_or(matches.len().saturating_sub(1))
+        };
+
+        let point = match matches.get(index) {
+            Some((point, _)) => *point,
+            None => {
+                self.notify("No \x1b[32mmatches\x1b[39m found");
+                return;
+            },
+        };
+
+        self.move_text_cursor(terminal, point);
+
+        // Keep navigating between matches until another key is pressed.
+        self.mode = SketchMode::SearchMatches;
+        self.notify(&format!(
+            "Match \x1b[32m{}/{}\x1b[39m, n/N for next/previous",
+            index + 1,
+            matches.len()
+        ));
+    }
+
+    /// Handle keystrokes while navigating between search matches.
+    ///
+    /// Any key other than n/N leaves the navigation and is processed as usual.
+    fn search_matches_input(&mut self, terminal: &mut Terminal, glyph: char) {
+        match glyph {
+            'n' => self.search_next(terminal, true),
+            'N' => self.search_next(terminal, false),
+            glyph => {
+                self.leave_search_matches();
+                if glyph != '\x1b' {
+                    self.keyboard_input(terminal, glyph);
+                }
+            },
+        }
+    }
+
+    /// Stop navigating between search matches.
+    fn leave_search_matches(&mut self) {
+        if let SketchMode::SearchMatches = self.mode {
+            self.mode = SketchMode::Sketching;
+            self.clear_toast();
+        }
+    }
+
+    /// Highlight all matches of the active search.
+    fn render_search(&mut self) {
+        let query = match &self.search {
+            Some(query) => query,
+            None => return,
+        };
+
+        for (start, columns) in self.canvas.grid.search(query) {
+            let end = Point { column: start.column + columns - 1, ..start };
+            self.preview_selection(start, end);
+        }
+    }
+
+    /// Move the text cursor to a cell, scrolling it into view.
+    fn move_text_cursor(&mut self, terminal: &mut Terminal, point: Point) {
+        self.text_cursor = Some(point);
+
+        // Bring the cell into view, unless it is already visible.
+        if self.viewport.clip(point, 1, 1).is_none() {
+            self.scroll_to(point);
+            self.redraw(terminal);
+        }
+
+        // Show the IBeam cursor at the cell, to edit it right away.
+        terminal.set_mode(TerminalMode::ShowCursor, true);
+        Terminal::set_cursor_shape(CursorShape::IBeam);
+        self.goto(point);
+    }
+
+    /// Store a copy of the canvas, replacing snapshots with the same name.
+    fn save_snapshot(&mut self, name: String) {
+        self.snapshots.retain(|(snapshot, _)| *snapshot != name);
+        self.notify(&format!("Saved snapshot \x1b[32m{name}"));
+        self.snapshots.push((name, self.canvas.grid.cells.clone()));
+    }
+
+    /// Replace the canvas with a snapshot.
+    ///
+    /// Restoring a snapshot can be undone like any other change.
+    fn restore_snapshot(&mut self, terminal: &mut Terminal, name: &str) {
+        let cells = match self.snapshots.iter().find(|(snapshot, _)| snapshot == name) {
+            Some((_, cells)) => cells,
+            None => return,
+        };
+
+        self.canvas.grid.restore_cells(cells);
+        self.bump_revision();
+
+        self.redraw(terminal);
+        self.notify(&format!("Restored snapshot \x1b[32m{name}"));
+    }
+
+    /// Insert a copy of the active frame after it and switch to the copy.
+    fn duplicate_frame(&mut self, terminal: &mut Terminal) {
+        let mut copy = Grid::new(self.canvas.grid.cells.clone(), Journal::default());
+        copy.journal.set_depth(self.undo_depth());
+        self.frames.insert(self.frame + 1, copy);
+
+        self.switch_frame(terminal, 1);
+    }
+
+    /// Remove the active frame and switch to the one following it.
+    fn delete_frame(&mut self, terminal: &mut Terminal) {
+        if self.frames.len() == 1 {
+            self.notify("Cannot delete the \x1b[32monly frame");
             return;
         }
 
-        // Write the help message into the last line.
-        Terminal::reset_sgr();
-        Terminal::goto(0, 0);
-        Terminal::write(HELP);
+        self.frames.remove(self.frame);
+        self.frame = min(self.frame, self.frames.len() - 1);
+        self.canvas.grid = mem::take(&mut self.frames[self.frame]);
+
+        self.fit_canvas();
+        self.record_canvas();
+        self.redraw(terminal);
+        self.notify(&format!("Deleted frame, showing \x1b[32m{}", self.frame_name()));
+    }
+
+    /// Switch to another frame, relative to the active one.
+    ///
+    /// Navigation wraps around at the first and last frame.
+    fn switch_frame(&mut self, terminal: &mut Terminal, offset: isize) {
+        let frame = (self.frame as isize + offset).rem_euclid(self.frames.len() as isize) as usize;
+
+        mem::swap(&mut self.canvas.grid, &mut self.frames[self.frame]);
+        mem::swap(&mut self.canvas.grid, &mut self.frames[frame]);
+        self.frame = frame;
+
+        // Frames might have been created before the last resize.
+        self.fit_canvas();
+        self.record_canvas();
+        self.text_cursor = None;
+
+        self.redraw(terminal);
+        self.notify(&format!("Showing \x1b[32m{}", self.frame_name()));
+    }
+
+    /// Human-readable position of the active frame.
+    fn frame_name(&self) -> String {
+        format!("frame {}/{}", self.frame + 1, self.frames.len())
+    }
+
+    /// Open the dialog listing all open buffers.
+    fn open_buffer_list_dialog(&mut self, terminal: &mut Terminal) {
+        let names = (0..self.buffers.len()).map(|i| self.buffer_name(i)).collect();
+        let dialog = BufferListDialog::new(names, self.buffer);
+        dialog.render(terminal);
+
+        self.mode = SketchMode::BufferListDialog(dialog);
+    }
+
+    /// Open a new empty buffer after the active one.
+    fn new_buffer(&mut self, terminal: &mut Terminal) {
+        self.buffers.insert(self.buffer + 1, Buffer::new(self.undo_depth()));
+        self.switch_buffer(terminal, self.buffer + 1);
+    }
+
+    /// Close the active buffer and switch to the one following it.
+    ///
+    /// Buffers with unsaved changes are never closed.
+    fn close_buffer(&mut self, terminal: &mut Terminal) {
+        self.wait_for_export();
+
+        if self.buffers.len() == 1 {
+            self.notify("Cannot close the \x1b[32monly buffer");
+            return;
+        } else if self.modified {
+            self.notify("Cannot close buffer with \x1b[31munsaved changes");
+            return;
+        }
+
+        self.buffers.remove(self.buffer);
+        self.buffer = min(self.buffer, self.buffers.len() - 1);
+        let buffer = mem::take(&mut self.buffers[self.buffer]);
+        self.restore_buffer(buffer);
+
+        self.fit_canvas();
+        self.record_canvas();
+        self.text_cursor = None;
+        self.redraw(terminal);
+        self.notify(&format!("Closed buffer, showing {}", self.buffer_position()));
+    }
+
+    /// Switch to another buffer.
+    fn switch_buffer(&mut self, terminal: &mut Terminal, index: usize) {
+        if index == self.buffer {
+            return;
+        }
+
+        // Attribute the running export to the buffer it was started in.
+        self.wait_for_export();
+
+        self.buffers[self.buffer] = self.take_buffer();
+        let buffer = mem::take(&mut self.buffers[index]);
+        self.restore_buffer(buffer);
+        self.buffer = index;
+
+        // Buffers might have been created before the last resize.
+        self.fit_canvas();
+        self.record_canvas();
+        self.text_cursor = None;
+
+        self.redraw(terminal);
+        self.notify(&format!("Showing {}", self.buffer_position()));
+    }
+
+    /// Switch to another buffer, relative to the active one.
+    ///
+    /// Navigation wraps around at the first and last buffer.
+    fn cycle_buffer(&mut self, terminal: &mut Terminal, offset: isize) {
+        let index = (self.buffer as isize + offset).rem_euclid(self.buffers.len() as isize);
+        self.switch_buffer(terminal, index as usize);
+    }
+
+    /// Move the active buffer's state out of the sketch.
+    fn take_buffer(&mut self) -> Buffer {
+        Buffer {
+            content: mem::take(&mut self.canvas.grid),
+            frames: mem::replace(&mut self.frames, vec![Grid::default()]),
+            frame: mem::take(&mut self.frame),
+            output: self.options.output.take(),
+            output_modified: mem::take(&mut self.output_modified),
+            modified: mem::take(&mut self.modified),
+            saved: self.saved.take(),
+        }
+    }
+
+    /// Make a buffer's state the active one.
+    fn restore_buffer(&mut self, buffer: Buffer) {
+        self.canvas.grid = buffer.content;
+        self.frames = buffer.frames;
+        self.frame = buffer.frame;
+        self.options.output = buffer.output;
+        self.output_modified = buffer.output_modified;
+        self.modified = buffer.modified;
+        self.saved = buffer.saved;
+    }
+
+    /// Human-readable position and name of the active buffer.
+    fn buffer_position(&self) -> String {
+        let (index, count) = (self.buffer + 1, self.buffers.len());
+        format!("\x1b[32mbuffer {index}/{count}\x1b[39m ({})", self.buffer_name(self.buffer))
+    }
+
+    /// Human-readable name of a buffer, with its unsaved changes marker.
+    fn buffer_name(&self, index: usize) -> String {
+        let (output, modified) = match index == self.buffer {
+            true => (self.options.output.as_ref(), self.modified),
+            false => (self.buffers[index].output.as_ref(), self.buffers[index].modified),
+        };
+
+        let mut name = match output.and_then(|path| path.file_name()) {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => String::from("untitled"),
+        };
+        if modified {
+            name.push_str(" [+]");
+        }
+
+        name
+    }
+
+    /// Render all frames for exporting them as animation.
+    ///
+    /// Lines which are empty in every frame are removed.
+    fn render_frames(&self) -> Vec<String> {
+        let frames: Vec<_> = (0..self.frames.len())
+            .map(|i| if i == self.frame { &self.canvas.grid } else { &self.frames[i] })
+            .collect();
+
+        // Find the lines with content in any frame.
+        let is_empty = |line: &Vec<Cell>| line.iter().all(Cell::is_empty);
+        let first_line = frames.iter().filter_map(|frame| frame.iter().position(|l| !is_empty(l)));
+        let last_line = frames.iter().filter_map(|frame| frame.iter().rposition(|l| !is_empty(l)));
+        let (Some(first_line), Some(last_line)) = (first_line.min(), last_line.max()) else {
+            return Vec::new();
+        };
+
+        let encoding = self.options.sgr_encoding();
+        let columns = frames.iter().filter_map(|frame| frame.first()).map(Vec::len).max();
+        let viewport = Viewport {
+            line_offset: first_line,
+            lines: last_line + 1 - first_line,
+            columns: columns.unwrap_or_default(),
+            ..Viewport::default()
+        };
+        frames
+            .iter()
+            .map(|frame| {
+                frame.render(&viewport, encoding.separator, |color| encoding.map_color(color))
+            })
+            .collect()
+    }
+
+    /// Open the dialog for toggling text styles.
+    fn open_style_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = StyleDialog::new(self.canvas.brush.style);
+        dialog.render(terminal);
+
+        self.mode = SketchMode::StyleDialog(dialog);
+    }
+
+    /// Cycle through brush shapes.
+    fn cycle_brush_shape(&mut self) {
+        self.canvas.brush.shape = self.canvas.brush.shape.next();
+        self.canvas.brush.template =
+            Brush::create_template(self.canvas.brush.shape, self.canvas.brush.size);
+        self.canvas.brush.stamp = None;
+
+        self.notify(&format!("Changed brush shape to \x1b[32m{}", self.canvas.brush.shape.name()));
+    }
+
+    /// Cycle through the axes brush strokes are mirrored across.
+    fn cycle_symmetry(&mut self) {
+        self.canvas.symmetry = self.canvas.symmetry.next();
+        self.notify(&format!("Changed symmetry to \x1b[32m{}", self.canvas.symmetry.name()));
+    }
+
+    /// Toggle automatic foreground contrast adjustment.
+    fn toggle_auto_contrast(&mut self) {
+        self.canvas.brush.auto_contrast = !self.canvas.brush.auto_contrast;
+
+        let state = if self.canvas.brush.auto_contrast { "on" } else { "off" };
+        self.notify(&format!("Turned automatic foreground contrast \x1b[32m{}", state));
+    }
+
+    /// Toggle border repair when erasing.
+    fn toggle_smart_erase(&mut self) {
+        self.canvas.smart_erase = !self.canvas.smart_erase;
+
+        let state = if self.canvas.smart_erase { "on" } else { "off" };
+        self.notify(&format!("Turned border repair when erasing \x1b[32m{}", state));
+    }
+
+    /// Toggle replacing rough freehand strokes with clean lines and boxes.
+    fn toggle_beautify(&mut self) {
+        self.beautify = !self.beautify;
+
+        let state = if self.beautify { "on" } else { "off" };
+        self.notify(&format!("Turned shape recognition \x1b[32m{}", state));
+    }
+
+    /// Toggle emphasis for lines and boxes.
+    fn toggle_emphasis(&mut self) {
+        self.canvas.brush.emphasis = !self.canvas.brush.emphasis;
+
+        let state = if self.canvas.brush.emphasis { "on" } else { "off" };
+        self.notify(&format!("Turned stroke emphasis \x1b[32m{}", state));
+    }
+
+    /// Start picking a region to flood fill.
+    fn start_fill(&mut self) {
+        self.mode = SketchMode::Tool(Box::new(FillTool));
+        self.notify("Click a region to \x1b[32mfill\x1b[39m it, ESC to cancel");
+    }
+
+    /// Flood-fill from cursor position.
+    fn fill(&mut self) {
+        self.canvas.fill(self.canvas.brush.position);
+        self.finish_write(WriteMode::Write);
+    }
+
+    /// Load sketch into canvas.
+    fn load(&mut self, terminal: &mut Terminal, sketch: &str, center_grid: bool) {
+        let origin = self.canvas.brush.position;
+        for (line_offset, line) in SketchParser::parse(sketch).iter().enumerate() {
+            for (column_offset, cell) in line.iter().enumerate() {
+                let point = Point {
+                    column: origin.column + column_offset,
+                    line: origin.line + line_offset,
+                };
+
+                // Text files cannot distinguish blank cells from transparent ones.
+                match cell.is_empty() {
+                    true => self.clear_cell(point),
+                    false => _ = self.write_cell(point, cell, true),
+                }
+            }
+        }
+
+        // Center grid after import from CLI.
+        if center_grid {
+            self.center(terminal);
+        }
+
+        self.bump_revision();
+    }
+
+    /// Import a sketch or restore a session from a file.
+    ///
+    /// Sketches imported at startup are centered on the canvas, otherwise they
+    /// are attached to the cursor for placement.
+    fn open(&mut self, terminal: &mut Terminal, path: &Path, startup: bool) -> io::Result<()> {
+        let sketch = fs::read_to_string(path)?;
+
+        if session::is_session(path) {
+            let session = session::parse(&sketch)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid session"))?;
+            self.restore(terminal, session);
+        } else if startup {
+            self.load(terminal, &sketch, true);
+        } else {
+            self.start_import(terminal, SketchParser::parse(&sketch));
+        }
+
+        Ok(())
+    }
+
+    /// Load a sketch as tracing layer.
+    fn open_trace(&mut self, terminal: &mut Terminal, path: &Path) -> io::Result<()> {
+        let sketch = fs::read_to_string(path)?;
+        self.trace = Some(SketchParser::parse(&sketch));
+
+        self.close_dialog(terminal);
+        self.notify("Loaded \x1b[32mtracing layer\x1b[39m, ALT + O to discard");
+
+        Ok(())
+    }
+
+    /// Load a tracing layer, or discard the active one.
+    fn toggle_trace(&mut self, terminal: &mut Terminal) {
+        if self.trace.take().is_none() {
+            self.mode = SketchMode::OpenDialog(OpenDialog::tracing());
+        }
+
+        self.redraw(terminal);
+    }
+
+    /// Render the tracing layer into empty cells.
+    fn render_trace(&mut self) {
+        let trace = self.trace.take();
+        if let Some(trace) = &trace {
+            self.render_dim_layer(trace);
+        }
+        self.trace = trace;
+    }
+
+    /// Load a sketch into the split view.
+    fn open_split(&mut self, terminal: &mut Terminal, path: &Path) -> io::Result<()> {
+        let sketch = fs::read_to_string(path)?;
+        let name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy().into_owned();
+        self.show_split(terminal, SketchParser::parse(&sketch), name);
+
+        self.close_dialog(terminal);
+
+        Ok(())
+    }
+
+    /// Show a snapshot of a buffer in the split view.
+    fn split_buffer(&mut self, terminal: &mut Terminal, index: usize) {
+        let cells = match index == self.buffer {
+            true => self.canvas.grid.cells.clone(),
+            false => self.buffers[index].content.cells.clone(),
+        };
+        self.show_split(terminal, cells, self.buffer_name(index));
+    }
+
+    /// Show a read-only sketch next to the canvas.
+    fn show_split(&mut self, terminal: &mut Terminal, cells: Vec<Vec<Cell>>, name: String) {
+        let share = self.split.as_ref().map_or(DEFAULT_SPLIT_SHARE, |split| split.share);
+        let content = Grid::new(cells, Journal::default());
+        self.split = Some(Split { content, name, share });
+
+        self.resize(terminal, terminal.dimensions());
+
+        let name = &self.split.as_ref().unwrap().name;
+        self.notify(&format!(
+            "Showing \x1b[32m{name}\x1b[39m in split view, ALT + SHIFT + S to close"
+        ));
     }
 
-    /// Set the grid's revision to a certain point in history.
-    fn set_revision(&mut self, terminal: &mut Terminal, revision: usize) {
-        // Only allow increasing to revisions that actually exist.
-        if revision > self.max_revision {
+    /// Load a sketch into the split view, or close the active one.
+    fn toggle_split(&mut self, terminal: &mut Terminal) {
+        if self.split.take().is_none() {
+            self.mode = SketchMode::OpenDialog(OpenDialog::split());
+            self.redraw(terminal);
             return;
         }
 
-        // Set grid state revision.
-        for line in self.content.iter_mut() {
-            for cell in line {
-                cell.set_revision(self.revision, revision);
+        self.resize(terminal, terminal.dimensions());
+    }
+
+    /// Grow or shrink the split view pane by a percentage of the terminal
+    /// width.
+    fn resize_split(&mut self, terminal: &mut Terminal, delta: isize) {
+        let Some(split) = &mut self.split else { return };
+        split.share = split
+            .share
+            .saturating_add_signed(delta)
+            .clamp(SPLIT_SHARE_STEP, 100 - SPLIT_SHARE_STEP);
+
+        self.resize(terminal, terminal.dimensions());
+    }
+
+    /// Terminal columns left for the canvas, next to the split view pane.
+    fn canvas_columns(&self, columns: usize) -> usize {
+        match &self.split {
+            Some(split) => columns.saturating_sub(columns * split.share / 100 + 1).max(1),
+            None => columns,
+        }
+    }
+
+    /// Render the split view pane right of the canvas.
+    ///
+    /// The pane is scrolled together with the canvas, to keep both aligned.
+    fn render_split(&self, terminal: &Terminal) {
+        let Some(split) = &self.split else { return };
+        let columns = terminal.dimensions().columns as usize;
+        let Some(pane_columns) = columns.checked_sub(self.viewport.columns + 1) else { return };
+
+        for line in 1..=self.viewport.lines {
+            Terminal::goto(self.viewport.columns + 1, line);
+            Terminal::set_dim();
+            Terminal::write(SPLIT_GLYPH);
+            Terminal::reset_sgr();
+
+            let viewport = Viewport {
+                line_offset: self.viewport.line_offset + line - 1,
+                columns: pane_columns,
+                lines: 1,
+                ..self.viewport
+            };
+            if viewport.line_offset < split.content.len() {
+                Terminal::write(split.content.render(&viewport, ':', Terminal::supported_color));
+                Terminal::reset_sgr();
+            }
+            Terminal::clear_line_end();
+        }
+    }
+
+    /// Render the previous animation frame into empty cells.
+    fn render_onion_skin(&mut self) {
+        let Some(index) = self.frame.checked_sub(1) else { return };
+
+        let previous = mem::take(&mut self.frames[index]);
+        self.render_dim_layer(&previous);
+        self.frames[index] = previous;
+    }
+
+    /// Render reference content dimmed into empty cells.
+    fn render_dim_layer(&mut self, layer: &[Vec<Cell>]) {
+        self.overlay.highlight = Highlight::Dim;
+        let (end_column, end_line) = self.viewport.end();
+        for line in self.viewport.line_offset + 1..=min(end_line, layer.len()) {
+            let cells = &layer[line - 1];
+            for column in self.viewport.column_offset + 1..=min(end_column, cells.len()) {
+                let (point, cell) = (Point { column, line }, &cells[column - 1]);
+                if !cell.is_empty()
+                    && self.canvas.grid.contains(point)
+                    && self.canvas.grid.get(point).transparent
+                {
+                    self.overlay.insert(point, cell, cell.c.width().unwrap_or(1));
+                }
             }
         }
-        self.revision = revision;
+        self.overlay.highlight = Highlight::None;
+    }
+
+    /// Attach an imported sketch to the cursor for placement.
+    fn start_import(&mut self, terminal: &mut Terminal, cells: Vec<Vec<Cell>>) {
+        self.close_dialog(terminal);
+
+        if cells.iter().flatten().all(Cell::is_empty) {
+            self.notify("Nothing to \x1b[32mimport");
+            return;
+        }
+
+        self.mode = SketchMode::Tool(Box::new(ImportTool::new(cells)));
+        self.notify("Click to place the \x1b[32mimported sketch");
+    }
+
+    /// Replace the canvas, brush, and undo history with a session.
+    fn restore(&mut self, terminal: &mut Terminal, session: Session) {
+        let position = self.canvas.brush.position;
+        self.canvas.brush = Brush { position, ..session.brush };
+
+        self.canvas.grid = session.content;
+        self.canvas.grid.journal.set_depth(self.undo_depth());
+        self.text_cursor = None;
+
+        // Ensure the restored canvas covers the viewport.
+        self.fit_canvas();
+        self.record_canvas();
 
-        // Render changes.
         self.redraw(terminal);
     }
 
-    /// Increment the current revision.
-    fn bump_revision(&mut self) {
-        // Ignore revision changes during bracketed paste.
-        if self.pasting {
+    /// Try to write the sketch to a file.
+    ///
+    /// Paths with the session extension store the full session including
+    /// undo history, asciicast paths store the recorded drawing process, and
+    /// shell scripts play back all frames. Everything else uses the exporter
+    /// for its extension.
+    fn persist(&mut self, path: &Path) -> io::Result<()> {
+        if session::is_session(path) {
+            fs::write(path, session::serialize(self))?;
+        } else if asciicast::is_asciicast(path) {
+            let asciicast = self.asciicast.as_ref().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Unsupported, "drawing process was not recorded")
+            })?;
+            fs::write(path, asciicast.serialize())?;
+        } else if animation::is_animation(path) {
+            fs::write(path, animation::script(&self.render_frames()))?;
+        } else {
+            self.canvas.grid.persist(path, self.options.export_settings())?;
+        }
+
+        // Keep the saved state around for comparing it to later changes.
+        self.saved = Some(Grid::new(self.canvas.grid.cells.clone(), Journal::default()));
+        self.modified = false;
+
+        Ok(())
+    }
+
+    /// Terminal window title, marking unsaved changes.
+    fn title(&self) -> String {
+        let path = self.options.output.as_ref().or(self.options.file.as_ref());
+        let mut title = match path.and_then(|path| path.file_name()) {
+            Some(name) => format!("sketch — {}", name.to_string_lossy()),
+            None => String::from("sketch"),
+        };
+
+        if self.modified {
+            title.push_str(" [+]");
+        }
+
+        title
+    }
+
+    /// Flip between the current canvas and its state as of the last save.
+    fn toggle_saved_view(&mut self, terminal: &mut Terminal) {
+        if let SketchMode::SavedView = self.mode {
+            self.mode = SketchMode::Sketching;
+            self.notify("Showing the \x1b[32mcurrent canvas");
+            self.redraw(terminal);
             return;
         }
 
-        // Clear redo history.
-        self.clear_history(self.revision);
+        let saved = match &mut self.saved {
+            Some(saved) => saved,
+            None => {
+                self.notify("Nothing was \x1b[32msaved\x1b[39m yet");
+                return;
+            },
+        };
+
+        // Cover the entire screen, even if the canvas grew since the save.
+        saved.grow(self.canvas.grid.first().map_or(0, Vec::len), self.canvas.grid.len());
+
+        self.mode = SketchMode::SavedView;
+        self.notify("Showing the \x1b[32mlast save\x1b[39m, ALT + V to return");
+        self.redraw(terminal);
+    }
 
-        // Bump the current revision.
-        self.revision += 1;
-        self.max_revision = self.revision;
+    /// Grid shown on the screen.
+    ///
+    /// This is the canvas, unless the last save is being viewed.
+    fn displayed_grid(&self) -> &Grid {
+        match (&self.mode, &self.saved) {
+            (SketchMode::SavedView, Some(saved)) => saved,
+            _ => &self.canvas.grid,
+        }
     }
 
-    /// Drop all revisions after `revision`.
-    fn clear_history(&mut self, revision: usize) {
-        // Remove redo history from all cells.
-        for line in self.content.iter_mut() {
-            for cell in line {
-                cell.clear_history(revision);
-            }
-        }
+    /// Check if a path is written by one of the exporters.
+    fn is_export(&self, path: &Path) -> bool {
+        !session::is_session(path)
+            && !asciicast::is_asciicast(path)
+            && !animation::is_animation(path)
+    }
 
-        // Limit redo history to new revision.
-        self.max_revision = revision;
+    /// Start exporting the sketch to a file in the background.
+    ///
+    /// Stripes of the canvas are rendered on multiple threads, while a
+    /// notification shows the progress until the export is done or cancelled.
+    fn export_in_background(&mut self, path: PathBuf) {
+        let exporter = export::for_path(&path, self.options.export_settings());
+        let format = exporter.name();
+        let canvas = Grid::new(self.canvas.grid.cells.clone(), Journal::default());
+
+        let target = path.clone();
+        let job = Job::spawn(move |progress| {
+            // Render everything before touching the file, so cancelling keeps it intact.
+            let mut output = Vec::new();
+            let update = |done, total| progress.update(done, total);
+            export::export_parallel(
+                &*exporter,
+                &canvas,
+                &mut output,
+                &update,
+                progress.cancelled(),
+            )?;
+            fs::write(&target, output)?;
+            Ok(canvas)
+        });
+
+        // Changes made during the export mark the canvas as modified again.
+        self.modified = false;
+
+        self.export = Some(Export { job, path, format, percent: 0 });
+        self.notify_export_progress();
     }
 
-    /// Toggle through text styles.
-    fn toggle_text_style(&mut self) {
-        // Switch to the next style.
-        let new_bits = (self.brush.style.bits() + 1) % (TextStyle::all().bits() + 1);
-        self.brush.style = TextStyle::from_bits(new_bits).unwrap();
+    /// Show the progress of the running export.
+    fn notify_export_progress(&mut self) {
+        let Some(export) = &self.export else { return };
+        let message = format!(
+            "Exporting {} \x1b[32m{}%\x1b[39m (Esc to cancel)",
+            export.format, export.percent
+        );
+        self.notify(&message);
+    }
 
-        // Print a helpful little message.
-        Terminal::reset_sgr();
-        Terminal::goto(0, usize::MAX);
-        Terminal::write(format!("Changed text style to \x1b[32m{}", self.brush.style.name()));
+    /// Update the progress of the running export, finishing it once it is done.
+    fn poll_export(&mut self) {
+        let Some(export) = &mut self.export else { return };
+        match export.job.poll() {
+            Some(result) => {
+                let export = self.export.take().unwrap();
+                self.finish_export(export, result);
+            },
+            None if export.job.percent() != export.percent => {
+                export.percent = export.job.percent();
+                self.notify_export_progress();
+            },
+            None => (),
+        }
     }
 
-    /// Flood-fill from cursor position.
-    fn fill(&mut self) {
-        // Use cell under the brush as template for filling.
-        let template = self.content.get(self.brush.position);
-        let template =
-            Cell::new(template.c, template.foreground, template.background, template.style);
-
-        // Setup the initial fill queue ranges.
-        let Point { mut column, line } = self.brush.position;
-        self.fill_queue.clear();
-        self.fill_queue.push_back((column, column, line, 1));
-        self.fill_queue.push_back((column, column, line - 1, -1));
-
-        while let Some((mut start_column, end_column, line, line_delta)) =
-            self.fill_queue.pop_front()
-        {
-            // Fill all empty cells to the left of this range's start.
-            column = start_column;
-            if self.content.cell_matches(column, line, &template) {
-                // Fill empty cells until we've reached a boundary on the left.
-                while self.content.cell_matches(column - 1, line, &template) {
-                    self.write(Point { line, column: column - 1 }, self.brush.glyph, true);
-                    column -= 1;
-                }
+    /// Block until the running export is done.
+    fn wait_for_export(&mut self) {
+        if let Some(export) = self.export.take() {
+            let result = export.job.wait();
+            self.finish_export(export, result);
+        }
+    }
 
-                // Add new queue element to search in the opposite vertical direction if our
-                // horizontal range expanded to the left.
-                if column < start_column {
-                    let next_line = (line as isize - line_delta) as usize;
-                    self.fill_queue.push_back((column, start_column - 1, next_line, -line_delta));
-                }
-            }
+    /// Ask the running export to stop early.
+    fn cancel_export(&mut self) {
+        if let Some(export) = &self.export {
+            export.job.cancel();
+            self.notify("Cancelling export…");
+        }
+    }
 
-            // Fill all empty cells to the right of this range's start.
-            while start_column <= end_column {
-                // Fill empty cells until we've reached a boundary on the right.
-                while self.content.cell_matches(start_column, line, &template) {
-                    self.write(Point { line, column: start_column }, self.brush.glyph, true);
-                    start_column += 1;
+    /// Report the result of a background export.
+    fn finish_export(&mut self, export: Export, result: io::Result<Grid>) {
+        match result {
+            Ok(canvas) => {
+                self.saved = Some(canvas);
+                self.options.output = Some(export.path);
+                self.output_modified = true;
+                self.notify(&format!("Saved sketch as \x1b[32m{}", export.format));
+            },
+            Err(err) => {
+                self.modified = true;
+                match err.kind() {
+                    io::ErrorKind::Interrupted => self.notify("Export \x1b[31mcancelled"),
+                    _ => self.notify(&format!("Export failed: \x1b[31m{err}")),
                 }
+            },
+        }
+    }
 
-                // If the range isn't empty, search it again on the next line.
-                if start_column > column {
-                    let next_line = (line as isize + line_delta) as usize;
-                    self.fill_queue.push_back((column, start_column - 1, next_line, line_delta));
-                }
+    /// Compare the sketch against the expected output stored in a file.
+    fn assert_output(&self, path: &Path) -> io::Result<()> {
+        let expected = fs::read_to_string(path)?;
+        let text = self.canvas.grid.trimmed_text(self.options.sgr_encoding());
+        if text == expected {
+            return Ok(());
+        }
 
-                // Add new queue element to search in the opposite vertical direction if our
-                // horizontal range expanded to the right.
-                if start_column - 1 > end_column {
-                    let next_line = (line as isize - line_delta) as usize;
-                    let start = end_column + 1;
-                    let end = start_column - 1;
-                    self.fill_queue.push_back((start, end, next_line, -line_delta));
-                }
+        // Report the first line which doesn't match.
+        let mut lines = text.split('\n').zip(expected.split('\n'));
+        let line = lines
+            .position(|(line, expected)| line != expected)
+            .unwrap_or_else(|| min(text.split('\n').count(), expected.split('\n').count()));
 
-                // Skip over occupied cells if we're not yet at the right boundary of the range.
-                start_column += 1;
-                while start_column < end_column
-                    && !self.content.cell_matches(start_column, line, &template)
-                {
-                    start_column += 1;
-                }
+        let message = format!("output differs from {} in line {}", path.display(), line + 1);
+        Err(io::Error::new(io::ErrorKind::InvalidData, message))
+    }
 
-                // Update right boundary of the last added range.
-                column = start_column;
-            }
+    /// Name of the format used for persisting to a path.
+    fn format_name(&self, path: &Path) -> &'static str {
+        if session::is_session(path) {
+            "session"
+        } else if asciicast::is_asciicast(path) {
+            "asciicast recording"
+        } else if animation::is_animation(path) {
+            "animation script"
+        } else {
+            export::for_path(path, self.options.export_settings()).name()
         }
+    }
 
+    /// Move all content by an offset, as a single undoable revision.
+    fn shift_content(&mut self, terminal: &mut Terminal, columns: isize, lines: isize) {
+        let lost = self.canvas.grid.shift(columns, lines);
         self.bump_revision();
+        self.update(terminal);
+
+        if lost > 0 {
+            self.notify("Discarded content shifted past the \x1b[31mcanvas edge");
+        }
     }
 
-    /// Load sketch into canvas.
-    fn load(&mut self, terminal: &mut Terminal, sketch: &str, center_grid: bool) {
-        let origin = self.brush.position;
-        let mut sketch_parser = SketchParser::new(self, origin);
-        let mut parser = Parser::new();
+    /// Crop the canvas to the sketch, surrounded by `padding` empty cells.
+    ///
+    /// The view is moved to the top left corner of the cropped canvas. Fixed
+    /// size canvases keep the cropped size.
+    fn crop(&mut self, terminal: &mut Terminal, padding: usize) {
+        let Some((start, end)) = self.canvas.grid.bounding_box() else {
+            self.notify("Nothing to crop, the canvas is \x1b[31mempty");
+            return;
+        };
 
-        for byte in sketch.as_bytes() {
-            parser.advance(&mut sketch_parser, *byte);
-        }
+        self.canvas.grid.crop(start, end, padding);
 
-        // Center grid after import from CLI.
-        if center_grid {
-            self.center(terminal);
+        let columns = self.canvas.grid.first().map_or(0, Vec::len);
+        let lines = self.canvas.grid.len();
+        if self.options.width.is_some() {
+            self.options.width = Some(columns);
+        }
+        if self.options.height.is_some() {
+            self.options.height = Some(lines);
         }
 
-        self.bump_revision();
+        self.viewport.column_offset = 0;
+        self.viewport.line_offset = 0;
+        self.fit_canvas();
+
+        self.notify(&format!("Cropped canvas to \x1b[32m{columns}x{lines}"));
+        self.redraw(terminal);
     }
 
     /// Center the current sketch within the grid.
     fn center(&mut self, terminal: &mut Terminal) {
-        let mut min_start_index = usize::MAX;
-        let mut max_end_index = usize::MIN;
-        let mut first_line = usize::MAX;
-        let mut last_line = usize::MIN;
-
-        // Find boundaries of the sketch.
-        for (line, line_content) in self.content.iter().enumerate() {
-            let mut first_changed = usize::MAX;
-            let mut last_changed = usize::MIN;
-
-            // Find boundaries for each line.
-            for (column, cell) in line_content.iter().enumerate() {
-                if !cell.is_empty() {
-                    if first_changed == usize::MAX {
-                        first_changed = column;
-                    }
-                    last_changed = column;
-                }
-            }
-
-            // Update global column limits.
-            min_start_index = min(first_changed, min_start_index);
-            max_end_index = max(last_changed, max_end_index);
+        // Skip centering if entire grid was empty.
+        let Some((columns, lines)) = self.center_offset(CenterAxes::Both) else { return };
 
-            // Update line limits
-            if first_changed != usize::MAX {
-                if first_line == usize::MAX {
-                    first_line = line;
-                }
-                last_line = line;
-            }
-        }
+        self.canvas.grid.translate(columns, lines);
 
-        // Skip centering if entire grid was empty.
-        if first_line == usize::MAX {
-            return;
-        }
+        self.redraw(terminal);
+    }
 
-        // Center sketch horizontally.
-        let sketch_width = max_end_index - min_start_index + 1;
-        let padding_columns = (self.content[0].len() - sketch_width) / 2;
-        for i in first_line..=last_line {
-            match padding_columns.cmp(&min_start_index) {
-                Ordering::Greater => {
-                    self.content[i].rotate_right(padding_columns - min_start_index)
-                },
-                Ordering::Less => self.content[i].rotate_left(min_start_index - padding_columns),
-                Ordering::Equal => (),
-            }
+    /// Center the sketch along some axes, as a single undoable revision.
+    fn center_content(&mut self, terminal: &mut Terminal, axes: CenterAxes) {
+        match self.center_offset(axes) {
+            Some((0, 0)) => (),
+            Some((columns, lines)) => self.shift_content(terminal, columns, lines),
+            None => self.notify("Nothing to center, the canvas is \x1b[31mempty"),
         }
+    }
 
-        // Center sketch vertically.
-        let sketch_height = last_line - first_line;
-        let padding_lines = (self.content.len() - sketch_height) / 2;
-        match padding_lines.cmp(&first_line) {
-            Ordering::Greater => self.content.rotate_right(padding_lines - first_line),
-            Ordering::Less => self.content.rotate_left(first_line - padding_lines),
-            Ordering::Equal => (),
-        }
+    /// Offset moving the sketch to the center of the canvas along some axes.
+    ///
+    /// Returns `None` if the canvas is empty.
+    fn center_offset(&self, axes: CenterAxes) -> Option<(isize, isize)> {
+        let (start, end) = self.canvas.grid.bounding_box()?;
+
+        // Distribute the free space evenly before and after the sketch.
+        let offset = |start: usize, end: usize, size: usize| {
+            let padding = (size - (end + 1 - start)) / 2;
+            padding as isize - (start - 1) as isize
+        };
+        let columns = match axes {
+            CenterAxes::Vertical => 0,
+            _ => offset(start.column, end.column, self.canvas.grid.first().map_or(0, Vec::len)),
+        };
+        let lines = match axes {
+            CenterAxes::Horizontal => 0,
+            _ => offset(start.line, end.line, self.canvas.grid.len()),
+        };
 
-        self.redraw(terminal);
+        Some((columns, lines))
     }
 
-    /// Calculate the combination of two line drawing characters.
-    ///
-    /// If either character is not a line drawing character, the new
-    /// character will be returned.
-    #[rustfmt::skip]
-    fn combine_line_chars(&self, existing: char, new: char) -> char {
-        // For reference:
-        // '─', '│', '┌', '┐', '└', '┘', '├', '┤', '┬', '┴', '┼'
-
-        match (existing, new) {
-            ('│', '┌' | '└')
-                | ('┌', '└' | '│')
-                | ('└', '┌' | '│')
-                | ('├', '│' | '┌' | '└') => '├',
-
-            ('│', '┐' | '┘')
-                |('┐', '┘' | '│')
-                | ('┘', '┐' | '│')
-                | ('┤', '│' | '┐' | '┘') => '┤',
-
-            ('─', '┌' | '┐')
-                |('┌', '┐' | '─')
-                | ('┐', '┌' | '─')
-                | ('┬', '─' | '┌' | '┐') => '┬',
-
-            ('─', '└' | '┘')
-                |('└', '┘' | '─')
-                | ('┘', '└' | '─')
-                | ('┴', '─' | '└' | '┘') => '┴',
-
-            ('─', '│' | '├' | '┤')
-                | ('│', '─' | '┬' | '┴')
-                | ('┌', '┘' | '┤' | '┴')
-                | ('┐', '└' | '├' | '┴')
-                | ('└', '┐' | '┤' | '┬')
-                | ('┘', '┌' | '├' | '┬')
-                | ('├', '─' | '┐' | '┘' | '┤' | '┬' | '┴')
-                | ('┤', '─' | '┌' | '└' | '├' | '┬' | '┴')
-                | ('┬', '│' | '└' | '┘' | '├' | '┤' | '┴')
-                | ('┴', '│' | '┌' | '┐' | '├' | '┤' | '┬')
-                | ('┼', _) => '┼',
-
-            _ => new,
-        }
+    /// Open the dialog for centering the sketch on the canvas.
+    fn open_center_dialog(&mut self, terminal: &mut Terminal) {
+        let dialog = CenterDialog::new();
+        dialog.render(terminal);
+
+        self.mode = SketchMode::CenterDialog(dialog);
     }
 }
 
 impl EventHandler for Sketch {
     fn keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) {
         // Hide mouse brush while typing.
-        self.redraw(terminal);
+        self.update(terminal);
 
         match &mut self.mode {
             // Allow closing dialogs with Escape.
             SketchMode::BrushCharacterDialog(_)
+            | SketchMode::BrushDialog(_)
             | SketchMode::ColorpickerDialog(_)
             | SketchMode::SaveDialog(_)
             | SketchMode::OpenDialog(_)
             | SketchMode::HelpDialog(_)
+            | SketchMode::StampLibraryDialog(_)
+            | SketchMode::QuantizeDialog(_)
+            | SketchMode::PacketDialog(_)
+            | SketchMode::TableDialog(_)
+            | SketchMode::ChartDialog(_)
+            | SketchMode::TreeDialog(_)
+            | SketchMode::LintDialog(_)
+            | SketchMode::HistoryDialog(_)
+            | SketchMode::SnapshotDialog(_)
+            | SketchMode::SnapshotListDialog(_)
+            | SketchMode::JumpDialog(_)
+            | SketchMode::CenterDialog(_)
+            | SketchMode::SearchDialog(_)
+            | SketchMode::BufferListDialog(_)
+            | SketchMode::StyleDialog(_)
                 if glyph == '\x1b' =>
             {
                 self.close_dialog(terminal);
             },
+            #[cfg(feature = "image")]
+            SketchMode::ImageDialog(_) if glyph == '\x1b' => self.close_dialog(terminal),
+            #[cfg(feature = "image")]
+            SketchMode::ImageDialog(dialog) => match glyph {
+                '\n' => {
+                    let Some(width) = dialog.width() else { return };
+                    match halfblock::load(dialog.path(), width) {
+                        Ok(cells) => self.start_import(terminal, cells),
+                        Err(err) => {
+                            self.close_dialog(terminal);
+                            self.notify(&format!("Image import failed: \x1b[31m{err}"));
+                        },
+                    }
+                },
+                glyph => {
+                    let redraw_required = dialog.keyboard_input(terminal, glyph);
+                    if redraw_required {
+                        self.redraw(terminal);
+                    }
+                },
+            },
             SketchMode::BrushCharacterDialog(dialog) => match glyph {
                 '\n' => {
-                    self.brush.glyph = dialog.glyph();
+                    if let Some(pattern) = dialog.pattern() {
+                        self.canvas.brush.pattern = pattern;
+                        self.canvas.brush.stamp = None;
+                        self.close_dialog(terminal);
+                    }
+                },
+                glyph => {
+                    let redraw_required = dialog.keyboard_input(terminal, glyph);
+                    if redraw_required {
+                        self.redraw(terminal);
+                    }
+                },
+            },
+            SketchMode::BrushDialog(dialog) => match glyph {
+                '\n' => {
+                    if let (Some(size), Some(pattern)) = (dialog.size(), dialog.pattern()) {
+                        self.canvas.brush.size = size;
+                        self.canvas.brush.shape = dialog.shape();
+                        self.canvas.brush.template =
+                            Brush::create_template(self.canvas.brush.shape, size);
+                        self.canvas.brush.pattern = pattern;
+                        self.canvas.brush.stamp = None;
+                        self.close_dialog(terminal);
+                    }
+                },
+                glyph => {
+                    let redraw_required = dialog.keyboard_input(terminal, glyph);
+                    if redraw_required {
+                        self.redraw(terminal);
+                    }
+                },
+            },
+            SketchMode::StyleDialog(dialog) => match glyph {
+                '\n' => {
+                    self.canvas.brush.style = dialog.style();
                     self.close_dialog(terminal);
+                    self.notify(&format!(
+                        "Changed text style to \x1b[32m{}",
+                        self.canvas.brush.style.name()
+                    ));
                 },
                 glyph => dialog.keyboard_input(terminal, glyph),
             },
             SketchMode::ColorpickerDialog(dialog) => match glyph {
                 // Reset to default color on ^E.
                 '\x05' => {
-                    self.brush.set_color(dialog.color_position(), Color::default());
+                    self.canvas.brush.set_color(dialog.color_position(), Color::default());
                     self.close_dialog(terminal);
                 },
                 '\n' => {
-                    self.brush.set_color(dialog.color_position(), dialog.color());
+                    self.canvas.brush.set_color(dialog.color_position(), dialog.color());
                     self.close_dialog(terminal);
                 },
                 glyph => dialog.keyboard_input(terminal, glyph),
             },
             SketchMode::SaveDialog(dialog) => match glyph {
+                '\n' | 'y' if dialog.has_question() => {
+                    dialog.keyboard_input(terminal, glyph);
+                    self.redraw(terminal);
+
+                    // Continue saving once the question is answered.
+                    if glyph == 'y' {
+                        self.keyboard_input(terminal, '\n');
+                    }
+                },
                 '\n' => {
                     let should_shutdown = dialog.shutdown_on_save();
 
@@ -807,18 +3337,50 @@ impl EventHandler for Sketch {
                         },
                     };
 
-                    // Attempt to persist the path.
-                    match self.content.persist(&path) {
+                    // Ask before creating directories or replacing files other than the output.
+                    let parent = path.parent().filter(|parent| !parent.as_os_str().is_empty());
+                    let missing_parent = parent.filter(|parent| !parent.exists());
+                    let question = if missing_parent.is_some() {
+                        Some(Confirmation::CreateDirectory)
+                    } else if path.exists() && self.options.output.as_ref() != Some(&path) {
+                        Some(Confirmation::Overwrite)
+                    } else {
+                        None
+                    };
+                    if let Some(question) = question.filter(|q| !dialog.is_confirmed(*q)) {
+                        dialog.ask(terminal, question);
+                        return;
+                    }
+
+                    // Attempt to create the directories and persist the path.
+                    let created = missing_parent.map_or(Ok(()), fs::create_dir_all);
+
+                    // Keep the interface responsive while exporting large canvases.
+                    if !should_shutdown && created.is_ok() && self.is_export(&path) {
+                        self.wait_for_export();
+                        self.close_dialog(terminal);
+                        self.export_in_background(path);
+                        return;
+                    }
+
+                    match created.and_then(|_| self.persist(&path)) {
                         Ok(()) if should_shutdown => {
+                            self.options.output = Some(path);
                             self.persisted = true;
                             terminal.shutdown();
                         },
                         Ok(()) => {
+                            let format = self.format_name(&path);
                             self.options.output = Some(path);
                             self.output_modified = true;
                             self.close_dialog(terminal);
+                            self.notify(&format!("Saved sketch as \x1b[32m{format}"));
+                        },
+                        Err(_) => {
+                            if let SketchMode::SaveDialog(dialog) = &mut self.mode {
+                                dialog.mark_failed(terminal);
+                            }
                         },
-                        Err(_) => dialog.mark_failed(terminal),
                     }
                 },
                 glyph => {
@@ -839,19 +3401,28 @@ impl EventHandler for Sketch {
                         },
                     };
 
-                    // Ensure we can read the sketch.
-                    let sketch = match fs::read_to_string(path) {
-                        Ok(sketch) => sketch,
-                        Err(_) => {
-                            dialog.mark_failed(terminal);
-                            return;
-                        },
-                    };
+                    // Ask for the size of images before importing them.
+                    #[cfg(feature = "image")]
+                    if dialog.target() == OpenTarget::Canvas && halfblock::is_image(&path) {
+                        self.open_image_dialog(terminal, &path);
+                        return;
+                    }
 
                     // Load sketch into canvas.
-                    self.load(terminal, &sketch, false);
-
-                    self.close_dialog(terminal);
+                    let result = match dialog.target() {
+                        OpenTarget::Canvas => self.open(terminal, &path, false),
+                        OpenTarget::Tracing => self.open_trace(terminal, &path),
+                        OpenTarget::Split => self.open_split(terminal, &path),
+                    };
+                    match result {
+                        Ok(()) if self.has_dialog() => self.close_dialog(terminal),
+                        Ok(()) => (),
+                        Err(_) => {
+                            if let SketchMode::OpenDialog(dialog) = &mut self.mode {
+                                dialog.mark_failed(terminal);
+                            }
+                        },
+                    }
                 },
                 glyph => {
                     let redraw_required = dialog.keyboard_input(terminal, glyph);
@@ -861,21 +3432,202 @@ impl EventHandler for Sketch {
                 },
             },
             SketchMode::HelpDialog(_) if glyph == '\n' => self.close_dialog(terminal),
-            // Cancel box/line drawing on escape.
-            SketchMode::LineDrawing(..) if glyph == '\x1b' => self.mode = SketchMode::Sketching,
+            SketchMode::QuantizeDialog(dialog) => match glyph {
+                '\t' => {
+                    dialog.next_palette();
+                    self.redraw(terminal);
+                },
+                '\n' => {
+                    let palette = dialog.palette();
+                    self.quantize(palette);
+                    self.close_dialog(terminal);
+                },
+                _ => (),
+            },
+            SketchMode::PacketDialog(dialog) => match glyph {
+                '\n' => match dialog.fields() {
+                    Some(fields) => {
+                        self.load_stamp(&packet::diagram(&fields).join("\n"));
+                        self.close_dialog(terminal);
+                    },
+                    None => dialog.mark_failed(terminal),
+                },
+                glyph => {
+                    let redraw_required = dialog.keyboard_input(terminal, glyph);
+                    if redraw_required {
+                        self.redraw(terminal);
+                    }
+                },
+            },
+            SketchMode::TableDialog(dialog) => match glyph {
+                '\n' => match dialog.size() {
+                    Some((rows, columns)) => {
+                        self.close_dialog(terminal);
+                        self.mode = SketchMode::Tool(Box::new(TableTool::new(rows, columns)));
+                        self.notify(&format!("Drag to draw a \x1b[32m{rows}x{columns} table"));
+                    },
+                    None => dialog.mark_failed(terminal),
+                },
+                glyph => {
+                    let redraw_required = dialog.keyboard_input(terminal, glyph);
+                    if redraw_required {
+                        self.redraw(terminal);
+                    }
+                },
+            },
+            SketchMode::ChartDialog(dialog) => match glyph {
+                '\n' => match dialog.values() {
+                    Some(values) => {
+                        let kind = dialog.kind();
+                        self.close_dialog(terminal);
+                        self.mode = SketchMode::Tool(Box::new(ChartTool::new(values, kind)));
+                        self.notify(&format!("Drag to draw a \x1b[32m{}", kind.name()));
+                    },
+                    None => dialog.mark_failed(terminal),
+                },
+                glyph => {
+                    let redraw_required = dialog.keyboard_input(terminal, glyph);
+                    if redraw_required {
+                        self.redraw(terminal);
+                    }
+                },
+            },
+            SketchMode::TreeDialog(dialog) => match glyph {
+                '\n' if dialog.line_empty() => match dialog.items() {
+                    Some(items) => {
+                        self.load_stamp(&tree::diagram(&items).join("\n"));
+                        self.close_dialog(terminal);
+                    },
+                    None => dialog.mark_failed(terminal),
+                },
+                glyph => {
+                    let redraw_required = dialog.keyboard_input(terminal, glyph);
+                    if redraw_required {
+                        self.redraw(terminal);
+                    }
+                },
+            },
+            SketchMode::HistoryDialog(dialog) => match glyph {
+                '\n' => match dialog.revisions() {
+                    Some(revisions) => {
+                        self.compact_history(revisions);
+                        self.close_dialog(terminal);
+                        self.notify(&format!("Compacted undo history, {}", self.history_summary()));
+                    },
+                    None => dialog.mark_failed(terminal),
+                },
+                glyph => dialog.keyboard_input(terminal, glyph),
+            },
+            SketchMode::SnapshotDialog(dialog) => match glyph {
+                '\n' => {
+                    if let Some(name) = dialog.name() {
+                        let name = name.to_owned();
+                        self.close_dialog(terminal);
+                        self.save_snapshot(name);
+                    }
+                },
+                glyph => {
+                    let redraw_required = dialog.keyboard_input(terminal, glyph);
+                    if redraw_required {
+                        self.redraw(terminal);
+                    }
+                },
+            },
+            SketchMode::SnapshotListDialog(dialog) => {
+                if let Some(name) = dialog.snapshot(glyph) {
+                    let name = name.to_owned();
+                    self.close_dialog(terminal);
+                    self.restore_snapshot(terminal, &name);
+                }
+            },
+            SketchMode::SearchDialog(dialog) => match glyph {
+                '\n' => {
+                    let query = dialog.query().to_owned();
+                    self.search = (!query.is_empty()).then_some(query);
+                    self.close_dialog(terminal);
+                    self.search_next(terminal, true);
+                },
+                glyph => {
+                    let redraw_required = dialog.keyboard_input(terminal, glyph);
+                    if redraw_required {
+                        self.redraw(terminal);
+                    }
+                },
+            },
+            SketchMode::JumpDialog(dialog) => {
+                if let Some(target) = dialog.target(glyph) {
+                    self.close_dialog(terminal);
+                    self.jump_to(terminal, target);
+                }
+            },
+            SketchMode::CenterDialog(dialog) => {
+                if let Some(axes) = dialog.axes(glyph) {
+                    self.close_dialog(terminal);
+                    self.center_content(terminal, axes);
+                }
+            },
+            SketchMode::BufferListDialog(dialog) => match dialog.keyboard_input(terminal, glyph) {
+                Some(BufferAction::Switch(index)) => {
+                    self.close_dialog(terminal);
+                    self.switch_buffer(terminal, index);
+                },
+                Some(BufferAction::Split(index)) => {
+                    self.close_dialog(terminal);
+                    self.split_buffer(terminal, index);
+                },
+                Some(BufferAction::New) => {
+                    self.close_dialog(terminal);
+                    self.new_buffer(terminal);
+                },
+                Some(BufferAction::Close) => {
+                    self.close_dialog(terminal);
+                    self.close_buffer(terminal);
+                },
+                None => (),
+            },
+            SketchMode::LintDialog(dialog) => {
+                if let Some(point) = dialog.issue(glyph) {
+                    self.scroll_to(point);
+                    self.close_dialog(terminal);
+                } else if dialog.keyboard_input(glyph) {
+                    self.redraw(terminal);
+                }
+            },
+            SketchMode::StampLibraryDialog(dialog) => {
+                if let Some(stamp) = dialog.stamp(glyph) {
+                    self.load_stamp(stamp);
+                    self.close_dialog(terminal);
+                }
+            },
+            SketchMode::PathText(_) => self.path_text_input(terminal, glyph),
+            SketchMode::BannerText(..) => self.banner_text_input(glyph),
+            SketchMode::WidenPrompt(..) => self.widen_prompt_input(terminal, glyph),
+            SketchMode::SharePrompt => self.share_prompt_input(glyph),
+            SketchMode::SearchMatches => self.search_matches_input(terminal, glyph),
+            // Leave the read-only view of the last save on escape.
+            SketchMode::SavedView if glyph == '\x1b' => self.toggle_saved_view(terminal),
+            SketchMode::SavedView => (),
+            // Cancel the active tool on escape.
+            SketchMode::Tool(_) if glyph == '\x1b' => self.mode = SketchMode::Sketching,
+            // Cancel the running export on escape.
+            SketchMode::Sketching if glyph == '\x1b' && self.export.is_some() => {
+                self.cancel_export()
+            },
             _ => match glyph {
                 // Open background colorpicker dialog on ^B.
                 '\x02' => self.open_color_dialog(terminal, ColorPosition::Background),
                 // Open foreground colorpicker dialog on ^F.
                 '\x06' => self.open_color_dialog(terminal, ColorPosition::Foreground),
-                // Perform flood fill at cursor location.
-                '\x05' => self.fill(),
+                // Pick a region to flood fill on ^E.
+                '\x05' => self.start_fill(),
                 // Open brush character dialog on ^G.
                 '\x07' => self.open_brush_character_dialog(terminal),
                 // Open save dialog on ^S.
                 '\x13' => self.open_save_dialog(terminal, false, false),
-                // Toggle through text styles on ^T.
-                '\x14' => self.toggle_text_style(),
+                // Open text style dialog on ^T.
+                '\x14' => self.open_style_dialog(terminal),
+                // Capture or drop brush stamp on ^Y.
+                '\x19' => self.toggle_stamp_capture(),
                 // Open import dialog on ^O.
                 '\x0f' => self.open_open_dialog(terminal),
                 // Open help dialog on ^?.
@@ -885,9 +3637,9 @@ impl EventHandler for Sketch {
                 // Clear the screen.
                 '\x0c' => self.clear(terminal),
                 // Undo last action.
-                '\x15' => self.set_revision(terminal, self.revision.saturating_sub(1)),
+                '\x15' => self.undo(terminal),
                 // Redo last undone action.
-                '\x12' => self.set_revision(terminal, self.revision + 1),
+                '\x12' => self.redo(terminal),
                 // Go to the next line.
                 '\n' => {
                     // Ignore enter without previous text input.
@@ -896,147 +3648,319 @@ impl EventHandler for Sketch {
                         None => return,
                     };
 
-                    // Move text cursor to next line.
-                    text_cursor.column = self.brush.position.column;
-                    text_cursor.line += 1;
-                    Terminal::goto(text_cursor.column, text_cursor.line);
-                },
-                // Write the character to the screen.
-                glyph if glyph.width().unwrap_or_default() > 0 => {
-                    // Show IBeam cursor while typing.
-                    terminal.set_mode(TerminalMode::ShowCursor, true);
-                    Terminal::set_cursor_shape(CursorShape::IBeam);
+                    // Move text cursor to next line.
+                    text_cursor.column = self.canvas.brush.position.column;
+                    text_cursor.line += 1;
+                    let text_cursor = *text_cursor;
+                    self.goto(text_cursor);
+                },
+                // Join combining characters with the glyph before the text cursor.
+                glyph if self.combine_glyph(glyph) => self.bump_revision(),
+                // Write the character to the screen.
+                glyph if glyph.width().unwrap_or_default() > 0 => {
+                    // Show IBeam cursor while typing.
+                    terminal.set_mode(TerminalMode::ShowCursor, true);
+                    Terminal::set_cursor_shape(CursorShape::IBeam);
+
+                    // Offer to widen boxes instead of typing over their border.
+                    let text_cursor = *self.text_cursor.get_or_insert(self.canvas.brush.position);
+                    if self.canvas.grid.box_right_border(text_cursor).is_some() {
+                        self.mode = SketchMode::WidenPrompt(text_cursor, glyph);
+                        self.notify("Widen the box? [y/n]");
+                        self.goto(text_cursor);
+                        return;
+                    }
+
+                    // Write character at text cursor location.
+                    self.text_cursor = Some(self.write(text_cursor, glyph, true));
+                    self.bump_revision();
+                },
+                _ => (),
+            },
+        }
+    }
+
+    fn alt_keyboard_input(&mut self, terminal: &mut Terminal, glyph: char) {
+        self.leave_search_matches();
+
+        // Flip between the current canvas and the last save on Alt+V.
+        if glyph == 'v' && matches!(self.mode, SketchMode::Sketching | SketchMode::SavedView) {
+            self.update(terminal);
+            self.toggle_saved_view(terminal);
+            return;
+        }
+
+        // Copy to the clipboard on Alt+Y, keeping the selection of active tools.
+        if glyph == 'y' && matches!(self.mode, SketchMode::Sketching | SketchMode::Tool(_)) {
+            self.copy_to_clipboard();
+            return;
+        }
+
+        // Translate box drawing between Unicode and ASCII on Alt+Shift+A.
+        if glyph == 'A' && matches!(self.mode, SketchMode::Sketching | SketchMode::Tool(_)) {
+            self.update(terminal);
+            self.translate_box_drawing();
+            return;
+        }
+
+        // Ignore modified keys outside of the default sketching mode.
+        if !matches!(self.mode, SketchMode::Sketching) {
+            return;
+        }
+
+        // Hide mouse brush while typing.
+        self.update(terminal);
+
+        match glyph {
+            // Toggle circle aspect ratio correction on Alt+A.
+            'a' => self.toggle_true_geometry(),
+            // Write banner text at the brush position on Alt+B.
+            'b' => self.start_banner_text(),
+            // Toggle automatic foreground contrast on Alt+C.
+            'c' => self.toggle_auto_contrast(),
+            // Cycle through mirror drawing axes on Alt+|.
+            '|' => self.cycle_symmetry(),
+            // Open stamp library dialog on Alt+L.
+            'l' => self.open_stamp_library_dialog(terminal),
+            // Open packet diagram dialog on Alt+D.
+            'd' => self.open_packet_dialog(terminal),
+            // Open undo history dialog on Alt+H.
+            'h' => self.open_history_dialog(terminal),
+            // Open snapshot list dialog on Alt+J.
+            'j' => self.open_snapshot_list_dialog(terminal),
+            // Open snapshot naming dialog on Alt+N.
+            'n' => self.open_snapshot_dialog(terminal),
+            // Toggle lint overlay on Alt+I.
+            'i' => self.toggle_lint(terminal),
+            // Toggle border repair when erasing on Alt+R.
+            'r' => self.toggle_smart_erase(),
+            // Toggle shape recognition for freehand strokes on Alt+Shift+R.
+            'R' => self.toggle_beautify(),
+            // Toggle emphasis for lines and boxes on Alt+E.
+            'e' => self.toggle_emphasis(),
+            // Open color quantization dialog on Alt+Q.
+            'q' => self.open_quantize_dialog(terminal),
+            // Upload the sketch to the paste service on Alt+U.
+            'u' => self.share(),
+            // Move content on Alt+M.
+            'm' => self.start_move(),
+            // Cycle through brush shapes on Alt+S.
+            's' => self.cycle_brush_shape(),
+            // Load or discard the tracing layer on Alt+O.
+            'o' => self.toggle_trace(terminal),
+            // Open the brush settings dialog on Alt+K.
+            'k' => self.open_brush_dialog(terminal),
+            // Open the dialog for jumping to cells matching the brush on Alt+F.
+            'f' => self.open_jump_dialog(terminal),
+            // Open the text search dialog on Alt+X.
+            'x' => self.open_search_dialog(terminal),
+            // Toggle the coordinate ruler on Alt+P.
+            'p' => self.toggle_ruler(terminal),
+            // Toggle the alignment grid on Alt+Z.
+            'z' => self.toggle_grid(terminal),
+            // Open the dialog for centering the sketch on Alt+Shift+Z.
+            'Z' => self.open_center_dialog(terminal),
+            // Navigate between animation frames on Alt+< and Alt+>.
+            '<' => self.switch_frame(terminal, -1),
+            '>' => self.switch_frame(terminal, 1),
+            // Duplicate the active animation frame on Alt+Shift+F.
+            'F' => self.duplicate_frame(terminal),
+            // Delete the active animation frame on Alt+Shift+D.
+            'D' => self.delete_frame(terminal),
+            // Switch to the previous buffer on Alt+{.
+            '{' => self.cycle_buffer(terminal, -1),
+            // Switch to the next buffer on Alt+}.
+            '}' => self.cycle_buffer(terminal, 1),
+            // Open buffer list dialog on Alt+Shift+B.
+            'B' => self.open_buffer_list_dialog(terminal),
+            // Toggle the split view on Alt+Shift+S.
+            'S' => self.toggle_split(terminal),
+            // Move the split view divider on Alt+Shift+H and Alt+Shift+L.
+            'H' => self.resize_split(terminal, SPLIT_SHARE_STEP as isize),
+            'L' => self.resize_split(terminal, -(SPLIT_SHARE_STEP as isize)),
+            // Cycle through eraser modes on Alt+Shift+E.
+            'E' => self.cycle_eraser_mode(),
+            // Cycle through brush paint modes on Alt+Shift+C.
+            'C' => self.cycle_paint_mode(),
+            // Cycle through stamp blend modes on Alt+Shift+V.
+            'V' => self.cycle_stamp_blend(),
+            // Cycle through brush intensities on Alt+Shift+U.
+            'U' => self.cycle_intensity(),
+            // Cycle through left mouse button drawing modes on Alt+Shift+M.
+            'M' => self.cycle_drawing_mode(),
+            // Add a row to the table under the cursor on Alt+G.
+            'g' => self.append_table_row(),
+            // Open the dialog for drawing tables on Alt+Shift+G.
+            'G' => self.open_table_dialog(terminal),
+            // Open the dialog for drawing charts on Alt+Shift+N.
+            'N' => self.open_chart_dialog(terminal),
+            // Measure regions on Alt+Shift+W.
+            'W' => self.start_measure(),
+            // Lock or unlock regions on Alt+Shift+K.
+            'K' => self.start_lock(),
+            // Open the dialog for generating tree diagrams on Alt+Shift+I.
+            'I' => self.open_tree_dialog(terminal),
+            // Write text along the path under the brush on Alt+T.
+            't' => self.start_path_text(),
+            // Connect two boxes on Alt+W.
+            'w' => self.start_connector(),
+            // Crop the canvas to the sketch on Alt+Shift+T.
+            'T' => self.crop(terminal, self.config.crop_padding.unwrap_or_default()),
+            _ => (),
+        }
+    }
+
+    fn arrow_input(&mut self, terminal: &mut Terminal, key: ArrowKey, modifiers: Modifiers) {
+        self.leave_search_matches();
+
+        // Shift all content by one cell with Alt+Shift+arrows.
+        if modifiers == Modifiers::ALT | Modifiers::SHIFT
+            && matches!(self.mode, SketchMode::Sketching)
+        {
+            match key {
+                ArrowKey::Up => self.shift_content(terminal, 0, -1),
+                ArrowKey::Down => self.shift_content(terminal, 0, 1),
+                ArrowKey::Right => self.shift_content(terminal, 1, 0),
+                ArrowKey::Left => self.shift_content(terminal, -1, 0),
+            }
+            return;
+        }
 
-                    // Write character at text cursor location.
-                    let text_cursor = *self.text_cursor.get_or_insert(self.brush.position);
-                    self.text_cursor = Some(self.write(text_cursor, glyph, true));
-                    self.bump_revision();
-                },
-                _ => (),
-            },
+        // Scroll the canvas with Alt+arrows.
+        if modifiers != Modifiers::ALT || self.has_dialog() {
+            return;
+        }
+
+        match key {
+            ArrowKey::Up => self.scroll(terminal, 0, -SCROLL_LINES),
+            ArrowKey::Down => self.scroll(terminal, 0, SCROLL_LINES),
+            ArrowKey::Right => self.scroll(terminal, SCROLL_COLUMNS, 0),
+            ArrowKey::Left => self.scroll(terminal, -SCROLL_COLUMNS, 0),
         }
     }
 
     fn mouse_input(&mut self, terminal: &mut Terminal, event: MouseEvent) {
+        // Convert the event's screen coordinates to canvas coordinates.
+        //
+        // The split view pane is treated like the right edge of the canvas.
+        let screen_point = Point { column: event.column, line: event.line };
+        let screen_column = min(event.column, self.viewport.columns);
+        let Point { column, line } = self.viewport.canvas_point(screen_column, event.line);
+
+        // Keep the point on fixed size canvases smaller than the viewport.
+        let columns = self.canvas.grid.first().map_or(0, Vec::len);
+        let (column, line) = (min(column, columns), min(line, self.canvas.grid.len()));
+        let event = MouseEvent { column, line, ..event };
+
         // Always keep track of cursor on position change.
-        self.brush.position = Point { column: event.column, line: event.line };
-        self.text_cursor = None;
+        self.canvas.brush.position = Point { column: event.column, line: event.line };
+
+        // Only clicks move the text cursor in text mode.
+        let text_mode = self.drawing_mode == DrawingMode::Text && event.modifiers.is_empty();
+        if !text_mode {
+            self.text_cursor = None;
+        }
+
+        // Allow picking colors from the swatches with the colorpicker open.
+        if let SketchMode::ColorpickerDialog(dialog) = &self.mode {
+            let color = self.swatch_at(terminal, screen_point);
+            if let (Some(color), ButtonState::Pressed) = (color, event.button_state) {
+                self.canvas.brush.set_color(dialog.color_position(), color);
+                self.close_dialog(terminal);
+            }
+            return;
+        }
 
         // Ignore mouse events while dialogs are open.
-        if let SketchMode::SaveDialog(_)
-        | SketchMode::OpenDialog(_)
-        | SketchMode::HelpDialog(_)
-        | SketchMode::BrushCharacterDialog(_)
-        | SketchMode::ColorpickerDialog(_) = self.mode
-        {
+        if self.has_dialog() {
             return;
         }
 
-        // Hide terminal cursor while using the mouse.
-        terminal.set_mode(TerminalMode::ShowCursor, false);
+        // Stop navigating between search matches on click.
+        if event.button_state == ButtonState::Pressed {
+            self.leave_search_matches();
+        }
 
-        self.redraw(terminal);
+        // Ignore the mouse while writing banner text.
+        if let SketchMode::BannerText(..) = self.mode {
+            return;
+        }
 
-        match (event, &self.mode) {
-            // Start line drawing mode.
-            (
-                MouseEvent {
-                    button: MouseButton::Left,
-                    button_state: ButtonState::Pressed,
-                    modifiers: Modifiers::CONTROL,
-                    ..
-                },
-                SketchMode::Sketching,
-            ) => {
-                let point = Point { column: event.column, line: event.line };
-                self.mode = SketchMode::LineDrawing(point, false);
-            },
-            // Preview the line drawing box.
-            (
-                MouseEvent { button_state: ButtonState::Up, .. },
-                SketchMode::LineDrawing(start_point, false),
-            ) => {
-                let end_point = Point { column: event.column, line: event.line };
-                let start_point = *start_point;
-                self.preview_box(start_point, end_point);
-            },
-            // Draw the box once line drawing mode is finished.
-            (
-                MouseEvent {
-                    button: MouseButton::Left, button_state: ButtonState::Pressed, ..
-                },
-                SketchMode::LineDrawing(start_point, false),
-            ) => {
-                let end_point = Point { column: event.column, line: event.line };
-                let start_point = *start_point;
-                self.write_box(start_point, end_point, WriteMode::Write);
-                self.mode = SketchMode::Sketching;
-            },
-            // Preview the line drawing line.
-            (
-                MouseEvent { button: MouseButton::Left, button_state: ButtonState::Down, .. },
-                SketchMode::LineDrawing(start_point, _),
-            ) => {
-                // Preview the line.
-                let end_point = Point { column: event.column, line: event.line };
-                let start_point = *start_point;
-                self.preview_line(start_point, end_point);
-
-                // Prevent box drawing since the cursor has moved.
-                self.mode = SketchMode::LineDrawing(start_point, true);
-            },
-            // Stop line drawing once the mouse was released after moving.
-            (
-                MouseEvent {
-                    button: MouseButton::Left, button_state: ButtonState::Released, ..
-                },
-                SketchMode::LineDrawing(start_point, true),
-            ) => {
-                let end_point = Point { column: event.column, line: event.line };
-                let start_point = *start_point;
-                self.write_line(start_point, end_point, WriteMode::Write);
-                self.mode = SketchMode::Sketching;
-            },
-            // Write brush with left mouse button pressed.
-            (MouseEvent { button: MouseButton::Left, button_state, .. }, SketchMode::Sketching)
-                if button_state == ButtonState::Down || button_state == ButtonState::Pressed =>
-            {
-                self.write_brush(WriteMode::Write)
-            },
-            // Erase brush with right mouse button pressed.
-            (
-                MouseEvent { button: MouseButton::Right, button_state, .. },
-                SketchMode::Sketching,
-            ) if button_state == ButtonState::Down || button_state == ButtonState::Pressed => {
-                self.write_brush(WriteMode::Erase)
-            },
-            // Increase brush size.
-            (MouseEvent { button: MouseButton::Index(4), .. }, SketchMode::Sketching) => {
-                self.brush.size = self.brush.size.saturating_add(1);
-                self.brush.template = Brush::create_template(self.brush.size);
-            },
-            // Decrease brush size.
-            (MouseEvent { button: MouseButton::Index(5), .. }, SketchMode::Sketching) => {
-                self.brush.size = max(1, self.brush.size - 1);
-                self.brush.template = Brush::create_template(self.brush.size);
+        // Ignore the mouse until the box widening or upload prompt is answered.
+        if let SketchMode::WidenPrompt(..) | SketchMode::SharePrompt = self.mode {
+            return;
+        }
+
+        // Prevent changes while the last save is shown.
+        if let SketchMode::SavedView = self.mode {
+            return;
+        }
+
+        // Keep the text cursor on the path while writing along it.
+        if let SketchMode::PathText(path_text) = &self.mode {
+            self.text_cursor = path_text.points.get(path_text.index).copied();
+            return;
+        }
+
+        // Pan the canvas while dragging with the middle mouse button.
+        match (event.button, event.button_state, self.pan_origin) {
+            (MouseButton::Middle, ButtonState::Pressed, _) => self.pan_origin = Some(screen_point),
+            (MouseButton::Middle, ButtonState::Down, Some(pan_origin)) => {
+                self.pan_origin = Some(screen_point);
+                let (columns, lines) = screen_point.offset_to(pan_origin);
+                self.scroll(terminal, columns, lines);
+                return;
             },
+            (MouseButton::Middle, ButtonState::Released, _) => self.pan_origin = None,
             _ => (),
         }
 
-        // Preview cursor only while sketching.
-        if self.mode == SketchMode::Sketching {
-            // Draw brush at size 1 for line drawing preview.
-            if event.modifiers.contains(Modifiers::CONTROL) && event.button != MouseButton::Right {
-                let original_size = mem::replace(&mut self.brush.size, 1);
-                self.brush.template = Brush::create_template(self.brush.size);
+        // Hide terminal cursor while using the mouse, unless it marks the text cursor.
+        if !text_mode {
+            terminal.set_mode(TerminalMode::ShowCursor, false);
+        }
+
+        // Present the update and the following previews as a single frame.
+        terminal.synchronized(|terminal| {
+            self.update(terminal);
+
+            // Let the active tool handle the event, falling back to the drawing mode's
+            // tool.
+            let (mut tool, freehand): (Box<dyn Tool>, bool) = match mem::take(&mut self.mode) {
+                SketchMode::Tool(tool) => (tool, false),
+                mode => {
+                    self.mode = mode;
+                    (self.default_tool(&event), true)
+                },
+            };
+
+            let action = tool.mouse_input(self, event);
+            match action {
+                Action::None => (),
+                Action::Preview => tool.preview(self, event),
+                Action::Commit | Action::Finish => tool.commit(self, event),
+            }
 
-                self.preview_brush();
+            // Keep the tool active, unless it is done or another mode was started.
+            if !freehand && action != Action::Finish && matches!(self.mode, SketchMode::Sketching) {
+                self.mode = SketchMode::Tool(tool);
+            }
 
-                self.brush.size = original_size;
-                self.brush.template = Brush::create_template(self.brush.size);
-            } else {
-                self.preview_brush();
+            // Preview cursor only while sketching.
+            if matches!(self.mode, SketchMode::Sketching) {
+                self.default_tool(&event).preview(self, event);
             }
+
+            self.present_overlay();
+        });
+
+        // Show the text cursor placed by the text tool.
+        if let (true, MouseButton::Left, ButtonState::Pressed, Some(text_cursor)) =
+            (text_mode, event.button, event.button_state, self.text_cursor)
+        {
+            self.goto(text_cursor);
+            terminal.set_mode(TerminalMode::ShowCursor, true);
+            Terminal::set_cursor_shape(CursorShape::IBeam);
         }
     }
 
@@ -1046,48 +3970,153 @@ impl EventHandler for Sketch {
     /// dimensions with no way to recover it.
     fn resize(&mut self, terminal: &mut Terminal, dimensions: Dimensions) {
         let Dimensions { columns, lines } = dimensions;
-        let (columns, lines) = (columns as usize, lines as usize);
+        let (columns, lines) = (self.canvas_columns(columns as usize), lines as usize);
+        let old_viewport = self.viewport;
 
-        // Add/remove lines.
-        self.content.resize(lines, vec![Cell::default(); columns]);
+        // Cells outside of the viewport are kept, so they can be restored once it
+        // grows.
+        let (mut leading_columns, mut leading_lines) =
+            self.viewport.resize(columns, lines, self.options.anchor.leading_share());
 
-        // Resize columns of each line.
-        for line in self.content.iter_mut() {
-            line.resize(columns, Cell::default());
+        // Never extend fixed size canvases.
+        if self.options.width.is_some() {
+            leading_columns = 0;
+        }
+        if self.options.height.is_some() {
+            leading_lines = 0;
         }
 
-        // Force redraw to make sure user is up to date.
-        self.redraw(terminal);
-    }
+        self.canvas.grid.insert_leading(leading_columns, leading_lines);
+        self.fit_canvas();
+
+        // Calculate how far the previous content has moved on the screen.
+        let column_shift = (old_viewport.column_offset + leading_columns) as isize
+            - self.viewport.column_offset as isize;
+        let line_shift = (old_viewport.line_offset + leading_lines) as isize
+            - self.viewport.line_offset as isize;
+
+        // Force redraw unless the previous content can be reused.
+        let grown = columns >= old_viewport.columns && lines >= old_viewport.lines;
+        if !grown
+            || column_shift != 0
+            || old_viewport.lines == 0
+            || !matches!(self.mode, SketchMode::Sketching)
+            || self.split.is_some()
+            || self.options.width.is_some()
+            || self.options.height.is_some()
+        {
+            self.redraw(terminal);
+            return;
+        }
 
-    /// Redraw the entire UI.
-    fn redraw(&mut self, terminal: &mut Terminal) {
-        // Re-print the entire stored buffer.
-        Terminal::goto(1, 1);
-        Terminal::write(self.content.to_string());
+        // Move the previous content down to keep the anchored part in place.
+        let line_shift = line_shift as usize;
+        if line_shift > 0 {
+            Terminal::set_scroll_region(1, old_viewport.lines + line_shift);
+            Terminal::scroll_down(line_shift);
+            Terminal::reset_scroll_region();
+        }
+
+        // Repaint the newly exposed area.
+        //
+        // The previous first and last lines are always repainted, since they might
+        // contain the help or status messages.
+        let old_lines = line_shift + 1..line_shift + old_viewport.lines + 1;
+        self.redraw_area(1..columns + 1, 1..old_lines.start + 1);
+        self.redraw_area(old_viewport.columns + 1..columns + 1, old_lines.clone());
+        self.redraw_area(1..columns + 1, old_lines.end - 1..lines + 1);
 
         self.render_help();
 
         // Restore text cursor.
         if let Some(text_cursor) = self.text_cursor {
-            Terminal::goto(text_cursor.column, text_cursor.line);
+            self.goto(text_cursor);
+        }
+    }
+
+    /// Redraw the entire UI.
+    fn redraw(&mut self, terminal: &mut Terminal) {
+        terminal.synchronized(|terminal| self.redraw_screen(terminal));
+    }
+
+    fn timer(&mut self, _terminal: &mut Terminal) {
+        self.poll_export();
+        self.poll_upload();
+
+        if self.toast.as_ref().is_some_and(|toast| toast.expires() <= Instant::now()) {
+            self.clear_toast();
         }
+    }
 
-        // Redraw dialogs.
-        match &mut self.mode {
-            SketchMode::BrushCharacterDialog(dialog) => dialog.render(terminal),
-            SketchMode::ColorpickerDialog(dialog) => dialog.render(terminal),
-            SketchMode::SaveDialog(dialog) => dialog.render(terminal),
-            SketchMode::OpenDialog(dialog) => dialog.render(terminal),
-            SketchMode::HelpDialog(dialog) => dialog.render(terminal),
-            _ => (),
+    fn flush(&mut self, terminal: &mut Terminal) {
+        // Keep checking on background jobs until they are done.
+        let mut deadline = self.toast.as_ref().map(Toast::expires);
+        if self.export.is_some() || self.upload.is_some() {
+            let poll = Instant::now() + POLL_INTERVAL;
+            deadline = Some(deadline.map_or(poll, |deadline| deadline.min(poll)));
         }
+        terminal.set_timer(deadline);
+        terminal.set_title(&self.title());
+
+        self.present_overlay();
+    }
+
+    fn control(&mut self, terminal: &mut Terminal, command: &str) -> String {
+        let command = match ControlCommand::parse(command) {
+            Ok(command) => command,
+            Err(err) => return format!("error: {err}"),
+        };
+
+        // Never interfere with dialogs or other modal input.
+        if !matches!(self.mode, SketchMode::Sketching) {
+            return String::from("error: busy");
+        }
+
+        match command {
+            ControlCommand::Draw(point) | ControlCommand::Text(point, _)
+                if !self.canvas.grid.contains(point) =>
+            {
+                return String::from("error: position outside of the canvas");
+            },
+            ControlCommand::Draw(point) => {
+                let position = mem::replace(&mut self.canvas.brush.position, point);
+                self.write_brush(WriteMode::Write);
+                self.canvas.brush.position = position;
+            },
+            ControlCommand::Text(point, text) => {
+                let mut cursor = point;
+                for glyph in text.graphemes(true) {
+                    cursor = self.canvas.write_many(cursor, glyph, 1, true);
+                }
+                self.finish_write(WriteMode::Write);
+            },
+            ControlCommand::Color(position, color) => self.canvas.brush.set_color(position, color),
+            ControlCommand::Save(path) => {
+                let Some(path) = path.or_else(|| self.options.output.clone()) else {
+                    return String::from("error: no output file");
+                };
+                if let Err(err) = self.persist(&path) {
+                    return format!("error: {err}");
+                }
+            },
+            ControlCommand::Crop(padding) => {
+                let padding = padding.or(self.config.crop_padding).unwrap_or_default();
+                self.crop(terminal, padding);
+            },
+        }
+
+        // Restore the text cursor moved by the writes.
+        if let Some(text_cursor) = self.text_cursor {
+            self.goto(text_cursor);
+        }
+
+        String::from("ok")
     }
 
     fn focus_changed(&mut self, terminal: &mut Terminal, focus: bool) {
         // Hide mouse brush while unfocused.
         if !focus {
-            self.redraw(terminal);
+            self.update(terminal);
         }
     }
 
@@ -1095,13 +4124,68 @@ impl EventHandler for Sketch {
         // If another dialog is open, close it.
         match self.mode {
             SketchMode::BrushCharacterDialog(_)
+            | SketchMode::BrushDialog(_)
             | SketchMode::ColorpickerDialog(_)
-            | SketchMode::HelpDialog(_) => self.close_dialog(terminal),
+            | SketchMode::HelpDialog(_)
+            | SketchMode::StampLibraryDialog(_)
+            | SketchMode::QuantizeDialog(_)
+            | SketchMode::PacketDialog(_)
+            | SketchMode::TableDialog(_)
+            | SketchMode::ChartDialog(_)
+            | SketchMode::TreeDialog(_)
+            | SketchMode::LintDialog(_)
+            | SketchMode::HistoryDialog(_)
+            | SketchMode::SnapshotDialog(_)
+            | SketchMode::SnapshotListDialog(_)
+            | SketchMode::JumpDialog(_)
+            | SketchMode::CenterDialog(_)
+            | SketchMode::SearchDialog(_)
+            | SketchMode::BufferListDialog(_)
+            | SketchMode::StyleDialog(_)
+            | SketchMode::SavedView => self.close_dialog(terminal),
+            #[cfg(feature = "image")]
+            SketchMode::ImageDialog(_) => self.close_dialog(terminal),
             _ => (),
         }
 
-        match &self.options.output {
-            Some(path) if !self.output_modified => match self.content.persist(path) {
+        // Check replayed sketches against the expected output instead of saving them.
+        if self.options.replay_input.is_some() {
+            if let Some(path) = self.options.assert_output.clone() {
+                match self.assert_output(&path) {
+                    Ok(()) => self.persisted = true,
+                    Err(err) => return terminal.fail(err),
+                }
+            }
+
+            terminal.shutdown();
+            return;
+        }
+
+        // Finish the running export, instead of cutting it off.
+        self.wait_for_export();
+
+        // Skip saving when the sketch is written to STDOUT.
+        if self.stdout.is_some() {
+            terminal.shutdown();
+            return;
+        }
+
+        // Save modified background buffers, stopping at the first one without a known
+        // path.
+        while let Some(index) = self.buffers.iter().position(|buffer| buffer.modified) {
+            self.switch_buffer(terminal, index);
+            match self.options.output.clone() {
+                Some(path) if !self.output_modified && self.persist(&path).is_ok() => (),
+                _ => {
+                    self.open_save_dialog(terminal, false, false);
+                    self.notify("Save this buffer before quitting");
+                    return;
+                },
+            }
+        }
+
+        match self.options.output.clone() {
+            Some(path) if !self.output_modified => match self.persist(&path) {
                 Ok(()) => {
                     self.persisted = true;
                     terminal.shutdown();
@@ -1112,306 +4196,233 @@ impl EventHandler for Sketch {
         }
     }
 
-    fn set_bracketed_paste_state(&mut self, active: bool) {
-        self.pasting = active;
-
-        // Create a revision once bracketed paste is done.
-        if !self.pasting {
-            self.bump_revision();
+    fn paste(&mut self, terminal: &mut Terminal, text: &str) {
+        // Keep line breaks when pasting lists into the tree dialog.
+        if let SketchMode::TreeDialog(dialog) = &mut self.mode {
+            let text = text.replace("\r\n", "\n").replace('\r', "\n").replace('\t', "    ");
+            let lines: Vec<_> = text.split('\n').map(|line| line.strip()).collect();
+            dialog.paste(terminal, &lines.join("\n"));
+            return;
         }
-    }
-}
 
-impl Drop for Sketch {
-    fn drop(&mut self) {
-        // Write Sketch to STDOUT if it wasn't saved to a file.
-        if !self.persisted {
-            print!("{}", self.content.trimmed_text());
+        // Keep line breaks separating values when pasting into the chart dialog.
+        if let SketchMode::ChartDialog(dialog) = &mut self.mode {
+            let text = text.replace('\r', "\n");
+            let lines: Vec<_> = text.split('\n').map(|line| line.strip()).collect();
+            dialog.paste(terminal, &lines.join("\n"));
+            return;
         }
-    }
-}
 
-/// Sketch content grid.
-#[derive(Default)]
-struct Grid(Vec<Vec<Cell>>);
-
-impl Grid {
-    /// Get a trimmed version of the sketch.
-    ///
-    /// This will remove all empty lines from the top and bottom of the sketch.
-    fn trimmed_text(&self) -> String {
-        let mut text = self.to_string();
-
-        // Find the first non-empty line.
-        let start_offset = text
-            .chars()
-            .enumerate()
-            .take_while(|&(_, c)| c.is_whitespace())
-            .fold(0, |acc, (i, c)| if c == '\n' { i + 1 } else { acc });
-
-        // Remove empty lines above or below the sketch.
-        text = text[start_offset..].trim_end().to_owned();
-        text.push('\n');
-
-        text
-    }
+        // Paste into dialogs and text modes as plain keyboard input.
+        if !matches!(self.mode, SketchMode::Sketching | SketchMode::Tool(_)) {
+            for glyph in text.strip().chars() {
+                self.keyboard_input(terminal, glyph);
+            }
+            return;
+        }
 
-    /// Try to write the Sketch to a file.
-    fn persist(&self, path: &Path) -> io::Result<()> {
-        let text = self.trimmed_text();
-        fs::write(path, text)
-    }
+        // Hide mouse brush while pasting.
+        self.update(terminal);
+
+        // Start every line in the column the paste started at, keeping escaped colors.
+        let origin = self.text_cursor.unwrap_or(self.canvas.brush.position);
+        let mut text_cursor = origin;
+        for (line_offset, line) in SketchParser::parse(text).iter().enumerate() {
+            for (column_offset, cell) in line.iter().enumerate() {
+                // Skip padding and spacers of fullwidth characters.
+                if cell.c == '\0' {
+                    continue;
+                }
 
-    /// Get cell at the specified point.
-    fn get(&self, point: Point) -> &Cell {
-        &self.0[point.line - 1][point.column - 1]
-    }
+                let point = Point {
+                    column: origin.column + column_offset,
+                    line: origin.line + line_offset,
+                };
+                text_cursor = self.write_cell(point, cell, true);
+            }
+        }
+        self.text_cursor = Some(text_cursor);
+        self.bump_revision();
 
-    /// Check if the content in a grid cell matches a template.
-    fn cell_matches(&self, column: usize, line: usize, template: &Cell) -> bool {
-        let try_index = |column, line| {
-            let column = usize::try_from(column as isize - 1).ok()?;
-            let line = usize::try_from(line as isize - 1).ok()?;
-            let grid_line = (line < self.len()).then(|| &self[line])?;
-            (column < grid_line.len()).then(|| &grid_line[column])
-        };
-        try_index(column, line).map_or(false, |cell| cell.content_eq(template))
+        // Show IBeam cursor at the end of the pasted text.
+        terminal.set_mode(TerminalMode::ShowCursor, true);
+        Terminal::set_cursor_shape(CursorShape::IBeam);
+        self.goto(text_cursor);
     }
 }
 
-impl Display for Grid {
-    /// Render the entire grid to the formatter.
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        if self.0.is_empty() {
-            return Ok(());
+impl Drop for Sketch {
+    fn drop(&mut self) {
+        if let Some(autosave) = &mut self.autosave {
+            autosave.save(&self.canvas.grid.cells);
         }
 
-        let mut text = String::new();
-
-        // Store colors/styles to reduce number of writes.
-        let mut foreground = Color::default();
-        let mut background = Color::default();
-        Terminal::set_color(foreground, background);
-        let mut style = TextStyle::empty();
-        Terminal::set_style(style);
-
-        for line in &self.0 {
-            let mut column = 0;
-            while column < line.len() {
-                let cell = &line[column];
-
-                // Set the cell's colors
-                if cell.foreground != foreground {
-                    text.push_str(&cell.foreground.escape(true));
-                    foreground = cell.foreground;
-                }
-                if cell.background != background {
-                    text.push_str(&cell.background.escape(false));
-                    background = cell.background;
-                }
+        // Keep the brush for the next session.
+        if Sketch::remembers_brush(&self.options, &self.config) {
+            if let Some(path) = config::brush_path() {
+                let _ = session::save_brush(&path, &self.canvas.brush);
+            }
+        }
 
-                // Set the cell's text style.
-                if cell.style != style {
-                    text.push_str(cell.style.escape());
-                    style = cell.style;
-                }
+        let text = self.canvas.grid.trimmed_text(self.options.sgr_encoding());
+        let dumped = self.stdout.is_some() || !self.persisted;
 
-                // Render empty cells as whitespace.
-                let width = cell.c.width();
-                match width {
-                    Some(1) | Some(2) => text.push(cell.c),
-                    _ => text.push(' '),
-                }
+        // Describe the result, using STDERR to keep the sketch output clean.
+        if self.options.summary && self.options.replay_input.is_none() {
+            let (columns, lines) = self.canvas.grid.content_size();
+            let colors = self.canvas.grid.used_colors().len();
+            let target = match &self.options.output {
+                Some(path) if !dumped => format!("Saved sketch to {}", path.display()),
+                _ => "Wrote sketch to STDOUT".into(),
+            };
+            eprintln!("{target} ({columns}x{lines} cells, {colors} colors)");
 
-                // Skip columns when dealing with fullwidth characters.
-                column += width.filter(|w| *w != 0).unwrap_or(1);
+            // Separate the dumped sketch from the summary.
+            if dumped {
+                eprintln!("{}", "─".repeat(max(columns, 1)));
             }
-            text.push('\n');
         }
 
-        write!(f, "{}", text.trim_end_matches('\n'))
+        // Always write to the original STDOUT in `--stdout` mode.
+        if let Some(stdout) = &mut self.stdout {
+            let _ = stdout.write_all(text.as_bytes());
+            return;
+        }
+
+        // Write Sketch to STDOUT if it wasn't saved to a file.
+        if !self.persisted {
+            print!("{}", text);
+        }
     }
 }
 
-impl Deref for Grid {
-    type Target = Vec<Vec<Cell>>;
+/// Format a number of bytes using binary units.
+fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit + 1 < UNITS.len() {
+        size /= 1024.;
+        unit += 1;
     }
-}
 
-impl DerefMut for Grid {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
     }
 }
 
-/// Content of a cell in the grid.
-#[derive(Debug, Clone, Default)]
-struct Cell {
-    // Cell contents.
-    c: char,
-    foreground: Color,
-    background: Color,
-    style: TextStyle,
-
-    /// Versioned cell change history.
-    history: HashMap<usize, Cell>,
+/// Sketch state which is independent for every open buffer.
+#[derive(Default)]
+struct Buffer {
+    content: Grid,
+    frames: Vec<Grid>,
+    frame: usize,
+    output: Option<PathBuf>,
+    output_modified: bool,
+    modified: bool,
+    saved: Option<Grid>,
 }
 
-impl Cell {
-    fn new(c: char, foreground: Color, background: Color, style: TextStyle) -> Self {
-        Self { c, style, foreground, background, history: HashMap::new() }
-    }
-
-    /// Reset the cell to the default content.
-    fn clear(&mut self, revision: usize) {
-        self.replace(Self::default(), revision);
-    }
-
-    /// Replace the cell with a new cell.
-    ///
-    /// This should be used over replacing the cell directly, since it correctly
-    /// keeps track of the cell's history for undoing changes in the future.
-    fn replace(&mut self, mut cell: Self, revision: usize) {
-        // Replace the current cell.
-        cell.history = mem::take(&mut self.history);
-        let old_cell = mem::replace(self, cell);
-
-        // Update history if this was the first update for the revision.
-        self.history.entry(revision).or_insert(old_cell);
-    }
-
-    /// Set the active revision of the cell.
-    fn set_revision(&mut self, current_revision: usize, new_revision: usize) {
-        // Find a change matching the revision.
-        let mut cell = match self.history.remove(&new_revision) {
-            Some(cell) => cell,
-            None => return,
-        };
-
-        // Swap old revision with current cell.
-        cell.history = mem::take(&mut self.history);
-        let old_cell = mem::replace(self, cell);
-        self.history.insert(current_revision, old_cell);
-    }
-
-    /// Drop all revisions after `revision`.
-    fn clear_history(&mut self, revision: usize) {
-        self.history.retain(|rev, _| *rev <= revision);
+impl Buffer {
+    /// Create a buffer with an empty canvas.
+    fn new(undo_depth: usize) -> Self {
+        let mut content = Grid::default();
+        content.journal.set_depth(undo_depth);
+        Self { content, frames: vec![Grid::default()], ..Default::default() }
     }
+}
 
-    /// Check if cell has any visible content.
-    fn is_empty(&self) -> bool {
-        (self.c.is_whitespace() || self.c == '\0') && self.background == Color::default()
-    }
+/// Read-only reference sketch shown in a pane right of the canvas.
+struct Split {
+    content: Grid,
+    name: String,
 
-    /// Check if this cell's content matches another's.
-    fn content_eq(&self, other: &Cell) -> bool {
-        let both_whitespace = (self.c.is_whitespace() || self.c == '\0')
-            && (other.c.is_whitespace() || other.c == '\0');
-        let c_matches = self.c == other.c || both_whitespace;
-        c_matches
-            && self.background == other.background
-            && (self.foreground == other.foreground || both_whitespace)
-    }
+    /// Percentage of the terminal width occupied by the pane.
+    share: usize,
 }
 
-/// Drawing brush.
-struct Brush {
-    template: Vec<Vec<bool>>,
-    foreground: Color,
-    background: Color,
-    style: TextStyle,
-    position: Point,
-    glyph: char,
-    size: u8,
+/// Temporary content drawn on top of the canvas, like previews.
+///
+/// Overlay cells are only written to the terminal when they differ from the
+/// previous frame, so unchanged parts of previews are never re-emitted.
+#[derive(Default)]
+struct Overlay {
+    /// Cells of the current frame.
+    cells: HashMap<Point, OverlayCell>,
+    /// Cells visible on the screen since the last frame.
+    rendered: HashMap<Point, OverlayCell>,
+    /// Highlight used for new cells.
+    highlight: Highlight,
 }
 
-impl Default for Brush {
-    fn default() -> Self {
-        Self {
-            template: Self::create_template(1),
-            glyph: '+',
-            size: 1,
-            foreground: Default::default(),
-            background: Default::default(),
-            position: Default::default(),
-            style: Default::default(),
+impl Overlay {
+    /// Add a cell to the current frame.
+    ///
+    /// Fullwidth characters also occupy the following column with a spacer.
+    fn insert(&mut self, point: Point, cell: &Cell, width: usize) {
+        let highlight = self.highlight;
+        self.cells.insert(point, OverlayCell { cell: cell.clone(), highlight });
+
+        if width == 2 {
+            let spacer = Cell { c: '\0', ..cell.clone() };
+            let point = Point { column: point.column + 1, ..point };
+            self.cells.insert(point, OverlayCell { cell: spacer, highlight });
         }
     }
-}
 
-impl Brush {
-    /// Update the brush's colors.
-    fn set_color(&mut self, position: ColorPosition, color: Color) {
-        match position {
-            ColorPosition::Foreground => self.foreground = color,
-            ColorPosition::Background => self.background = color,
-        }
+    /// Start a new frame, without any cells.
+    fn clear(&mut self) {
+        self.cells.clear();
     }
 
-    /// Create a new brush template.
-    ///
-    /// The brush will always be hexagon shaped, the resulting template is a
-    /// matrix that stores `true` for every cell that contains a brush glyph
-    /// and `false` for all empty cells.
-    ///
-    /// A brush with size 6 might look like this (`+`: `true`, `-`: `false`):
-    ///
-    /// ```
-    /// --++++++--
-    /// -++++++++-
-    /// ++++++++++
-    /// -++++++++-
-    /// --++++++--
-    /// ```
-    fn create_template(size: u8) -> Vec<Vec<bool>> {
-        // Special case the default 1x1 cursor.
-        if size == 1 {
-            return vec![vec![true]];
-        }
-
-        let size = size as usize;
-
-        let width = size + (size / 2 - 1) * 2;
-        let height = size - 1;
-
-        // Initialize an empty cursor.
-        let mut cursor = vec![vec![false; width]; height];
-
-        let mid_point = (size - 1) as f32 / 2.;
-        let mut num_occupied = size;
-        for (i, line) in cursor.iter_mut().enumerate().take(height) {
-            // Set all occupied bits in the current line.
-            for column in 0..num_occupied {
-                let column = (width - num_occupied) / 2 + column;
-                line[column] = true;
-            }
+    /// Mark cells as overwritten on the screen.
+    fn invalidate(&mut self, line: usize, columns: Range<usize>) {
+        self.rendered.retain(|point, _| point.line != line || !columns.contains(&point.column));
+    }
+}
 
-            // Increment/Decrement based on current line in hexagon.
-            if i as f32 + 1. < mid_point {
-                num_occupied += 2;
-            } else if i as f32 + 1. > mid_point {
-                num_occupied -= 2;
-            }
-        }
+/// Cell of the overlay.
+#[derive(Clone, PartialEq, Eq)]
+struct OverlayCell {
+    cell: Cell,
+    highlight: Highlight,
+}
 
-        cursor
-    }
+/// Text attributes distinguishing overlay cells from the canvas.
+#[derive(Default, Copy, Clone, PartialEq, Eq)]
+enum Highlight {
+    #[default]
+    None,
+    Dim,
+    Reverse,
 }
 
 /// Current application state.
-#[derive(Default, PartialEq, Eq)]
+#[derive(Default)]
 enum SketchMode {
     /// Default drawing mode.
     #[default]
     Sketching,
-    /// Line/Box drawing mode.
-    LineDrawing(Point, bool),
+    /// Mouse driven drawing tool.
+    Tool(Box<dyn Tool>),
+    /// Text input along a path.
+    PathText(PathText),
+    /// Banner text input at a point.
+    BannerText(Point, String),
+    /// Typed glyph waiting for confirmation to widen the box at its position.
+    WidenPrompt(Point, char),
+    /// Confirmation of uploading the sketch to the paste service.
+    SharePrompt,
+    /// Read-only view of the canvas as of the last save.
+    SavedView,
+    /// Navigation between the matches of the active search.
+    SearchMatches,
     /// Brush character dialog prompt.
     BrushCharacterDialog(BrushCharacterDialog),
+    /// Brush settings dialog.
+    BrushDialog(BrushDialog),
     /// Colorpicker dialog.
     ColorpickerDialog(ColorpickerDialog),
     /// Save dialog.
@@ -1420,60 +4431,211 @@ enum SketchMode {
     OpenDialog(OpenDialog),
     /// Help dialog.
     HelpDialog(HelpDialog),
+    /// Stamp library dialog.
+    StampLibraryDialog(StampLibraryDialog),
+    /// Color quantization dialog.
+    QuantizeDialog(QuantizeDialog),
+    /// Packet diagram generator dialog.
+    PacketDialog(PacketDialog),
+    /// Table size dialog.
+    TableDialog(TableDialog),
+    /// Chart values dialog.
+    ChartDialog(ChartDialog),
+    /// Tree diagram list dialog.
+    TreeDialog(TreeDialog),
+    /// Lint issue list dialog.
+    LintDialog(LintDialog),
+    /// Undo history stats and compaction dialog.
+    HistoryDialog(HistoryDialog),
+    /// Snapshot naming dialog.
+    SnapshotDialog(SnapshotDialog),
+    /// Snapshot restoration dialog.
+    SnapshotListDialog(SnapshotListDialog),
+    /// Dialog for jumping to cells matching the brush.
+    JumpDialog(JumpDialog),
+    /// Dialog for centering the sketch on the canvas.
+    CenterDialog(CenterDialog),
+    /// Text search dialog.
+    SearchDialog(SearchDialog),
+    /// Open buffer list dialog.
+    BufferListDialog(BufferListDialog),
+    /// Text style toggle dialog.
+    StyleDialog(StyleDialog),
+    /// Image import size dialog.
+    #[cfg(feature = "image")]
+    ImageDialog(ImageDialog),
 }
 
-/// Modes for writing text to the grid.
-#[derive(Debug, PartialEq, Eq)]
-enum WriteMode {
-    /// Write to the terminal without storing the result.
-    WriteVolatile,
-    /// Write to the terminal and internal state.
-    Write,
-    /// Write whitespace to erase content from terminal and internal state.
-    Erase,
+/// Export of the canvas running off the event loop.
+struct Export {
+    /// Background job, returning the exported canvas.
+    job: Job<Grid>,
+    /// Destination of the export.
+    path: PathBuf,
+    /// Human-readable name of the export format.
+    format: &'static str,
+    /// Last progress shown to the user, in percent.
+    percent: usize,
 }
 
-/// Coordinate in the terminal grid.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-struct Point {
-    column: usize,
-    line: usize,
-}
-
-impl Default for Point {
-    fn default() -> Self {
-        Self { column: 1, line: 1 }
-    }
+/// State for writing text along a path.
+#[derive(PartialEq, Eq)]
+struct PathText {
+    /// Cells along the path.
+    points: Vec<Point>,
+    /// Original content of the cells along the path.
+    cells: Vec<Cell>,
+    /// Index of the next point on the path.
+    index: usize,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::terminal::backend::TestBackend;
+
+    /// Create a sketch with a terminal capturing all output.
+    fn harness(columns: u16, lines: u16) -> (Sketch, Terminal, TestBackend) {
+        let backend = TestBackend::default();
+        let dimensions = Dimensions { columns, lines };
+        let mut terminal = Terminal::with_backend(dimensions, backend.clone());
+
+        let config = Config { remember_brush: Some(false), ..Config::default() };
+        let mut sketch = Sketch::with_config(Options::parse_from(["sketch"]), config).unwrap();
+        sketch.resize(&mut terminal, dimensions);
+
+        (sketch, terminal, backend)
+    }
+
+    /// Create a left mouse button event.
+    fn click(button_state: ButtonState, column: usize, line: usize) -> MouseEvent {
+        let modifiers = Modifiers::empty();
+        MouseEvent { button_state, modifiers, button: MouseButton::Left, column, line }
+    }
+
+    #[test]
+    fn draw_and_undo() {
+        let (mut sketch, mut terminal, backend) = harness(20, 5);
+        backend.take_output();
+
+        sketch.mouse_input(&mut terminal, click(ButtonState::Pressed, 5, 3));
+        sketch.mouse_input(&mut terminal, click(ButtonState::Released, 5, 3));
+        sketch.flush(&mut terminal);
+        assert_eq!(sketch.canvas.grid.get(Point { column: 5, line: 3 }).c, '+');
+        assert!(backend.take_output().contains("\x1b[3;5H+"));
+
+        sketch.keyboard_input(&mut terminal, '\x15');
+        sketch.flush(&mut terminal);
+        assert!(sketch.canvas.grid.get(Point { column: 5, line: 3 }).is_empty());
+        assert!(!sketch.canvas.grid.undo());
+    }
+
+    #[test]
+    fn help_dialog() {
+        let (mut sketch, mut terminal, backend) = harness(80, 30);
+        backend.take_output();
+
+        sketch.keyboard_input(&mut terminal, '\x1f');
+        assert!(matches!(sketch.mode, SketchMode::HelpDialog(_)));
+        assert!(backend.take_output().contains("brush size"));
+
+        sketch.keyboard_input(&mut terminal, '\x1b');
+        assert!(matches!(sketch.mode, SketchMode::Sketching));
+    }
+
+    #[test]
+    fn share_confirmation() {
+        let (mut sketch, mut terminal, backend) = harness(80, 5);
+        backend.take_output();
+
+        sketch.alt_keyboard_input(&mut terminal, 'u');
+        sketch.flush(&mut terminal);
+        assert!(matches!(sketch.mode, SketchMode::SharePrompt));
+        assert!(backend.take_output().contains("[y/N]"));
+
+        sketch.keyboard_input(&mut terminal, '\n');
+        assert!(matches!(sketch.mode, SketchMode::Sketching));
+        assert!(sketch.upload.is_none());
+    }
+
+    #[test]
+    fn table_tool() {
+        let (mut sketch, mut terminal, _backend) = harness(20, 8);
+
+        sketch.alt_keyboard_input(&mut terminal, 'G');
+        for glyph in "2x2\n".chars() {
+            sketch.keyboard_input(&mut terminal, glyph);
+        }
+        sketch.mouse_input(&mut terminal, click(ButtonState::Pressed, 2, 2));
+        sketch.mouse_input(&mut terminal, click(ButtonState::Released, 8, 6));
+
+        let text: Vec<String> = (2..=6)
+            .map(|line| {
+                let cells = &sketch.canvas.grid[line - 1][1..8];
+                cells.iter().map(|cell| if cell.is_empty() { ' ' } else { cell.c }).collect()
+            })
+            .collect();
+        assert_eq!(text, ["┌──┬──┐", "│  │  │", "├──┼──┤", "│  │  │", "└──┴──┘"]);
+    }
+
+    #[test]
+    fn duplicate_selection() {
+        let (mut sketch, mut terminal, _backend) = harness(20, 5);
+        for (column, c) in [(2, 'a'), (3, 'b')] {
+            sketch.write(Point { column, line: 2 }, c, true);
+        }
+        sketch.bump_revision();
+
+        sketch.alt_keyboard_input(&mut terminal, 'm');
+        sketch.mouse_input(&mut terminal, click(ButtonState::Pressed, 1, 1));
+        sketch.mouse_input(&mut terminal, click(ButtonState::Released, 4, 3));
+
+        let alt_click =
+            MouseEvent { modifiers: Modifiers::ALT, ..click(ButtonState::Pressed, 2, 2) };
+        sketch.mouse_input(&mut terminal, alt_click);
+        sketch.mouse_input(&mut terminal, click(ButtonState::Released, 10, 3));
+
+        let text = |sketch: &Sketch, line: usize| -> String {
+            let cells = &sketch.canvas.grid[line - 1][..12];
+            cells.iter().map(|cell| if cell.is_empty() { ' ' } else { cell.c }).collect()
+        };
+        assert_eq!(text(&sketch, 2), " ab         ");
+        assert_eq!(text(&sketch, 3), "         ab ");
+
+        // Duplicating is a single revision.
+        sketch.keyboard_input(&mut terminal, '\x15');
+        assert_eq!(text(&sketch, 2), " ab         ");
+        assert_eq!(text(&sketch, 3), "            ");
+    }
+
+    #[test]
+    fn locked_region() {
+        let (mut sketch, mut terminal, _backend) = harness(20, 5);
+
+        sketch.alt_keyboard_input(&mut terminal, 'K');
+        sketch.mouse_input(&mut terminal, click(ButtonState::Pressed, 2, 2));
+        sketch.mouse_input(&mut terminal, click(ButtonState::Released, 4, 3));
+
+        for column in [3, 6] {
+            sketch.mouse_input(&mut terminal, click(ButtonState::Pressed, column, 2));
+            sketch.mouse_input(&mut terminal, click(ButtonState::Released, column, 2));
+        }
+        assert!(sketch.canvas.grid.get(Point { column: 3, line: 2 }).is_empty());
+        assert_eq!(sketch.canvas.grid.get(Point { column: 6, line: 2 }).c, '+');
+
+        // Clicking a locked region unlocks it.
+        sketch.alt_keyboard_input(&mut terminal, 'K');
+        sketch.mouse_input(&mut terminal, click(ButtonState::Pressed, 3, 3));
+        sketch.mouse_input(&mut terminal, click(ButtonState::Released, 3, 3));
+        sketch.mouse_input(&mut terminal, click(ButtonState::Pressed, 3, 2));
+        sketch.mouse_input(&mut terminal, click(ButtonState::Released, 3, 2));
+        assert_eq!(sketch.canvas.grid.get(Point { column: 3, line: 2 }).c, '+');
+    }
 
     #[test]
-    #[rustfmt::skip]
-    fn cursor() {
-        let cursor = Brush::create_template(1);
-        assert_eq!(cursor, vec![vec![true]]);
-
-        let cursor = Brush::create_template(2);
-        assert_eq!(cursor, vec![
-            vec![true, true],
-        ]);
-
-        let cursor = Brush::create_template(3);
-        assert_eq!(cursor, vec![
-            vec![true, true, true],
-            vec![true, true, true],
-        ]);
-
-        let cursor = Brush::create_template(6);
-        assert_eq!(cursor, vec![
-            vec![false, false, true, true, true, true, true, true, false, false],
-            vec![false, true,  true, true, true, true, true, true, true,  false],
-            vec![true,  true,  true, true, true, true, true, true, true,  true ],
-            vec![false, true,  true, true, true, true, true, true, true,  false],
-            vec![false, false, true, true, true, true, true, true, false, false],
-        ]);
+    fn byte_units() {
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
     }
 }
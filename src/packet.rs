@@ -0,0 +1,185 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Number of bits in every row of the diagram.
+pub const ROW_BITS: usize = 32;
+
+/// Maximum number of bits in a single diagram.
+const MAX_BITS: usize = 64 * ROW_BITS;
+
+/// Named field of a packet.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Field {
+    pub name: String,
+    pub bits: usize,
+}
+
+/// Parse a comma separated list of `name:bits` fields.
+///
+/// Returns `None` if any field is malformed or the list is empty.
+pub fn parse(spec: &str) -> Option<Vec<Field>> {
+    let mut fields = Vec::new();
+    let mut total = 0;
+
+    for field in spec.split(',') {
+        let (name, bits) = field.rsplit_once(':')?;
+        let bits: usize = bits.trim().parse().ok().filter(|bits| *bits > 0)?;
+
+        total += bits;
+        if total > MAX_BITS {
+            return None;
+        }
+
+        fields.push(Field { name: name.trim().into(), bits });
+    }
+
+    Some(fields)
+}
+
+/// Render fields as a packet diagram.
+///
+/// The diagram starts with the bit number header, followed by the fields
+/// wrapped into rows of [`ROW_BITS`] bits. Every bit takes up two columns.
+pub fn diagram(fields: &[Field]) -> Vec<String> {
+    // Map every bit to the index of the field it belongs to.
+    let owners: Vec<usize> =
+        fields.iter().enumerate().flat_map(|(i, field)| vec![i; field.bits]).collect();
+    let rows = owners.len().div_ceil(ROW_BITS);
+    let owner = |row: usize, bit: usize| match bit < ROW_BITS {
+        true => owners.get(row * ROW_BITS + bit).copied(),
+        false => None,
+    };
+
+    // Bit numbering header.
+    let header_bits = owners.len().min(ROW_BITS);
+    let tens = (0..header_bits).step_by(10).map(|bit| format!("{:<20}", bit / 10));
+    let ones = (0..header_bits).map(|bit| format!("{} ", bit % 10));
+    let mut lines = vec![
+        format!(" {}", tens.collect::<String>()).trim_end().into(),
+        format!(" {}", ones.collect::<String>()).trim_end().into(),
+    ];
+
+    // Separator between the row above and the row below.
+    let separator = |above: Option<usize>, below: Option<usize>| {
+        let owner_above = |bit| above.and_then(|row| owner(row, bit));
+        let owner_below = |bit| below.and_then(|row| owner(row, bit));
+
+        // Use dashes unless both rows are part of the same field.
+        let dashes: Vec<bool> =
+            (0..ROW_BITS).map(|bit| owner_above(bit) != owner_below(bit)).collect();
+
+        let mut line = String::new();
+        for bit in 0..=ROW_BITS {
+            let dash_left = bit > 0 && dashes[bit - 1];
+            let dash_right = dashes.get(bit).copied().unwrap_or(false);
+            let border_above = bit.checked_sub(1).and_then(owner_above) != owner_above(bit);
+            let border_below = bit.checked_sub(1).and_then(owner_below) != owner_below(bit);
+
+            line.push(if dash_left || dash_right || border_above || border_below {
+                '+'
+            } else {
+                ' '
+            });
+            line.push(if dash_right { '-' } else { ' ' });
+        }
+        line.trim_end().into()
+    };
+
+    for row in 0..rows {
+        lines.push(separator(row.checked_sub(1), Some(row)));
+
+        // Add field borders.
+        //
+        // Every column holds one grapheme cluster, with empty spacers after
+        // fullwidth characters.
+        let mut line: Vec<&str> = vec![" "; 2 * ROW_BITS + 1];
+        for bit in 0..=ROW_BITS {
+            let left = bit.checked_sub(1).and_then(|bit| owner(row, bit));
+            if left != owner(row, bit) {
+                line[2 * bit] = "|";
+            }
+        }
+
+        // Add the labels of all fields starting in this row.
+        let mut offset = 0;
+        for field in fields {
+            let (start_row, start_bit) = (offset / ROW_BITS, offset % ROW_BITS);
+            offset += field.bits;
+            if start_row != row {
+                continue;
+            }
+
+            // Truncate the label to the available width.
+            let end_bit = (start_bit + field.bits).min(ROW_BITS);
+            let width = 2 * (end_bit - start_bit) - 1;
+            let mut label_width = 0;
+            let label: Vec<&str> = field
+                .name
+                .graphemes(true)
+                .filter(|glyph| glyph.width() > 0)
+                .take_while(|glyph| {
+                    label_width += glyph.width();
+                    label_width <= width
+                })
+                .collect();
+            let label_width: usize = label.iter().map(|glyph| glyph.width()).sum();
+
+            let mut column = 2 * start_bit + 1 + (width - label_width) / 2;
+            for glyph in label {
+                line[column] = glyph;
+                if glyph.width() > 1 {
+                    line[column + 1] = "";
+                }
+                column += glyph.width();
+            }
+        }
+
+        lines.push(line.concat().trim_end().into());
+    }
+    lines.push(separator(rows.checked_sub(1), None));
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_fields() {
+        let fields = parse("Source Port:16, Destination Port: 16").unwrap();
+        assert_eq!(fields[0], Field { name: "Source Port".into(), bits: 16 });
+        assert_eq!(fields[1], Field { name: "Destination Port".into(), bits: 16 });
+
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("Flags:0"), None);
+        assert_eq!(parse("Flags:4,"), None);
+    }
+
+    #[test]
+    fn packet_diagram() {
+        let fields =
+            parse("Source Port:16,Destination Port:16,Sequence Number:64,Flags:8").unwrap();
+        let expected = [
+            " 0                   1                   2                   3",
+            " 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1",
+            "+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+",
+            "|          Source Port          |       Destination Port        |",
+            "+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+",
+            "|                        Sequence Number                        |",
+            "+                                                               +",
+            "|                                                               |",
+            "+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+",
+            "|     Flags     |",
+            "+-+-+-+-+-+-+-+-+",
+        ];
+        assert_eq!(diagram(&fields), expected);
+    }
+
+    #[test]
+    fn wide_labels() {
+        let fields = parse("界:4,e\u{301}:2").unwrap();
+        let lines = diagram(&fields);
+        assert_eq!(lines[3], "|  界   | e\u{301} |");
+    }
+}
@@ -0,0 +1,95 @@
+use serde::Deserialize;
+
+use crate::color::{Color, NamedColor, Rgb};
+
+/// Set of colors sketches can be reduced to.
+#[derive(Deserialize, Default, Copy, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Palette {
+    /// The 16 CTerm colors.
+    #[default]
+    Ansi16,
+    /// XTerm's 256 color palette.
+    Xterm256,
+}
+
+impl Palette {
+    /// Get the palette's color closest to `color`.
+    ///
+    /// The default color is always preserved.
+    pub fn nearest(self, color: Color) -> Color {
+        let rgb = match color.rgb() {
+            Some(rgb) => rgb,
+            None => return color,
+        };
+
+        let palette_size = match self {
+            Self::Ansi16 => 16,
+            Self::Xterm256 => 256,
+        };
+
+        // Find the palette index with the lowest distance.
+        let index = (0..palette_size)
+            .min_by_key(|index| {
+                let palette_rgb = Color::Indexed(*index as u8).rgb().unwrap_or_default();
+                distance(rgb, palette_rgb)
+            })
+            .unwrap_or_default() as u8;
+
+        index_to_color(index)
+    }
+
+    /// Get the palette following this one.
+    pub fn next(self) -> Self {
+        match self {
+            Self::Ansi16 => Self::Xterm256,
+            Self::Xterm256 => Self::Ansi16,
+        }
+    }
+
+    /// Get human-readable name of the palette.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ansi16 => "16 colors",
+            Self::Xterm256 => "256 colors",
+        }
+    }
+}
+
+/// Convert a palette index to a color, preferring named colors.
+fn index_to_color(index: u8) -> Color {
+    let named = match index {
+        0 => NamedColor::Black,
+        1 => NamedColor::Red,
+        2 => NamedColor::Green,
+        3 => NamedColor::Yellow,
+        4 => NamedColor::Blue,
+        5 => NamedColor::Magenta,
+        6 => NamedColor::Cyan,
+        7 => NamedColor::White,
+        index => return Color::Indexed(index),
+    };
+    Color::Named(named)
+}
+
+/// Squared euclidean distance between two colors.
+fn distance(a: Rgb, b: Rgb) -> u32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel(a.r, b.r) + channel(a.g, b.g) + channel(a.b, b.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_color() {
+        let orange = Color::Rgb(Rgb { r: 250, g: 10, b: 5 });
+        assert_eq!(Palette::Ansi16.nearest(orange), Color::Indexed(9));
+
+        let gray = Color::Rgb(Rgb { r: 120, g: 121, b: 119 });
+        assert_eq!(Palette::Xterm256.nearest(gray), Color::Indexed(243));
+
+        assert_eq!(Palette::Ansi16.nearest(Color::default()), Color::default());
+    }
+}
@@ -0,0 +1,350 @@
+use std::fmt::Write;
+use std::path::Path;
+use std::{fs, io};
+
+use sketch::brush::BrushShape;
+use sketch::grid::{MAX_COLUMNS, MAX_LINES};
+use sketch::journal::{Change, Journal};
+use sketch::{Brush, Cell, Grid, Point};
+
+use crate::terminal::{Color, NamedColor, Rgb, TextStyle};
+use crate::Sketch;
+
+/// File extension used for session files.
+const EXTENSION: &str = "sketch";
+
+/// First line of every session file, including the format version.
+const HEADER: &str = "sketch-session 2";
+
+/// First line of the brush state file, including the format version.
+const BRUSH_HEADER: &str = "sketch-brush 1";
+
+/// Restorable sketch state.
+pub struct Session {
+    pub content: Grid,
+    pub brush: Brush,
+}
+
+/// Check if a path should use the session format.
+pub fn is_session(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == EXTENSION)
+}
+
+/// Serialize the canvas with its undo history and the brush settings.
+///
+/// Only cells with content are stored, one per line. Every undo and redo
+/// revision is stored on a separate line, listing all its changes.
+pub fn serialize(sketch: &Sketch) -> String {
+    let columns = sketch.canvas.grid.first().map_or(0, Vec::len);
+    let mut session = format!("{HEADER}\nsize {columns} {}\n", sketch.canvas.grid.len());
+
+    let _ = writeln!(session, "brush {}", brush_fields(&sketch.canvas.brush));
+
+    for (line, cells) in sketch.canvas.grid.iter().enumerate() {
+        for (column, cell) in cells.iter().enumerate() {
+            if *cell == Cell::default() {
+                continue;
+            }
+
+            let _ = writeln!(session, "cell {} {} {}", column + 1, line + 1, cell_fields(cell));
+        }
+    }
+
+    let journal = &sketch.canvas.grid.journal;
+    let undo = journal.undo_revisions().map(|changes| ("undo", changes));
+    let redo = journal.redo_revisions().map(|changes| ("redo", changes));
+    for (kind, changes) in undo.chain(redo) {
+        session.push_str(kind);
+        for (point, cell) in changes {
+            let _ = write!(session, " {},{}={}", point.column, point.line, cell_fields(cell));
+        }
+        session.push('\n');
+    }
+
+    session
+}
+
+/// Parse a serialized session.
+///
+/// Returns `None` if the session is malformed.
+pub fn parse(session: &str) -> Option<Session> {
+    let mut lines = session.lines();
+    if lines.next()? != HEADER {
+        return None;
+    }
+
+    let mut cells: Vec<Vec<Cell>> = Vec::new();
+    let mut brush = Brush::default();
+    let (mut undo, mut redo) = (Vec::new(), Vec::new());
+
+    for line in lines {
+        let mut fields = line.split(' ');
+        match fields.next()? {
+            "size" => {
+                let columns = fields.next()?.parse().ok().filter(|c| *c <= MAX_COLUMNS)?;
+                let lines = fields.next()?.parse().ok().filter(|l| *l <= MAX_LINES)?;
+                cells = vec![vec![Cell::default(); columns]; lines];
+            },
+            "brush" => brush = parse_brush(fields)?,
+            "cell" => {
+                let column: usize = fields.next()?.parse().ok()?;
+                let line: usize = fields.next()?.parse().ok()?;
+                let cell = parse_cell(fields.next()?)?;
+
+                let target =
+                    cells.get_mut(line.checked_sub(1)?)?.get_mut(column.checked_sub(1)?)?;
+                *target = cell;
+            },
+            "undo" => undo.push(parse_changes(fields, &cells)?),
+            "redo" => redo.push(parse_changes(fields, &cells)?),
+            _ => return None,
+        }
+    }
+
+    Some(Session { content: Grid::new(cells, Journal::new(undo, redo)), brush })
+}
+
+/// Store the brush settings for the next session.
+pub fn save_brush(path: &Path, brush: &Brush) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, format!("{BRUSH_HEADER}\n{}\n", brush_fields(brush)))
+}
+
+/// Load the brush settings stored by [`save_brush`].
+///
+/// Returns `None` if the file is missing or malformed.
+pub fn load_brush(path: &Path) -> Option<Brush> {
+    let state = fs::read_to_string(path).ok()?;
+    let mut lines = state.lines();
+    if lines.next()? != BRUSH_HEADER {
+        return None;
+    }
+    parse_brush(lines.next()?.split(' '))
+}
+
+/// Serialize the brush settings, without its position or stamp.
+fn brush_fields(brush: &Brush) -> String {
+    let pattern: Vec<_> = brush.pattern.iter().map(|glyph| glyph_code(glyph)).collect();
+    format!(
+        "{} {} {} {} {} {} {} {}",
+        color(brush.foreground),
+        color(brush.background),
+        brush.style.bits(),
+        shape_name(brush.shape),
+        brush.size,
+        brush.emphasis as u8,
+        brush.auto_contrast as u8,
+        pattern.join(","),
+    )
+}
+
+/// Parse brush settings serialized by [`brush_fields`].
+fn parse_brush<'a>(mut fields: impl Iterator<Item = &'a str>) -> Option<Brush> {
+    let mut brush = Brush {
+        foreground: parse_color(fields.next()?)?,
+        background: parse_color(fields.next()?)?,
+        style: TextStyle::from_bits(fields.next()?.parse().ok()?)?,
+        shape: parse_shape(fields.next()?)?,
+        size: fields.next()?.parse().ok().filter(|size| *size > 0)?,
+        emphasis: fields.next()? == "1",
+        auto_contrast: fields.next()? == "1",
+        ..Default::default()
+    };
+    brush.pattern = fields
+        .next()?
+        .split(',')
+        .filter(|code| !code.is_empty())
+        .map(parse_glyph)
+        .collect::<Option<Vec<_>>>()
+        .filter(|pattern| !pattern.is_empty())?;
+    brush.template = Brush::create_template(brush.shape, brush.size);
+    Some(brush)
+}
+
+/// Parse the changes of a revision.
+///
+/// Returns `None` if any change is malformed or outside of the canvas.
+fn parse_changes<'a>(
+    fields: impl Iterator<Item = &'a str>,
+    cells: &[Vec<Cell>],
+) -> Option<Vec<Change>> {
+    fields
+        .map(|field| {
+            let (point, cell) = field.split_once('=')?;
+            let (column, line) = point.split_once(',')?;
+            let point = Point { column: column.parse().ok()?, line: line.parse().ok()? };
+            cells.get(point.line.checked_sub(1)?)?.get(point.column.checked_sub(1)?)?;
+            Some((point, parse_cell(cell)?))
+        })
+        .collect()
+}
+
+/// Serialize a cell's content without its history.
+///
+/// Transparent cells are marked with a trailing `/t` field.
+pub fn cell_fields(cell: &Cell) -> String {
+    let (foreground, background) = (color(cell.foreground), color(cell.background));
+    let glyph = glyph_code(&cell.glyph());
+    let transparent = if cell.transparent { "/t" } else { "" };
+    format!("{glyph}/{foreground}/{background}/{}{transparent}", cell.style.bits())
+}
+
+/// Parse a cell serialized by [`cell_fields`].
+///
+/// Cells stored without the transparency marker are only transparent if they
+/// match the default cell, to support files written before the marker existed.
+pub fn parse_cell(text: &str) -> Option<Cell> {
+    let mut fields = text.split('/');
+    let glyph = parse_glyph(fields.next()?)?;
+    let foreground = parse_color(fields.next()?)?;
+    let background = parse_color(fields.next()?)?;
+    let style = TextStyle::from_bits(fields.next()?.parse().ok()?)?;
+    let mut cell = Cell::with_glyph(&glyph, foreground, background, style)?;
+    cell.transparent = match fields.next() {
+        Some("t") => true,
+        Some(_) => return None,
+        None => Cell { transparent: true, ..cell.clone() } == Cell::default(),
+    };
+    Some(cell)
+}
+
+/// Serialize a grapheme cluster as `+`-separated hexadecimal codepoints.
+fn glyph_code(glyph: &str) -> String {
+    let codepoints: Vec<_> = glyph.chars().map(|c| format!("{:x}", c as u32)).collect();
+    codepoints.join("+")
+}
+
+/// Parse a grapheme cluster serialized by [`glyph_code`].
+fn parse_glyph(code: &str) -> Option<String> {
+    code.split('+').map(|c| char::from_u32(u32::from_str_radix(c, 16).ok()?)).collect()
+}
+
+/// Serialize a color.
+fn color(color: Color) -> String {
+    match color {
+        Color::Named(color) => format!("n{}", color as u8),
+        Color::Indexed(index) => format!("i{index}"),
+        Color::Rgb(Rgb { r, g, b }) => format!("#{r:02x}{g:02x}{b:02x}"),
+    }
+}
+
+/// Parse a color serialized by [`color`].
+fn parse_color(text: &str) -> Option<Color> {
+    if let Some(rgb) = text.strip_prefix('#') {
+        return rgb.parse().ok().map(Color::Rgb);
+    } else if let Some(index) = text.strip_prefix('i') {
+        return index.parse().ok().map(Color::Indexed);
+    }
+
+    let named = match text.strip_prefix('n')? {
+        "0" => NamedColor::Black,
+        "1" => NamedColor::Red,
+        "2" => NamedColor::Green,
+        "3" => NamedColor::Yellow,
+        "4" => NamedColor::Blue,
+        "5" => NamedColor::Magenta,
+        "6" => NamedColor::Cyan,
+        "7" => NamedColor::White,
+        "9" => NamedColor::Default,
+        _ => return None,
+    };
+    Some(Color::Named(named))
+}
+
+/// Serialized name of a brush shape.
+fn shape_name(shape: BrushShape) -> &'static str {
+    match shape {
+        BrushShape::Hexagon => "hexagon",
+        BrushShape::Square => "square",
+        BrushShape::HorizontalLine => "horizontal",
+        BrushShape::VerticalLine => "vertical",
+        BrushShape::Diamond => "diamond",
+    }
+}
+
+/// Parse a brush shape serialized by [`shape_name`].
+fn parse_shape(name: &str) -> Option<BrushShape> {
+    match name {
+        "hexagon" => Some(BrushShape::Hexagon),
+        "square" => Some(BrushShape::Square),
+        "horizontal" => Some(BrushShape::HorizontalLine),
+        "vertical" => Some(BrushShape::VerticalLine),
+        "diamond" => Some(BrushShape::Diamond),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_roundtrip() {
+        let colors = [
+            Color::default(),
+            Color::Named(NamedColor::Cyan),
+            Color::Indexed(208),
+            Color::Rgb(Rgb { r: 0x12, g: 0xab, b: 0xff }),
+        ];
+        for original in colors {
+            assert_eq!(parse_color(&color(original)), Some(original));
+        }
+        assert_eq!(parse_color("n8"), None);
+    }
+
+    #[test]
+    fn cell_roundtrip() {
+        let cell = Cell::new(' ', Color::Indexed(3), Color::default(), TextStyle::BOLD);
+        assert_eq!(parse_cell(&cell_fields(&cell)), Some(cell));
+
+        let cell = Cell::new('│', Color::default(), Color::default(), TextStyle::empty());
+        assert_eq!(parse_cell(&cell_fields(&cell)), Some(cell));
+    }
+
+    #[test]
+    fn brush_roundtrip() {
+        let path = std::env::temp_dir().join(format!("sketch-brush-{}", std::process::id()));
+
+        let mut brush = Brush {
+            foreground: Color::Indexed(9),
+            style: TextStyle::ITALICS,
+            shape: BrushShape::Diamond,
+            size: 4,
+            pattern: vec!["░".into(), "👩\u{200d}💻".into()],
+            ..Default::default()
+        };
+        brush.template = Brush::create_template(brush.shape, brush.size);
+        save_brush(&path, &brush).unwrap();
+
+        let restored = load_brush(&path);
+        let _ = fs::remove_file(&path);
+        let restored = restored.unwrap();
+        assert_eq!(restored.foreground, brush.foreground);
+        assert_eq!(restored.style, brush.style);
+        assert_eq!(restored.size, brush.size);
+        assert_eq!(restored.pattern, brush.pattern);
+        assert_eq!(restored.template, brush.template);
+
+        assert!(parse_brush("n9 n9 0 hexagon 0 0 0 2b".split(' ')).is_none());
+        assert!(parse_brush("n9 n9 0 hexagon 1 0 0 ".split(' ')).is_none());
+    }
+
+    #[test]
+    fn reject_malformed() {
+        assert!(parse("sketch-session 1\n").is_none());
+        assert!(parse(&format!("{HEADER}\nsize 2 2\ncell 3 1 41/n9/n9/0\n")).is_none());
+        assert!(parse(&format!("{HEADER}\nunknown\n")).is_none());
+        assert!(parse(&format!("{HEADER}\nsize 99999 99999\n")).is_none());
+
+        assert!(parse(&format!("{HEADER}\nsize 2 2\nundo 3,1=0/n9/n9/0\n")).is_none());
+
+        let session =
+            parse(&format!("{HEADER}\nsize 2 2\ncell 2 1 41/n1/n9/0\nundo 2,1=0/n9/n9/0\n"));
+        let mut content = session.unwrap().content;
+        assert_eq!(content[0][1].c, 'A');
+        assert!(content.undo());
+        assert_eq!(content[0][1], Cell::default());
+    }
+}
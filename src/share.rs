@@ -0,0 +1,34 @@
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Maximum time to wait for the paste service.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Upload text to a netcat-style paste service like termbin.
+///
+/// The text is sent as-is over a TCP connection to `host`, which responds with
+/// the URL of the paste once the connection is closed for writing.
+pub fn upload(host: &str, text: &str) -> io::Result<String> {
+    let address = host.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, format!("could not resolve {host}"))
+    })?;
+
+    let mut stream = TcpStream::connect_timeout(&address, TIMEOUT)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    stream.write_all(text.as_bytes())?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    // Termbin terminates its response with whitespace and a NUL byte.
+    let url = response.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+    if url.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "empty response"));
+    }
+
+    Ok(url.into())
+}
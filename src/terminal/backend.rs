@@ -0,0 +1,55 @@
+use std::io::{self, Write};
+#[cfg(test)]
+use std::{cell::RefCell, rc::Rc};
+
+/// Destination of all terminal output.
+pub trait TerminalBackend {
+    /// Write text and escape sequences to the terminal.
+    fn write(&mut self, bytes: &[u8]);
+}
+
+/// Backend writing to STDOUT.
+pub struct StdoutBackend;
+
+impl TerminalBackend for StdoutBackend {
+    fn write(&mut self, bytes: &[u8]) {
+        let mut stdout = io::stdout();
+        let _ = stdout.write(bytes);
+        let _ = stdout.flush();
+    }
+}
+
+/// Backend discarding all output.
+pub struct NullBackend;
+
+impl TerminalBackend for NullBackend {
+    fn write(&mut self, _bytes: &[u8]) {}
+}
+
+/// Backend storing all output in memory.
+///
+/// Clones share the same output, so a clone can be used to inspect everything
+/// written after the backend was passed to [`Terminal::with_backend`].
+///
+/// [`Terminal::with_backend`]: super::Terminal::with_backend
+#[cfg(test)]
+#[derive(Clone, Default)]
+pub struct TestBackend {
+    output: Rc<RefCell<Vec<u8>>>,
+}
+
+#[cfg(test)]
+impl TestBackend {
+    /// Take all output written since the last call.
+    pub fn take_output(&self) -> String {
+        let output = self.output.take();
+        String::from_utf8_lossy(&output).into_owned()
+    }
+}
+
+#[cfg(test)]
+impl TerminalBackend for TestBackend {
+    fn write(&mut self, bytes: &[u8]) {
+        self.output.borrow_mut().extend_from_slice(bytes);
+    }
+}
@@ -0,0 +1,82 @@
+use std::env;
+
+/// Query SGR mouse support with DECRQM and RGB color support with XTGETTCAP.
+pub const CAPABILITY_QUERY: &str = "\x1b[?1006$p\x1bP+q524742\x1b\\";
+
+/// Hex-encoded name of the terminfo capability for RGB colors.
+pub const RGB_CAPABILITY: &[u8] = b"524742";
+
+/// Terminals known to support RGB colors, even without `$COLORTERM`.
+const TRUECOLOR_TERMS: [&str; 7] =
+    ["alacritty", "contour", "foot", "ghostty", "iterm", "kitty", "wezterm"];
+
+/// Terminals without support for SGR mouse reporting.
+const LEGACY_MOUSE_TERMS: [&str; 5] = ["ansi", "cons25", "dumb", "linux", "vt"];
+
+/// Optional terminal features.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Capabilities {
+    /// Whether mouse events can be reported with SGR encoding.
+    pub sgr_mouse: bool,
+    /// Whether colors can be specified as RGB values.
+    pub truecolor: bool,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+impl Capabilities {
+    /// Support for all optional features.
+    pub const ALL: Self = Self { sgr_mouse: true, truecolor: true };
+
+    /// Guess the supported features based on `$TERM` and `$COLORTERM`.
+    ///
+    /// The Windows console supports all features, without setting `$TERM`.
+    pub fn from_env() -> Self {
+        if cfg!(windows) {
+            return Self::ALL;
+        }
+
+        let term = env::var("TERM").unwrap_or_default();
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        Self::from_vars(&term, &colorterm)
+    }
+
+    /// Guess the supported features based on the terminal's name.
+    fn from_vars(term: &str, colorterm: &str) -> Self {
+        let sgr_mouse =
+            !term.is_empty() && !LEGACY_MOUSE_TERMS.iter().any(|legacy| term.starts_with(legacy));
+
+        let truecolor = matches!(colorterm, "truecolor" | "24bit")
+            || term.ends_with("-direct")
+            || TRUECOLOR_TERMS.iter().any(|truecolor| term.contains(truecolor));
+
+        Self { sgr_mouse, truecolor }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn environment_heuristics() {
+        let capabilities = Capabilities::from_vars("xterm-256color", "truecolor");
+        assert_eq!(capabilities, Capabilities::ALL);
+
+        let capabilities = Capabilities::from_vars("xterm-kitty", "");
+        assert_eq!(capabilities, Capabilities::ALL);
+
+        let capabilities = Capabilities::from_vars("screen-256color", "");
+        assert_eq!(capabilities, Capabilities { sgr_mouse: true, truecolor: false });
+
+        let capabilities = Capabilities::from_vars("linux", "");
+        assert_eq!(capabilities, Capabilities { sgr_mouse: false, truecolor: false });
+
+        let capabilities = Capabilities::from_vars("vt220", "");
+        assert!(!capabilities.sgr_mouse);
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, ErrorKind, Read, Write};
+use std::os::unix::net::UnixStream as StdUnixStream;
+use std::path::{Path, PathBuf};
+
+use mio::net::{UnixListener, UnixStream};
+use mio::{Interest, Registry, Token};
+
+/// Maximum length of a single command in bytes.
+const MAX_COMMAND_LEN: usize = u16::MAX as usize;
+
+/// Unix domain socket accepting newline-terminated commands.
+///
+/// Every client connection receives one response line per command.
+pub struct ControlSocket {
+    listener: UnixListener,
+    path: PathBuf,
+    clients: HashMap<Token, Client>,
+
+    /// Mio token for the next accepted client.
+    next_token: Token,
+}
+
+impl ControlSocket {
+    /// Listen for clients on a socket file.
+    ///
+    /// Stale socket files left behind by crashed instances are replaced.
+    pub fn bind(path: &Path, first_token: Token) -> io::Result<Self> {
+        let listener = match UnixListener::bind(path) {
+            Err(err)
+                if err.kind() == ErrorKind::AddrInUse && StdUnixStream::connect(path).is_err() =>
+            {
+                fs::remove_file(path)?;
+                UnixListener::bind(path)?
+            },
+            result => result?,
+        };
+
+        Ok(Self { listener, path: path.into(), clients: HashMap::new(), next_token: first_token })
+    }
+
+    /// Register the socket for new client connections.
+    pub fn register(&mut self, registry: &Registry, token: Token) -> io::Result<()> {
+        registry.register(&mut self.listener, token, Interest::READABLE)
+    }
+
+    /// Accept all pending client connections.
+    pub fn accept(&mut self, registry: &Registry) {
+        while let Ok((mut stream, _)) = self.listener.accept() {
+            let token = self.next_token;
+            self.next_token = Token(token.0 + 1);
+
+            if registry.register(&mut stream, token, Interest::READABLE).is_ok() {
+                self.clients.insert(token, Client { stream, input: Vec::new(), closed: false });
+            }
+        }
+    }
+
+    /// Read all complete commands sent by a client.
+    pub fn read(&mut self, token: Token) -> Vec<String> {
+        let Some(client) = self.clients.get_mut(&token) else { return Vec::new() };
+
+        let mut buf = [0; 4096];
+        loop {
+            match client.stream.read(&mut buf) {
+                Ok(0) => {
+                    client.closed = true;
+                    break;
+                },
+                Ok(read) => client.input.extend_from_slice(&buf[..read]),
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(_) => {
+                    client.closed = true;
+                    break;
+                },
+            }
+        }
+
+        let mut commands = Vec::new();
+        while let Some(end) = client.input.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = client.input.drain(..=end).collect();
+            let command = String::from_utf8_lossy(&line[..end]);
+            commands.push(command.trim_end_matches('\r').to_owned());
+        }
+
+        // Drop clients which never terminate their commands.
+        if client.input.len() > MAX_COMMAND_LEN {
+            client.closed = true;
+        }
+
+        commands
+    }
+
+    /// Send the response to a command back to its client.
+    pub fn reply(&mut self, token: Token, response: &str) {
+        if let Some(client) = self.clients.get_mut(&token) {
+            let _ = client.stream.write_all(format!("{response}\n").as_bytes());
+        }
+    }
+
+    /// Disconnect a client, if it has closed its connection.
+    pub fn drop_closed(&mut self, registry: &Registry, token: Token) {
+        if self.clients.get(&token).is_some_and(|client| client.closed) {
+            if let Some(mut client) = self.clients.remove(&token) {
+                let _ = registry.deregister(&mut client.stream);
+            }
+        }
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Connection to a control socket client.
+struct Client {
+    stream: UnixStream,
+
+    /// Received bytes which are not a complete command yet.
+    input: Vec<u8>,
+
+    /// Whether the client has closed its side of the connection.
+    closed: bool,
+}
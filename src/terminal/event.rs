@@ -9,6 +9,28 @@ pub trait EventHandler {
     /// Keyboard characters.
     fn keyboard_input(&mut self, _terminal: &mut Terminal, _glyph: char) {}
 
+    /// Keyboard characters pressed while holding Alt.
+    fn alt_keyboard_input(&mut self, _terminal: &mut Terminal, _glyph: char) {}
+
+    /// Keys with modifiers, like they are reported by the kitty keyboard
+    /// protocol.
+    ///
+    /// By default these are translated to their legacy encoding.
+    fn key_input(&mut self, terminal: &mut Terminal, key: char, modifiers: Modifiers) {
+        match legacy_glyph(key, modifiers) {
+            // Ctrl+C is not sent as signal with the kitty keyboard protocol.
+            Some('\x03' | '\x04') => self.shutdown(terminal),
+            Some(glyph) if modifiers.contains(Modifiers::ALT) => {
+                self.alt_keyboard_input(terminal, glyph)
+            },
+            Some(glyph) => self.keyboard_input(terminal, glyph),
+            None => (),
+        }
+    }
+
+    /// Arrow keys.
+    fn arrow_input(&mut self, _terminal: &mut Terminal, _key: ArrowKey, _modifiers: Modifiers) {}
+
     /// Terminal columns/lines have changed.
     fn resize(&mut self, _terminal: &mut Terminal, _dimensions: Dimensions) {}
 
@@ -22,8 +44,33 @@ pub trait EventHandler {
     /// Terminal focus has changed.
     fn focus_changed(&mut self, _terminal: &mut Terminal, _focus: bool) {}
 
-    /// Set whether a bracketed paste is being performed.
-    fn set_bracketed_paste_state(&mut self, _active: bool) {}
+    /// Timer set with [`Terminal::set_timer`] expired.
+    fn timer(&mut self, _terminal: &mut Terminal) {}
+
+    /// Text inserted with bracketed paste.
+    ///
+    /// The text is passed without any processing, so it might contain escape
+    /// sequences. By default every character is handled as keyboard input.
+    fn paste(&mut self, terminal: &mut Terminal, text: &str) {
+        for glyph in text.chars() {
+            self.keyboard_input(terminal, glyph);
+        }
+    }
+
+    /// Event processing is done.
+    ///
+    /// This is called after every event, to write all pending output.
+    fn flush(&mut self, _terminal: &mut Terminal) {}
+
+    /// Command received over the control socket.
+    ///
+    /// Returns the response sent back to the client.
+    fn control(&mut self, _terminal: &mut Terminal, _command: &str) -> String {
+        String::from("error: unsupported")
+    }
+
+    /// Reply to a query sent to the terminal.
+    fn terminal_response(&mut self, _terminal: &mut Terminal, _response: TerminalResponse) {}
 
     /// Shutdown request.
     ///
@@ -106,6 +153,59 @@ bitflags! {
     }
 }
 
+/// Translate a key with modifiers to the character sent by legacy terminals.
+///
+/// Returns `None` for functional keys without any legacy character.
+pub fn legacy_glyph(key: char, modifiers: Modifiers) -> Option<char> {
+    // Skip functional keys, which use Unicode's private use area.
+    if ('\u{e000}'..='\u{f8ff}').contains(&key) {
+        return None;
+    }
+
+    let glyph = match key {
+        '\r' => '\n',
+        key if modifiers.contains(Modifiers::CONTROL) => match key {
+            'a'..='z' | 'A'..='Z' => (key.to_ascii_lowercase() as u8 & 0x1f) as char,
+            '/' | '?' => '\x1f',
+            key => key,
+        },
+        key if modifiers.contains(Modifiers::SHIFT) => key.to_uppercase().next()?,
+        key => key,
+    };
+
+    Some(glyph)
+}
+
+/// Reply to a terminal query.
+///
+/// These are received as input, but must never be treated as keystrokes.
+#[allow(unused)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TerminalResponse {
+    /// Cursor position report (CPR), with one-based coordinates.
+    CursorPosition { column: u16, line: u16 },
+    /// Primary device attributes (DA1), listing the supported features.
+    DeviceAttributes(Vec<u16>),
+    /// Device status report (DSR), indicating whether the terminal is ok.
+    Status(bool),
+    /// Private mode report (DECRPM), with the mode's state.
+    ///
+    /// The state is `0` for unknown modes, `1`/`3` for set and `2`/`4` for
+    /// reset modes.
+    Mode { mode: u16, state: u16 },
+    /// Operating system command (OSC) reply, like color queries.
+    Osc(Vec<Vec<u8>>),
+}
+
+/// Keyboard arrow keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArrowKey {
+    Up,
+    Down,
+    Right,
+    Left,
+}
+
 /// Mouse cursor button state.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ButtonState {
@@ -146,3 +246,13 @@ bitflags! {
         const CONTROL = 0b0001_0000;
     }
 }
+
+impl Modifiers {
+    /// Create modifiers from the parameter of a CSI key sequence.
+    ///
+    /// Keyboard sequences encode modifiers as `1 + bits`, with the same bit
+    /// order as mouse events, just without the offset.
+    pub fn from_csi(param: u16) -> Self {
+        Self::from_bits_truncate((param.saturating_sub(1) << 2) as u8)
+    }
+}
@@ -1,27 +1,66 @@
-use std::collections::HashMap;
-use std::io::{self, Read, Write};
-use std::mem::{self, MaybeUninit};
+use std::cell::{Cell, RefCell};
+use std::cmp::min;
+use std::collections::BTreeMap;
 use std::ops::{Deref, DerefMut};
-use std::os::unix::io::AsRawFd;
-use std::ptr;
-use std::str::{self, FromStr};
-
-use bitflags::bitflags;
-use libc::{self, SIGCONT, SIGHUP, SIGINT, SIGTERM, SIGTSTP, SIGWINCH};
-use mio::unix::SourceFd;
-use mio::{Events, Interest, Poll, Token};
+use std::path::Path;
+use std::time::Instant;
+use std::{io, mem, str};
+
+pub use sketch::color::{Color, NamedColor, Rgb, TextStyle};
+use sketch::palette::Palette;
 use vte::{Parser, Perform};
 
-use crate::terminal::event::EventHandler;
+use crate::terminal::backend::{NullBackend, StdoutBackend, TerminalBackend};
+use crate::terminal::capabilities::{Capabilities, CAPABILITY_QUERY};
+use crate::terminal::event::{EventHandler, MouseEvent, SgrEvent};
+use crate::terminal::recording::Recorder;
 
+pub mod backend;
+mod capabilities;
+#[cfg(unix)]
+mod control;
 pub mod event;
 mod parser;
+pub mod recording;
+#[cfg(unix)]
 mod signal;
+#[cfg(unix)]
+mod unix;
+#[cfg(windows)]
+mod windows;
+
+pub use platform::use_controlling_tty;
+#[cfg(unix)]
+use unix as platform;
+#[cfg(windows)]
+use windows as platform;
+
+/// Enable the kitty keyboard protocol's disambiguated escape codes.
+const KITTY_KEYBOARD_PUSH: &str = "\x1b[>1u";
+
+/// Save the window and icon title on the terminal's title stack.
+const TITLE_PUSH: &str = "\x1b[22;0t";
+/// Restore the window and icon title from the terminal's title stack.
+const TITLE_POP: &str = "\x1b[23;0t";
+
+/// Escape terminating bracketed pastes.
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+/// Request a report of the cursor position.
+const CURSOR_POSITION_QUERY: &str = "\x1b[6n";
+
+thread_local! {
+    /// Destination of all terminal output written on this thread.
+    static BACKEND: RefCell<Box<dyn TerminalBackend>> = RefCell::new(Box::new(StdoutBackend));
+
+    /// Optional features supported by the terminal this thread writes to.
+    static CAPABILITIES: Cell<Capabilities> = const { Cell::new(Capabilities::ALL) };
 
-/// Mio token for reading from STDIN.
-const STDIN_TOKEN: Token = Token(0);
-/// Mio token for signal handling.
-const SIGNAL_TOKEN: Token = Token(1);
+    /// Number of screen lines above the inline region.
+    ///
+    /// This is `None` while the alternate screen is used.
+    static INLINE_OFFSET: Cell<Option<u16>> = const { Cell::new(None) };
+}
 
 /// Terminal emulation state.
 ///
@@ -35,22 +74,147 @@ pub struct Terminal {
     event_handler: Box<dyn EventHandler>,
 
     /// Terminal attributes for reset after we're done.
-    original_termios: libc::termios,
+    ///
+    /// This is `None` for headless terminals, which never touch the TTY.
+    tty_state: Option<platform::TtyState>,
     /// Terminal modes for reset after we're done.
     modes: TerminalModes,
 
+    /// Recorder for all terminal input.
+    recorder: Option<Recorder>,
+
+    /// Platform specific source of input and signals.
+    ///
+    /// This is `None` for headless terminals and while [`Self::run`] is
+    /// active.
+    event_loop: Option<platform::EventLoop>,
+
+    /// Raw bytes of the bracketed paste in progress.
+    paste: Option<Vec<u8>>,
+
+    /// Raw bytes of the legacy mouse report in progress.
+    legacy_mouse: Option<Vec<u8>>,
+    /// Last button pressed with legacy mouse reporting.
+    ///
+    /// Legacy releases do not report which button was released.
+    legacy_button: u8,
+
+    /// Whether mouse reporting is enabled.
+    mouse: bool,
+
+    /// Content of the XTGETTCAP reply in progress.
+    capability_reply: Option<Vec<u8>>,
+
+    /// Whether the kitty keyboard protocol is enabled.
+    kitty_keyboard: bool,
+
+    /// Number of lines reserved below the prompt in inline mode.
+    inline_lines: Option<u16>,
+    /// Whether the position of the inline region is still unknown.
+    inline_query: bool,
+
+    /// Window title set by the application.
+    title: Option<String>,
+
+    /// Time at which the event handler's timer fires.
+    deadline: Option<Instant>,
+
+    /// Whether the next `ESC \\` terminates an OSC or DCS string.
+    ///
+    /// This prevents string terminators of terminal responses from being
+    /// interpreted as Alt+\\.
+    string_terminator: bool,
+
     /// Shared state to allow for termination from the parser.
     terminated: bool,
+    /// Error causing the termination.
+    error: Option<io::Error>,
 }
 
 impl Terminal {
-    pub fn new() -> Self {
+    pub fn new() -> io::Result<Self> {
+        CAPABILITIES.set(Capabilities::from_env());
+        INLINE_OFFSET.set(None);
+
+        Ok(Terminal {
+            event_loop: Some(platform::EventLoop::new()?),
+            modes: TerminalModes::default(),
+            dimensions: platform::tty_dimensions(),
+            tty_state: Some(platform::setup_tty()),
+            event_handler: Box::new(()),
+            recorder: None,
+            string_terminator: false,
+            kitty_keyboard: false,
+            inline_lines: None,
+            inline_query: false,
+            title: None,
+            paste: None,
+            legacy_mouse: None,
+            legacy_button: 0,
+            mouse: false,
+            capability_reply: None,
+            deadline: None,
+            terminated: false,
+            error: None,
+        })
+    }
+
+    /// Create a terminal without any interface, for replaying recorded input.
+    ///
+    /// All output written on the current thread is discarded.
+    pub fn headless(dimensions: Dimensions) -> Self {
+        Self::with_backend(dimensions, NullBackend)
+    }
+
+    /// Create a terminal without any interface, writing to a custom backend.
+    ///
+    /// All output written on the current thread is passed to the backend,
+    /// instead of STDOUT.
+    pub fn with_backend(dimensions: Dimensions, backend: impl TerminalBackend + 'static) -> Self {
+        BACKEND.with_borrow_mut(|current| *current = Box::new(backend));
+        CAPABILITIES.set(Capabilities::ALL);
+        INLINE_OFFSET.set(None);
+
         Terminal {
+            dimensions,
             modes: TerminalModes::default(),
-            dimensions: Self::tty_dimensions(),
-            original_termios: setup_tty(),
+            tty_state: None,
             event_handler: Box::new(()),
+            recorder: None,
+            event_loop: None,
+            string_terminator: false,
+            kitty_keyboard: false,
+            inline_lines: None,
+            inline_query: false,
+            title: None,
+            paste: None,
+            legacy_mouse: None,
+            legacy_button: 0,
+            mouse: false,
+            capability_reply: None,
+            deadline: None,
             terminated: false,
+            error: None,
+        }
+    }
+
+    /// Record all input received by [`run`] to a file.
+    ///
+    /// [`run`]: Self::run
+    pub fn record_input(&mut self, path: &Path) -> io::Result<()> {
+        self.recorder = Some(Recorder::create(path, self.dimensions)?);
+        Ok(())
+    }
+
+    /// Accept commands on a Unix domain socket while [`run`] is active.
+    ///
+    /// Commands are passed to [`EventHandler::control`].
+    ///
+    /// [`run`]: Self::run
+    pub fn listen(&mut self, path: &Path) -> io::Result<()> {
+        match &mut self.event_loop {
+            Some(event_loop) => event_loop.listen(path),
+            None => Err(io::Error::new(io::ErrorKind::Unsupported, "headless terminal")),
         }
     }
 
@@ -68,67 +232,78 @@ impl Terminal {
     /// registered to this terminal will be called whenever a new event is
     /// received.
     pub fn run(&mut self) -> io::Result<()> {
+        let mut event_loop = self
+            .event_loop
+            .take()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "headless terminal"))?;
+
         // Setup terminal escape sequence parser.
         let mut parser = Parser::new();
 
-        // Setup mio.
-        let mut poll = Poll::new()?;
-        let mut events = Events::with_capacity(1024);
-
-        // Register STDIN read interest.
-        let mut stdin = io::stdin();
-        poll.registry().register(&mut SourceFd(&0), STDIN_TOKEN, Interest::READABLE)?;
-
-        // Register signal handlers.
-        let mut signal_receiver = signal::mio_receiver()?;
-        poll.registry().register(&mut signal_receiver, SIGNAL_TOKEN, Interest::READABLE)?;
-        signal::register(SIGWINCH)?;
-        signal::register(SIGTSTP)?;
-        signal::register(SIGCONT)?;
-        signal::register(SIGTERM)?;
-        signal::register(SIGINT)?;
-        signal::register(SIGHUP)?;
-
-        // Reserve buffer for reading from STDIN.
-        let mut buf = [0; u16::MAX as usize];
-
         while !self.terminated {
-            // Stop if we run into a polling error we cannot handle ourselves.
-            if let Err(err) = poll.poll(&mut events, None) {
-                if err.kind() != io::ErrorKind::Interrupted {
-                    return Err(err);
-                }
-            }
-
-            for event in &events {
-                match event.token() {
-                    STDIN_TOKEN => {
-                        // Pass STDIN to parser.
-                        let read = stdin.read(&mut buf)?;
-
-                        if buf[..read] == [b'\x1b'] {
+            let timeout = self.deadline.map(|deadline| deadline - Instant::now().min(deadline));
+            for event in event_loop.poll(timeout)? {
+                match event {
+                    PlatformEvent::Input(bytes) => {
+                        if bytes == [b'\x1b'] && self.paste.is_none() {
                             // Treat a single ESC read as a key press.
+                            self.record(recording::ESCAPE);
                             self.print('\x1b');
                         } else {
+                            self.record(&bytes);
+
                             // Pass read bytes to VT parser.
-                            for byte in &buf[..read] {
-                                parser.advance(self, *byte);
+                            for byte in bytes {
+                                self.advance(&mut parser, byte);
                             }
                         }
                     },
-                    SIGNAL_TOKEN => {
-                        let mut signal = [0; 4];
-                        while signal_receiver.read_exact(&mut signal).is_ok() {
-                            let signal = unsafe { mem::transmute::<[u8; 4], libc::c_int>(signal) };
-                            self.handle_signal(signal)?;
-                        }
+                    PlatformEvent::Signal(signal) => self.handle_signal(signal)?,
+                    PlatformEvent::Command(client, command) => {
+                        let mut response = String::new();
+                        self.handle_event(|handler, terminal| {
+                            response = handler.control(terminal, &command)
+                        });
+                        event_loop.reply(client, &response);
                     },
-                    _ => unreachable!(),
                 }
             }
+
+            if self.deadline.is_some_and(|deadline| deadline <= Instant::now()) {
+                self.deadline = None;
+                self.handle_event(|handler, terminal| handler.timer(terminal));
+            }
         }
 
-        Ok(())
+        self.error.take().map_or(Ok(()), Err)
+    }
+
+    /// Pass recorded input to the event handler.
+    ///
+    /// A shutdown is requested once all input was processed, unless the
+    /// recording terminated the event loop already.
+    pub fn replay(&mut self, input: &[u8]) -> io::Result<()> {
+        let mut parser = Parser::new();
+        for byte in input {
+            if self.terminated {
+                break;
+            }
+            self.advance(&mut parser, *byte);
+        }
+
+        if !self.terminated {
+            self.handle_event(|handler, terminal| handler.shutdown(terminal));
+        }
+
+        self.error.take().map_or(Ok(()), Err)
+    }
+
+    /// Schedule a call to [`EventHandler::timer`], replacing the previous
+    /// timer.
+    ///
+    /// Timers never fire while replaying input.
+    pub fn set_timer(&mut self, deadline: Option<Instant>) {
+        self.deadline = deadline;
     }
 
     /// Shutdown the terminal event handler.
@@ -136,53 +311,49 @@ impl Terminal {
         self.terminated = true;
     }
 
-    /// Handle a POSIX signal.
+    /// Shutdown the terminal event handler, reporting an error.
+    pub fn fail(&mut self, error: io::Error) {
+        self.error = Some(error);
+        self.terminated = true;
+    }
+
+    /// Handle a process signal.
     ///
     /// # Errors
     ///
-    /// This function will raise an [`io::ErrorKind::Interrupted`] error if the
-    /// signal requested an application shutdown.
-    fn handle_signal(&mut self, signal: libc::c_int) -> io::Result<()> {
+    /// This function will raise an [`io::ErrorKind::BrokenPipe`] error if the
+    /// controlling terminal was closed.
+    fn handle_signal(&mut self, signal: Signal) -> io::Result<()> {
         match signal {
             // Try to tear everything down nicely when the controlling terminal died.
-            SIGHUP => return Err(io::ErrorKind::BrokenPipe.into()),
-            // Allow application to handle SIGINT/SIGTERM shutdown requests.
-            SIGINT | SIGTERM => {
+            Signal::Hangup => return Err(io::ErrorKind::BrokenPipe.into()),
+            // Allow application to handle shutdown requests.
+            Signal::Shutdown => {
+                self.record(recording::SHUTDOWN);
                 self.handle_event(|handler, terminal| handler.shutdown(terminal));
             },
-            // Handle terminal resize.
-            SIGWINCH => self.update_size(),
-            SIGCONT => {
+            Signal::Resize => self.update_size(),
+            Signal::Continue => {
                 // Restore the terminal state.
                 self.restore_modes();
-                self.original_termios = setup_tty();
-
-                // Restore the SIGTSTP signal handler.
-                signal::register(SIGTSTP)?;
+                self.reserve_inline_lines();
+                self.tty_state = Some(platform::setup_tty());
+                platform::resume()?;
 
                 // Check for potential dimension changes.
                 //
-                // This is necessary since `SIGWINCH` is not sent when the application is in
-                // the background.
+                // This is necessary since resizes are not reported while the application is
+                // in the background.
                 self.update_size();
 
                 // Request application state update.
                 self.handle_event(|handler, terminal| handler.redraw(terminal));
             },
-            SIGTSTP => {
+            Signal::Suspend => {
                 // Clear terminal state.
                 self.reset();
-
-                // Remove SIGTSTP handler and self-request another suspension.
-                signal::unregister(SIGTSTP)?;
-                unsafe {
-                    let result = libc::raise(SIGTSTP);
-                    if result != 0 {
-                        return Err(io::Error::last_os_error());
-                    }
-                }
+                platform::suspend()?;
             },
-            _ => unreachable!(),
         }
 
         Ok(())
@@ -198,6 +369,25 @@ impl Terminal {
         self.modes.insert(mode, enabled);
     }
 
+    /// Run `f` as a synchronized update.
+    ///
+    /// Supporting terminals only render the output once the update is done,
+    /// which avoids flickering. Nested calls are part of the outermost update.
+    pub fn synchronized<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let nested = self.modes.get(&TerminalMode::SynchronizedUpdate) == Some(&true);
+        if !nested {
+            self.set_mode(TerminalMode::SynchronizedUpdate, true);
+        }
+
+        let result = f(self);
+
+        if !nested {
+            self.set_mode(TerminalMode::SynchronizedUpdate, false);
+        }
+
+        result
+    }
+
     /// Set the color for all following characters.
     pub fn set_color(foreground: Color, background: Color) {
         Self::set_foreground(foreground);
@@ -206,17 +396,48 @@ impl Terminal {
 
     /// Set foreground color for all following characters.
     pub fn set_foreground(color: Color) {
-        Self::write(color.escape(true));
+        Self::write(Self::supported_color(color).escape(true));
     }
 
     /// Set background color for all following characters.
     pub fn set_background(color: Color) {
-        Self::write(color.escape(false));
+        Self::write(Self::supported_color(color).escape(false));
+    }
+
+    /// Approximate colors the terminal cannot display.
+    ///
+    /// RGB colors are replaced by the closest color of the 256 color palette,
+    /// unless the terminal supports truecolor.
+    pub fn supported_color(color: Color) -> Color {
+        match color {
+            Color::Rgb(_) if !CAPABILITIES.get().truecolor => Palette::Xterm256.nearest(color),
+            color => color,
+        }
     }
 
     /// Clear the terminal screen.
+    ///
+    /// In inline mode, only the inline region is cleared.
     pub fn clear() {
-        Self::write("\x1b[2J");
+        match INLINE_OFFSET.get() {
+            Some(_) => {
+                Self::write("\x1b7");
+                Self::goto(1, 1);
+                Self::clear_screen_end();
+                Self::write("\x1b8");
+            },
+            None => Self::write("\x1b[2J"),
+        }
+    }
+
+    /// Clear everything from the cursor to the end of the line.
+    pub fn clear_line_end() {
+        Self::write("\x1b[K");
+    }
+
+    /// Clear everything from the cursor to the end of the screen.
+    pub fn clear_screen_end() {
+        Self::write("\x1b[J");
     }
 
     /// Decrease intensity for the following characters.
@@ -224,6 +445,11 @@ impl Terminal {
         Self::write("\x1b[2m");
     }
 
+    /// Swap foreground and background colors for the following characters.
+    pub fn set_reverse() {
+        Self::write("\x1b[7m");
+    }
+
     /// Set the text style.
     pub fn set_style(style: TextStyle) {
         Self::write(style.escape())
@@ -241,9 +467,24 @@ impl Terminal {
 
     /// Write some text at the current cursor location.
     pub fn write<T: Into<String>>(text: T) {
-        let mut stdout = io::stdout();
-        let _ = stdout.write(text.into().as_bytes());
-        let _ = stdout.flush();
+        let text = text.into();
+        BACKEND.with_borrow_mut(|backend| backend.write(text.as_bytes()));
+    }
+
+    /// Restrict scrolling to the lines from `top` to `bottom`.
+    pub fn set_scroll_region(top: usize, bottom: usize) {
+        let offset = INLINE_OFFSET.get().unwrap_or_default() as usize;
+        Self::write(format!("\x1b[{};{}r", top + offset, bottom + offset));
+    }
+
+    /// Reset the scrolling region to the entire screen.
+    pub fn reset_scroll_region() {
+        Self::write("\x1b[r");
+    }
+
+    /// Scroll the content of the scrolling region down by `count` lines.
+    pub fn scroll_down(count: usize) {
+        Self::write(format!("\x1b[{}T", count));
     }
 
     /// Repeat the last character `count` times.
@@ -253,16 +494,114 @@ impl Terminal {
 
     /// Move the cursor to a specific point in the grid.
     ///
-    /// The indexing for both column and line is 1-based.
+    /// The indexing for both column and line is 1-based. In inline mode, lines
+    /// are relative to the start of the inline region.
     pub fn goto(column: usize, line: usize) {
+        let line = line.max(1) + INLINE_OFFSET.get().unwrap_or_default() as usize;
         Self::write(format!("\x1b[{};{}H", line, column));
     }
 
+    /// Store text in the clipboard using OSC 52.
+    ///
+    /// This also reaches the local clipboard when running over SSH.
+    pub fn set_clipboard(text: &str) {
+        Self::write(format!("\x1b]52;c;{}\x07", base64(text.as_bytes())));
+    }
+
+    /// Set the terminal window title.
+    ///
+    /// The original title is saved on the terminal's title stack and restored
+    /// once the terminal is reset.
+    pub fn set_title(&mut self, title: &str) {
+        // Drop control characters, which could terminate the escape sequence.
+        let title: String = title.chars().filter(|c| !c.is_control()).collect();
+        if self.title.as_ref() == Some(&title) {
+            return;
+        }
+
+        if self.title.is_none() {
+            Self::write(TITLE_PUSH);
+        }
+        Self::write(format!("\x1b]0;{title}\x07"));
+        self.title = Some(title);
+    }
+
+    /// Draw in lines below the prompt, instead of the alternate screen.
+    ///
+    /// Until the terminal reports the cursor position, the inline region is
+    /// assumed to be at the bottom of the screen.
+    pub fn set_inline(&mut self, lines: u16) {
+        let screen_lines = self.dimensions.lines;
+        let lines = lines.clamp(1, screen_lines.max(1));
+        INLINE_OFFSET.set(Some(screen_lines - lines));
+        self.inline_lines = Some(lines);
+        self.dimensions.lines = lines;
+
+        self.reserve_inline_lines();
+    }
+
+    /// Make room for the inline region below the cursor and query its position.
+    fn reserve_inline_lines(&mut self) {
+        let Some(lines) = self.inline_lines else { return };
+        Self::write(format!("\r{}{CURSOR_POSITION_QUERY}", "\n".repeat(lines as usize - 1)));
+        self.inline_query = true;
+    }
+
+    /// Move the inline region after the terminal reported the cursor position.
+    ///
+    /// The cursor is on the last line of the region, once all lines have been
+    /// reserved.
+    fn set_inline_position(&mut self, line: u16) {
+        let Some(lines) = self.inline_lines.filter(|_| mem::take(&mut self.inline_query)) else {
+            return;
+        };
+
+        let offset = line.saturating_sub(lines);
+        let old_offset = INLINE_OFFSET.replace(Some(offset)).unwrap_or_default();
+        if offset == old_offset {
+            return;
+        }
+
+        // Remove everything drawn at the assumed position.
+        INLINE_OFFSET.set(Some(offset.min(old_offset)));
+        Self::goto(1, 1);
+        Self::clear_screen_end();
+        INLINE_OFFSET.set(Some(offset));
+
+        self.handle_event(|handler, terminal| handler.redraw(terminal));
+    }
+
+    /// Convert a screen line to a line of the inline region.
+    ///
+    /// Lines outside of the region are clamped to its edges.
+    fn inline_line(&self, line: u16) -> u16 {
+        match INLINE_OFFSET.get() {
+            Some(offset) => line.saturating_sub(offset).clamp(1, self.dimensions.lines),
+            None => line,
+        }
+    }
+
     /// Reset all terminal modifications.
     fn reset(&self) {
         Self::reset_modes();
+
+        // Clear only the inline region, leaving the cursor at its start.
+        if INLINE_OFFSET.get().is_some() {
+            Self::reset_sgr();
+            Self::goto(1, 1);
+            Self::clear_screen_end();
+        }
+
+        if self.kitty_keyboard {
+            Self::write("\x1b[<u");
+        }
+        if self.title.is_some() {
+            Self::write(TITLE_POP);
+        }
         Self::set_cursor_shape(CursorShape::Default);
-        reset_tty(self.original_termios);
+        if let Some(tty_state) = &self.tty_state {
+            platform::reset_tty(tty_state);
+        }
     }
 
     /// Restore terminal modes from internal state.
@@ -271,24 +610,167 @@ impl Terminal {
         for (mode, value) in self.modes.iter() {
             Self::set_mode_raw(*mode, *value);
         }
+        if self.kitty_keyboard {
+            Self::write(KITTY_KEYBOARD_PUSH);
+        }
+        if let Some(title) = &self.title {
+            Self::write(format!("{TITLE_PUSH}\x1b]0;{title}\x07"));
+        }
+    }
+
+    /// Request the kitty keyboard protocol.
+    ///
+    /// The protocol is only enabled once the terminal reports support for it,
+    /// all other terminals keep using legacy key encodings.
+    pub fn request_kitty_keyboard(&self) {
+        Self::write("\x1b[?u");
+    }
+
+    /// Enable mouse reporting for clicks and motion.
+    ///
+    /// Terminals without SGR mouse support use the legacy X10 encoding.
+    pub fn enable_mouse(&mut self) {
+        self.mouse = true;
+        self.set_mouse_modes();
+    }
+
+    /// Query support for optional terminal features.
+    ///
+    /// Until the terminal responds, support is guessed based on the
+    /// environment. Terminals which do not respond keep using these guesses.
+    pub fn request_capabilities(&self) {
+        Self::write(CAPABILITY_QUERY);
+    }
+
+    /// Switch mouse encoding after the terminal reported SGR mouse support.
+    fn set_sgr_mouse(&mut self, supported: bool) {
+        let capabilities = CAPABILITIES.get();
+        if capabilities.sgr_mouse != supported {
+            CAPABILITIES.set(Capabilities { sgr_mouse: supported, ..capabilities });
+            if self.mouse {
+                self.set_mouse_modes();
+            }
+        }
+    }
+
+    /// Enable truecolor output after the terminal reported support for it.
+    fn enable_truecolor(&mut self) {
+        let capabilities = CAPABILITIES.get();
+        if !capabilities.truecolor {
+            CAPABILITIES.set(Capabilities { truecolor: true, ..capabilities });
+            self.handle_event(|handler, terminal| handler.redraw(terminal));
+        }
+    }
+
+    /// Set the mouse modes for the supported mouse encoding.
+    fn set_mouse_modes(&mut self) {
+        let sgr_mouse = CAPABILITIES.get().sgr_mouse;
+        self.set_mode(TerminalMode::SgrMouse, sgr_mouse);
+        self.set_mode(TerminalMode::NormalMouse, !sgr_mouse);
+        self.set_mode(TerminalMode::MouseMotion, true);
+    }
+
+    /// Dispatch a mouse report using the legacy X10 encoding.
+    ///
+    /// All values are offset by 32, to make them printable.
+    fn legacy_mouse_input(&mut self, button: u8, column: u8, line: u8) {
+        let button = button.wrapping_sub(32);
+        let column = column.wrapping_sub(32) as u16;
+        let line = self.inline_line(line.wrapping_sub(32) as u16);
+
+        // Restore the released button from the last press.
+        let event_bits = SgrEvent::DOWN | SgrEvent::EXTENDED1 | SgrEvent::EXTENDED2;
+        let modifier_bits = button & !SgrEvent::BUTTONS.bits() & !SgrEvent::DOWN.bits();
+        let (button, action) = if button & event_bits.bits() == 0
+            && button & SgrEvent::NONE.bits() == SgrEvent::NONE.bits()
+        {
+            (self.legacy_button | modifier_bits, 'm')
+        } else {
+            if button & event_bits.bits() == 0 {
+                self.legacy_button = button & SgrEvent::BUTTONS.bits();
+            }
+            (button, 'M')
+        };
+
+        let event = MouseEvent::new(button as u16, column, line, action);
+        self.handle_event(|handler, terminal| handler.mouse_input(terminal, event));
+    }
+
+    /// Enable the kitty keyboard protocol after the terminal confirmed support.
+    fn enable_kitty_keyboard(&mut self) {
+        if !self.kitty_keyboard {
+            self.kitty_keyboard = true;
+            Self::write(KITTY_KEYBOARD_PUSH);
+        }
     }
 
     /// Check if the terminal dimensions have changed.
     fn update_size(&mut self) {
+        let mut dimensions = platform::tty_dimensions();
+
+        // Keep the inline region on the screen.
+        if let Some(lines) = self.inline_lines {
+            let lines = min(lines, dimensions.lines);
+            let offset = INLINE_OFFSET.get().unwrap_or_default();
+            INLINE_OFFSET.set(Some(min(offset, dimensions.lines - lines)));
+            dimensions.lines = lines;
+        }
+
+        self.set_dimensions(dimensions);
+    }
+
+    /// Update the terminal dimensions.
+    fn set_dimensions(&mut self, dimensions: Dimensions) {
         // Skip resize that do not change columns/lines.
-        let dimensions = Self::tty_dimensions();
         if dimensions != self.dimensions {
             self.dimensions = dimensions;
 
+            if let Some(recorder) = &mut self.recorder {
+                recorder.resize(dimensions);
+            }
+
             // Notify event handler about the change.
             self.handle_event(|handler, terminal| handler.resize(terminal, dimensions));
         }
     }
 
+    /// Pass input to the parser, collecting bracketed pastes without parsing
+    /// them.
+    fn advance(&mut self, parser: &mut Parser, byte: u8) {
+        if let Some(report) = &mut self.legacy_mouse {
+            report.push(byte);
+            if let [button, column, line] = report[..] {
+                self.legacy_mouse = None;
+                self.legacy_mouse_input(button, column, line);
+            }
+            return;
+        }
+
+        let Some(paste) = &mut self.paste else {
+            parser.advance(self, byte);
+            return;
+        };
+
+        paste.push(byte);
+        if let Some(text) = paste.strip_suffix(PASTE_END) {
+            let text = String::from_utf8_lossy(text).into_owned();
+            self.paste = None;
+            self.handle_event(|handler, terminal| handler.paste(terminal, &text));
+        }
+    }
+
+    /// Append input to the recording, if input is being recorded.
+    fn record(&mut self, bytes: &[u8]) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.input(bytes);
+        }
+    }
+
     /// Dispatch an event with a reference to the terminal attached.
     fn handle_event<F: FnMut(&mut dyn EventHandler, &mut Terminal)>(&mut self, mut f: F) {
         let mut event_handler = mem::replace(&mut self.event_handler, Box::new(()));
         f(event_handler.as_mut(), self);
+        event_handler.flush(self);
         self.event_handler = event_handler;
     }
 
@@ -308,19 +790,6 @@ impl Terminal {
             Self::write(format!("\x1b[?{}l", mode as u16));
         }
     }
-
-    /// Query terminal dimensions in columns and lines.
-    fn tty_dimensions() -> Dimensions {
-        unsafe {
-            let mut winsize = MaybeUninit::<libc::winsize>::uninit();
-            let result = libc::ioctl(0, libc::TIOCGWINSZ, winsize.as_mut_ptr());
-            if result != -1 {
-                return winsize.assume_init().into();
-            }
-        }
-
-        Dimensions::default()
-    }
 }
 
 impl Drop for Terminal {
@@ -343,45 +812,75 @@ pub struct Dimensions {
     pub lines: u16,
 }
 
-impl From<libc::winsize> for Dimensions {
-    fn from(winsize: libc::winsize) -> Self {
-        Self { columns: winsize.ws_col, lines: winsize.ws_row }
-    }
+/// Event received by the platform's event loop.
+enum PlatformEvent {
+    /// Bytes read from STDIN.
+    Input(Vec<u8>),
+    /// Process signal.
+    Signal(Signal),
+    /// Command sent by a control socket client.
+    #[cfg_attr(windows, allow(unused))]
+    Command(usize, String),
+}
+
+/// Platform independent process signals.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Signal {
+    /// Controlling terminal was closed.
+    Hangup,
+    /// Application shutdown request.
+    Shutdown,
+    /// Terminal was resized.
+    Resize,
+    /// Process was continued after a suspension.
+    #[cfg_attr(windows, allow(unused))]
+    Continue,
+    /// Process suspension request.
+    #[cfg_attr(windows, allow(unused))]
+    Suspend,
 }
 
 /// Terminal modes.
-#[derive(Copy, Clone, Hash, PartialEq, Eq)]
+///
+/// Modes are restored ordered by their number, which enables motion reporting
+/// after normal mouse reporting. Enabling one mouse reporting mode disables all
+/// others.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum TerminalMode {
     LineWrap = 7,
     ShowCursor = 25,
     SgrMouse = 1006,
+    NormalMouse = 1000,
     MouseMotion = 1003,
     FocusInOut = 1004,
     AltScreen = 1049,
     BracketedPaste = 2004,
+    SynchronizedUpdate = 2026,
 }
 
 /// Track active terminal modes.
-pub struct TerminalModes(HashMap<TerminalMode, bool>);
+pub struct TerminalModes(BTreeMap<TerminalMode, bool>);
 
 impl Default for TerminalModes {
     fn default() -> Self {
         // Fill the modes with what should be the defaults for every terminal.
-        let mut modes = HashMap::new();
+        let mut modes = BTreeMap::new();
         modes.insert(TerminalMode::LineWrap, true);
         modes.insert(TerminalMode::ShowCursor, true);
         modes.insert(TerminalMode::SgrMouse, false);
+        modes.insert(TerminalMode::NormalMouse, false);
         modes.insert(TerminalMode::MouseMotion, false);
         modes.insert(TerminalMode::FocusInOut, false);
         modes.insert(TerminalMode::AltScreen, false);
         modes.insert(TerminalMode::BracketedPaste, false);
+        modes.insert(TerminalMode::SynchronizedUpdate, false);
 
         Self(modes)
     }
 }
 
 impl Deref for TerminalModes {
-    type Target = HashMap<TerminalMode, bool>;
+    type Target = BTreeMap<TerminalMode, bool>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -394,145 +893,25 @@ impl DerefMut for TerminalModes {
     }
 }
 
-/// Terminal color.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum Color {
-    Named(NamedColor),
-    Indexed(u8),
-    Rgb(Rgb),
-}
-
-impl Default for Color {
-    fn default() -> Self {
-        Self::Named(NamedColor::Default)
-    }
-}
-
-impl Color {
-    pub fn escape(&self, foreground: bool) -> String {
-        match (self, foreground) {
-            // Foreground:
-            (Color::Named(color), true) => format!("\x1b[3{}m", *color as u8),
-            (Color::Indexed(color), true) => format!("\x1b[38:5:{}m", color),
-            (Color::Rgb(Rgb { r, g, b }), true) => format!("\x1b[38:2:{}:{}:{}m", r, g, b),
-            // Background:
-            (Color::Named(color), false) => format!("\x1b[4{}m", *color as u8),
-            (Color::Indexed(color), false) => format!("\x1b[48:5:{}m", color),
-            (Color::Rgb(Rgb { r, g, b }), false) => format!("\x1b[48:2:{}:{}:{}m", r, g, b),
-        }
-    }
-}
-
-/// CTerm color.
-#[allow(unused)]
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum NamedColor {
-    Black = 0,
-    Red = 1,
-    Green = 2,
-    Yellow = 3,
-    Blue = 4,
-    Magenta = 5,
-    Cyan = 6,
-    White = 7,
-    Default = 9,
-}
-
-/// RGB color.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct Rgb {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
-}
-
-impl FromStr for Rgb {
-    type Err = ();
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 6 {
-            return Err(());
-        }
-
-        Ok(Rgb {
-            r: u8::from_str_radix(&s[0..2], 16).map_err(|_| ())?,
-            g: u8::from_str_radix(&s[2..4], 16).map_err(|_| ())?,
-            b: u8::from_str_radix(&s[4..6], 16).map_err(|_| ())?,
-        })
-    }
-}
-
-/// Enable raw terminal input handling.
-fn setup_tty() -> libc::termios {
-    unsafe {
-        let mut previous_termios = MaybeUninit::uninit();
-        change_terminal_attributes(|termios| {
-            ptr::write(&mut previous_termios, MaybeUninit::new(*termios));
-            termios.c_lflag &= !(libc::ECHO | libc::ICANON);
-        });
-        previous_termios.assume_init()
-    }
-}
-
-/// Disable raw terminal input handling.
-fn reset_tty(nonraw_termios: libc::termios) {
-    change_terminal_attributes(|termios| *termios = nonraw_termios);
-}
-
-/// Change the tty properties.
-fn change_terminal_attributes(change_attributes: impl FnOnce(&mut libc::termios)) {
-    let mut termios = MaybeUninit::uninit();
-    let stdout_fd = io::stdout().as_raw_fd();
-
-    unsafe {
-        libc::tcgetattr(stdout_fd, termios.as_mut_ptr());
-        let mut termios = termios.assume_init();
-        change_attributes(&mut termios);
-        libc::tcsetattr(stdout_fd, 0, &termios);
-    }
-}
-
-bitflags! {
-    /// Text character style.
-    #[derive(PartialEq, Eq, Copy, Clone, Debug)]
-    pub struct TextStyle: u8 {
-        const BOLD    = 1 << 0;
-        const ITALICS = 1 << 1;
-    }
-}
-
-impl TextStyle {
-    /// Get the escape sequence to enable this style.
-    pub fn escape(&self) -> &'static str {
-        if self.contains(Self::BOLD | Self::ITALICS) {
-            "\x1b[1;3m"
-        } else if self.contains(Self::BOLD) {
-            "\x1b[1;23m"
-        } else if self.contains(Self::ITALICS) {
-            "\x1b[21;3m"
-        } else {
-            "\x1b[21;23m"
-        }
-    }
-
-    /// Get human-readable name of the current mode.
-    pub fn name(&self) -> &'static str {
-        if self.contains(Self::BOLD | Self::ITALICS) {
-            "bold italic"
-        } else if self.contains(Self::BOLD) {
-            "bold"
-        } else if self.contains(Self::ITALICS) {
-            "italic"
-        } else {
-            "default"
+/// Encode bytes using the standard base64 alphabet with padding.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |bits, (i, byte)| bits | (*byte as u32) << (16 - i * 8));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(ALPHABET[(bits >> (18 - i * 6)) as usize & 0x3f] as char),
+                false => encoded.push('='),
+            }
         }
     }
-}
 
-impl Default for TextStyle {
-    fn default() -> Self {
-        TextStyle::empty()
-    }
+    encoded
 }
 
 /// Helper trait to implement escape stripping on STD strings.
@@ -568,3 +947,82 @@ impl<B: AsRef<[u8]>> EscapeStripper for B {
         stripped.text
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::terminal::backend::TestBackend;
+    use crate::terminal::event::{ButtonState, Modifiers, MouseButton};
+
+    #[test]
+    fn base64_padding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64("┌─┐".as_bytes()), "4pSM4pSA4pSQ");
+    }
+
+    #[test]
+    fn legacy_mouse() {
+        struct Mouse(Rc<RefCell<Vec<MouseEvent>>>);
+        impl EventHandler for Mouse {
+            fn mouse_input(&mut self, _terminal: &mut Terminal, event: MouseEvent) {
+                self.0.borrow_mut().push(event);
+            }
+        }
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut terminal = Terminal::headless(Default::default());
+        terminal.set_event_handler(Box::new(Mouse(events.clone())));
+
+        // Right press, drag, and release with Shift held, followed by SGR mouse
+        // reports.
+        terminal.replay(b"\x1b[M\"!#\x1b[MB$#\x1b[M'$#\x1b[<0;1;1M").unwrap();
+
+        let events = events.borrow();
+        let states: Vec<_> = events.iter().map(|event| event.button_state).collect();
+        assert_eq!(states, [
+            ButtonState::Pressed,
+            ButtonState::Down,
+            ButtonState::Released,
+            ButtonState::Pressed
+        ]);
+        assert!(events[..3].iter().all(|event| event.button == MouseButton::Right));
+        assert!(events[2].modifiers.contains(Modifiers::SHIFT));
+        assert_eq!((events[1].column, events[1].line), (4, 3));
+    }
+
+    #[test]
+    fn inline_region() {
+        struct Mouse(Rc<RefCell<Vec<usize>>>);
+        impl EventHandler for Mouse {
+            fn mouse_input(&mut self, _terminal: &mut Terminal, event: MouseEvent) {
+                self.0.borrow_mut().push(event.line);
+            }
+        }
+
+        let lines = Rc::new(RefCell::new(Vec::new()));
+        let backend = TestBackend::default();
+        let mut terminal =
+            Terminal::with_backend(Dimensions { columns: 10, lines: 10 }, backend.clone());
+        terminal.set_event_handler(Box::new(Mouse(lines.clone())));
+
+        terminal.set_inline(4);
+        assert_eq!(terminal.dimensions.lines, 4);
+        assert_eq!(backend.take_output(), "\r\n\n\n\x1b[6n");
+
+        // The region stays at the bottom until the cursor position is known.
+        Terminal::goto(1, 1);
+        assert_eq!(backend.take_output(), "\x1b[7;1H");
+
+        terminal.replay(b"\x1b[5;1R\x1b[<0;1;3M\x1b[<0;1;9M").unwrap();
+        backend.take_output();
+
+        Terminal::goto(1, 1);
+        assert_eq!(backend.take_output(), "\x1b[2;1H");
+        assert_eq!(*lines.borrow(), [2, 4]);
+    }
+}
@@ -1,14 +1,19 @@
+use std::mem;
+
 use vte::{Params, Perform};
 
-use crate::terminal::event::MouseEvent;
-use crate::terminal::Terminal;
+use crate::terminal::capabilities::RGB_CAPABILITY;
+use crate::terminal::event::{ArrowKey, Modifiers, MouseEvent, TerminalResponse};
+use crate::terminal::{Dimensions, Terminal, TerminalMode};
 
 impl Perform for Terminal {
     fn print(&mut self, c: char) {
+        self.string_terminator = false;
         self.handle_event(|handler, terminal| handler.keyboard_input(terminal, c));
     }
 
     fn execute(&mut self, byte: u8) {
+        self.string_terminator = false;
         match byte {
             // Handle Ctrl+D.
             4 => self.handle_event(|handler, terminal| handler.shutdown(terminal)),
@@ -16,30 +21,139 @@ impl Perform for Terminal {
         }
     }
 
+    fn hook(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, action: char) {
+        // Collect valid XTGETTCAP replies.
+        if action == 'r' && intermediates == [b'+'] && params.iter().next() == Some(&[1]) {
+            self.capability_reply = Some(Vec::new());
+        }
+    }
+
+    fn put(&mut self, byte: u8) {
+        if let Some(reply) = &mut self.capability_reply {
+            reply.push(byte);
+        }
+    }
+
+    fn unhook(&mut self) {
+        // Device control strings are only parsed to drop them with their terminator.
+        self.string_terminator = true;
+
+        // Handle XTGETTCAP replies, which are formatted as `name=value`.
+        let reply = self.capability_reply.take().unwrap_or_default();
+        if reply.split(|byte| *byte == b'=').next() == Some(RGB_CAPABILITY) {
+            self.enable_truecolor();
+        }
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        self.string_terminator = !bell_terminated;
+
+        let params = params.iter().map(|param| param.to_vec()).collect();
+        let response = TerminalResponse::Osc(params);
+        self.handle_event(|handler, terminal| {
+            handler.terminal_response(terminal, response.clone())
+        });
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], _ignore: bool, byte: u8) {
+        // Skip the string terminator following OSC and DCS responses.
+        if mem::take(&mut self.string_terminator) && intermediates.is_empty() && byte == b'\\' {
+            return;
+        }
+
+        // Handle Alt+key, which is sent as escape followed by the key.
+        if intermediates.is_empty() {
+            let glyph = byte as char;
+            self.handle_event(|handler, terminal| handler.alt_keyboard_input(terminal, glyph));
+        }
+    }
+
     fn csi_dispatch(&mut self, params: &Params, intermediates: &[u8], _ignore: bool, action: char) {
+        self.string_terminator = false;
+
         match (action, intermediates) {
             // Handle mouse events.
             ('M', [b'<']) | ('m', [b'<']) => {
                 let params: Vec<u16> = params.into_iter().flatten().copied().collect();
                 if params.len() >= 3 {
-                    let event = MouseEvent::new(params[0], params[1], params[2], action);
+                    let line = self.inline_line(params[2]);
+                    let event = MouseEvent::new(params[0], params[1], line, action);
                     self.handle_event(|handler, terminal| handler.mouse_input(terminal, event));
                 }
             },
+            // Handle legacy mouse events, which are followed by three raw bytes.
+            ('M', []) if params.iter().all(|param| param == [0]) => {
+                self.legacy_mouse = Some(Vec::new())
+            },
+            // Handle arrow keys.
+            ('A' | 'B' | 'C' | 'D', []) => {
+                let key = match action {
+                    'A' => ArrowKey::Up,
+                    'B' => ArrowKey::Down,
+                    'C' => ArrowKey::Right,
+                    _ => ArrowKey::Left,
+                };
+
+                let modifiers = params.into_iter().nth(1).map(|param| param[0]).unwrap_or(1);
+                let modifiers = Modifiers::from_csi(modifiers);
+
+                self.handle_event(|handler, terminal| {
+                    handler.arrow_input(terminal, key, modifiers)
+                });
+            },
             ('I', _) => {
                 self.handle_event(|handler, terminal| handler.focus_changed(terminal, true));
             },
             ('O', _) => {
                 self.handle_event(|handler, terminal| handler.focus_changed(terminal, false));
             },
-            ('~', _) => match params.into_iter().next() {
-                Some([200]) => {
-                    self.handle_event(|handler, _| handler.set_bracketed_paste_state(true))
-                },
-                Some([201]) => {
-                    self.handle_event(|handler, _| handler.set_bracketed_paste_state(false))
-                },
-                _ => (),
+            // Collect everything until the end of a bracketed paste.
+            ('~', _) if params.into_iter().next() == Some(&[200]) => self.paste = Some(Vec::new()),
+            // Handle keys encoded by the kitty keyboard protocol.
+            ('u', []) => {
+                let mut params = params.into_iter();
+                let key = params.next().and_then(|param| char::from_u32(param[0].into()));
+                let modifiers = params.next().map(|param| param[0]).unwrap_or(1);
+                let modifiers = Modifiers::from_csi(modifiers);
+
+                if let Some(key) = key {
+                    self.handle_event(|handler, terminal| {
+                        handler.key_input(terminal, key, modifiers)
+                    });
+                }
+            },
+            // Handle kitty keyboard protocol support reports.
+            ('u', [b'?']) => self.enable_kitty_keyboard(),
+            // Handle XTWINOPS size reports, used by recordings for resizes.
+            ('t', []) => {
+                let params: Vec<u16> = params.into_iter().flatten().copied().collect();
+                if let [8, lines, columns] = params[..] {
+                    self.set_dimensions(Dimensions { columns, lines });
+                }
+            },
+            // Handle replies to terminal queries.
+            ('R' | 'c' | 'n' | 'y', _) => {
+                let params: Vec<u16> = params.into_iter().flatten().copied().collect();
+                let response = match (action, intermediates, &params[..]) {
+                    ('R', [], &[line, _]) if self.inline_query => {
+                        self.set_inline_position(line);
+                        return;
+                    },
+                    ('R', [], &[line, column]) => TerminalResponse::CursorPosition { column, line },
+                    ('c', [b'?'], _) => TerminalResponse::DeviceAttributes(params),
+                    ('n', [], &[status]) => TerminalResponse::Status(status == 0),
+                    ('y', [b'?', b'$'], &[mode, state]) => {
+                        // Switch mouse encoding based on SGR mouse support.
+                        if mode == TerminalMode::SgrMouse as u16 {
+                            self.set_sgr_mouse(matches!(state, 1..=3));
+                        }
+                        TerminalResponse::Mode { mode, state }
+                    },
+                    _ => return,
+                };
+                self.handle_event(|handler, terminal| {
+                    handler.terminal_response(terminal, response.clone())
+                });
             },
             _ => (),
         }
@@ -0,0 +1,91 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::terminal::Dimensions;
+
+/// Header identifying recorded input files.
+const HEADER: &str = "sketch-input";
+
+/// Escape key press, encoded like the kitty keyboard protocol.
+///
+/// A lone ESC byte cannot be told apart from the start of an escape sequence
+/// once reads are concatenated, so it is stored using this sequence instead.
+pub const ESCAPE: &[u8] = b"\x1b[27u";
+
+/// Shutdown request received as a signal.
+pub const SHUTDOWN: &[u8] = b"\x04";
+
+/// Writer for recording all terminal input to a file.
+///
+/// Recordings start with a header containing the initial terminal dimensions,
+/// followed by the raw input. Resizes are stored as XTWINOPS size reports.
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    /// Start a new recording.
+    pub fn create(path: &Path, dimensions: Dimensions) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        writeln!(file, "{HEADER} {}x{}", dimensions.columns, dimensions.lines)?;
+        Ok(Self { file })
+    }
+
+    /// Append raw terminal input.
+    ///
+    /// Recording is best effort, failed writes are ignored.
+    pub fn input(&mut self, bytes: &[u8]) {
+        let _ = self.file.write_all(bytes);
+    }
+
+    /// Record a change of the terminal dimensions.
+    pub fn resize(&mut self, dimensions: Dimensions) {
+        let report = format!("\x1b[8;{};{}t", dimensions.lines, dimensions.columns);
+        self.input(report.as_bytes());
+    }
+}
+
+/// Terminal input recorded by a [`Recorder`].
+pub struct Recording {
+    /// Terminal dimensions at the start of the recording.
+    pub dimensions: Dimensions,
+    /// Raw terminal input.
+    pub input: Vec<u8>,
+}
+
+impl Recording {
+    /// Load a recording from a file.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(fs::read(path)?)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid input recording"))
+    }
+
+    /// Parse a recording's header and input.
+    fn parse(mut bytes: Vec<u8>) -> Option<Self> {
+        let header_end = bytes.iter().position(|byte| *byte == b'\n')?;
+        let header = std::str::from_utf8(&bytes[..header_end]).ok()?;
+
+        let size = header.strip_prefix(HEADER)?.strip_prefix(' ')?;
+        let (columns, lines) = size.split_once('x')?;
+        let dimensions = Dimensions { columns: columns.parse().ok()?, lines: lines.parse().ok()? };
+
+        bytes.drain(..=header_end);
+        Some(Self { dimensions, input: bytes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_header() {
+        let recording = Recording::parse(b"sketch-input 80x24\n\x1b[27ux".to_vec()).unwrap();
+        assert!(recording.dimensions == Dimensions { columns: 80, lines: 24 });
+        assert_eq!(recording.input, b"\x1b[27ux");
+
+        assert!(Recording::parse(b"sketch-input 80\n".to_vec()).is_none());
+        assert!(Recording::parse(b"sketch-session 80x24\n".to_vec()).is_none());
+    }
+}
@@ -20,7 +20,7 @@ pub fn mio_receiver() -> io::Result<Receiver> {
 /// Add a new signal to the signal handler.
 pub fn register(signal: libc::c_int) -> io::Result<()> {
     unsafe {
-        let result = libc::signal(signal, handler as libc::sighandler_t);
+        let result = libc::signal(signal, handler as *const () as libc::sighandler_t);
         if result == libc::SIG_ERR {
             return Err(io::Error::last_os_error());
         }
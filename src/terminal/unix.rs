@@ -0,0 +1,231 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read};
+use std::mem::MaybeUninit;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::Path;
+use std::ptr;
+use std::time::Duration;
+
+use libc::{SIGCONT, SIGHUP, SIGINT, SIGTERM, SIGTSTP, SIGWINCH};
+use mio::unix::pipe::Receiver;
+use mio::unix::SourceFd;
+use mio::{Events, Interest, Poll, Token};
+
+use crate::terminal::control::ControlSocket;
+use crate::terminal::{signal, Dimensions, PlatformEvent, Signal};
+
+/// Mio token for reading from STDIN.
+const STDIN_TOKEN: Token = Token(0);
+/// Mio token for signal handling.
+const SIGNAL_TOKEN: Token = Token(1);
+/// Mio token for new control socket connections.
+const CONTROL_TOKEN: Token = Token(2);
+/// Mio token of the first control socket client.
+const FIRST_CLIENT_TOKEN: Token = Token(3);
+
+/// Terminal attributes before entering raw mode.
+pub type TtyState = libc::termios;
+
+/// Event loop for STDIN, POSIX signals, and the control socket.
+pub struct EventLoop {
+    poll: Poll,
+    events: Events,
+    signal_receiver: Receiver,
+
+    /// Socket accepting commands from external tools.
+    control: Option<ControlSocket>,
+
+    /// Control socket clients which might have closed their connection.
+    ///
+    /// Closed clients are only disconnected on the next poll, to allow replying
+    /// to their last commands.
+    read_clients: Vec<Token>,
+
+    /// Buffer for reading from STDIN.
+    buf: Box<[u8]>,
+}
+
+impl EventLoop {
+    pub fn new() -> io::Result<Self> {
+        let poll = Poll::new()?;
+
+        // Register STDIN read interest.
+        poll.registry().register(&mut SourceFd(&0), STDIN_TOKEN, Interest::READABLE)?;
+
+        // Register signal handlers.
+        let mut signal_receiver = signal::mio_receiver()?;
+        poll.registry().register(&mut signal_receiver, SIGNAL_TOKEN, Interest::READABLE)?;
+        for signal in [SIGWINCH, SIGTSTP, SIGCONT, SIGTERM, SIGINT, SIGHUP] {
+            signal::register(signal)?;
+        }
+
+        Ok(Self {
+            poll,
+            signal_receiver,
+            events: Events::with_capacity(1024),
+            control: None,
+            read_clients: Vec::new(),
+            buf: vec![0; u16::MAX as usize].into_boxed_slice(),
+        })
+    }
+
+    /// Accept commands on a Unix domain socket.
+    pub fn listen(&mut self, path: &Path) -> io::Result<()> {
+        let mut control = ControlSocket::bind(path, FIRST_CLIENT_TOKEN)?;
+        control.register(self.poll.registry(), CONTROL_TOKEN)?;
+        self.control = Some(control);
+        Ok(())
+    }
+
+    /// Wait for the next events.
+    ///
+    /// Returns an empty list if the timeout expired without any events.
+    pub fn poll(&mut self, timeout: Option<Duration>) -> io::Result<Vec<PlatformEvent>> {
+        if let Some(control) = &mut self.control {
+            for token in self.read_clients.drain(..) {
+                control.drop_closed(self.poll.registry(), token);
+            }
+        }
+
+        // Stop if we run into a polling error we cannot handle ourselves.
+        if let Err(err) = self.poll.poll(&mut self.events, timeout) {
+            if err.kind() != io::ErrorKind::Interrupted {
+                return Err(err);
+            }
+        }
+
+        let mut platform_events = Vec::new();
+        for event in &self.events {
+            match event.token() {
+                STDIN_TOKEN => {
+                    let read = io::stdin().read(&mut self.buf)?;
+                    platform_events.push(PlatformEvent::Input(self.buf[..read].to_vec()));
+                },
+                SIGNAL_TOKEN => {
+                    let mut signal = [0; 4];
+                    while self.signal_receiver.read_exact(&mut signal).is_ok() {
+                        let signal = match libc::c_int::from_ne_bytes(signal) {
+                            SIGHUP => Signal::Hangup,
+                            SIGINT | SIGTERM => Signal::Shutdown,
+                            SIGWINCH => Signal::Resize,
+                            SIGCONT => Signal::Continue,
+                            SIGTSTP => Signal::Suspend,
+                            _ => unreachable!(),
+                        };
+                        platform_events.push(PlatformEvent::Signal(signal));
+                    }
+                },
+                CONTROL_TOKEN => {
+                    if let Some(control) = &mut self.control {
+                        control.accept(self.poll.registry());
+                    }
+                },
+                token => {
+                    let Some(control) = &mut self.control else { continue };
+                    for command in control.read(token) {
+                        platform_events.push(PlatformEvent::Command(token.0, command));
+                    }
+                    self.read_clients.push(token);
+                },
+            }
+        }
+
+        Ok(platform_events)
+    }
+
+    /// Send the response to a command back to its control socket client.
+    pub fn reply(&mut self, client: usize, response: &str) {
+        if let Some(control) = &mut self.control {
+            control.reply(Token(client), response);
+        }
+    }
+}
+
+/// Move all terminal interaction to the controlling terminal.
+///
+/// This replaces STDIN and STDOUT with `/dev/tty`, so the interface keeps
+/// working while they are redirected. The original STDOUT is returned for
+/// writing the application's output.
+pub fn use_controlling_tty() -> io::Result<File> {
+    let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+
+    unsafe {
+        let stdout = libc::dup(1);
+        if stdout == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        let stdout = File::from_raw_fd(stdout);
+
+        for fd in [0, 1] {
+            if libc::dup2(tty.as_raw_fd(), fd) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(stdout)
+    }
+}
+
+/// Query terminal dimensions in columns and lines.
+pub fn tty_dimensions() -> Dimensions {
+    unsafe {
+        let mut winsize = MaybeUninit::<libc::winsize>::uninit();
+        let result = libc::ioctl(0, libc::TIOCGWINSZ, winsize.as_mut_ptr());
+        if result != -1 {
+            let winsize = winsize.assume_init();
+            return Dimensions { columns: winsize.ws_col, lines: winsize.ws_row };
+        }
+    }
+
+    Dimensions::default()
+}
+
+/// Enable raw terminal input handling.
+pub fn setup_tty() -> TtyState {
+    unsafe {
+        let mut previous_termios = MaybeUninit::uninit();
+        change_terminal_attributes(|termios| {
+            ptr::write(&mut previous_termios, MaybeUninit::new(*termios));
+            termios.c_lflag &= !(libc::ECHO | libc::ICANON);
+        });
+        previous_termios.assume_init()
+    }
+}
+
+/// Disable raw terminal input handling.
+pub fn reset_tty(state: &TtyState) {
+    change_terminal_attributes(|termios| *termios = *state);
+}
+
+/// Change the tty properties.
+fn change_terminal_attributes(change_attributes: impl FnOnce(&mut libc::termios)) {
+    let mut termios = MaybeUninit::uninit();
+    let stdout_fd = io::stdout().as_raw_fd();
+
+    unsafe {
+        libc::tcgetattr(stdout_fd, termios.as_mut_ptr());
+        let mut termios = termios.assume_init();
+        change_attributes(&mut termios);
+        libc::tcsetattr(stdout_fd, 0, &termios);
+    }
+}
+
+/// Suspend the process, after the terminal was reset.
+pub fn suspend() -> io::Result<()> {
+    // Remove SIGTSTP handler and self-request another suspension.
+    signal::unregister(SIGTSTP)?;
+    unsafe {
+        let result = libc::raise(SIGTSTP);
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+
+    Ok(())
+}
+
+/// Prepare for the next suspension, after the process was continued.
+pub fn resume() -> io::Result<()> {
+    // Restore the SIGTSTP signal handler.
+    signal::register(SIGTSTP)
+}
@@ -0,0 +1,216 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read};
+use std::mem::MaybeUninit;
+use std::os::windows::io::{FromRawHandle, IntoRawHandle};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use windows_sys::Win32::Foundation::{BOOL, FALSE, INVALID_HANDLE_VALUE, TRUE};
+use windows_sys::Win32::System::Console::{
+    GetConsoleMode, GetConsoleOutputCP, GetConsoleScreenBufferInfo, GetStdHandle,
+    SetConsoleCtrlHandler, SetConsoleMode, SetConsoleOutputCP, SetStdHandle, CONSOLE_MODE,
+    CONSOLE_SCREEN_BUFFER_INFO, CTRL_BREAK_EVENT, CTRL_C_EVENT, ENABLE_ECHO_INPUT,
+    ENABLE_EXTENDED_FLAGS, ENABLE_LINE_INPUT, ENABLE_QUICK_EDIT_MODE,
+    ENABLE_VIRTUAL_TERMINAL_INPUT, ENABLE_VIRTUAL_TERMINAL_PROCESSING, STD_INPUT_HANDLE,
+    STD_OUTPUT_HANDLE,
+};
+
+use crate::terminal::{Dimensions, PlatformEvent, Signal};
+
+/// Code page identifier for UTF-8.
+const CP_UTF8: u32 = 65001;
+
+/// Interval for checking the console size.
+///
+/// Consoles in virtual terminal input mode do not report size changes, so
+/// they have to be polled.
+const RESIZE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Channel for passing console control events to the event loop.
+static CTRL_EVENTS: Mutex<Option<Sender<PlatformEvent>>> = Mutex::new(None);
+
+/// Console modes and output code page before entering raw mode.
+#[derive(Copy, Clone)]
+pub struct TtyState {
+    input: CONSOLE_MODE,
+    output: CONSOLE_MODE,
+    code_page: u32,
+}
+
+/// Event loop for console input and control events.
+///
+/// Console input cannot be polled together with other events, so it is read
+/// on a separate thread.
+pub struct EventLoop {
+    receiver: Receiver<PlatformEvent>,
+
+    /// Console size during the last poll.
+    dimensions: Dimensions,
+}
+
+impl EventLoop {
+    pub fn new() -> io::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+
+        let input_sender = sender.clone();
+        thread::spawn(move || {
+            let mut stdin = io::stdin();
+            let mut buf = vec![0; u16::MAX as usize];
+            loop {
+                let event = match stdin.read(&mut buf) {
+                    Ok(0) | Err(_) => PlatformEvent::Signal(Signal::Hangup),
+                    Ok(read) => PlatformEvent::Input(buf[..read].to_vec()),
+                };
+                let closed = matches!(event, PlatformEvent::Signal(_));
+                if input_sender.send(event).is_err() || closed {
+                    break;
+                }
+            }
+        });
+
+        // Handle Ctrl+C like the POSIX shutdown signals.
+        *CTRL_EVENTS.lock().unwrap() = Some(sender);
+        if unsafe { SetConsoleCtrlHandler(Some(ctrl_handler), TRUE) } == FALSE {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { receiver, dimensions: tty_dimensions() })
+    }
+
+    /// Accept commands on a Unix domain socket.
+    ///
+    /// This is not supported on Windows.
+    pub fn listen(&mut self, _path: &Path) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "control sockets require Unix"))
+    }
+
+    /// Wait for the next events.
+    ///
+    /// Returns an empty list if the timeout expired without any events.
+    pub fn poll(&mut self, timeout: Option<Duration>) -> io::Result<Vec<PlatformEvent>> {
+        let timeout = timeout.map_or(RESIZE_INTERVAL, |timeout| timeout.min(RESIZE_INTERVAL));
+
+        let mut events = Vec::new();
+        match self.receiver.recv_timeout(timeout) {
+            Ok(event) => events.push(event),
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => {
+                events.push(PlatformEvent::Signal(Signal::Hangup))
+            },
+        }
+        events.extend(self.receiver.try_iter());
+
+        let dimensions = tty_dimensions();
+        if dimensions != self.dimensions {
+            self.dimensions = dimensions;
+            events.push(PlatformEvent::Signal(Signal::Resize));
+        }
+
+        Ok(events)
+    }
+
+    /// Send the response to a command back to its control socket client.
+    pub fn reply(&mut self, _client: usize, _response: &str) {}
+}
+
+/// Console control handler for [`SetConsoleCtrlHandler`].
+unsafe extern "system" fn ctrl_handler(ctrl_type: u32) -> BOOL {
+    let signal = match ctrl_type {
+        CTRL_C_EVENT | CTRL_BREAK_EVENT => Signal::Shutdown,
+        _ => Signal::Hangup,
+    };
+
+    if let Some(sender) = CTRL_EVENTS.lock().ok().as_deref().and_then(Option::as_ref) {
+        let _ = sender.send(PlatformEvent::Signal(signal));
+    }
+
+    TRUE
+}
+
+/// Move all terminal interaction to the console.
+///
+/// This replaces STDIN and STDOUT with the console's input and output buffers,
+/// so the interface keeps working while they are redirected. The original
+/// STDOUT is returned for writing the application's output.
+pub fn use_controlling_tty() -> io::Result<File> {
+    let input = OpenOptions::new().read(true).write(true).open("CONIN$")?;
+    let output = OpenOptions::new().read(true).write(true).open("CONOUT$")?;
+
+    unsafe {
+        let stdout = GetStdHandle(STD_OUTPUT_HANDLE);
+        if stdout == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        let stdout = File::from_raw_handle(stdout);
+
+        for (std_handle, file) in [(STD_INPUT_HANDLE, input), (STD_OUTPUT_HANDLE, output)] {
+            if SetStdHandle(std_handle, file.into_raw_handle()) == FALSE {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(stdout)
+    }
+}
+
+/// Query console dimensions in columns and lines.
+pub fn tty_dimensions() -> Dimensions {
+    unsafe {
+        let mut info = MaybeUninit::<CONSOLE_SCREEN_BUFFER_INFO>::uninit();
+        let result = GetConsoleScreenBufferInfo(GetStdHandle(STD_OUTPUT_HANDLE), info.as_mut_ptr());
+        if result != FALSE {
+            let window = info.assume_init().srWindow;
+            let columns = (window.Right - window.Left + 1) as u16;
+            let lines = (window.Bottom - window.Top + 1) as u16;
+            return Dimensions { columns, lines };
+        }
+    }
+
+    Dimensions::default()
+}
+
+/// Enable raw console input and escape sequence processing.
+pub fn setup_tty() -> TtyState {
+    unsafe {
+        let input = GetStdHandle(STD_INPUT_HANDLE);
+        let output = GetStdHandle(STD_OUTPUT_HANDLE);
+
+        let mut state = TtyState { input: 0, output: 0, code_page: GetConsoleOutputCP() };
+        GetConsoleMode(input, &mut state.input);
+        GetConsoleMode(output, &mut state.output);
+
+        // Receive keys and mouse events as escape sequences, without echo.
+        let enabled = ENABLE_VIRTUAL_TERMINAL_INPUT | ENABLE_EXTENDED_FLAGS;
+        let disabled = ENABLE_ECHO_INPUT | ENABLE_LINE_INPUT | ENABLE_QUICK_EDIT_MODE;
+        SetConsoleMode(input, (state.input | enabled) & !disabled);
+
+        SetConsoleMode(output, state.output | ENABLE_VIRTUAL_TERMINAL_PROCESSING);
+        SetConsoleOutputCP(CP_UTF8);
+
+        state
+    }
+}
+
+/// Restore the original console modes.
+pub fn reset_tty(state: &TtyState) {
+    unsafe {
+        SetConsoleMode(GetStdHandle(STD_INPUT_HANDLE), state.input);
+        SetConsoleMode(GetStdHandle(STD_OUTPUT_HANDLE), state.output);
+        SetConsoleOutputCP(state.code_page);
+    }
+}
+
+/// Suspend the process, after the terminal was reset.
+///
+/// Windows has no job control, so suspensions are never requested.
+pub fn suspend() -> io::Result<()> {
+    Ok(())
+}
+
+/// Prepare for the next suspension, after the process was continued.
+pub fn resume() -> io::Result<()> {
+    Ok(())
+}
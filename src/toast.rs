@@ -0,0 +1,37 @@
+use std::time::{Duration, Instant};
+
+use sketch::import::SketchParser;
+use sketch::Cell;
+use unicode_width::UnicodeWidthChar;
+
+/// Time a notification stays visible.
+const DURATION: Duration = Duration::from_secs(3);
+
+/// Transient notification at the bottom of the screen.
+pub struct Toast {
+    cells: Vec<Cell>,
+    expires: Instant,
+}
+
+impl Toast {
+    /// Create a notification from a message, which may contain SGR escapes.
+    pub fn new(message: &str) -> Self {
+        let cells = SketchParser::parse(message).into_iter().next().unwrap_or_default();
+        Self { cells, expires: Instant::now() + DURATION }
+    }
+
+    /// Glyphs of the message, without spacers for fullwidth characters.
+    pub fn cells(&self) -> impl Iterator<Item = &Cell> {
+        self.cells.iter().filter(|cell| cell.c != '\0')
+    }
+
+    /// Number of columns covered by the message.
+    pub fn width(&self) -> usize {
+        self.cells().map(|cell| cell.c.width().unwrap_or(1)).sum()
+    }
+
+    /// Time at which the notification disappears.
+    pub fn expires(&self) -> Instant {
+        self.expires
+    }
+}
@@ -0,0 +1,674 @@
+use std::mem;
+
+use sketch::canvas::WriteMode;
+use sketch::geometry::{self, Shape};
+use sketch::{Brush, Cell, Point};
+
+use crate::chart::ChartKind;
+use crate::terminal::event::{ButtonState, Modifiers, MouseButton, MouseEvent};
+use crate::{Highlight, Sketch, SketchMode};
+
+/// Mouse driven drawing tool.
+///
+/// The active tool receives all mouse events on the canvas and decides
+/// whether they preview or commit its result. Previews are only written to
+/// the screen and disappear with the next redraw.
+pub trait Tool {
+    /// Update the tool's state for a mouse event.
+    fn mouse_input(&mut self, sketch: &mut Sketch, event: MouseEvent) -> Action;
+
+    /// Preview the result for a mouse event, without changing the canvas.
+    fn preview(&self, _sketch: &mut Sketch, _event: MouseEvent) {}
+
+    /// Write the result for a mouse event to the canvas.
+    fn commit(&mut self, _sketch: &mut Sketch, _event: MouseEvent) {}
+
+    /// Restore previews which should survive a redraw.
+    fn redraw(&self, _sketch: &mut Sketch) {}
+
+    /// Corners of the selected region, if the tool has a selection.
+    fn selection(&self) -> Option<(Point, Point)> {
+        None
+    }
+}
+
+/// Reaction of a tool to a mouse event.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Action {
+    /// Nothing to draw.
+    None,
+    /// Preview the tool's result.
+    Preview,
+    /// Commit the tool's result, keeping the tool active.
+    Commit,
+    /// Commit the tool's result and go back to freehand drawing.
+    Finish,
+}
+
+/// Freehand drawing with the brush.
+///
+/// This is the default tool, which is also used to start line and ellipse
+/// drawing.
+pub struct Freehand;
+
+impl Tool for Freehand {
+    fn mouse_input(&mut self, sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state, event.modifiers) {
+            // Start line drawing mode.
+            (MouseButton::Left, ButtonState::Pressed, modifiers)
+                if modifiers == Modifiers::CONTROL
+                    || (modifiers.is_empty() && sketch.drawing_mode.draws_lines()) =>
+            {
+                let start = sketch.snap_to_guides(position(&event));
+                sketch.mode = SketchMode::Tool(Box::new(LineTool { start, moved: false }));
+                Action::None
+            },
+            // Start ellipse drawing mode.
+            (MouseButton::Left, ButtonState::Pressed, Modifiers::ALT) => {
+                let center = position(&event);
+                sketch.mode = SketchMode::Tool(Box::new(EllipseTool { center }));
+                Action::None
+            },
+            // Start a stroke which is replaced by clean shapes.
+            (MouseButton::Left, ButtonState::Pressed, modifiers)
+                if modifiers.is_empty() && sketch.beautify =>
+            {
+                let stroke = StrokeTool { points: vec![position(&event)] };
+                sketch.mode = SketchMode::Tool(Box::new(stroke));
+                Action::None
+            },
+            // Write or erase brush with left or right mouse button pressed.
+            (
+                MouseButton::Left | MouseButton::Right,
+                ButtonState::Pressed | ButtonState::Down,
+                _,
+            ) => Action::Commit,
+            // Change eraser size while scrolling with shift held down.
+            (MouseButton::Index(4), _, Modifiers::SHIFT) => {
+                sketch.resize_eraser(1);
+                Action::None
+            },
+            (MouseButton::Index(5), _, Modifiers::SHIFT) => {
+                sketch.resize_eraser(-1);
+                Action::None
+            },
+            // Increase brush size.
+            (MouseButton::Index(4), ..) => {
+                let brush = &mut sketch.canvas.brush;
+                brush.stamp = None;
+                brush.size = brush.size.saturating_add(1);
+                brush.template = Brush::create_template(brush.shape, brush.size);
+                Action::None
+            },
+            // Decrease brush size.
+            (MouseButton::Index(5), ..) => {
+                let brush = &mut sketch.canvas.brush;
+                brush.stamp = None;
+                brush.size = brush.size.saturating_sub(1).max(1);
+                brush.template = Brush::create_template(brush.shape, brush.size);
+                Action::None
+            },
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, event: MouseEvent) {
+        // Draw brush at size 1 for line drawing preview.
+        let draws_lines = event.modifiers.contains(Modifiers::CONTROL)
+            || (event.modifiers.is_empty() && sketch.drawing_mode.draws_lines());
+        if draws_lines && event.button != MouseButton::Right {
+            let brush = &mut sketch.canvas.brush;
+            let original_size = mem::replace(&mut brush.size, 1);
+            brush.template = Brush::create_template(brush.shape, brush.size);
+
+            sketch.preview_brush();
+
+            let brush = &mut sketch.canvas.brush;
+            brush.size = original_size;
+            brush.template = Brush::create_template(brush.shape, brush.size);
+        } else {
+            sketch.preview_brush();
+        }
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, event: MouseEvent) {
+        match event.button {
+            MouseButton::Right => sketch.write_brush(WriteMode::Erase),
+            _ => sketch.write_brush(WriteMode::Write),
+        }
+    }
+}
+
+/// Flood filling the region under a click.
+pub struct FillTool;
+
+impl Tool for FillTool {
+    fn mouse_input(&mut self, _sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state) {
+            (MouseButton::Left, ButtonState::Pressed) => Action::Finish,
+            _ => Action::None,
+        }
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, _event: MouseEvent) {
+        sketch.fill();
+    }
+}
+
+/// Text cursor placement, used instead of freehand drawing in text mode.
+///
+/// Only the left mouse button places the text cursor, all other buttons draw
+/// like the freehand tool.
+pub struct TextTool;
+
+impl TextTool {
+    /// Check if an event is handled by the text tool instead of freehand
+    /// drawing.
+    fn places_cursor(event: &MouseEvent) -> bool {
+        matches!(event.button, MouseButton::Left | MouseButton::None)
+    }
+}
+
+impl Tool for TextTool {
+    fn mouse_input(&mut self, sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state) {
+            _ if !Self::places_cursor(&event) => Freehand.mouse_input(sketch, event),
+            (MouseButton::Left, ButtonState::Pressed) => Action::Commit,
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, event: MouseEvent) {
+        if !Self::places_cursor(&event) {
+            Freehand.preview(sketch, event);
+        }
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, event: MouseEvent) {
+        if Self::places_cursor(&event) {
+            sketch.text_cursor = Some(position(&event));
+        } else {
+            Freehand.commit(sketch, event);
+        }
+    }
+}
+
+/// Line and box drawing.
+///
+/// Dragging the mouse draws a line, while clicking twice draws a box.
+pub struct LineTool {
+    start: Point,
+    /// Whether the mouse was dragged, preventing box drawing.
+    moved: bool,
+}
+
+impl Tool for LineTool {
+    fn mouse_input(&mut self, _sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state) {
+            // Preview the box.
+            (_, ButtonState::Up) if !self.moved => Action::Preview,
+            // Draw the box once the second corner was clicked.
+            (MouseButton::Left, ButtonState::Pressed) if !self.moved => Action::Finish,
+            // Preview the line.
+            (MouseButton::Left, ButtonState::Down) => {
+                self.moved = true;
+                Action::Preview
+            },
+            // Draw the line once the mouse was released after moving.
+            (MouseButton::Left, ButtonState::Released) if self.moved => Action::Finish,
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, event: MouseEvent) {
+        let end = sketch.snap_to_guides(position(&event));
+        if self.moved {
+            let snap = event.modifiers.contains(Modifiers::SHIFT);
+            sketch.preview_line(self.start, end, snap);
+        } else {
+            sketch.preview_box(self.start, end);
+        }
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, event: MouseEvent) {
+        let end = sketch.snap_to_guides(position(&event));
+        if self.moved {
+            let snap = event.modifiers.contains(Modifiers::SHIFT);
+            sketch.write_line(self.start, end, snap, WriteMode::Write);
+        } else {
+            sketch.write_box(self.start, end, WriteMode::Write);
+        }
+    }
+}
+
+/// Freehand stroke which is replaced by a line or box, if it resembles one.
+pub struct StrokeTool {
+    points: Vec<Point>,
+}
+
+impl StrokeTool {
+    /// Write the brush at every point of the stroke.
+    fn write(&self, sketch: &mut Sketch, mode: WriteMode) {
+        let position = sketch.canvas.brush.position;
+        for point in &self.points {
+            sketch.canvas.brush.position = *point;
+            sketch.write_brush(mode);
+        }
+        sketch.canvas.brush.position = position;
+    }
+}
+
+impl Tool for StrokeTool {
+    fn mouse_input(&mut self, _sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state) {
+            (MouseButton::Left, ButtonState::Down) => {
+                let point = position(&event);
+                if self.points.last() != Some(&point) {
+                    self.points.push(point);
+                }
+                Action::Preview
+            },
+            (MouseButton::Left, ButtonState::Released) => Action::Finish,
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, _event: MouseEvent) {
+        self.write(sketch, WriteMode::WriteVolatile);
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, _event: MouseEvent) {
+        let stroke: Vec<_> =
+            self.points.iter().map(|point| (point.column as isize, point.line as isize)).collect();
+        let point =
+            |(column, line): (isize, isize)| Point { column: column as usize, line: line as usize };
+
+        match geometry::recognize_shape(&stroke) {
+            Some(Shape::Line(start, end)) => {
+                sketch.write_line(point(start), point(end), true, WriteMode::Write)
+            },
+            Some(Shape::Rectangle(start, end)) => {
+                sketch.write_box(point(start), point(end), WriteMode::Write)
+            },
+            None => self.write(sketch, WriteMode::Write),
+        }
+    }
+}
+
+/// Table drawing by dragging its outer corners.
+pub struct TableTool {
+    /// Number of rows and columns.
+    size: (usize, usize),
+    start: Option<Point>,
+}
+
+impl TableTool {
+    pub fn new(rows: usize, columns: usize) -> Self {
+        Self { size: (rows, columns), start: None }
+    }
+}
+
+impl Tool for TableTool {
+    fn mouse_input(&mut self, _sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state, self.start) {
+            (MouseButton::Left, ButtonState::Pressed, _) => {
+                self.start = Some(position(&event));
+                Action::Preview
+            },
+            (MouseButton::Left, ButtonState::Down, Some(_)) => Action::Preview,
+            (MouseButton::Left, ButtonState::Released, Some(_)) => Action::Finish,
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, event: MouseEvent) {
+        if let Some(start) = self.start {
+            sketch.preview_table(start, position(&event), self.size);
+        }
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, event: MouseEvent) {
+        if let Some(start) = self.start {
+            sketch.write_table(start, position(&event), self.size, WriteMode::Write);
+        }
+    }
+}
+
+/// Chart drawing by dragging its outer corners.
+pub struct ChartTool {
+    values: Vec<f64>,
+    kind: ChartKind,
+    start: Option<Point>,
+}
+
+impl ChartTool {
+    pub fn new(values: Vec<f64>, kind: ChartKind) -> Self {
+        Self { values, kind, start: None }
+    }
+}
+
+impl Tool for ChartTool {
+    fn mouse_input(&mut self, _sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state, self.start) {
+            (MouseButton::Left, ButtonState::Pressed, _) => {
+                self.start = Some(position(&event));
+                Action::Preview
+            },
+            (MouseButton::Left, ButtonState::Down, Some(_)) => Action::Preview,
+            (MouseButton::Left, ButtonState::Released, Some(_)) => Action::Finish,
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, event: MouseEvent) {
+        if let Some(start) = self.start {
+            sketch.preview_chart(start, position(&event), &self.values, self.kind);
+        }
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, event: MouseEvent) {
+        let Some(start) = self.start else { return };
+        if !sketch.write_chart(start, position(&event), &self.values, self.kind, WriteMode::Write) {
+            sketch.notify(&format!("Area too small for the \x1b[31m{}", self.kind.name()));
+        }
+    }
+}
+
+/// Locking regions by dragging their outer corners.
+#[derive(Default)]
+pub struct LockTool {
+    start: Option<Point>,
+}
+
+impl Tool for LockTool {
+    fn mouse_input(&mut self, _sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state, self.start) {
+            (MouseButton::Left, ButtonState::Pressed, _) => {
+                self.start = Some(position(&event));
+                Action::Preview
+            },
+            (MouseButton::Left, ButtonState::Down, Some(_)) => Action::Preview,
+            (MouseButton::Left, ButtonState::Released, Some(_)) => Action::Finish,
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, event: MouseEvent) {
+        if let Some(start) = self.start {
+            sketch.preview_selection(start, position(&event));
+        }
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, event: MouseEvent) {
+        if let Some(start) = self.start {
+            sketch.toggle_lock(start, position(&event));
+        }
+    }
+}
+
+/// Region measurement without drawing anything.
+#[derive(Default)]
+pub struct MeasureTool {
+    /// Corners of the last measured region.
+    region: Option<(Point, Point)>,
+}
+
+impl Tool for MeasureTool {
+    fn mouse_input(&mut self, _sketch: &mut Sketch, event: MouseEvent) -> Action {
+        let point = position(&event);
+        match (event.button, event.button_state, &mut self.region) {
+            (MouseButton::Left, ButtonState::Pressed, _) => {
+                self.region = Some((point, point));
+                Action::Preview
+            },
+            (MouseButton::Left, ButtonState::Down | ButtonState::Released, Some((_, end))) => {
+                *end = point;
+                Action::Preview
+            },
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, _event: MouseEvent) {
+        self.redraw(sketch);
+    }
+
+    fn redraw(&self, sketch: &mut Sketch) {
+        // Keep showing the last measurement until the tool is closed.
+        if let Some((start, end)) = self.region {
+            sketch.preview_measure(start, end);
+        }
+    }
+}
+
+/// Circle and ellipse drawing around a center point.
+pub struct EllipseTool {
+    center: Point,
+}
+
+impl Tool for EllipseTool {
+    fn mouse_input(&mut self, _sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state) {
+            (_, ButtonState::Up) => Action::Preview,
+            (MouseButton::Left, ButtonState::Pressed) => Action::Finish,
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, event: MouseEvent) {
+        let free = event.modifiers.contains(Modifiers::CONTROL);
+        sketch.preview_ellipse(self.center, position(&event), free);
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, event: MouseEvent) {
+        let free = event.modifiers.contains(Modifiers::CONTROL);
+        sketch.write_ellipse(self.center, position(&event), free, WriteMode::Write);
+    }
+}
+
+/// Selection of a region for the brush stamp.
+#[derive(Default)]
+pub struct StampCapture {
+    start: Option<Point>,
+}
+
+impl Tool for StampCapture {
+    fn mouse_input(&mut self, _sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state, self.start) {
+            (MouseButton::Left, ButtonState::Pressed, None) => {
+                self.start = Some(position(&event));
+                Action::Preview
+            },
+            (MouseButton::Left, ButtonState::Down, Some(_)) => Action::Preview,
+            (MouseButton::Left, ButtonState::Released, Some(_)) => Action::Finish,
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, event: MouseEvent) {
+        if let Some(start) = self.start {
+            sketch.preview_selection(start, position(&event));
+        }
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, event: MouseEvent) {
+        if let Some(start) = self.start {
+            sketch.capture_stamp(start, position(&event));
+        }
+    }
+}
+
+/// Moving connected content or a selected region.
+pub struct MoveTool {
+    state: MoveState,
+}
+
+impl Default for MoveTool {
+    fn default() -> Self {
+        Self { state: MoveState::Picking(None) }
+    }
+}
+
+/// State of the move tool.
+enum MoveState {
+    /// Waiting for a region to be picked, with an optional selection.
+    Picking(Option<(Point, Point)>),
+    /// Selecting a rectangular region, starting at a point.
+    Selecting(Point),
+    /// Dragging cells which were picked up at `origin`.
+    ///
+    /// With `duplicate`, the original cells are left untouched.
+    Dragging { origin: Point, points: Vec<Point>, duplicate: bool },
+}
+
+impl Tool for MoveTool {
+    fn mouse_input(&mut self, sketch: &mut Sketch, event: MouseEvent) -> Action {
+        let point = position(&event);
+        match (event.button, event.button_state, &self.state) {
+            // Pick the selection when the point is inside of it, or the connected content
+            // under the point otherwise. If neither exists, a new selection is started.
+            (MouseButton::Left, ButtonState::Pressed, MoveState::Picking(selection)) => {
+                let points = match *selection {
+                    Some((start, end)) if point.within(start, end) => {
+                        sketch.canvas.grid.region(start, end)
+                    },
+                    _ => sketch.canvas.grid.connected_region(point),
+                };
+
+                if points.is_empty() {
+                    self.state = MoveState::Selecting(point);
+                    Action::Preview
+                } else {
+                    let duplicate = event.modifiers.contains(Modifiers::ALT);
+                    self.state = MoveState::Dragging { origin: point, points, duplicate };
+                    Action::None
+                }
+            },
+            // Complete the selection of the region that should be moved.
+            (MouseButton::Left, ButtonState::Released, MoveState::Selecting(start)) => {
+                self.state = MoveState::Picking(Some(Point::rect(*start, point)));
+                Action::Preview
+            },
+            (MouseButton::Left, ButtonState::Down, MoveState::Selecting(_))
+            | (MouseButton::Left, ButtonState::Down, MoveState::Dragging { .. }) => Action::Preview,
+            // Drop the region at its new location.
+            (MouseButton::Left, ButtonState::Released, MoveState::Dragging { .. }) => {
+                Action::Finish
+            },
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, event: MouseEvent) {
+        let point = position(&event);
+        match &self.state {
+            MoveState::Picking(_) => self.redraw(sketch),
+            MoveState::Selecting(start) => sketch.preview_selection(*start, point),
+            MoveState::Dragging { origin, points, duplicate } => {
+                let offset = origin.offset_to(point);
+                sketch.move_region(points, offset, *duplicate, WriteMode::WriteVolatile);
+            },
+        }
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, event: MouseEvent) {
+        if let MoveState::Dragging { origin, points, duplicate } = &self.state {
+            let offset = origin.offset_to(position(&event));
+            sketch.move_region(points, offset, *duplicate, WriteMode::Write);
+        }
+    }
+
+    fn redraw(&self, sketch: &mut Sketch) {
+        // Keep showing the selection while picking a region to move.
+        if let MoveState::Picking(Some((start, end))) = self.state {
+            sketch.preview_selection(start, end);
+        }
+    }
+
+    fn selection(&self) -> Option<(Point, Point)> {
+        match self.state {
+            MoveState::Picking(selection) => selection,
+            _ => None,
+        }
+    }
+}
+
+/// Orthogonal connector between two box borders.
+#[derive(Default)]
+pub struct ConnectorTool {
+    start: Option<Point>,
+}
+
+impl Tool for ConnectorTool {
+    fn mouse_input(&mut self, sketch: &mut Sketch, event: MouseEvent) -> Action {
+        let point = position(&event);
+        let on_border = sketch.canvas.grid.border_exit(point).is_some();
+        match (event.button, event.button_state, self.start) {
+            // Pick the connector's starting point.
+            (MouseButton::Left, ButtonState::Pressed, None) if on_border => {
+                self.start = Some(point);
+                Action::None
+            },
+            (MouseButton::Left, ButtonState::Pressed, None) => {
+                sketch.notify("Connectors must start on a \x1b[32mbox border");
+                Action::None
+            },
+            // Preview the connector while hovering over another border.
+            (_, ButtonState::Up, Some(_)) => Action::Preview,
+            // Draw the connector once the second border was picked.
+            (MouseButton::Left, ButtonState::Pressed, Some(_)) if on_border => Action::Finish,
+            (MouseButton::Left, ButtonState::Pressed, Some(_)) => {
+                sketch.notify("Connectors must end on a \x1b[32mbox border");
+                Action::None
+            },
+            _ => Action::None,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, event: MouseEvent) {
+        if let Some(start) = self.start {
+            sketch.preview_connector(start, position(&event));
+        }
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, event: MouseEvent) {
+        if let Some(start) = self.start {
+            sketch.write_connector(start, position(&event), WriteMode::Write);
+        }
+    }
+}
+
+/// Placement of an imported sketch at the cursor.
+pub struct ImportTool {
+    cells: Vec<Vec<Cell>>,
+}
+
+impl ImportTool {
+    pub fn new(cells: Vec<Vec<Cell>>) -> Self {
+        Self { cells }
+    }
+}
+
+impl Tool for ImportTool {
+    fn mouse_input(&mut self, _sketch: &mut Sketch, event: MouseEvent) -> Action {
+        match (event.button, event.button_state) {
+            (MouseButton::Left, ButtonState::Pressed) => Action::Finish,
+            _ => Action::Preview,
+        }
+    }
+
+    fn preview(&self, sketch: &mut Sketch, _event: MouseEvent) {
+        sketch.overlay.highlight = Highlight::Dim;
+        sketch.write_stamp(&self.cells, WriteMode::WriteVolatile);
+        sketch.overlay.highlight = Highlight::None;
+    }
+
+    fn commit(&mut self, sketch: &mut Sketch, _event: MouseEvent) {
+        sketch.write_stamp(&self.cells, WriteMode::Write);
+    }
+}
+
+/// Canvas position of a mouse event.
+fn position(event: &MouseEvent) -> Point {
+    Point { column: event.column, line: event.line }
+}
@@ -0,0 +1,103 @@
+/// Number of columns a tab is expanded to when measuring indentation.
+const TAB_WIDTH: usize = 4;
+
+/// Markdown list markers stripped from the start of every item.
+const LIST_MARKERS: [&str; 3] = ["- ", "* ", "+ "];
+
+/// Item of an indented list.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Item {
+    pub name: String,
+    pub depth: usize,
+}
+
+/// Parse an indented text list.
+///
+/// Every line more indented than the line above it becomes a child of that
+/// line. Empty lines are ignored.
+pub fn parse(text: &str) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut indents: Vec<usize> = Vec::new();
+
+    for line in text.lines() {
+        let name = line.trim();
+        if name.is_empty() {
+            continue;
+        }
+
+        // Measure indentation, expanding tabs to the next tab stop.
+        let indent = line.chars().take_while(|c| c.is_whitespace()).fold(0, |indent, c| match c {
+            '\t' => (indent / TAB_WIDTH + 1) * TAB_WIDTH,
+            _ => indent + 1,
+        });
+
+        // Find the closest less indented item to attach to.
+        while indents.last().is_some_and(|last| *last >= indent) {
+            indents.pop();
+        }
+        let depth = indents.len();
+        indents.push(indent);
+
+        let name = LIST_MARKERS.iter().find_map(|marker| name.strip_prefix(marker));
+        let name = name.unwrap_or(line.trim()).trim_start();
+        items.push(Item { name: name.into(), depth });
+    }
+
+    items
+}
+
+/// Render items as a tree diagram, like the output of the `tree` command.
+pub fn diagram(items: &[Item]) -> Vec<String> {
+    let mut lines = Vec::with_capacity(items.len());
+
+    // Whether the ancestor at each depth has further siblings below it.
+    let mut open: Vec<bool> = Vec::new();
+
+    for (i, item) in items.iter().enumerate() {
+        // An item is the last child unless a sibling follows before its parent ends.
+        let last = !items[i + 1..]
+            .iter()
+            .find(|next| next.depth <= item.depth)
+            .is_some_and(|next| next.depth == item.depth);
+
+        open.truncate(item.depth);
+
+        let mut line = String::new();
+        if item.depth > 0 {
+            for has_siblings in &open[1..] {
+                line.push_str(if *has_siblings { "│   " } else { "    " });
+            }
+            line.push_str(if last { "└── " } else { "├── " });
+        }
+        line.push_str(&item.name);
+        lines.push(line);
+
+        open.push(!last);
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_diagram() {
+        let text = "src\n  - main.rs\n  - dialog\n\t\tmod.rs\n\n\t\ttree.rs\n  lib.rs\nREADME.md";
+        let expected = [
+            "src",
+            "├── main.rs",
+            "├── dialog",
+            "│   ├── mod.rs",
+            "│   └── tree.rs",
+            "└── lib.rs",
+            "README.md",
+        ];
+        assert_eq!(diagram(&parse(text)), expected);
+
+        let text = "a\n    b\n        c\n  d";
+        let expected = ["a", "├── b", "│   └── c", "└── d"];
+        assert_eq!(diagram(&parse(text)), expected);
+    }
+}
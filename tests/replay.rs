@@ -0,0 +1,36 @@
+use std::fs;
+use std::process::Command;
+
+/// Replay every recorded session and compare it against its expected output.
+///
+/// Sessions are stored as `tests/replay/<name>.input`, recorded using
+/// `--record-input`, with the expected sketch in `tests/replay/<name>.txt`.
+/// Additional command line arguments are read from `tests/replay/<name>.args`.
+#[test]
+fn replay_sessions() {
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/replay");
+
+    let mut replayed = 0;
+    for entry in fs::read_dir(dir).unwrap() {
+        let input = entry.unwrap().path();
+        if input.extension().map_or(true, |extension| extension != "input") {
+            continue;
+        }
+
+        let args = fs::read_to_string(input.with_extension("args")).unwrap_or_default();
+        let output = Command::new(env!("CARGO_BIN_EXE_sketch"))
+            .args(args.split_whitespace())
+            .arg("--replay-input")
+            .arg(&input)
+            .arg("--assert-output")
+            .arg(input.with_extension("txt"))
+            .output()
+            .unwrap();
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(output.status.success(), "{}: {stderr}", input.display());
+        replayed += 1;
+    }
+
+    assert!(replayed > 0);
+}